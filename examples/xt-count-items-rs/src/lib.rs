@@ -49,6 +49,11 @@ impl XTension for CountItemsXTension {
         }
     }
 
+    // minimal version of X-Ways Forensics required by this X-Tension, XT_Init fails on older versions
+    fn min_xwf_version() -> (u16, u16) {
+        (20, 3)
+    }
+
     //function to initialize the X-Tension. Wraps XT_Init() Function from C API
     fn xt_init(&mut self, _version: XtVersion, _: XtInitFlags, _: Option<Window>, _: XtLicenseInfo) -> Result<XtInitReturn, Self::XTensionError> {
         // compute cache for report table assignments
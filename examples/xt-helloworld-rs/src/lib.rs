@@ -1,6 +1,6 @@
 use xwf_api_rs::{
     xwferror,
-    export_all_functions,
+    xtension,
     xwf_types::*,
     traits::XTension,
     error::XwfError,
@@ -16,6 +16,8 @@ pub struct HelloWorldXTension {}
 
 // implement XTension Trait
 // most of the function have a default and empty implementation already
+// the #[xtension] attribute defines and exports the low-level C functions of the implemented callbacks
+#[xtension]
 impl XTension for HelloWorldXTension {
 
     // define your error type here. You can also define you own error type or use predefined "XwfError"
@@ -26,6 +28,11 @@ impl XTension for HelloWorldXTension {
         HelloWorldXTension {}
     }
 
+    // minimal version of X-Ways Forensics required by this X-Tension, XT_Init fails on older versions
+    fn min_xwf_version() -> (u16, u16) {
+        (20, 3)
+    }
+
     //function to initialize the X-Tension. Wraps XT_Init() Function from C API
     fn xt_init(&mut self, _version: XtVersion, _: XtInitFlags, _: Option<Window>, _: XtLicenseInfo) -> Result<XtInitReturn, Self::XTensionError> {
         Ok(XtInitReturn::RunSingleThreaded)
@@ -44,19 +51,19 @@ impl XTension for HelloWorldXTension {
         }
 
         //request a string input from user, use default string if nothing entered
-        let title = Application::get_user_input_str("enter a title".to_string(), true)
-            .or(Some("HelloWorld".to_string())).unwrap();
+        let title = Application::get_user_input_str("enter a title", true)
+            .unwrap_or("HelloWorld".to_string());
 
         //show progress bar
         Application::show_progress(title, ProgressFlags::empty());
 
 
         //request integer number from user and compute number of rounds
-        let num_rounds = (Application::get_user_input_integer("seconds to run".to_string())
-            .or(Some(1000)).unwrap()*100) as u32;
+        let num_rounds = (Application::get_user_input_integer("seconds to run")
+            .unwrap_or(1000)*100) as u32;
 
         //iterate over number of rounds
-        for i in 0..num_rounds as u32 {
+        for i in 0..num_rounds {
 
             //check if user wants to stop operation
             if Application::should_stop().is_err() {
@@ -67,7 +74,7 @@ impl XTension for HelloWorldXTension {
             std::thread::sleep(std::time::Duration::from_millis(10));
 
             //set progress and description text of progress bar
-            Application::set_progress_percentage(i, num_rounds as u32);
+            Application::set_progress_percentage(i, num_rounds);
             Application::set_progress_description(format!("{:.2} seconds have passed", i as f32/100.0f32));
         }
 
@@ -78,10 +85,3 @@ impl XTension for HelloWorldXTension {
         Ok(XtPrepareReturn::Negative(XtPrepareNegativeReturn::JustCallXtFinalize))
     }
 }
-
-//macro to automatically define/register all low-level C functions for dynamic library. always needed
-//first argument represents the name of a static variable. Can be anything.
-//second argument is the name of your XTension struct
-//also a similar export_all_functions_ex! macro available,
-//that defines the XT_ProcessItemEx() function instead of XT_ProcessItem()
-export_all_functions!(HELLO_WORLD_XTENSION, HelloWorldXTension);
\ No newline at end of file
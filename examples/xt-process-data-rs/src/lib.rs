@@ -31,6 +31,11 @@ impl XTension for ProcessDataXtension {
         }
     }
 
+    // minimal version of X-Ways Forensics required by this X-Tension, XT_Init fails on older versions
+    fn min_xwf_version() -> (u16, u16) {
+        (20, 6)
+    }
+
     //function to initialize the X-Tension. Wraps XT_Init() Function from C API
     fn xt_init(&mut self, _version: XtVersion, _: XtInitFlags, _: Option<Window>, _: XtLicenseInfo) -> Result<XtInitReturn, Self::XTensionError> {
 
@@ -90,7 +95,7 @@ impl XTension for ProcessDataXtension {
                 let file_type = item.get_item_type(false)?;
 
                 // get first bytes of file to check if contains a JPG header
-                if let Some(header) = handle.read_chunk(0, JPG_HEADER.len()) {
+                if let Some(header) = handle.read_chunk(0, JPG_HEADER.len())? {
                     if header.eq(&JPG_HEADER) {
                         // construct destination path by unique id and item type
                         let output_file = output_path.join(item_uid.to_string() + "." + &file_type);
@@ -0,0 +1,185 @@
+//! Batched write access to items: annotations are collected per item and applied in one pass,
+//! e.g. at the end of `XT_ProcessItemEx` or in `XT_Finalize`.
+
+use std::collections::BTreeMap;
+use crate::error::XwfError;
+use crate::item::Item;
+use crate::xwf_types::*;
+
+/// Single change of an item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Annotation {
+    SetFlags(ItemInfoFlags),
+    RemoveFlags(ItemInfoFlags),
+    Classification(ItemInfoClassification),
+    Comment { text: String, flags: AddTextFlags },
+    ExtractedMetadata { text: String, flags: AddTextFlags },
+    ItemType { description: String, status: FileTypeStatus },
+    ReportTable { name: String, flags: AddReportTableFlags },
+    HashValue { hash: Vec<u8>, secondary: bool },
+}
+
+impl Annotation {
+    pub fn apply(&self, item: &Item) -> Result<(), XwfError> {
+        match self {
+            Annotation::SetFlags(flags) => item.set_item_info_flags(*flags, false),
+            Annotation::RemoveFlags(flags) => item.set_item_info_flags(*flags, true),
+            Annotation::Classification(classification) => item.set_item_info_classification(*classification),
+            Annotation::Comment { text, flags } => item.add_comment(text, *flags),
+            Annotation::ExtractedMetadata { text, flags } => item.add_extracted_metadata(text, *flags),
+            Annotation::ItemType { description, status } => item.set_item_type(description, *status),
+            Annotation::ReportTable { name, flags } => item.try_add_to_report_table(name, *flags),
+            Annotation::HashValue { hash, secondary } => item.set_hash_value(hash, *secondary),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AnnotationFailure {
+    pub item_id: i32,
+    pub annotation: Annotation,
+    pub error: XwfError,
+}
+
+/// Result of [`ItemAnnotator::apply`]
+#[derive(Debug, Default)]
+pub struct AnnotationSummary {
+    /// number of annotations that were applied successfully
+    pub succeeded: usize,
+    pub failed: Vec<AnnotationFailure>,
+}
+
+impl AnnotationSummary {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Collects annotations per item, nothing is written before [`ItemAnnotator::apply`] is called.
+///
+/// Flag changes of an item are merged, so that each item needs at most one call for setting
+/// and one for removing flags. All other annotations are applied in the order they were added.
+#[derive(Debug, Default)]
+pub struct ItemAnnotator {
+    pending: BTreeMap<i32, Vec<Annotation>>,
+}
+
+impl ItemAnnotator {
+    pub fn new() -> ItemAnnotator {
+        ItemAnnotator::default()
+    }
+
+    pub fn annotate(&mut self, item: &Item, annotation: Annotation) -> &mut Self {
+        self.pending.entry(item.item_id).or_default().push(annotation);
+        self
+    }
+
+    pub fn tag(&mut self, item: &Item) -> &mut Self {
+        self.annotate(item, Annotation::SetFlags(ItemInfoFlags::Tagged))
+    }
+
+    pub fn untag(&mut self, item: &Item) -> &mut Self {
+        self.annotate(item, Annotation::RemoveFlags(ItemInfoFlags::Tagged))
+    }
+
+    pub fn hide(&mut self, item: &Item) -> &mut Self {
+        self.annotate(item, Annotation::SetFlags(ItemInfoFlags::HiddenByExaminer))
+    }
+
+    pub fn unhide(&mut self, item: &Item) -> &mut Self {
+        self.annotate(item, Annotation::RemoveFlags(ItemInfoFlags::HiddenByExaminer))
+    }
+
+    pub fn mark_viewed(&mut self, item: &Item) -> &mut Self {
+        self.annotate(item, Annotation::SetFlags(ItemInfoFlags::ViewedByExaminer))
+    }
+
+    pub fn set_classification(&mut self, item: &Item, classification: ItemInfoClassification) -> &mut Self {
+        self.annotate(item, Annotation::Classification(classification))
+    }
+
+    /// replaces the comment of the item
+    pub fn set_comment<S: Into<String>>(&mut self, item: &Item, comment: S) -> &mut Self {
+        self.annotate(item, Annotation::Comment { text: comment.into(), flags: AddTextFlags::empty() })
+    }
+
+    pub fn append_comment<S: Into<String>>(&mut self, item: &Item, comment: S) -> &mut Self {
+        self.annotate(item, Annotation::Comment { text: comment.into(), flags: AddTextFlags::Append })
+    }
+
+    pub fn append_extracted_metadata<S: Into<String>>(&mut self, item: &Item, metadata: S) -> &mut Self {
+        self.annotate(item, Annotation::ExtractedMetadata { text: metadata.into(), flags: AddTextFlags::Append })
+    }
+
+    pub fn set_type<S: Into<String>>(&mut self, item: &Item, description: S, status: FileTypeStatus) -> &mut Self {
+        self.annotate(item, Annotation::ItemType { description: description.into(), status })
+    }
+
+    pub fn add_to_report_table<S: Into<String>>(&mut self, item: &Item, name: S, flags: AddReportTableFlags) -> &mut Self {
+        self.annotate(item, Annotation::ReportTable { name: name.into(), flags })
+    }
+
+    pub fn set_hash_value(&mut self, item: &Item, hash: &[u8], secondary: bool) -> &mut Self {
+        self.annotate(item, Annotation::HashValue { hash: hash.to_vec(), secondary })
+    }
+
+    /// number of annotations not applied yet
+    pub fn pending_count(&self) -> usize {
+        self.pending.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn discard(&mut self) {
+        self.pending.clear();
+    }
+
+    /// applies all pending annotations, a failing annotation does not stop the remaining ones
+    pub fn apply(&mut self) -> AnnotationSummary {
+        let mut summary = AnnotationSummary::default();
+
+        for (item_id, annotations) in std::mem::take(&mut self.pending) {
+            let item = Item::new(item_id);
+            for annotation in merge_flags(annotations) {
+                match annotation.apply(&item) {
+                    Ok(()) => summary.succeeded += 1,
+                    Err(error) => summary.failed.push(AnnotationFailure { item_id, annotation, error }),
+                }
+            }
+        }
+        summary
+    }
+}
+
+// combines all flag changes of an item, a later change of the same flag wins
+pub(crate) fn merge_flags(annotations: Vec<Annotation>) -> Vec<Annotation> {
+    let mut set = ItemInfoFlags::empty();
+    let mut remove = ItemInfoFlags::empty();
+    let mut others = Vec::new();
+
+    for annotation in annotations {
+        match annotation {
+            Annotation::SetFlags(flags) => {
+                set |= flags;
+                remove -= flags;
+            },
+            Annotation::RemoveFlags(flags) => {
+                remove |= flags;
+                set -= flags;
+            },
+            other => others.push(other),
+        }
+    }
+
+    let mut merged = Vec::with_capacity(others.len() + 2);
+    if !set.is_empty() {
+        merged.push(Annotation::SetFlags(set));
+    }
+    if !remove.is_empty() {
+        merged.push(Annotation::RemoveFlags(remove));
+    }
+    merged.extend(others);
+    merged
+}
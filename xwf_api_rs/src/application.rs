@@ -1,21 +1,24 @@
-use std::ptr::null_mut;
-use winapi::shared::ntdef::LPWSTR;
-use crate::get_raw_api;
+use crate::backend::backend;
+use crate::capabilities::xwf_supports;
+use crate::mt::is_worker_thread;
 use crate::xwf_types::*;
-use crate::raw_api::RAW_API;
 
 use crate::error::XwfError;
 
 
-#[cfg(feature="api_20_6")]
+/// LogToOutputWindow is dropped at runtime for X-Ways Forensics versions before v20.6
 pub const DEFAULT_OUTPUT_FLAGS: OutputMessageFlags = OutputMessageFlags::LogToOutputWindow;
-#[cfg(not(feature="api_20_6"))]
-pub const DEFAULT_OUTPUT_FLAGS: OutputMessageFlags = OutputMessageFlags::empty();
 
 
 pub struct Application {
 
 }
+impl Default for Application {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Application {
 
     pub fn new() -> Application {
@@ -24,20 +27,31 @@ impl Application {
     }
 
     pub fn output(msg: &[u8], flags: OutputMessageFlags) {
-        let s = winsafe::WString::from_str(String::from_utf8_lossy(msg));
-        (get_raw_api!().output_message)(s.as_ptr() ,flags.bits())
+        backend().output_message(&String::from_utf8_lossy(msg), Self::supported_output_flags(flags).bits())
     }
 
     pub fn output_string<S: AsRef<str>>(msg: S, flags: OutputMessageFlags) {
-        (get_raw_api!().output_message)(winsafe::WString::from_str(&msg).as_ptr(), flags.bits())
+        backend().output_message(msg.as_ref(), Self::supported_output_flags(flags).bits())
+    }
+
+    fn supported_output_flags(flags: OutputMessageFlags) -> OutputMessageFlags {
+        if xwf_supports(20, 6) {
+            flags
+        } else {
+            flags - OutputMessageFlags::LogToOutputWindow
+        }
     }
 
     pub fn log<S: AsRef<str>>(msg: S) {
         Self::output_string(msg, OutputMessageFlags::empty());
     }
 
+    /// not available in worker threads of multi-threaded X-Tensions
     pub fn get_user_input_integer<S: AsRef<str>>(msg: S)-> Option<u64> {
-        let ret = (get_raw_api!().get_user_input)(unsafe { winsafe::WString::from_str(&msg).as_mut_ptr() }, null_mut(), 0, 0x1);
+        if is_worker_thread() {
+            return None;
+        }
+        let ret = backend().get_user_input(msg.as_ref(), None, 0x1);
         if ret < 0 {
             None
         } else {
@@ -45,44 +59,60 @@ impl Application {
         }
     }
 
+    /// not available in worker threads of multi-threaded X-Tensions
     pub fn get_user_input_str<S: AsRef<str>>(msg: S, allow_empty: bool) -> Option<String> {
+        if is_worker_thread() {
+            return None;
+        }
         let flags = if allow_empty {0x2} else {0x0};
-        let mut s = winsafe::WString::new_alloc_buf(65535);
-        let ret: i64 =  (get_raw_api!().get_user_input)(winsafe::WString::from_str(msg).as_ptr() as LPWSTR , unsafe {s.as_mut_ptr()},  s.buf_len() as u32, flags);
+        let mut s = String::new();
+        let ret: i64 = backend().get_user_input(msg.as_ref(), Some(&mut s), flags);
         if ret > 0 {
-            Some(s.to_string())
+            Some(s)
         } else {
             None
         }        
     }
 
+    // progress functions are ignored in worker threads of multi-threaded X-Tensions
     pub fn show_progress<S: AsRef<str>>(caption: S, flags: ProgressFlags) {
-        (get_raw_api!().show_progress)(winsafe::WString::from_str(&caption).as_ptr() as LPWSTR, flags.bits())
+        if is_worker_thread() {
+            return;
+        }
+        backend().show_progress(caption.as_ref(), flags.bits())
     }
 
     pub fn set_progress_description<S: AsRef<str>>(caption: S) {
-        (get_raw_api!().set_progress_description)(winsafe::WString::from_str(&caption).as_ptr() as LPWSTR)
+        if is_worker_thread() {
+            return;
+        }
+        backend().set_progress_description(caption.as_ref())
     }
 
     pub fn should_stop() -> Result<(), XwfError> {
-        if (get_raw_api!().should_stop)() != 0 {
+        if backend().should_stop() {
             Err(XwfError::OperationAbortedByUser)
         } else {
             Ok(())
         }
     }
     pub fn hide_progress() {
-        (get_raw_api!().hide_progress)()
+        if is_worker_thread() {
+            return;
+        }
+        backend().hide_progress()
     }
 
     pub fn set_progress_percentage(num: u32, total: u32) {
-        let percentage:u32;
-        if total > 0 {
-            percentage = (100.0f32 * num as f32 / total as f32).round() as u32;
-        } else {
-            percentage = 100;
+        if is_worker_thread() {
+            return;
         }
-        (get_raw_api!().set_progress_percentage)(percentage);
+        let percentage: u32 = if total > 0 {
+            (100.0f32 * num as f32 / total as f32).round() as u32
+        } else {
+            100
+        };
+        backend().set_progress_percentage(percentage);
     }
 
 }
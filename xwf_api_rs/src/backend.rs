@@ -0,0 +1,200 @@
+use std::sync::{Arc, RwLock};
+use serde::{Deserialize, Serialize};
+use crate::capabilities::Capabilities;
+use crate::xwf_types::XwfHandle;
+use crate::evidence::ReportTableListItem;
+use crate::search::SearchHit;
+use crate::event::Event;
+use crate::xwf_types::SrcInfo;
+
+/// Abstraction of the X-Tension C API. Every XWF_* function exported by X-Ways has a
+/// counterpart here, but takes and returns safe Rust types instead of raw buffers and pointers.
+///
+/// The high-level types (`Item`, `Volume`, `Evidence`, ...) exclusively talk to the backend
+/// that is currently installed via [`set_backend`]. The DLL-loaded [`crate::raw_api::RawApi`]
+/// is the default implementation, but test doubles or wrappers can be installed as well.
+pub trait XwfBackend: Send + Sync {
+    fn output_message(&self, message: &str, flags: u32);
+
+    fn get_volume_name(&self, volume: XwfHandle, name_type: u32) -> String;
+    fn get_volume_information(&self, volume: XwfHandle) -> Option<VolumeInformation>;
+    fn select_volume_snapshot(&self, volume: XwfHandle) -> i32;
+    fn get_vs_prop(&self, prop_type: i32, value: Option<u32>) -> i64;
+    fn get_item_count(&self, selected_in_dbc: bool) -> u32;
+
+    fn get_item_name(&self, item_id: u32) -> String;
+    fn get_item_size(&self, item_id: i32) -> i64;
+    fn set_item_size(&self, item_id: i32, size: i64);
+    fn get_item_ofs(&self, item_id: i32) -> (i64, i64);
+    fn get_item_information(&self, item_id: i32, info_type: i32) -> Option<i64>;
+    fn set_item_information(&self, item_id: i32, info_type: i32, value: i64) -> bool;
+    fn get_item_type(&self, item_id: i32, type_descr: &mut String, flags: u32) -> i32;
+    fn set_item_type(&self, item_id: i32, type_descr: &str, type_status: i32);
+    fn get_item_parent(&self, item_id: i32) -> i32;
+    fn set_item_parent(&self, child_item_id: i32, parent_item_id: i32);
+    fn get_report_table_assocs(&self, item_id: i32, assocs: &mut String) -> u32;
+    fn add_to_report_table(&self, item_id: i32, report_table_name: &str, flags: u32) -> i32;
+    fn get_hashset_assocs(&self, item_id: i32, assocs: &mut String) -> i32;
+    fn get_comment(&self, item_id: i32) -> Option<String>;
+    fn add_comment(&self, item_id: i32, comment: &str, flags: u32) -> bool;
+    fn get_extracted_metadata(&self, item_id: i32) -> Option<String>;
+    fn add_extracted_metadata(&self, item_id: i32, metadata: &str, flags: u32) -> bool;
+    /// returns the metadata as string, so there is no need for calling `XWF_ReleaseMem` afterwards
+    fn get_metadata_ex(&self, item: XwfHandle, flags: &mut u32) -> Option<String>;
+    fn get_hash_value(&self, item_id: i32, buffer: &mut [u8]) -> bool;
+    fn set_hash_value(&self, item_id: i32, hash: &[u8], param: u32) -> bool;
+    fn create_file(&self, name: &str, creation_flags: u32, parent_item_id: i32, src_info: &mut SrcInfo) -> i32;
+
+    fn open_item(&self, volume: XwfHandle, item_id: i32, flags: u32) -> XwfHandle;
+    fn close(&self, volume_or_item: XwfHandle);
+    fn read(&self, volume_or_item: XwfHandle, offset: i64, buffer: &mut [u8]) -> u32;
+    /// for property types that are returned as string pointer by XWF, the string is written to `string`
+    fn get_prop(&self, volume_or_item: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64;
+
+    fn get_first_ev_obj(&self) -> XwfHandle;
+    fn get_next_ev_obj(&self, prev_evidence: XwfHandle) -> XwfHandle;
+    fn get_ev_obj(&self, ev_obj_id: u32) -> XwfHandle;
+    fn open_ev_obj(&self, evidence: XwfHandle, flags: u32) -> XwfHandle;
+    fn close_ev_obj(&self, evidence: XwfHandle);
+    /// for property types with string results (buffer or pointer), the string is written to `string`
+    fn get_ev_obj_prop(&self, evidence: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64;
+    fn get_ev_obj_report_table_assocs(&self, evidence: XwfHandle, flags: i32) -> Option<Vec<ReportTableListItem>>;
+
+    /// for property types with string results, the string is written to `string`
+    fn get_case_prop(&self, prop_type: i32, string: Option<&mut String>) -> i64;
+    fn get_report_table_info(&self, report_table_id: i32, optional: &mut i32) -> Option<String>;
+
+    fn show_progress(&self, caption: &str, flags: u32);
+    fn set_progress_description(&self, description: &str);
+    fn set_progress_percentage(&self, percent: u32);
+    fn hide_progress(&self);
+    fn should_stop(&self) -> bool;
+    fn get_user_input(&self, message: &str, input: Option<&mut String>, flags: u32) -> i64;
+
+    /// `search_terms` are delimited by line breaks, code pages are passed as null if `None`
+    fn search(&self, volume: XwfHandle, search_terms: &str, flags: u32, search_window: u32, code_pages: Option<[u16; 5]>) -> i32;
+    fn get_search_term(&self, search_term_id: i32) -> Option<String>;
+    /// calls `XWF_GetSearchTerm` with id -1, which returns the number of search terms
+    fn get_search_term_count(&self) -> i32;
+    fn add_search_term(&self, name: &str, flags: u32) -> i32;
+    fn get_search_hit(&self, hit_no: i32) -> Option<SearchHit>;
+    fn set_search_hit(&self, hit_no: i32, hit: &SearchHit) -> bool;
+    fn add_search_hit(&self, hit: &SearchHit) -> i32;
+
+    /// `None` if the evidence object has no event with the given number
+    fn get_event(&self, evidence: XwfHandle, event_no: u32) -> Option<Event>;
+    fn add_event(&self, event: &Event) -> i32;
+
+    /// called by the exported `XT_*` functions before the X-Tension implementation is invoked,
+    /// `args` are the raw arguments passed by XWF (handles as integers)
+    fn callback_invoked(&self, _name: &str, _args: &[i64]) {}
+
+    /// describes which `XWF_*` functions are provided, methods of missing functions return defaults
+    fn capabilities(&self) -> Capabilities { Capabilities::all() }
+}
+
+// generates methods of [`XwfBackend`] that forward the call to the wrapped backend `self.inner`,
+// for backend wrappers that only intercept some of the calls
+#[cfg(any(feature = "trace", feature = "journal"))]
+macro_rules! delegate_to_inner {
+    ($(fn $name:ident(&self $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)?;)*) => {
+        $(
+            fn $name(&self $(, $arg: $ty)*) $(-> $ret)? {
+                self.inner.$name($($arg),*)
+            }
+        )*
+    };
+}
+#[cfg(any(feature = "trace", feature = "journal"))]
+pub(crate) use delegate_to_inner;
+
+/// Output values of `XWF_GetVolumeInformation`, see [`VolumeInfo`](crate::volume::VolumeInfo) for the converted values
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct VolumeInformation {
+    pub file_system: i32,
+    pub bytes_per_sector: u32,
+    pub sectors_per_cluster: u32,
+    pub cluster_count: i64,
+    pub first_cluster_sector_no: i64,
+}
+
+
+static BACKEND: RwLock<Option<Arc<dyn XwfBackend>>> = RwLock::new(None);
+
+// serializes runs with a temporarily installed backend (simulated or replayed runs, e.g. in parallel tests)
+#[cfg(any(test, feature = "simulator", feature = "trace"))]
+static EXCLUSIVE_BACKEND_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(any(test, feature = "simulator", feature = "trace"))]
+pub(crate) fn lock_exclusive_backend() -> std::sync::MutexGuard<'static, ()> {
+    EXCLUSIVE_BACKEND_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set_backend<B: XwfBackend + 'static>(backend: B) {
+    *BACKEND.write().unwrap() = Some(Arc::new(backend));
+}
+
+pub fn reset_backend() -> Option<Arc<dyn XwfBackend>> {
+    BACKEND.write().unwrap().take()
+}
+
+pub fn has_backend() -> bool {
+    BACKEND.read().unwrap().is_some()
+}
+
+/// returns the currently installed backend. Panics if no backend was installed, which is done
+/// by `XT_Init()` automatically.
+pub fn backend() -> Arc<dyn XwfBackend> {
+    BACKEND.read().unwrap().as_ref().expect("no XWF backend installed").clone()
+}
+
+pub fn notify_callback(name: &str, args: &[i64]) {
+    if let Some(b) = BACKEND.read().unwrap().as_ref() {
+        b.callback_invoked(name, args);
+    }
+}
+
+/// installs the backend for running inside X-Ways Forensics, which calls the `XWF_*` functions
+/// exported by the running process. If the `trace` feature is enabled, all calls are recorded
+/// to a trace file in the case directory additionally. If the `journal` feature is enabled, all
+/// modifications are journaled in the case directory. If the trace or journal file cannot be
+/// created, the X-Tension runs without it and a warning is written to the messages window.
+#[cfg(windows)]
+pub fn install_xwf_backend(extension_name: &str) -> Result<(), &'static str> {
+    let raw_api = crate::raw_api::RawApi::load()?;
+
+    #[cfg(feature = "journal")]
+    match crate::journal::JournalingBackend::create_in_case_dir(raw_api, extension_name) {
+        Ok(journal) => install_traced(journal, extension_name),
+        Err((raw_api, e)) => {
+            install_traced(raw_api, extension_name);
+            crate::xwfwarn!("failed to create journal file, modifications are not journaled: {}", e);
+        }
+    }
+    #[cfg(not(feature = "journal"))]
+    install_traced(raw_api, extension_name);
+    Ok(())
+}
+
+// installs `backend`, wrapped by a tracer if the `trace` feature is enabled
+#[cfg(windows)]
+fn install_traced<B: XwfBackend + 'static>(backend: B, extension_name: &str) {
+    #[cfg(feature = "trace")]
+    match crate::trace::TracingBackend::create_in_case_dir(backend, extension_name) {
+        Ok(tracer) => set_backend(tracer),
+        Err((backend, e)) => {
+            set_backend(backend);
+            crate::xwfwarn!("failed to create trace file, calls are not traced: {}", e);
+        }
+    }
+    #[cfg(not(feature = "trace"))]
+    {
+        let _ = extension_name;
+        set_backend(backend);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn install_xwf_backend(_extension_name: &str) -> Result<(), &'static str> {
+    Err("X-Ways Forensics is only available on Windows")
+}
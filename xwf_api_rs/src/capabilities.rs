@@ -0,0 +1,181 @@
+use std::sync::RwLock;
+use crate::backend::{backend, has_backend};
+use crate::error::XwfError;
+use crate::xwf_types::XtVersion;
+use crate::xwfwarn;
+
+/// all `XWF_*` functions used by xwf-api-rs with the version of X-Ways Forensics that introduced
+/// them according to the X-Tension API documentation
+pub const XWF_FUNCTIONS: [(&str, (u16, u16)); 55] = [
+    ("XWF_OutputMessage", (16, 5)),
+    ("XWF_GetVolumeName", (16, 5)),
+    ("XWF_GetVolumeInformation", (16, 5)),
+    ("XWF_SetItemInformation", (16, 5)),
+    ("XWF_GetItemInformation", (16, 5)),
+    ("XWF_GetItemParent", (16, 5)),
+    ("XWF_GetItemSize", (16, 5)),
+    ("XWF_GetItemType", (16, 5)),
+    ("XWF_SetItemType", (16, 5)),
+    ("XWF_Read", (16, 5)),
+    ("XWF_GetFirstEvObj", (17, 1)),
+    ("XWF_GetNextEvObj", (17, 1)),
+    ("XWF_GetCaseProp", (17, 1)),
+    ("XWF_GetEvObjProp", (17, 1)),
+    ("XWF_GetEvObj", (17, 6)),
+    ("XWF_GetReportTableInfo", (17, 7)),
+    ("XWF_GetEvObjReportTableAssocs", (17, 7)),
+    ("XWF_OpenEvObj", (17, 1)),
+    ("XWF_AddToReportTable", (16, 5)),
+    ("XWF_GetHashValue", (16, 8)),
+    ("XWF_SetHashValue", (18, 8)),
+    ("XWF_OpenItem", (16, 5)),
+    ("XWF_GetVSProp", (17, 4)),
+    ("XWF_SelectVolumeSnapshot", (17, 6)),
+    ("XWF_GetItemName", (16, 5)),
+    ("XWF_GetItemCount", (16, 5)),
+    ("XWF_GetProp", (16, 5)),
+    ("XWF_CloseEvObj", (17, 1)),
+    ("XWF_Close", (16, 5)),
+    ("XWF_ShowProgress", (16, 5)),
+    ("XWF_SetProgressDescription", (16, 5)),
+    ("XWF_SetProgressPercentage", (16, 5)),
+    ("XWF_HideProgress", (16, 5)),
+    ("XWF_ShouldStop", (16, 5)),
+    ("XWF_GetUserInput", (16, 5)),
+    ("XWF_GetReportTableAssocs", (16, 5)),
+    ("XWF_GetHashSetAssocs", (17, 5)),
+    ("XWF_GetExtractedMetadata", (17, 7)),
+    ("XWF_GetMetadataEx", (19, 9)),
+    ("XWF_ReleaseMem", (19, 9)),
+    ("XWF_GetItemOfs", (16, 5)),
+    ("XWF_GetComment", (16, 5)),
+    ("XWF_AddComment", (16, 5)),
+    ("XWF_AddExtractedMetadata", (17, 7)),
+    ("XWF_SetItemParent", (16, 7)),
+    ("XWF_SetItemSize", (16, 7)),
+    ("XWF_CreateFile", (16, 7)),
+    ("XWF_Search", (16, 5)),
+    ("XWF_GetSearchTerm", (17, 7)),
+    ("XWF_AddSearchTerm", (18, 8)),
+    ("XWF_GetSearchHit", (18, 7)),
+    ("XWF_SetSearchHit", (18, 7)),
+    ("XWF_AddSearchHit", (18, 7)),
+    ("XWF_GetEvent", (17, 6)),
+    ("XWF_AddEvent", (17, 6)),
+];
+
+static XWF_VERSION: RwLock<Option<XtVersion>> = RwLock::new(None);
+
+/// Describes which `XWF_*` functions are provided by the running X-Ways Forensics
+/// and the version passed to `XT_Init`.
+#[derive(Debug, Clone, Default)]
+pub struct Capabilities {
+    version: Option<XtVersion>,
+    missing: Vec<&'static str>,
+}
+
+impl Capabilities {
+    /// all functions are available
+    pub fn all() -> Capabilities {
+        Capabilities::default()
+    }
+
+    pub fn with_missing(missing: Vec<&'static str>) -> Capabilities {
+        Capabilities { version: None, missing }
+    }
+
+    pub fn version(&self) -> Option<XtVersion> {
+        self.version
+    }
+
+    pub fn is_available(&self, function_name: &str) -> bool {
+        !self.missing.contains(&function_name)
+    }
+
+    pub fn missing_functions(&self) -> &[&'static str] {
+        &self.missing
+    }
+
+    pub fn require(&self, function_name: &'static str) -> Result<(), XwfError> {
+        if self.is_available(function_name) {
+            Ok(())
+        } else {
+            Err(XwfError::FunctionNotAvailable(function_name))
+        }
+    }
+
+    /// true if the version passed to `XT_Init` is at least `major.minor`. If no version is known
+    /// (e.g. `XT_Init` was not called yet), the latest version is assumed.
+    pub fn supports(&self, major: u16, minor: u16) -> bool {
+        match self.version {
+            Some(v) => (v.major, v.minor) >= (major, minor),
+            None => true,
+        }
+    }
+
+    pub fn require_version(&self, feature: &'static str, major: u16, minor: u16) -> Result<(), XwfError> {
+        if self.supports(major, minor) {
+            Ok(())
+        } else {
+            Err(XwfError::RequiresXwfVersion(feature, (major, minor)))
+        }
+    }
+
+    /// returns the missing functions, that should be available according to the given version
+    /// (see [`XWF_FUNCTIONS`]). Unknown functions are always returned.
+    pub fn unexpectedly_missing(&self, version: XtVersion) -> Vec<&'static str> {
+        self.missing.iter()
+            .filter(|f| function_min_version(f).is_none_or(|min| (version.major, version.minor) >= min))
+            .copied()
+            .collect()
+    }
+}
+
+/// version of X-Ways Forensics that introduced the function, `None` if the function is not used by xwf-api-rs
+pub fn function_min_version(function_name: &str) -> Option<(u16, u16)> {
+    XWF_FUNCTIONS.iter().find(|(name, _)| *name == function_name).map(|(_, version)| *version)
+}
+
+/// returns the capabilities of the installed backend
+pub fn capabilities() -> Capabilities {
+    let mut caps = if has_backend() { backend().capabilities() } else { Capabilities::all() };
+    caps.version = xwf_version();
+    caps
+}
+
+/// returns the version of X-Ways Forensics passed to `XT_Init`
+pub fn xwf_version() -> Option<XtVersion> {
+    *XWF_VERSION.read().unwrap()
+}
+
+/// stores the version passed to `XT_Init` and warns about functions that are missing unexpectedly
+#[doc(hidden)]
+pub fn init_capabilities(version: XtVersion) {
+    *XWF_VERSION.write().unwrap() = Some(version);
+
+    for f in capabilities().unexpectedly_missing(version) {
+        xwfwarn!("{} is not exported by X-Ways Forensics {}.{}, dependent functionality is not available", f, version.major, version.minor);
+    }
+}
+
+// capabilities without the functions of the backend, for checking the version only
+fn version_capabilities() -> Capabilities {
+    Capabilities { version: xwf_version(), missing: Vec::new() }
+}
+
+/// true if the running X-Ways Forensics is at least version `major.minor`, see [`Capabilities::supports`]
+pub fn xwf_supports(major: u16, minor: u16) -> bool {
+    version_capabilities().supports(major, minor)
+}
+
+pub(crate) fn require_xwf_version(feature: &'static str, major: u16, minor: u16) -> Result<(), XwfError> {
+    version_capabilities().require_version(feature, major, minor)
+}
+
+pub(crate) fn require_function(function_name: &'static str) -> Result<(), XwfError> {
+    if has_backend() {
+        backend().capabilities().require(function_name)
+    } else {
+        Ok(())
+    }
+}
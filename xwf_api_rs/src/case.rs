@@ -1,18 +1,17 @@
+use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
-use std::ptr::null_mut;
 use serde::Serialize;
-use winapi::shared::minwindef::LPVOID;
-use winapi::shared::ntdef::{LONG, LPWSTR, PLONG};
 use chrono::{DateTime, Utc};
-use winsafe::WString;
 use crate::application::Application;
-use crate::get_raw_api;
+use std::path::PathBuf;
+use crate::backend::{backend, XwfBackend};
 use crate::evidence::{Evidence, EvidenceIterator};
 use crate::item::Item;
+use crate::volume::Volume;
 use crate::error::XwfError;
 use crate::xwf_types::*;
-use crate::raw_api::RAW_API;
+use crate::capabilities::require_function;
 
 
 
@@ -38,22 +37,35 @@ impl Hash for ReportTable {
 }
 
 impl ReportTable {
-    pub fn find_by_name(list: &Vec<ReportTable>, name: String) -> Option<&ReportTable> {
-        for i in list {
-            if i.name == name {
-                return Some(i);
-            }
-        }
-        None
+    pub fn find_by_name(list: &[ReportTable], name: String) -> Option<&ReportTable> {
+        list.iter().find(|i| i.name == name)
     }
 
-    pub fn find_by_id(list: &Vec<ReportTable>, id: u16) -> Option<&ReportTable> {
-        for i in list {
-            if i.id == id {
-                return Some(&i);
-            }
-        }
-        None
+    pub fn find_by_id(list: &[ReportTable], id: u16) -> Option<&ReportTable> {
+        list.iter().find(|i| i.id == id)
+    }
+}
+
+/// Receives the items visited by [`Case::visit_items`] one after another, so that they can be
+/// processed without collecting them first. Implemented for closures taking the item.
+pub trait ItemVisitor {
+    /// called after the volume snapshot of the evidence was selected, returns the id of the first
+    /// item to visit or `None` to skip the evidence
+    fn begin_evidence(&mut self, _evidence: &Evidence, _volume: &Volume, _num_items: i32) -> Result<Option<i32>, XwfError> {
+        Ok(Some(0))
+    }
+
+    fn visit_item(&mut self, item: Item, volume: &Volume, evidence: &Evidence) -> Result<(), XwfError>;
+
+    /// called after the last item of the evidence was visited
+    fn end_evidence(&mut self, _evidence: &Evidence, _volume: &Volume) -> Result<(), XwfError> {
+        Ok(())
+    }
+}
+
+impl<F> ItemVisitor for F where F: FnMut(Item, &Volume, &Evidence) -> Result<(), XwfError> {
+    fn visit_item(&mut self, item: Item, volume: &Volume, evidence: &Evidence) -> Result<(), XwfError> {
+        self(item, volume, evidence)
     }
 }
 
@@ -76,6 +88,12 @@ pub struct CaseInfo {
 
 
 
+impl Default for Case {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Case {
 
     pub fn new() -> Case {
@@ -86,92 +104,77 @@ impl Case {
         }
     }
 
+    /// collects the results of `item_consumer` for all items of all evidences in memory,
+    /// see [`Case::visit_items`] for processing one item at a time
     pub fn iterate_ext<F, R>(&self, item_consumer: F) -> Result<Vec<R>, XwfError>
     where F: Fn(&Case, &Evidence, &Item) -> Result<R,XwfError> {
         let mut ret:Vec<R> = Vec::new();
-
-        Application::show_progress("Iterating over all evidences and items", ProgressFlags::empty());
-
-        let mut evidence_iterator = EvidenceIterator::new();
-        while let Some(ev) = evidence_iterator.next() {
-
-            let vol = ev.open()?;
-            vol.select()?;
-
-            // get number of elements within volume
-            let num_items = vol.get_item_count()?;
-
-            // set progress description
-            Application::set_progress_description(format!("processing evidence \"{}\"", ev.get_name()?));
-            Application::set_progress_percentage(0, num_items as u32);
-
-
-            // iterate over all items (number == item id)
-            for item_id in 0..num_items {
-                Application::should_stop()?;
-
-                let item = Item::new(item_id as i32);
-                ret.push(item_consumer(self, &ev, &item)?);
-                Application::set_progress_percentage((item_id+1) as u32, num_items as u32);
-            }
-        }
-
-        Application::hide_progress();
+        Case::visit_items(EvidenceIterator::new(), "Iterating over all evidences and items", &mut |item: Item, _: &Volume, ev: &Evidence| {
+            ret.push(item_consumer(self, ev, &item)?);
+            Ok(())
+        })?;
         Ok(ret)
     }
 
+    /// collects the results of `item_consumer` for all items of all evidences in memory,
+    /// see [`Case::visit_items`] for processing one item at a time
     pub fn iterate<F, R>(item_consumer: F) -> Result<Vec<R>, XwfError>
     where F: Fn(Item) -> Result<R,XwfError> {
-
         let mut ret:Vec<R> = Vec::new();
+        Case::visit_items(EvidenceIterator::new(), "Iterating over all evidences and items", &mut |item: Item, _: &Volume, _: &Evidence| {
+            ret.push(item_consumer(item)?);
+            Ok(())
+        })?;
+        Ok(ret)
+    }
 
-        Application::show_progress("Iterating over all evidences and items", ProgressFlags::empty());
-
-        let mut evidence_iterator = EvidenceIterator::new();
-        while let Some(ev) = evidence_iterator.next() {
-
-            let vol = ev.open()?;
-            vol.select()?;
+    /// Passes all items of the evidences to `visitor` in ascending order of item ids, one at a time,
+    /// while showing the progress with the given caption. The volume snapshot of each evidence is
+    /// selected before its items are visited. Stops with [`XwfError::OperationAbortedByUser`]
+    /// if the user aborts.
+    pub fn visit_items<I, V>(evidences: I, caption: &str, visitor: &mut V) -> Result<(), XwfError>
+    where I: IntoIterator, I::Item: Borrow<Evidence>, V: ItemVisitor + ?Sized {
+        Application::show_progress(caption, ProgressFlags::empty());
+        let result = evidences.into_iter().try_for_each(|ev| Case::visit_evidence(ev.borrow(), visitor));
+        Application::hide_progress();
+        result
+    }
 
-            // get number of elements within volume
-            let num_items = vol.get_item_count()?;
+    fn visit_evidence<V: ItemVisitor + ?Sized>(ev: &Evidence, visitor: &mut V) -> Result<(), XwfError> {
+        let volume = ev.open()?;
+        let num_items = volume.select()?;
 
-            // set progress description
-            Application::set_progress_description(format!("processing evidence \"{}\"", ev.get_name()?));
-            Application::set_progress_percentage(0, num_items as u32);
+        let Some(first_item_id) = visitor.begin_evidence(ev, &volume, num_items)? else {
+            return Ok(());
+        };
 
+        Application::set_progress_description(format!("processing evidence \"{}\"", ev.get_name()?));
+        Application::set_progress_percentage(first_item_id as u32, num_items as u32);
 
-            // iterate over all items (number == item id)
-            for item_id in 0..num_items {
-                Application::should_stop()?;
+        // iterate over all items (number == item id)
+        for item_id in first_item_id..num_items {
+            Application::should_stop()?;
 
-                ret.push(item_consumer(Item::new(item_id as i32))?);
-                Application::set_progress_percentage((item_id+1) as u32, num_items as u32);
-            }
+            visitor.visit_item(Item::new(item_id), &volume, ev)?;
+            Application::set_progress_percentage((item_id + 1) as u32, num_items as u32);
         }
-
-        Application::hide_progress();
-        Ok(ret)
+        visitor.end_evidence(ev, &volume)
     }
 
     pub fn contained_in_report_table(&self, t: &Option<&ReportTable>, evidence: &Evidence, item: &Item) -> bool {
         t.and_then(|t| {
             self.report_table_map.get(&evidence.get_id()).and_then(|tables| {
-                tables.get(t).and_then(|itemset| {
-                    Some(itemset.contains(&(item.item_id as u32)))
-                })
+                tables.get(t).map(|itemset| itemset.contains(&(item.item_id as u32)))
             })
         }).unwrap_or(false)
     }
 
     pub fn get_cached_report_tables(&self, evidence: &Evidence, item: &Item) -> Vec<&ReportTable> {
         
-        self.report_table_map.get(&evidence.get_id()).and_then(|report_tables| {
-            Some(report_tables.iter()
+        self.report_table_map.get(&evidence.get_id()).map(|report_tables| report_tables.iter()
             .filter(|i| i.1.contains(&(item.item_id as u32)))
             .map(|i| i.0)
-            .collect())
-        }).unwrap_or(vec![])
+            .collect()).unwrap_or(vec![])
     }
 
     pub fn get_report_table_by_name(&self, name: &str) -> Option<&ReportTable> {
@@ -193,7 +196,7 @@ impl Case {
 
         
         ev.iter().for_each(|e| {
-            e.get_report_table_assocs(false).and_then(|assocs| {
+            if let Some(assocs) = e.get_report_table_assocs(false) {
                 for (table_id,v) in assocs {
                     let table = self.get_report_table_by_id(&table_id).unwrap().clone();
                     let evidence_id = e.get_id();
@@ -217,50 +220,46 @@ impl Case {
                         id_set.insert(item_id);
                     }
                 }
-                Some(())
-            });
+            }
         });
 
         Ok(())
     }
 
     pub fn get_case_infos() -> Result<CaseInfo, XwfError> {
-        let mut buf = [0u16; 256];
-
+        require_function("XWF_GetCaseProp")?;
+        let id = backend().get_case_prop(0, None);
 
-        let id = (get_raw_api!().get_case_prop)(null_mut(), 0, null_mut(), 0);
-
-        let creation = (get_raw_api!().get_case_prop)(null_mut(), 2, null_mut(), 0);
+        let creation = backend().get_case_prop(2, None);
         if creation <= 0 {
             return Err(XwfError::XwfFunctionCallFailed("get_case_prop"));
         }
 
         let creation_date: DateTime<Utc> = DateTime::from_timestamp( creation / 10000000 - 11644473600, 0).ok_or(XwfError::InvalidInputArgument)?;
         
-        let buf_len = (get_raw_api!().get_case_prop)(null_mut(), 3, buf.as_mut_ptr() as LPVOID, buf.len() as LONG);
+        let mut examiner = String::new();
+        let buf_len = backend().get_case_prop(3, Some(&mut examiner));
         if buf_len < 0 {
             return Err(XwfError::XwfFunctionCallFailed("get_case_prop"));
         }
-        let examiner: String = WString::from_wchars_slice(&buf).to_string();
 
-        let buf_len = (get_raw_api!().get_case_prop)(null_mut(), 1, buf.as_mut_ptr() as LPVOID, buf.len() as LONG);
+        let mut title = String::new();
+        let buf_len = backend().get_case_prop(1, Some(&mut title));
         if buf_len < 0 {
             return Err(XwfError::XwfFunctionCallFailed("get_case_prop"));
         }
-        let title = WString::from_wchars_slice(&buf).to_string();
 
-        let buf_len = (get_raw_api!().get_case_prop)(null_mut(), 5, buf.as_mut_ptr() as LPVOID, buf.len() as LONG);
+        let mut file = String::new();
+        let buf_len = backend().get_case_prop(5, Some(&mut file));
         if buf_len < 0 {
             return Err(XwfError::XwfFunctionCallFailed("get_case_prop"));
         }
 
-        let file = WString::from_wchars_slice(&buf).to_string();
-
-        let buf_len = (get_raw_api!().get_case_prop)(null_mut(), 6, buf.as_mut_ptr() as LPVOID, buf.len() as LONG);
+        let mut dir = String::new();
+        let buf_len = backend().get_case_prop(6, Some(&mut dir));
         if buf_len < 0 {
             return Err(XwfError::XwfFunctionCallFailed("get_case_prop"));
         }
-        let dir = WString::from_wchars_slice(&buf).to_string();
 
 
         Ok(CaseInfo {
@@ -277,34 +276,44 @@ impl Case {
 
 
     pub fn get_ev_obj(obj_id: u32) -> Option<Evidence> {
-        Evidence::new((get_raw_api!().get_ev_obj)(obj_id))
+        Evidence::new(backend().get_ev_obj(obj_id))
     }
 
     pub fn get_report_tables() -> Vec<ReportTable> {
-        let mut optional: LONG = 0;
+        let mut optional: i32 = 0;
         let mut ret: Vec<ReportTable> = Vec::new();
         //get num of report tables
-        (get_raw_api!().get_report_table_info)(null_mut(), -1, &mut optional as PLONG);
+        backend().get_report_table_info(-1, &mut optional);
 
         let num_tables = optional;
 
 
         for i in 0..num_tables {
             optional = 0;
-            let wstr_ptr = (get_raw_api!().get_report_table_info)(null_mut(), i, &mut optional as PLONG) as LPWSTR;
-            if wstr_ptr != null_mut() {
-                unsafe {
-                    ret.push(ReportTable {
-                        name: WString::from_wchars_nullt(wstr_ptr).to_string(),
-                        id: i as u16,
-                        flags: ReportTableFlags::from_bits_truncate(optional as u32),
-
-                    });
-                }
+            if let Some(name) = backend().get_report_table_info(i, &mut optional) {
+                ret.push(ReportTable {
+                    name,
+                    id: i as u16,
+                    flags: ReportTableFlags::from_bits_truncate(optional as u32),
+                });
             }
 
         }
-        return ret;
+        ret
 
     }
-}
\ No newline at end of file
+}
+
+/// directory of the currently opened case, the temp directory if no case is open
+pub(crate) fn case_dir<B: XwfBackend + ?Sized>(backend: &B) -> PathBuf {
+    let mut case_dir = String::new();
+    backend.get_case_prop(6, Some(&mut case_dir));
+
+    if case_dir.is_empty() { std::env::temp_dir() } else { PathBuf::from(case_dir) }
+}
+
+/// path of the file `<stem>_<timestamp>.<extension>` in the [`case_dir`]. The timestamp includes
+/// milliseconds, so the file names sort chronologically.
+pub(crate) fn case_file<B: XwfBackend + ?Sized>(backend: &B, stem: &str, extension: &str) -> PathBuf {
+    case_dir(backend).join(format!("{}_{}.{}", stem, chrono::Local::now().format("%Y%m%d_%H%M%S_%3f"), extension))
+}
@@ -0,0 +1,286 @@
+//! Writer for Digital Forensics XML (DFXML), to exchange file system metadata with other
+//! forensic tools. Each volume snapshot becomes a `<volume>` element with a `<fileobject>` per item.
+
+use std::borrow::Borrow;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::backend::backend;
+use crate::case::{case_file, Case, ItemVisitor};
+use crate::error::XwfError;
+use crate::evidence::{Evidence, EvidenceIterator};
+use crate::item::Item;
+use crate::record::{ItemRecord, RecordOptions};
+use crate::volume::{HashType, Volume, VolumeInfo};
+use crate::xwf_types::*;
+
+const DFXML_VERSION: &str = "1.2.0";
+
+/// name of the hash algorithm as used in the `type` attribute of `<hashdigest>`
+pub fn hash_algorithm_name(hash_type: HashType) -> &'static str {
+    match hash_type {
+        HashType::CS8 => "cs8",
+        HashType::CS16 => "cs16",
+        HashType::CS32 => "cs32",
+        HashType::CS64 => "cs64",
+        HashType::CRC16 => "crc16",
+        HashType::CRC32 => "crc32",
+        HashType::MD5 => "md5",
+        HashType::SHA1 => "sha1",
+        HashType::SHA256 => "sha256",
+        HashType::RIPEMD128 => "ripemd128",
+        HashType::RIPEMD160 => "ripemd160",
+        HashType::MD4 => "md4",
+        HashType::ED2K => "ed2k",
+        HashType::ADLER32 => "adler32",
+        HashType::TigerTreeHash => "tth",
+        HashType::Tiger128 => "tiger128",
+        HashType::Tiger160 => "tiger160",
+        HashType::Tiger192 => "tiger192",
+        HashType::MD5Folded => "md5folded",
+    }
+}
+
+// escapes markup characters and replaces characters that are not allowed in XML 1.0
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' || c == '\u{fffe}' || c == '\u{ffff}' => escaped.push(char::REPLACEMENT_CHARACTER),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// the volume snapshot whose items are written
+struct CurrentVolume {
+    info: Option<VolumeInfo>,
+    hash_types: [Option<HashType>; 2],
+}
+
+/// Streams DFXML to a file. Items are written either for whole evidences with
+/// [`DfxmlWriter::export_evidences`], or one by one between [`DfxmlWriter::begin_volume`] and
+/// [`DfxmlWriter::end_volume`], e.g. in `xt_prepare`, `xt_process_item` and `xt_finalize`.
+///
+/// ```ignore
+/// let mut writer = DfxmlWriter::create_in_case_dir("my_xtension")?;
+/// writer.export_all()?;
+/// writer.finish()?;
+/// ```
+pub struct DfxmlWriter {
+    out: BufWriter<Box<dyn Write + Send>>,
+    path: Option<PathBuf>,
+    volume: Option<CurrentVolume>,
+    exported: usize,
+}
+
+impl DfxmlWriter {
+    /// writes the XML declaration and the DFXML header
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Result<DfxmlWriter, XwfError> {
+        let mut writer = DfxmlWriter {
+            out: BufWriter::new(Box::new(writer)),
+            path: None,
+            volume: None,
+            exported: 0,
+        };
+        writer.write_header()?;
+        Ok(writer)
+    }
+
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<DfxmlWriter, XwfError> {
+        let mut writer = DfxmlWriter::new(File::create(path.as_ref())?)?;
+        writer.path = Some(path.as_ref().to_path_buf());
+        Ok(writer)
+    }
+
+    /// creates the file `<name>_<timestamp>.dfxml` in the case directory
+    pub fn create_in_case_dir(name: &str) -> Result<DfxmlWriter, XwfError> {
+        DfxmlWriter::create(case_file(&*backend(), name, "dfxml"))
+    }
+
+    /// `None` if the writer was created with [`DfxmlWriter::new`]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// number of file objects written so far
+    pub fn exported(&self) -> usize {
+        self.exported
+    }
+
+    fn write_header(&mut self) -> Result<(), XwfError> {
+        let mut case_title = String::new();
+        backend().get_case_prop(1, Some(&mut case_title));
+
+        writeln!(self.out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(self.out, "<dfxml xmlns=\"http://www.forensicswiki.org/wiki/Category:Digital_Forensics_XML\" \
+                            xmlns:dc=\"http://purl.org/dc/elements/1.1/\" version=\"{}\">", DFXML_VERSION)?;
+        writeln!(self.out, "  <metadata>")?;
+        writeln!(self.out, "    <dc:type>File system metadata</dc:type>")?;
+        if !case_title.is_empty() {
+            writeln!(self.out, "    <dc:title>{}</dc:title>", escape_xml(&case_title))?;
+        }
+        writeln!(self.out, "  </metadata>")?;
+        writeln!(self.out, "  <creator>")?;
+        writeln!(self.out, "    <program>{}</program>", env!("CARGO_PKG_NAME"))?;
+        writeln!(self.out, "    <version>{}</version>", env!("CARGO_PKG_VERSION"))?;
+        writeln!(self.out, "    <execution_environment>")?;
+        writeln!(self.out, "      <start_time>{}</start_time>", chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ"))?;
+        writeln!(self.out, "    </execution_environment>")?;
+        writeln!(self.out, "  </creator>")?;
+        Ok(())
+    }
+
+    /// starts the `<volume>` element of the evidence. The volume snapshot has to be selected.
+    pub fn begin_volume(&mut self, volume: &Volume, evidence: &Evidence) -> Result<(), XwfError> {
+        if self.volume.is_some() {
+            self.end_volume()?;
+        }
+
+        let info = volume.get_information().ok();
+        writeln!(self.out, "  <volume>")?;
+        writeln!(self.out, "    <!-- {} -->", escape_xml(&evidence.get_name()?).replace("--", "- -"))?;
+        if let Some(info) = &info {
+            writeln!(self.out, "    <ftype_str>{}</ftype_str>", escape_xml(&info.file_system.to_string()))?;
+            if info.cluster_size() > 0 {
+                writeln!(self.out, "    <block_size>{}</block_size>", info.cluster_size())?;
+            }
+            if info.cluster_count > 0 {
+                writeln!(self.out, "    <block_count>{}</block_count>", info.cluster_count)?;
+            }
+        }
+
+        self.volume = Some(CurrentVolume {
+            info,
+            hash_types: [volume.get_hash_type(false), volume.get_hash_type(true)],
+        });
+        Ok(())
+    }
+
+    /// ends the `<volume>` element started with [`DfxmlWriter::begin_volume`]
+    pub fn end_volume(&mut self) -> Result<(), XwfError> {
+        if self.volume.take().is_some() {
+            writeln!(self.out, "  </volume>")?;
+        }
+        Ok(())
+    }
+
+    /// writes the `<fileobject>` of an item, the `<volume>` is started if necessary
+    pub fn write_item(&mut self, item: &Item, volume: &Volume, evidence: &Evidence) -> Result<(), XwfError> {
+        if self.volume.is_none() {
+            self.begin_volume(volume, evidence)?;
+        }
+        let record = ItemRecord::new(item, volume, evidence,
+                                     RecordOptions::Path | RecordOptions::Timestamps | RecordOptions::Hashes)?;
+        let byte_runs = self.byte_runs(item, &record);
+
+        let mut xml = String::from("    <fileobject>\n");
+        let mut element = |name: &str, value: &str| {
+            xml.push_str(&format!("      <{0}>{1}</{0}>\n", name, escape_xml(value)));
+        };
+
+        // DFXML file names are relative to the root directory and separated by slashes
+        let path = record.path.as_deref().unwrap_or(&record.name);
+        element("filename", path.trim_start_matches('\\').replace('\\', "/").as_str());
+        element("id", &record.item_id.to_string());
+        element("name_type", if record.flags.contains(ItemInfoFlags::IsDirectory) { "d" } else { "r" });
+        if let Some(size) = record.size {
+            element("filesize", &size.to_string());
+        }
+        element("alloc", if record.deletion == ItemInfoDeletion::Existing { "1" } else { "0" });
+
+        for (name, time) in [
+            ("mtime", &record.modification_time),
+            ("ctime", &record.entry_modification_time),
+            ("atime", &record.last_access_time),
+            ("crtime", &record.creation_time),
+            ("dtime", &record.deletion_time),
+        ] {
+            if let Some(time) = time {
+                element(name, &time.to_rfc3339());
+            }
+        }
+
+        if let Some((fs_offset, len)) = byte_runs {
+            let len = len.map(|len| format!(" len=\"{}\"", len)).unwrap_or_default();
+            xml.push_str("      <byte_runs>\n");
+            xml.push_str(&format!("        <byte_run file_offset=\"0\" fs_offset=\"{}\"{}/>\n", fs_offset, len));
+            xml.push_str("      </byte_runs>\n");
+        }
+
+        let hash_types = self.volume.as_ref().map(|v| v.hash_types).unwrap_or_default();
+        for (hash_type, hash) in hash_types.iter().zip([&record.hash1, &record.hash2]) {
+            if let (Some(hash_type), Some(hash)) = (hash_type, hash) {
+                xml.push_str(&format!("      <hashdigest type=\"{}\">{}</hashdigest>\n", hash_algorithm_name(*hash_type), hash));
+            }
+        }
+        xml.push_str("    </fileobject>\n");
+
+        self.out.write_all(xml.as_bytes())?;
+        self.exported += 1;
+        Ok(())
+    }
+
+    // offset of the item data relative to the volume and its length. The data of carved files is
+    // contiguous, for other files only the start is known since they may be fragmented.
+    fn byte_runs(&self, item: &Item, record: &ItemRecord) -> Option<(u64, Option<u64>)> {
+        let (def_ofs, start_sector) = item.get_item_offset().ok().flatten()?;
+
+        if record.deletion == ItemInfoDeletion::CarvedFile {
+            return Some((def_ofs as u64, record.size));
+        }
+        let bytes_per_sector = self.volume.as_ref()?.info?.bytes_per_sector as u64;
+        if bytes_per_sector == 0 {
+            return None;
+        }
+        Some((start_sector as u64 * bytes_per_sector, None))
+    }
+
+    /// writes a `<volume>` with all items for each evidence with [`Case::visit_items`].
+    /// Returns the number of written items.
+    pub fn export_evidences(&mut self, evidences: &[Evidence]) -> Result<usize, XwfError> {
+        self.export(evidences)
+    }
+
+    /// exports all evidences of the case
+    pub fn export_all(&mut self) -> Result<usize, XwfError> {
+        self.export(EvidenceIterator::new())
+    }
+
+    fn export<I>(&mut self, evidences: I) -> Result<usize, XwfError> where I: IntoIterator, I::Item: Borrow<Evidence> {
+        let exported = self.exported;
+        let result = Case::visit_items(evidences, "Exporting DFXML", self);
+        self.out.flush()?;
+        result.map(|()| self.exported - exported)
+    }
+
+    /// closes the open elements, flushes the file and returns the number of written items
+    pub fn finish(mut self) -> Result<usize, XwfError> {
+        self.end_volume()?;
+        writeln!(self.out, "</dfxml>")?;
+        self.out.flush()?;
+        Ok(self.exported)
+    }
+}
+
+impl ItemVisitor for DfxmlWriter {
+    fn begin_evidence(&mut self, evidence: &Evidence, volume: &Volume, _num_items: i32) -> Result<Option<i32>, XwfError> {
+        self.begin_volume(volume, evidence)?;
+        Ok(Some(0))
+    }
+
+    fn visit_item(&mut self, item: Item, volume: &Volume, evidence: &Evidence) -> Result<(), XwfError> {
+        self.write_item(&item, volume, evidence)
+    }
+
+    fn end_evidence(&mut self, _evidence: &Evidence, _volume: &Volume) -> Result<(), XwfError> {
+        self.end_volume()
+    }
+}
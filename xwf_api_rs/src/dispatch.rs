@@ -0,0 +1,350 @@
+//! Invocation of the X-Tension callbacks with argument conversion and error handling.
+//!
+//! The exported `XT_*` functions, the [`simulator`](crate::simulator) and replayed traces all call
+//! the X-Tension through this module ([`crate::mt::MtInstance`] for multi-threaded X-Tensions),
+//! so X-Tensions behave the same no matter who the caller is. Panics are caught by the caller,
+//! see [`crate::panic_guard::catch_panic`].
+
+use std::fmt::Display;
+use std::mem;
+use std::sync::{Mutex, MutexGuard};
+use std::thread::{self, ThreadId};
+use crate::capabilities::init_capabilities;
+use crate::evidence::Evidence;
+use crate::ffi_types::PVOID;
+use crate::item::{Item, ItemHandle};
+use crate::panic_guard::PanicReturns;
+use crate::search::{PrepareSearch, SearchHit};
+use crate::traits::XTension;
+use crate::util::check_supported_xwf_version;
+use crate::volume::Volume;
+use crate::window::Window;
+use crate::xwf_types::*;
+use crate::xwferror;
+
+/// Owns the instance of a single-threaded X-Tension and dispatches the X-Tension callbacks to it.
+/// Used as `static` by the export macros, so no `static mut` is needed.
+///
+/// X-Ways Forensics calls `XT_ProcessSearchHit` from within `XWF_Search`, so a search with
+/// [`SearchFlags::CallProcessSearchHit`] started by a callback reports its hits while the X-Tension
+/// is still borrowed by that callback. These hits are passed to `xt_process_search_hit` right after
+/// the callback returned, stopping the search from there is not possible.
+pub struct StInstance<T: XTension> {
+    extension: Mutex<Option<T>>,
+    // thread that runs a callback of the X-Tension
+    running: Mutex<Option<ThreadId>>,
+    // search hits reported while a callback was running
+    deferred_hits: Mutex<Vec<SearchHit>>,
+}
+
+impl<T: XTension> Default for StInstance<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: XTension> StInstance<T> {
+    pub const fn new() -> StInstance<T> {
+        StInstance {
+            extension: Mutex::new(None),
+            running: Mutex::new(None),
+            deferred_hits: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// gives access to the X-Tension, if `XT_Init` was called
+    pub fn extension(&self) -> MutexGuard<'_, Option<T>> {
+        assert!(!self.is_running(), "X-Tension callback called while another callback is running");
+        self.extension.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// return values passed to X-Ways Forensics if one of the callbacks panics
+    pub fn panic_returns(&self) -> PanicReturns {
+        T::panic_returns()
+    }
+
+    fn is_running(&self) -> bool {
+        *self.running.lock().unwrap_or_else(|e| e.into_inner()) == Some(thread::current().id())
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R where T::XTensionError: Display {
+        let mut ext = self.extension();
+        let ext = ext.as_mut().expect("X-Tension is not initialized");
+        let _running = Running::enter(&self.running);
+        let ret = f(ext);
+
+        // hits of searches started by the callback, or by the hits' callbacks
+        loop {
+            let hits = mem::take(&mut *self.deferred_hits.lock().unwrap_or_else(|e| e.into_inner()));
+            if hits.is_empty() {
+                break;
+            }
+            for hit in hits {
+                if xt_process_search_hit(ext, hit) == XtProcessSearchHitReturn::StopSearch as i32 {
+                    self.deferred_hits.lock().unwrap_or_else(|e| e.into_inner()).clear();
+                    break;
+                }
+            }
+        }
+        ret
+    }
+}
+
+// marks the current thread as running a callback until it is dropped, also if the callback panics
+struct Running<'a>(&'a Mutex<Option<ThreadId>>);
+
+impl Running<'_> {
+    fn enter(running: &Mutex<Option<ThreadId>>) -> Running<'_> {
+        *running.lock().unwrap_or_else(|e| e.into_inner()) = Some(thread::current().id());
+        Running(running)
+    }
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl<T: XTension> StInstance<T> where T::XTensionError: Display {
+    pub fn xt_init(&self, version: XtVersion, flags: XtInitFlags, window: Option<Window>) -> i32 {
+        xt_init(self.extension().insert(T::create()), version, flags, window)
+    }
+
+    pub fn xt_done(&self) -> i32 {
+        let ext = self.extension().take();
+        ext.map(|mut ext| xt_done(&mut ext)).unwrap_or(0)
+    }
+
+    pub fn xt_about(&self, window: Option<Window>) -> i32 {
+        self.with(|ext| xt_about(ext, window))
+    }
+
+    pub fn xt_prepare(&self, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32 {
+        self.with(|ext| xt_prepare(ext, volume, evidence, op_type))
+    }
+
+    pub fn xt_finalize(&self, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32 {
+        self.with(|ext| xt_finalize(ext, volume, evidence, op_type))
+    }
+
+    pub fn xt_process_item(&self, item_id: i32) -> i32 {
+        self.with(|ext| xt_process_item(ext, item_id))
+    }
+
+    pub fn xt_process_item_ex(&self, item_id: i32, item: XwfHandle) -> i32 {
+        self.with(|ext| xt_process_item_ex(ext, item_id, item))
+    }
+
+    pub fn xt_prepare_search(&self, search: &mut PrepareSearch) -> i32 {
+        self.with(|ext| xt_prepare_search(ext, search))
+    }
+
+    pub fn xt_process_search_hit(&self, hit: SearchHit) -> i32 {
+        if self.is_running() {
+            self.deferred_hits.lock().unwrap_or_else(|e| e.into_inner()).push(hit);
+            return XtProcessSearchHitReturn::Ok.into();
+        }
+        self.with(|ext| xt_process_search_hit(ext, hit))
+    }
+
+    pub fn xt_view(&self, item_id: i32, item: XwfHandle, volume: XwfHandle, evidence: XwfHandle) -> Option<Vec<u8>> {
+        self.with(|ext| xt_view(ext, item_id, item, volume, evidence))
+    }
+}
+
+pub(crate) fn xt_init<T: XTension>(ext: &mut T, version: XtVersion, flags: XtInitFlags, window: Option<Window>) -> i32 where T::XTensionError: Display {
+    if !check_version(version, T::min_xwf_version()) {
+        return XtInitReturn::PreventFurtherUseOfDll.into();
+    }
+    init_return(ext.xt_init(version, flags, window, XtLicenseInfo {}))
+}
+
+pub(crate) fn xt_done<T: XTension>(ext: &mut T) -> i32 where T::XTensionError: Display {
+    log_error("XT_Done", ext.xt_done());
+    0
+}
+
+pub(crate) fn xt_about<T: XTension>(ext: &mut T, window: Option<Window>) -> i32 where T::XTensionError: Display {
+    log_error("XT_About", ext.xt_about(window));
+    0
+}
+
+pub(crate) fn xt_prepare<T: XTension>(ext: &mut T, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32 where T::XTensionError: Display {
+    crate::tree::invalidate();
+    prepare_return(ext.xt_prepare(Volume::new(volume).ok(), Evidence::new(evidence), op_type))
+}
+
+pub(crate) fn xt_finalize<T: XTension>(ext: &mut T, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32 where T::XTensionError: Display {
+    finalize_return(ext.xt_finalize(Volume::new(volume).ok(), Evidence::new(evidence), op_type))
+}
+
+pub(crate) fn xt_process_item<T: XTension>(ext: &mut T, item_id: i32) -> i32 where T::XTensionError: Display {
+    process_item_return(item_id, ext.xt_process_item(Item::new(item_id)))
+}
+
+pub(crate) fn xt_process_item_ex<T: XTension>(ext: &mut T, item_id: i32, item: XwfHandle) -> i32 where T::XTensionError: Display {
+    match item_handle(item_id, item) {
+        Some(handle) => process_item_ex_return(item_id, ext.xt_process_item_ex(handle)),
+        None => XtProcessItemExReturn::StopCurrentOperation.into(),
+    }
+}
+
+pub(crate) fn xt_prepare_search<T: XTension>(ext: &mut T, search: &mut PrepareSearch) -> i32 where T::XTensionError: Display {
+    let before = search.clone();
+    let res = ext.xt_prepare_search(search);
+    prepare_search_return(search, before, res)
+}
+
+pub(crate) fn xt_process_search_hit<T: XTension>(ext: &mut T, hit: SearchHit) -> i32 where T::XTensionError: Display {
+    let item_id = hit.item_id;
+    process_search_hit_return(item_id, ext.xt_process_search_hit(hit))
+}
+
+pub(crate) fn xt_view<T: XTension>(ext: &mut T, item_id: i32, item: XwfHandle, volume: XwfHandle, evidence: XwfHandle) -> Option<Vec<u8>> where T::XTensionError: Display {
+    let handle = ItemHandle::new(item, Item::new(item_id)).ok()?;
+    view_return(ext.xt_view(handle, Volume::new(volume).ok(), Evidence::new(evidence)))
+}
+
+// conversion of the callback results into the return values of the XT_* functions,
+// shared with the multi-threaded X-Tensions
+
+// checks the version of X-Ways Forensics, false if the X-Tension must not be used
+pub(crate) fn check_version(version: XtVersion, min_version: (u16, u16)) -> bool {
+    if let Err(e) = check_supported_xwf_version(version, min_version) {
+        xwferror!("X-Tension API version check failed: {}", e);
+        return false;
+    }
+    init_capabilities(version);
+    true
+}
+
+pub(crate) fn log_error<E: Display>(callback: &str, res: Result<(), E>) {
+    if let Err(e) = res {
+        xwferror!("{}: {}", callback, e);
+    }
+}
+
+pub(crate) fn init_return<E: Display>(res: Result<XtInitReturn, E>) -> i32 {
+    match res {
+        Ok(ret) => ret.into(),
+        Err(e) => {
+            xwferror!("XT_Init: {}", e);
+            XtInitReturn::PreventFurtherUseOfDll.into()
+        }
+    }
+}
+
+pub(crate) fn prepare_return<E: Display>(res: Result<XtPrepareReturn, E>) -> i32 {
+    match res {
+        Ok(ret) => ret.into(),
+        Err(e) => {
+            xwferror!("XT_Prepare: {}", e);
+            XtPrepareNegativeReturn::JustCallXtFinalize.into()
+        }
+    }
+}
+
+pub(crate) fn finalize_return<E: Display>(res: Result<XtFinalizeReturn, E>) -> i32 {
+    match res {
+        Ok(ret) => ret.into(),
+        Err(e) => {
+            xwferror!("XT_Finalize: {}", e);
+            XtPrepareNegativeReturn::JustCallXtFinalize.into()
+        }
+    }
+}
+
+pub(crate) fn process_item_return<E: Display>(item_id: i32, res: Result<XtProcessItemReturn, E>) -> i32 {
+    match res {
+        Ok(ret) => ret.into(),
+        Err(e) => {
+            xwferror!("XT_ProcessItem: error occurred in processing item id {}", item_id);
+            xwferror!("XT_ProcessItem: {}", e);
+            xwferror!("XT_ProcessItem: stopping operation due to previous error");
+            XtProcessItemReturn::StopCurrentOperation.into()
+        }
+    }
+}
+
+pub(crate) fn item_handle(item_id: i32, item: XwfHandle) -> Option<ItemHandle> {
+    match ItemHandle::new(item, Item::new(item_id)) {
+        Ok(handle) => Some(handle),
+        Err(_) => {
+            xwferror!("failed to parse hItem Argument");
+            xwferror!("XT_ProcessItemEx: stopping operation due to previous error");
+            None
+        }
+    }
+}
+
+pub(crate) fn process_item_ex_return<E: Display>(item_id: i32, res: Result<XtProcessItemExReturn, E>) -> i32 {
+    match res {
+        Ok(ret) => ret.into(),
+        Err(e) => {
+            xwferror!("XT_ProcessItemEx: error occurred in processing item id {}", item_id);
+            xwferror!("XT_ProcessItemEx: {}", e);
+            xwferror!("XT_ProcessItemEx: stopping operation due to previous error");
+            XtProcessItemExReturn::StopCurrentOperation.into()
+        }
+    }
+}
+
+// 1 if the X-Tension changed the search settings, which are reset on error
+pub(crate) fn prepare_search_return<E: Display>(search: &mut PrepareSearch, before: PrepareSearch, res: Result<(), E>) -> i32 {
+    match res {
+        Ok(()) => (*search != before) as i32,
+        Err(e) => {
+            xwferror!("XT_PrepareSearch: {}", e);
+            *search = before;
+            0
+        }
+    }
+}
+
+pub(crate) fn process_search_hit_return<E: Display>(item_id: i32, res: Result<XtProcessSearchHitReturn, E>) -> i32 {
+    match res {
+        Ok(ret) => ret.into(),
+        Err(e) => {
+            xwferror!("XT_ProcessSearchHit: error occurred in processing search hit in item id {}", item_id);
+            xwferror!("XT_ProcessSearchHit: {}", e);
+            xwferror!("XT_ProcessSearchHit: stopping search due to previous error");
+            XtProcessSearchHitReturn::StopSearch.into()
+        }
+    }
+}
+
+// an empty buffer cannot be told apart from "not viewable" by X-Ways Forensics
+pub(crate) fn view_return<E: Display>(res: Result<Option<Vec<u8>>, E>) -> Option<Vec<u8>> {
+    match res {
+        Ok(data) => data.filter(|d| !d.is_empty()),
+        Err(e) => {
+            xwferror!("XT_View: {}", e);
+            None
+        }
+    }
+}
+
+// buffers returned by XT_View, owned by the X-Tension until X-Ways Forensics calls XT_ReleaseMem
+static VIEW_BUFFERS: Mutex<Vec<Box<[u8]>>> = Mutex::new(Vec::new());
+
+/// keeps `data` alive until it is released by [`release_view_buffer`], returns the pointer and size
+/// passed to X-Ways Forensics as result of `XT_View`
+pub fn into_view_buffer(data: Vec<u8>) -> (PVOID, i64) {
+    let buffer = data.into_boxed_slice();
+    let ret = (buffer.as_ptr() as PVOID, buffer.len() as i64);
+    VIEW_BUFFERS.lock().unwrap_or_else(|e| e.into_inner()).push(buffer);
+    ret
+}
+
+/// frees a buffer returned by `XT_View`, false if `buffer` was not returned by [`into_view_buffer`]
+pub fn release_view_buffer(buffer: PVOID) -> bool {
+    let mut buffers = VIEW_BUFFERS.lock().unwrap_or_else(|e| e.into_inner());
+    match buffers.iter().position(|b| b.as_ptr() as PVOID == buffer) {
+        Some(idx) => {
+            buffers.swap_remove(idx);
+            true
+        }
+        None => false,
+    }
+}
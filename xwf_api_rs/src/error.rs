@@ -22,6 +22,15 @@ pub enum XwfError {
     InvalidVersionNumber,
     IncompatibleXwfVersion(XtVersion, (u16, u16)),
     IoError(io::Error),
+    InvalidFixture(String),
+    InvalidJournal(String),
+    FunctionNotAvailable(&'static str),
+    RequiresXwfVersion(&'static str, (u16, u16)),
+    NotAllowedInWorkerThread(&'static str),
+    InvalidVolumeGeometry,
+    ReadVolumeDataFailed(u64),
+    PathNotFound(String),
+    DatabaseError(String),
 }
 
 
@@ -44,11 +53,37 @@ impl Display for XwfError {
             XwfError::MaxItemIdExceeded => write!(f, "maximum ItemID exceeded (max of int32). This should never happen, but if it does anyway, it would probably lead to unexpected behaviour"),
             XwfError::InvalidVersionNumber => write!(f, "invalid X-Ways Version Number detected"),
             XwfError::IncompatibleXwfVersion(version, expected) => write!(f, "XWF Incompatibility detected. \
-            Current Version {}.{} SR-{}, minimal required version {}.{} \
-            Consider upgrading XWF or downgrading API level of xwf-api-rs (feature \"api_<major>_<minor>\")", version.major, version.minor, version.service_release,  expected.0, expected.1),
+            Current Version {}.{} SR-{}, minimal required version {}.{}", version.major, version.minor, version.service_release,  expected.0, expected.1),
             XwfError::IoError(e) => write!(f, "io error occurred: {}", e),
+            XwfError::InvalidFixture(e) => write!(f, "invalid case fixture: {}", e),
+            XwfError::InvalidJournal(e) => write!(f, "invalid journal: {}", e),
+            XwfError::FunctionNotAvailable(func) => write!(f, "XWF function {} is not available in this version of X-Ways Forensics", func),
+            XwfError::RequiresXwfVersion(feature, version) => write!(f, "{} requires X-Ways Forensics {}.{} or later", feature, version.0, version.1),
+            XwfError::NotAllowedInWorkerThread(func) => write!(f, "{} must not be called from a worker thread of a multi-threaded X-Tension", func),
+            XwfError::InvalidVolumeGeometry => write!(f, "invalid sector or cluster size of volume"),
+            XwfError::ReadVolumeDataFailed(offset) => write!(f, "failed to read volume data at offset {}", offset),
+            XwfError::PathNotFound(path) => write!(f, "no item found at path {}", path),
+            XwfError::DatabaseError(e) => write!(f, "database error: {}", e),
         }
     }
 }
 
-impl Error for XwfError {}
\ No newline at end of file
+impl Error for XwfError {}
+
+impl XwfError {
+    /// converts an io error into an XwfError, unwrapping XwfErrors that were passed through io,
+    /// e.g. by [`crate::reader::ItemReader`]
+    pub fn from_io(e: io::Error) -> XwfError {
+        if e.get_ref().is_some_and(|inner| inner.is::<XwfError>()) {
+            *e.into_inner().unwrap().downcast::<XwfError>().unwrap()
+        } else {
+            XwfError::IoError(e)
+        }
+    }
+}
+
+impl From<io::Error> for XwfError {
+    fn from(e: io::Error) -> Self {
+        XwfError::from_io(e)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,127 @@
+//! Event list of X-Ways Forensics: reading the timeline events of an evidence object and adding
+//! events for artefacts parsed by the X-Tension (e.g. browser visits, log entries).
+
+use serde::{Deserialize, Serialize};
+use crate::backend::backend;
+use crate::capabilities::require_function;
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::xwf_types::*;
+
+/// Event of the event list of an evidence object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    pub evidence: XwfHandle,
+    pub event_type: EventType,
+    pub flags: EventFlags,
+    pub timestamp: XwfDateTime,
+    /// -1 if the event does not belong to an item
+    pub item_id: i32,
+    /// offset of the event data within the item, -1 if not available
+    pub offset: i64,
+    pub description: String,
+}
+
+impl Event {
+    pub fn new<S: Into<String>>(evidence: &Evidence, event_type: EventType, timestamp: XwfDateTime, description: S) -> Event {
+        Event {
+            evidence: evidence.handle(),
+            event_type,
+            flags: EventFlags::empty(),
+            timestamp,
+            item_id: -1,
+            offset: -1,
+            description: description.into(),
+        }
+    }
+
+    /// the item the event was found in
+    pub fn with_item(mut self, item: &Item) -> Event {
+        self.item_id = item.item_id;
+        self
+    }
+
+    /// offset of the event data within the item
+    pub fn with_offset(mut self, offset: i64) -> Event {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: EventFlags) -> Event {
+        self.flags = flags;
+        self
+    }
+
+    pub fn get(evidence: &Evidence, event_no: u32) -> Option<Event> {
+        if require_function("XWF_GetEvent").is_err() {
+            return None;
+        }
+        backend().get_event(evidence.handle(), event_no)
+    }
+
+    /// iterates over all events of the given evidence object
+    pub fn iter(evidence: &Evidence) -> Result<EventIterator, XwfError> {
+        require_function("XWF_GetEvent")?;
+        Ok(EventIterator { evidence: evidence.handle(), next_event_no: 0 })
+    }
+
+    /// adds the event to the event list of its evidence object
+    pub fn add(&self) -> Result<(), XwfError> {
+        require_function("XWF_AddEvent")?;
+        if self.evidence.is_null() {
+            return Err(XwfError::InputHandleIsNull);
+        }
+        if backend().add_event(self) > 0 {
+            Ok(())
+        } else {
+            Err(XwfError::XwfFunctionCallFailed("add_event"))
+        }
+    }
+
+    pub fn evidence(&self) -> Option<Evidence> {
+        Evidence::new(self.evidence)
+    }
+
+    pub fn item(&self) -> Option<Item> {
+        if self.item_id < 0 { None } else { Some(Item::new(self.item_id)) }
+    }
+
+    /// flags as passed to X-Ways Forensics, timestamps without time zone are marked
+    /// with [`EventFlags::TimestampNotInUTC`]
+    pub fn raw_flags(&self) -> EventFlags {
+        match self.timestamp {
+            XwfDateTime::NoTimezone(_) => self.flags | EventFlags::TimestampNotInUTC,
+            _ => self.flags,
+        }
+    }
+
+    /// creates an event from the fields of an `EventInfo`, the description is not part of the struct
+    pub fn from_raw(info: &EventInfo, description: String) -> Option<Event> {
+        let flags = EventFlags::from_bits_retain(info.n_flags);
+        Some(Event {
+            evidence: info.h_evidence,
+            event_type: EventType::from(info.n_evt_type),
+            flags,
+            timestamp: XwfDateTime::from_filetime(info.time_stamp, flags.contains(EventFlags::TimestampNotInUTC))?,
+            item_id: info.n_item_id,
+            offset: info.n_ofs,
+            description,
+        })
+    }
+}
+
+pub struct EventIterator {
+    evidence: XwfHandle,
+    next_event_no: u32,
+}
+
+impl Iterator for EventIterator {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = backend().get_event(self.evidence, self.next_event_no)?;
+        self.next_event_no += 1;
+        Some(event)
+    }
+}
@@ -1,29 +1,37 @@
 use std::collections::HashMap;
-use std::ptr::{null, null_mut};
-use winapi::shared::minwindef::DWORD;
-use winapi::shared::ntdef::{HANDLE, LONG, LPWSTR, PVOID};
-use winsafe::WString;
-use crate::get_raw_api;
+use serde::{Deserialize, Serialize};
+use crate::ffi_types::LONG;
+use crate::xwf_types::XwfHandle;
+use crate::backend::backend;
 use crate::volume::Volume;
 use crate::error::XwfError;
 
-use crate::raw_api::RAW_API;
 use crate::xwf_types::*;
-
+use crate::capabilities::require_function;
+use crate::mt::require_main_thread;
+use crate::event::{Event, EventIterator};
+
+/// Evidence object of the case.
+///
+/// `Send` and `Sync`: it only holds the handle and ids of the evidence object, which X-Ways
+/// Forensics keeps valid for all threads while the case is open. The property getters only read.
+/// [`Evidence::close`] changes the global state and fails in worker threads of multi-threaded X-Tensions.
 #[derive(Clone)]
 pub struct Evidence {
-    evidence_handle: HANDLE,
+    evidence_handle: XwfHandle,
     child_evidence_id: Option<u32>,
     id: u32,
     short_id: u16
 }
 
-#[repr(packed(1))]
+#[repr(C, packed(1))]
 #[derive(Copy,Clone)]
-struct ReportTableListItemPacked {
+pub(crate) struct ReportTableListItemPacked {
     pub report_table_id: u16,
     pub item_id: u32,
 }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ReportTableListItem {
     pub report_table_id: u16,
     pub item_id: u32,
@@ -33,22 +41,12 @@ pub struct ReportTableListItem {
 
 
 impl ReportTableListItem {
-    pub fn insert_to_map(ptr: *const u8, num_elements: i32, table_map: &mut ReportTableMap) {
-        let list_ptr = ptr as *const ReportTableListItemPacked;
-        let mut idx: i32 = 0;
-        while idx < num_elements {
-            unsafe {
-                let item = *(list_ptr.add(idx as usize));
-                let report_table_id = item.report_table_id;
-                let item_id = item.item_id;
-                match table_map.get_mut(&report_table_id) {
-                    Some(x) => { x.push(item_id); },
-                    None => {table_map.insert(report_table_id, vec![item_id]);}
-
-                }
+    pub fn insert_to_map(list: &[ReportTableListItem], table_map: &mut ReportTableMap) {
+        for item in list {
+            match table_map.get_mut(&item.report_table_id) {
+                Some(x) => { x.push(item.item_id); },
+                None => {table_map.insert(item.report_table_id, vec![item.item_id]);}
             }
-            idx+=1;
-
         }
     }
 }
@@ -56,7 +54,7 @@ impl ReportTableListItem {
 pub type ReportTableMap = HashMap<u16, Vec<u32>>;
 
 pub struct EvidenceIterator {
-    current_ev: HANDLE,
+    current_ev: XwfHandle,
 }
 
 impl Iterator for EvidenceIterator {
@@ -66,8 +64,8 @@ impl Iterator for EvidenceIterator {
 
         let ev = Evidence::new(self.current_ev);
 
-        if self.current_ev != null_mut() {
-            let next_ev_obj = (get_raw_api!().get_next_ev_obj)(self.current_ev, null_mut());
+        if !self.current_ev.is_null() {
+            let next_ev_obj = backend().get_next_ev_obj(self.current_ev);
             self.current_ev = next_ev_obj;
         }
 
@@ -76,19 +74,25 @@ impl Iterator for EvidenceIterator {
     }
 }
 
+impl Default for EvidenceIterator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EvidenceIterator {
     pub fn new() -> EvidenceIterator {
         match Evidence::get_first_evidence() {
             Some(ev) => ev.iter(),
-            None => EvidenceIterator{ current_ev: null_mut()}
+            None => EvidenceIterator{ current_ev: XwfHandle::NULL}
         }
 
     }
 }
 
 impl Evidence {
-    pub fn new(evidence_handle: HANDLE) -> Option<Evidence> {
-        if evidence_handle == null_mut() {
+    pub fn new(evidence_handle: XwfHandle) -> Option<Evidence> {
+        if evidence_handle.is_null() {
             return None
         }
 
@@ -98,8 +102,8 @@ impl Evidence {
         Some(Evidence{
             evidence_handle,
             child_evidence_id: None,
-            id: id,
-            short_id: short_id,
+            id,
+            short_id,
         })  
     }
 
@@ -107,19 +111,20 @@ impl Evidence {
         EvidenceIterator { current_ev: self.handle() }
     }
 
-    pub fn handle(&self) -> HANDLE {
-        return self.evidence_handle
+    pub fn handle(&self) -> XwfHandle {
+        self.evidence_handle
     }
 
     pub fn open(&self) -> Result<Volume, XwfError> {
-        let handle = (get_raw_api!().open_ev_obj)(self.evidence_handle, 0);
+        require_function("XWF_OpenEvObj")?;
+        let handle = backend().open_ev_obj(self.evidence_handle, 0);
         Volume::new(handle)
     }
 
     pub fn get_first_evidence() -> Option<Evidence> {
-        let first_ev_obj = (get_raw_api!().get_first_ev_obj)(null_mut());
+        let first_ev_obj = backend().get_first_ev_obj();
 
-        if first_ev_obj == null_mut() {
+        if first_ev_obj.is_null() {
             return None;
         }
 
@@ -130,13 +135,12 @@ impl Evidence {
         let mut ret: Vec<Evidence> = Vec::new();
         let mut parent_ids: Vec<u32> = Vec::new();
 
-        let mut ev_iterator = Evidence::get_first_evidence()?.iter();
+        let ev_iterator = Evidence::get_first_evidence()?.iter();
 
-        while let Some(evidence) = ev_iterator.next() {
+        for evidence in ev_iterator {
     
-            let opt_parent_id = evidence.get_parent_id();
-            if opt_parent_id.is_some() {
-                parent_ids.push(opt_parent_id.unwrap())
+            if let Some(parent_id) = evidence.get_parent_id() {
+                parent_ids.push(parent_id)
             }
             ret.push(evidence);
         }
@@ -152,33 +156,30 @@ impl Evidence {
         Some(ret)
        }
 
-    pub fn close(&self) {
-        (get_raw_api!().close_ev_obj)(self.evidence_handle);
+    pub fn close(&self) -> Result<(), XwfError> {
+        require_main_thread("XWF_CloseEvObj")?;
+        backend().close_ev_obj(self.evidence_handle);
+        Ok(())
     }
 
     pub fn get_report_table_assocs(&self, sorted: bool) -> Option<ReportTableMap> {
         let mut flags: LONG = 0;
-        let mut num_pairs: LONG = 0;
         let mut ret = ReportTableMap::new();
         if sorted { flags = 0x1; }
 
-        let ptr_list = (get_raw_api!().get_ev_obj_report_table_assocs)(self.evidence_handle,flags,&mut num_pairs) as *const u8;
+        let list = backend().get_ev_obj_report_table_assocs(self.evidence_handle, flags)?;
 
-        if ptr_list == null() {
-            return None;
-        }
-
-        ReportTableListItem::insert_to_map(ptr_list, num_pairs, &mut ret);
+        ReportTableListItem::insert_to_map(&list, &mut ret);
         Some(ret)
     }
 
-    pub fn get_id_via_api(ev: HANDLE) -> u32 {
-        let ret = (get_raw_api!().get_ev_obj_prop)(ev, EvObjPropType::ObjId as DWORD, null_mut());
+    pub fn get_id_via_api(ev: XwfHandle) -> u32 {
+        let ret = backend().get_ev_obj_prop(ev, EvObjPropType::ObjId as u32, None);
         ret as u32
     }
 
-    pub fn get_short_id_via_api(ev: HANDLE) -> u16 {
-        let ret = (get_raw_api!().get_ev_obj_prop)(ev, EvObjPropType::ShortEvObjId as DWORD, null_mut());
+    pub fn get_short_id_via_api(ev: XwfHandle) -> u16 {
+        let ret = backend().get_ev_obj_prop(ev, EvObjPropType::ShortEvObjId as u32, None);
         ret as u16
     }
 
@@ -191,14 +192,14 @@ impl Evidence {
     }
 
     pub fn get_parent_id(&self) -> Option<u32> {
-        let ret = (get_raw_api!().get_ev_obj_prop)(self.evidence_handle, EvObjPropType::ParentObjId as DWORD, null_mut());
+        let ret = backend().get_ev_obj_prop(self.evidence_handle, EvObjPropType::ParentObjId as u32, None);
         if ret > 0 { Some(ret as u32)}
         else { None }
 
     }
 
     pub fn get_parent(&self) -> Option<Evidence> {
-        let ret = (get_raw_api!().get_ev_obj_prop)(self.evidence_handle, EvObjPropType::ParentObjId as DWORD, null_mut());
+        let ret = backend().get_ev_obj_prop(self.evidence_handle, EvObjPropType::ParentObjId as u32, None);
         if ret > 0 {
             Evidence::get_ev_obj(ret as u32)
         } else {
@@ -207,8 +208,8 @@ impl Evidence {
     }
 
     pub fn get_ev_obj(id: u32) -> Option<Evidence> {
-        let handle = (get_raw_api!().get_ev_obj)(id) ;
-        if  handle != null_mut() {
+        let handle = backend().get_ev_obj(id);
+        if  !handle.is_null() {
             Some(Evidence::new(handle).unwrap())
         } else {
             None
@@ -216,43 +217,58 @@ impl Evidence {
     }
 
     pub fn get_flags(&self) -> EvObjPropFlags {
-        let ret = (get_raw_api!().get_ev_obj_prop)(self.evidence_handle, EvObjPropType::Flags as DWORD, null_mut());
+        let ret = backend().get_ev_obj_prop(self.evidence_handle, EvObjPropType::Flags as u32, None);
         EvObjPropFlags::from_bits_truncate(ret as u32)
     }
 
+    /// file system of the evidence object, `None` if it is not a volume or the file system is not known
+    pub fn get_file_system(&self) -> Option<FileSystemType> {
+        let ret = backend().get_ev_obj_prop(self.evidence_handle, EvObjPropType::FileSystemID as u32, None);
+        match FileSystemType::from(ret as i32) {
+            FileSystemType::Unknown => None,
+            fs => Some(fs),
+        }
+    }
+
+    /// iterates over the event list of the evidence object
+    pub fn events(&self) -> Result<EventIterator, XwfError> {
+        Event::iter(self)
+    }
+
     pub fn get_name(&self) -> Result<String, XwfError> {
-        let mut buf = [0u16; 256];
-        let ret = (get_raw_api!().get_ev_obj_prop)(self.evidence_handle, EvObjPropType::AbbrevObjTitle as DWORD, buf.as_mut_ptr() as PVOID);
+        require_function("XWF_GetEvObjProp")?;
+        let mut name = String::new();
+        let ret = backend().get_ev_obj_prop(self.evidence_handle, EvObjPropType::AbbrevObjTitle as u32, Some(&mut name));
         if ret == -1 {
             Err(XwfError::XwfFunctionCallFailed("get_ev_obj_prop"))
         } else {
-            Ok(WString::from_wchars_slice(&buf).to_string())
+            Ok(name)
         }
     }
 
     pub fn get_description(&self) -> Option<String> {
-        let ret = (get_raw_api!().get_ev_obj_prop)(self.evidence_handle, EvObjPropType::Description as DWORD, null_mut() as PVOID);
+        let mut description = String::new();
+        let ret = backend().get_ev_obj_prop(self.evidence_handle, EvObjPropType::Description as u32, Some(&mut description));
         if ret == -1 || ret == 0 {
             None
         } else {
-            unsafe { Some(WString::from_wchars_nullt(ret as LPWSTR).to_string()) }
+            Some(description)
         }
 
     }
 
     pub fn get_comments(&self) -> Option<String> {
-        let ret = (get_raw_api!().get_ev_obj_prop)(self.evidence_handle, EvObjPropType::ExaminerComments as DWORD, null_mut() as PVOID);
+        let mut comments = String::new();
+        let ret = backend().get_ev_obj_prop(self.evidence_handle, EvObjPropType::ExaminerComments as u32, Some(&mut comments));
         if ret == -1 || ret == 0 {
             None
         } else {
-            unsafe { Some(WString::from_wchars_nullt(ret as LPWSTR).to_string()) }
+            Some(comments)
         }
     }
 
     pub fn child(&self) -> Option<Evidence> {
-        if self.child_evidence_id.is_none() {
-            return None;
-        }
+        self.child_evidence_id?;
         Evidence::get_ev_obj(self.child_evidence_id.unwrap())
     }
 
@@ -264,6 +280,3 @@ impl Evidence {
         self.child_evidence_id = Some(evidence_id)
     }
 }
-
-unsafe impl Send for Evidence {}
-unsafe impl Sync for Evidence {}
\ No newline at end of file
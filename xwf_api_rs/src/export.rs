@@ -0,0 +1,377 @@
+//! Streaming export of item records to JSON Lines or CSV files (feature `export`). Records are
+//! written one by one as they are read, so exports of large volume snapshots need no more memory
+//! than a single record.
+
+use std::borrow::Borrow;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+use crate::backend::backend;
+use crate::case::{case_file, Case, ItemVisitor};
+use crate::error::XwfError;
+use crate::evidence::{Evidence, EvidenceIterator};
+use crate::item::Item;
+use crate::record::{ItemRecord, RecordOptions};
+use crate::util::escape_csv;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExportFormat {
+    JsonLines,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::JsonLines => "jsonl",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Column of an export, named like the corresponding field of [`ItemRecord`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Column {
+    EvidenceId,
+    ShortEvidenceId,
+    ItemId,
+    /// `<short evidence id>-<item id>`, as displayed by X-Ways Forensics
+    UniqueId,
+    Name,
+    Path,
+    Size,
+    PhysicalSize,
+    CreationTime,
+    ModificationTime,
+    LastAccessTime,
+    EntryModificationTime,
+    DeletionTime,
+    InternalCreationTime,
+    Flags,
+    Attributes,
+    Deletion,
+    Classification,
+    FileType,
+    FileTypeDescription,
+    Category,
+    TypeStatus,
+    Consistency,
+    Hash1,
+    Hash2,
+    ReportTables,
+    HashSets,
+    Comment,
+    ExtractedMetadata,
+}
+
+impl Column {
+    pub const ALL: [Column; 29] = [
+        Column::EvidenceId, Column::ShortEvidenceId, Column::ItemId, Column::UniqueId, Column::Name,
+        Column::Path, Column::Size, Column::PhysicalSize, Column::CreationTime, Column::ModificationTime,
+        Column::LastAccessTime, Column::EntryModificationTime, Column::DeletionTime,
+        Column::InternalCreationTime, Column::Flags, Column::Attributes, Column::Deletion,
+        Column::Classification, Column::FileType, Column::FileTypeDescription, Column::Category,
+        Column::TypeStatus, Column::Consistency, Column::Hash1, Column::Hash2, Column::ReportTables,
+        Column::HashSets, Column::Comment, Column::ExtractedMetadata,
+    ];
+
+    /// columns used if none are configured, all of them are cheap to read
+    pub const DEFAULT: [Column; 9] = [
+        Column::UniqueId, Column::Name, Column::Path, Column::Size, Column::CreationTime,
+        Column::ModificationTime, Column::LastAccessTime, Column::FileType, Column::Hash1,
+    ];
+
+    /// header in CSV files and key in JSON Lines files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::EvidenceId => "evidence_id",
+            Column::ShortEvidenceId => "short_ev_id",
+            Column::ItemId => "item_id",
+            Column::UniqueId => "unique_id",
+            Column::Name => "name",
+            Column::Path => "path",
+            Column::Size => "size",
+            Column::PhysicalSize => "physical_size",
+            Column::CreationTime => "creation_time",
+            Column::ModificationTime => "modification_time",
+            Column::LastAccessTime => "last_access_time",
+            Column::EntryModificationTime => "entry_modification_time",
+            Column::DeletionTime => "deletion_time",
+            Column::InternalCreationTime => "internal_creation_time",
+            Column::Flags => "flags",
+            Column::Attributes => "attributes",
+            Column::Deletion => "deletion",
+            Column::Classification => "classification",
+            Column::FileType => "file_type",
+            Column::FileTypeDescription => "file_type_description",
+            Column::Category => "category",
+            Column::TypeStatus => "type_status",
+            Column::Consistency => "consistency",
+            Column::Hash1 => "hash1",
+            Column::Hash2 => "hash2",
+            Column::ReportTables => "report_tables",
+            Column::HashSets => "hash_sets",
+            Column::Comment => "comment",
+            Column::ExtractedMetadata => "extracted_metadata",
+        }
+    }
+
+    /// fields of the [`ItemRecord`] that have to be fetched for the column
+    pub fn options(&self) -> RecordOptions {
+        match self {
+            Column::Path => RecordOptions::Path,
+            Column::PhysicalSize => RecordOptions::PhysicalSize,
+            Column::CreationTime | Column::ModificationTime | Column::LastAccessTime
+            | Column::EntryModificationTime | Column::DeletionTime
+            | Column::InternalCreationTime => RecordOptions::Timestamps,
+            Column::FileType | Column::FileTypeDescription | Column::Category
+            | Column::TypeStatus | Column::Consistency => RecordOptions::FileType,
+            Column::Hash1 | Column::Hash2 => RecordOptions::Hashes,
+            Column::ReportTables => RecordOptions::ReportTables,
+            Column::HashSets => RecordOptions::HashSets,
+            Column::Comment => RecordOptions::Comment,
+            Column::ExtractedMetadata => RecordOptions::ExtractedMetadata,
+            _ => RecordOptions::empty(),
+        }
+    }
+
+    /// value of the column, `Null` if it is not available
+    pub fn value(&self, record: &ItemRecord) -> Value {
+        match self {
+            Column::EvidenceId => record.evidence_id.into(),
+            Column::ShortEvidenceId => record.short_ev_id.into(),
+            Column::ItemId => record.item_id.into(),
+            Column::UniqueId => record.unique_id().to_string().into(),
+            Column::Name => record.name.clone().into(),
+            Column::Path => record.path.clone().into(),
+            Column::Size => record.size.into(),
+            Column::PhysicalSize => record.physical_size.into(),
+            Column::CreationTime => format_time(&record.creation_time),
+            Column::ModificationTime => format_time(&record.modification_time),
+            Column::LastAccessTime => format_time(&record.last_access_time),
+            Column::EntryModificationTime => format_time(&record.entry_modification_time),
+            Column::DeletionTime => format_time(&record.deletion_time),
+            Column::InternalCreationTime => format_time(&record.internal_creation_time),
+            Column::Flags => to_value(&record.flags),
+            Column::Attributes => to_value(&record.attributes),
+            Column::Deletion => to_value(&record.deletion),
+            Column::Classification => to_value(&record.classification),
+            Column::FileType => record.file_type.clone().into(),
+            Column::FileTypeDescription => record.file_type_description.clone().into(),
+            Column::Category => to_value(&record.category),
+            Column::TypeStatus => to_value(&record.type_status),
+            Column::Consistency => to_value(&record.consistency),
+            Column::Hash1 => record.hash1.clone().into(),
+            Column::Hash2 => record.hash2.clone().into(),
+            Column::ReportTables => record.report_tables.clone().into(),
+            Column::HashSets => record.hash_sets.clone().into(),
+            Column::Comment => record.comment.clone().into(),
+            Column::ExtractedMetadata => record.extracted_metadata.clone().into(),
+        }
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn format_time(time: &Option<XwfDateTime>) -> Value {
+    time.as_ref().map(XwfDateTime::to_rfc3339).into()
+}
+
+fn csv_field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values.iter().map(csv_field).collect::<Vec<_>>().join("; "),
+        v => v.to_string(),
+    }
+}
+
+// JSON object with the columns in their configured order
+struct JsonRow<'a> {
+    columns: &'a [Column],
+    record: &'a ItemRecord,
+}
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for column in self.columns {
+            map.serialize_entry(column.name(), &column.value(self.record))?;
+        }
+        map.end()
+    }
+}
+
+/// Writes [`ItemRecord`]s with the configured columns to a JSON Lines or CSV file. Either all
+/// items of evidences are exported with [`ItemExporter::export_evidences`], or single items,
+/// e.g. those passed to `xt_process_item`, with [`ItemExporter::export_item`].
+///
+/// ```ignore
+/// let mut exporter = ItemExporter::create_in_case_dir("my_xtension", ExportFormat::Csv)?
+///     .with_columns(&[Column::UniqueId, Column::Path, Column::Size, Column::Hash1]);
+/// exporter.export_all()?;
+/// exporter.finish()?;
+/// ```
+pub struct ItemExporter {
+    out: BufWriter<Box<dyn Write + Send>>,
+    path: Option<PathBuf>,
+    format: ExportFormat,
+    columns: Vec<Column>,
+    options: RecordOptions,
+    delimiter: char,
+    utf8_bom: bool,
+    header_written: bool,
+    exported: usize,
+}
+
+impl ItemExporter {
+    pub fn new<W: Write + Send + 'static>(writer: W, format: ExportFormat) -> ItemExporter {
+        ItemExporter {
+            out: BufWriter::new(Box::new(writer)),
+            path: None,
+            format,
+            columns: Vec::new(),
+            options: RecordOptions::empty(),
+            delimiter: ',',
+            utf8_bom: true,
+            header_written: false,
+            exported: 0,
+        }
+        .with_columns(&Column::DEFAULT)
+    }
+
+    pub fn create<P: AsRef<Path>>(path: P, format: ExportFormat) -> Result<ItemExporter, XwfError> {
+        let mut exporter = ItemExporter::new(File::create(path.as_ref())?, format);
+        exporter.path = Some(path.as_ref().to_path_buf());
+        Ok(exporter)
+    }
+
+    /// creates the file `<name>_export_<timestamp>.<jsonl|csv>` in the case directory
+    pub fn create_in_case_dir(name: &str, format: ExportFormat) -> Result<ItemExporter, XwfError> {
+        ItemExporter::create(case_file(&*backend(), &format!("{}_export", name), format.extension()), format)
+    }
+
+    /// columns in the given order. Only the fields of the records required by the columns are read.
+    pub fn with_columns(mut self, columns: &[Column]) -> ItemExporter {
+        self.columns = columns.to_vec();
+        self.options = columns.iter().fold(RecordOptions::empty(), |options, c| options | c.options());
+        self
+    }
+
+    /// field delimiter of CSV files, e.g. `;` for spreadsheet applications in some locales
+    pub fn with_delimiter(mut self, delimiter: char) -> ItemExporter {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// CSV files start with a byte order mark by default, so that spreadsheet applications
+    /// recognize them as UTF-8 and display non-ASCII names correctly
+    pub fn with_utf8_bom(mut self, utf8_bom: bool) -> ItemExporter {
+        self.utf8_bom = utf8_bom;
+        self
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// `None` if the exporter was created with [`ItemExporter::new`]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// number of records written so far
+    pub fn exported(&self) -> usize {
+        self.exported
+    }
+
+    /// writes the record, reduced to the configured columns
+    pub fn write_record(&mut self, record: &ItemRecord) -> Result<(), XwfError> {
+        self.write_header()?;
+
+        match self.format {
+            ExportFormat::JsonLines => {
+                let row = JsonRow { columns: &self.columns, record };
+                serde_json::to_writer(&mut self.out, &row).map_err(|e| XwfError::IoError(e.into()))?;
+                self.out.write_all(b"\n")?;
+            },
+            ExportFormat::Csv => {
+                let row: Vec<String> = self.columns.iter()
+                    .map(|c| escape_csv(&csv_field(&c.value(record)), self.delimiter))
+                    .collect();
+                self.write_csv_line(&row)?;
+            },
+        }
+        self.exported += 1;
+        Ok(())
+    }
+
+    /// exports a single item of the volume, e.g. in `xt_process_item`
+    pub fn export_item(&mut self, item: &Item, volume: &Volume, evidence: &Evidence) -> Result<(), XwfError> {
+        let record = ItemRecord::new(item, volume, evidence, self.options)?;
+        self.write_record(&record)
+    }
+
+    /// exports all items of the evidences with [`Case::visit_items`], the records exported before an
+    /// error are kept. Returns the number of exported items.
+    pub fn export_evidences(&mut self, evidences: &[Evidence]) -> Result<usize, XwfError> {
+        self.export(evidences)
+    }
+
+    /// exports all items of all evidences of the case
+    pub fn export_all(&mut self) -> Result<usize, XwfError> {
+        self.export(EvidenceIterator::new())
+    }
+
+    fn export<I>(&mut self, evidences: I) -> Result<usize, XwfError> where I: IntoIterator, I::Item: Borrow<Evidence> {
+        let exported = self.exported;
+        let result = Case::visit_items(evidences, "Exporting items", self);
+        self.out.flush()?;
+        result.map(|()| self.exported - exported)
+    }
+
+    /// flushes the file and returns the number of exported records
+    pub fn finish(mut self) -> Result<usize, XwfError> {
+        // an export without records still gets its CSV header
+        self.write_header()?;
+        self.out.flush()?;
+        Ok(self.exported)
+    }
+
+    fn write_header(&mut self) -> Result<(), XwfError> {
+        if self.header_written {
+            return Ok(());
+        }
+        self.header_written = true;
+
+        if self.format == ExportFormat::Csv {
+            if self.utf8_bom {
+                self.out.write_all("\u{feff}".as_bytes())?;
+            }
+            let header: Vec<String> = self.columns.iter()
+                .map(|c| escape_csv(c.name(), self.delimiter))
+                .collect();
+            self.write_csv_line(&header)?;
+        }
+        Ok(())
+    }
+
+    fn write_csv_line(&mut self, fields: &[String]) -> Result<(), XwfError> {
+        let line = fields.join(self.delimiter.encode_utf8(&mut [0; 4]));
+        self.out.write_all(line.as_bytes())?;
+        self.out.write_all(b"\r\n")?;
+        Ok(())
+    }
+}
+
+impl ItemVisitor for ItemExporter {
+    fn visit_item(&mut self, item: Item, volume: &Volume, evidence: &Evidence) -> Result<(), XwfError> {
+        self.export_item(&item, volume, evidence)
+    }
+}
@@ -0,0 +1,23 @@
+// portable equivalents of the Windows C types used by the X-Tension API, so that the
+// function signatures and structs compile on every target (the ABI is identical on Windows)
+#![allow(non_camel_case_types, clippy::upper_case_acronyms)]
+
+use std::ffi::{c_char, c_void};
+
+pub type BOOL = i32;
+pub type BYTE = u8;
+pub type WORD = u16;
+pub type DWORD = u32;
+pub type LONG = i32;
+pub type __int64 = i64;
+pub type WCHAR = u16;
+
+pub type PVOID = *mut c_void;
+pub type LPVOID = *mut c_void;
+pub type LPBOOL = *mut BOOL;
+pub type LPLONG = *mut LONG;
+pub type PLONG = *mut LONG;
+pub type PDWORD = *mut DWORD;
+pub type LPSTR = *mut c_char;
+pub type LPWSTR = *mut WCHAR;
+pub type LPWCH = *mut WCHAR;
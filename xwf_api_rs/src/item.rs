@@ -1,33 +1,27 @@
 use std::fmt::{Display, Formatter};
 use std::fs::File;
-use std::io::{Write};
+use std::io::{self, Read};
 use std::ops::BitOr;
 use std::path::Path;
-use std::ptr::null_mut;
 use std::str::FromStr;
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, FixedOffset};
 
 use std::hash::{Hash, Hasher};
 
-use winapi::shared::minwindef::{BOOL, DWORD, LPVOID, PDWORD};
-use winapi::shared::ntdef::{HANDLE, LPWSTR, PVOID};
+use crate::xwf_types::XwfHandle;
 use serde::{Deserialize, Serialize};
-use crate::{get_raw_api, util};
+use crate::util;
+use crate::backend::backend;
 use crate::error::XwfError;
 use crate::evidence::Evidence;
 use crate::traits::NativeHandle;
-use crate::raw_api::RAW_API;
 use crate::volume::{HashType, Volume};
+use crate::tree::VolumeTree;
+use crate::record::{ItemRecord, RecordOptions};
 use crate::xwf_types::*;
 use regex::Regex;
-use winapi::ctypes::__int64;
-use winsafe::WString;
-use crate::application::Application;
-use crate::util::char_ptr_to_string;
-
-const DEFAULT_DATA_CHUNK_SIZE: usize = 1*1024*1024;
-const BUF_SIZE_REPORT_TABLE_QUERY: usize = 8192;
-const BUF_SIZE_REPORT_HASHSET_QUERY: usize = 4096;
+use crate::capabilities::{require_function, require_xwf_version};
+use crate::reader::ItemReader;
 
 pub struct ItemIterator {
     cur_item: Option<Item>,
@@ -49,7 +43,8 @@ impl Iterator for ItemIterator {
 
         match self.cur_item {
             Some(i) => {
-                self.cur_item = i.get_parent_item();
+                // stops at the item if XWF_GetItemParent is not available, see [`Item::iter`]
+                self.cur_item = i.get_parent_item().ok().flatten();
                 Some(i)
             },
             None => None,
@@ -76,7 +71,7 @@ impl Hash for Item {
 }
 
 
-#[derive(Copy, Clone, Debug, Hash)]
+#[derive(Copy, Clone, Debug)]
 pub struct UniqueItemId {
     pub item_id: i32,
     pub evidence_id: u32,
@@ -91,6 +86,13 @@ impl PartialEq for UniqueItemId {
 
 impl Eq for UniqueItemId {}
 
+impl Hash for UniqueItemId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.item_id.hash(state);
+        self.evidence_id.hash(state);
+    }
+}
+
 impl PartialOrd for UniqueItemId {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -109,8 +111,8 @@ impl UniqueItemId {
     pub fn new(evidence_id: u32, short_ev_id: u16, item_id: u32) -> UniqueItemId {
         UniqueItemId {
             item_id: item_id as i32,
-            evidence_id: evidence_id,
-            short_ev_id: short_ev_id
+            evidence_id,
+            short_ev_id
         }
     }
     pub fn item(&self) -> Item {
@@ -118,7 +120,7 @@ impl UniqueItemId {
     }
 
     pub fn evidence(&self) -> Evidence {
-        Evidence::new((get_raw_api!().get_ev_obj)(self.evidence_id)).unwrap()
+        Evidence::new(backend().get_ev_obj(self.evidence_id)).unwrap()
     }
 }
 
@@ -138,17 +140,17 @@ impl TryFrom<String> for UniqueItemId {
         let item_id: i32 = caps[2].parse()?;
 
         Ok(UniqueItemId {
-            item_id: item_id,
+            item_id,
             evidence_id: evidence_id as u32,
             short_ev_id: evidence_id }
         )
     }
 }
 
-impl Into<i64> for UniqueItemId {
-    fn into(self) -> i64 {
-        let mut ret = self.item_id as i64;
-        ret |= (self.evidence_id as i64) << 32;
+impl From<UniqueItemId> for i64 {
+    fn from(val: UniqueItemId) -> Self {
+        let mut ret = val.item_id as i64;
+        ret |= (val.evidence_id as i64) << 32;
         ret
     }
 }
@@ -187,6 +189,8 @@ impl<'de> Deserialize<'de> for UniqueItemId {
 
 impl Item {
 
+    /// iterates over the item and its ancestors. Yields only the item itself if
+    /// `XWF_GetItemParent` is not available, use [`Item::get_hierarchy`] to detect this.
     pub fn iter(&self) -> ItemIterator {
         ItemIterator::create(self)
     }
@@ -197,33 +201,19 @@ impl Item {
     }
 
     pub fn get_child_items(&self, volume: &Volume) -> Result<Vec<Item>, XwfError> {
-        let mut ret: Vec<Item> = Vec::new();
-        let num_items = volume.select()?;
-
-        for i in 0..num_items {
-            let item = Item::new(i);
-            match item.get_parent_item() {
-                Some(parent_item) => { 
-                    if self.item_id == parent_item.item_id {
-                        ret.push(item)
-                    }
-                },
-                None => {},
-            }
-
-        }
-        Ok(ret)
+        Ok(VolumeTree::get(volume)?.children(self).collect())
     }
 
 
 
     pub fn open(&self, volume: &Volume, flags: OpenItemFlags) -> Result<ItemHandle, XwfError> {
-        let handle = (get_raw_api!().open_item)(
+        require_function("XWF_OpenItem")?;
+        let handle = backend().open_item(
             volume.handle(),
             self.item_id,
             flags.bits());
 
-        if handle == null_mut() {
+        if handle.is_null() {
             return Err(XwfError::FailedToGetItemHandle(self.item_id));
         }
 
@@ -232,7 +222,9 @@ impl Item {
 
 
 
-    pub fn get_hash_value(&self, hash_type: HashType, get_secondary: bool) -> Option<Vec<u8>>{
+    /// `None` if the item has no hash value of the given type
+    pub fn get_hash_value(&self, hash_type: HashType, get_secondary: bool) -> Result<Option<Vec<u8>>, XwfError> {
+        require_function("XWF_GetHashValue")?;
         let hash_size = hash_type.get_hash_size();
         let mut buf_size = hash_size;
         if buf_size < 4 {
@@ -245,30 +237,30 @@ impl Item {
             flags = 0x02u32;
         }
 
-        let mut buf: Vec<u8> = Vec::with_capacity(buf_size);
-        buf.resize(buf_size,0);
+        let mut buf: Vec<u8> = vec![0; buf_size];
         buf[0..4].clone_from_slice(&flags.to_le_bytes());
 
-        let ret = (get_raw_api!().get_hash_value)(self.item_id, buf.as_mut_ptr() as LPVOID);
+        let ret = backend().get_hash_value(self.item_id, &mut buf);
 
-        if ret != 0 {
+        if ret {
             buf.resize(hash_size, 0u8);
-            return Some(buf);
+            Ok(Some(buf))
         } else {
-            return None;
+            Ok(None)
         }
     }
 
-    pub fn set_hash_value(&self, hash_value: &Vec<u8>, set_secondary: bool) -> Result<(), XwfError>{
+    pub fn set_hash_value(&self, hash_value: &[u8], set_secondary: bool) -> Result<(), XwfError>{
+        require_function("XWF_SetHashValue")?;
         let mut param: u32 = 1;
 
         if set_secondary {
             param = 2;
         }
 
-        let x = (get_raw_api!().set_hash_value)(self.item_id, hash_value.as_ptr() as LPVOID, param);
+        let x = backend().set_hash_value(self.item_id, hash_value, param);
 
-        if x != 0 {
+        if x {
             Ok(())
         } else {
             Err(XwfError::XwfFunctionCallFailed("set_hash_value"))
@@ -276,11 +268,12 @@ impl Item {
     }
 
     pub fn set_item_info_flags(&self, flags: ItemInfoFlags, remove_flags: bool) -> Result<(), XwfError> {
+        require_function("XWF_SetItemInformation")?;
         let n_info_type = if remove_flags { 65 } else { 64 };
 
-        let result = (get_raw_api!().set_item_information)(self.item_id, n_info_type, flags.bits() as i64);
+        let result = backend().set_item_information(self.item_id, n_info_type, flags.bits() as i64);
 
-        if result != 0 {
+        if result {
             Ok(())
         } else {
             Err(XwfError::XwfFunctionCallFailed("set_item_information"))
@@ -289,23 +282,72 @@ impl Item {
 
 
     pub fn set_item_info_classification(&self, classification: ItemInfoClassification) -> Result<(), XwfError> {
+        self.set_item_info(XwfItemInfoTypes::Classification, classification as i64)
+    }
 
-        let result = (get_raw_api!().set_item_information)(self.item_id, 5, classification as i64);
+    /// sets an item information via `XWF_SetItemInformation`, fails for item information that
+    /// can only be read (see [`XwfItemInfoTypes::is_writable`])
+    pub fn set_item_info(&self, infotype: XwfItemInfoTypes, value: i64) -> Result<(), XwfError> {
+        require_function("XWF_SetItemInformation")?;
+        if !infotype.is_writable() {
+            return Err(XwfError::InvalidInputArgument);
+        }
 
-        if result != 0 {
+        if backend().set_item_information(self.item_id, infotype as i32, value) {
             Ok(())
         } else {
             Err(XwfError::XwfFunctionCallFailed("set_item_information"))
         }
     }
 
+    pub fn set_item_info_deletion(&self, deletion: ItemInfoDeletion) -> Result<(), XwfError> {
+        self.set_item_info(XwfItemInfoTypes::Deletion, deletion as i64)
+    }
+
+    pub fn set_attributes(&self, attributes: ItemInfoAttributes) -> Result<(), XwfError> {
+        self.set_item_info(XwfItemInfoTypes::Attr, attributes.bits())
+    }
+
+    pub fn set_link_count(&self, link_count: u32) -> Result<(), XwfError> {
+        self.set_item_info(XwfItemInfoTypes::LinkCount, link_count as i64)
+    }
+
+    pub fn set_color_analysis(&self, color_analysis: ColorAnalysis) -> Result<(), XwfError> {
+        self.set_item_info(XwfItemInfoTypes::ColorAnalysis, color_analysis.into())
+    }
+
+    pub fn set_pixel_index(&self, pixel_index: u64) -> Result<(), XwfError> {
+        self.set_item_info(XwfItemInfoTypes::PixelIndex, pixel_index as i64)
+    }
+
+    pub fn set_file_count(&self, file_count: u32) -> Result<(), XwfError> {
+        self.set_item_info(XwfItemInfoTypes::FileCount, file_count as i64)
+    }
+
+    /// `None` marks the item as not embedded (stored as -1, as 0 is a valid offset)
+    pub fn set_embedded_offset(&self, offset: Option<u64>) -> Result<(), XwfError> {
+        self.set_item_info(XwfItemInfoTypes::EmbeddedOffset, offset.map(|o| o as i64).unwrap_or(-1))
+    }
 
-    pub fn create_file(&self, name: &String, creation_flags: FileCreationFlags, src_info: &mut SrcInfo) -> Result<Item, XwfError> {
-        let wstr = WString::from_str(name);
+    /// sets one of the timestamps (`CreationTime` ... `InternalCreationTime`), `None` removes it
+    pub fn set_item_info_time(&self, infotype: XwfItemInfoTypes, time: Option<&XwfDateTime>) -> Result<(), XwfError> {
+        match infotype {
+            XwfItemInfoTypes::CreationTime
+            | XwfItemInfoTypes::ModificationTime
+            | XwfItemInfoTypes::LastAccessTime
+            | XwfItemInfoTypes::EntryModificationTime
+            | XwfItemInfoTypes::DeletionTime
+            | XwfItemInfoTypes::InternalCreationTime => {}
+            _ => return Err(XwfError::InvalidInputArgument),
+        }
+        self.set_item_info(infotype, time.map(|t| t.to_filetime()).unwrap_or(0))
+    }
 
-        let p_src_info: *mut SrcInfo = src_info;
 
-        let result = (get_raw_api!().create_file)(wstr.as_ptr() as LPWSTR, creation_flags.bits(), self.item_id, p_src_info as PVOID);
+    pub fn create_file(&self, name: &str, creation_flags: FileCreationFlags, src_info: &mut SrcInfo) -> Result<Item, XwfError> {
+        require_function("XWF_CreateFile")?;
+        let result = backend().create_file(name, creation_flags.bits(), self.item_id, src_info);
+        crate::tree::invalidate();
 
         if result < 0 {
             Err(XwfError::XwfFunctionCallFailed("create_file"))
@@ -315,15 +357,11 @@ impl Item {
     }
 
     pub fn get_item_info(&self, infotype: XwfItemInfoTypes) -> Result<i64, XwfError> {
-        let mut success: Box<BOOL> = Box::new(1);
-        let success_ptr: *mut BOOL = &mut *success;
-        let result = (get_raw_api!().get_item_information)(self.item_id, infotype as i32, success_ptr);
-
-        if *success != 0 {
-            Ok(result)
-        } else {
-            Err(XwfError::XwfFunctionCallFailed("get_item_information"))
-        }
+        require_function("XWF_GetItemInformation")?;
+        let (major, minor) = infotype.min_xwf_version();
+        require_xwf_version("item information type", major, minor)?;
+        backend().get_item_information(self.item_id, infotype as i32)
+            .ok_or(XwfError::XwfFunctionCallFailed("get_item_information"))
     }
 
     
@@ -372,22 +410,9 @@ impl Item {
                     return None;
                 }
 
-                let unix_epoch_sec = ( result / 10_000_000 ) - 11644473600i64;
-                let nsec_fraction = (( result % 10_000_000) * 100) as u32;
-
-
-                if time_config.1 {
-                    let time = DateTime::from_timestamp(unix_epoch_sec, nsec_fraction)?.naive_utc();
-                    Some(XwfDateTime::NoTimezone(time))
-                } else {
-                    let time = Utc.timestamp_opt(unix_epoch_sec, nsec_fraction).unwrap();
-
-                    if local_time {
-                        Some(XwfDateTime::Local(DateTime::from(time)))
-                    } else {
-                        Some(XwfDateTime::Utc(time))
-
-                    }
+                match XwfDateTime::from_filetime(result, time_config.1)? {
+                    XwfDateTime::Utc(time) if local_time => Some(XwfDateTime::Local(DateTime::from(time))),
+                    time => Some(time),
                 }
             },
             Err(_) => {
@@ -402,59 +427,125 @@ impl Item {
         ItemInfoClassification::try_from(result)
     }
 
-    pub fn get_size(&self) -> usize {
-        (get_raw_api!().get_item_size)(self.item_id) as usize
+    /// original id of the item in the volume snapshot it was taken over from
+    pub fn get_orig_id(&self) -> Result<i64, XwfError> {
+        self.get_item_info(XwfItemInfoTypes::OrigId)
     }
 
-    pub fn get_name(&self) -> String {
-        let wchr_ptr = (get_raw_api!().get_item_name)(self.item_id as DWORD);
-        let s = unsafe { winsafe::WString::from_wchars_nullt(wchr_ptr) };
-        s.to_string()
+    pub fn get_attributes(&self) -> Result<ItemInfoAttributes, XwfError> {
+        let result = self.get_item_info(XwfItemInfoTypes::Attr)?;
+        Ok(ItemInfoAttributes::from_bits_retain(result))
     }
 
-    pub fn get_path(&self) -> String {
+    /// number of hard links
+    pub fn get_link_count(&self) -> Result<u32, XwfError> {
+        Ok(self.get_item_info(XwfItemInfoTypes::LinkCount)?.max(0) as u32)
+    }
+
+    pub fn get_color_analysis(&self) -> Result<ColorAnalysis, XwfError> {
+        Ok(ColorAnalysis::from(self.get_item_info(XwfItemInfoTypes::ColorAnalysis)?))
+    }
+
+    /// pixel index of a picture, `None` if not computed
+    pub fn get_pixel_index(&self) -> Result<Option<u64>, XwfError> {
+        let result = self.get_item_info(XwfItemInfoTypes::PixelIndex)?;
+        Ok(if result > 0 { Some(result as u64) } else { None })
+    }
+
+    /// number of child items (files in a directory or archive)
+    pub fn get_file_count(&self) -> Result<u32, XwfError> {
+        Ok(self.get_item_info(XwfItemInfoTypes::FileCount)?.max(0) as u32)
+    }
+
+    /// offset of the item within its parent file, `None` if the item is not embedded (negative value)
+    pub fn get_embedded_offset(&self) -> Result<Option<u64>, XwfError> {
+        let result = self.get_item_info(XwfItemInfoTypes::EmbeddedOffset)?;
+        Ok(u64::try_from(result).ok())
+    }
+
+    /// time zone offset (in minutes) X-Ways Forensics uses to display one of the timestamps
+    /// (`CreationTime` ... `InternalCreationTime`), requires v21.2
+    pub fn get_time_display_offset(&self, infotype: XwfItemInfoTypes) -> Result<FixedOffset, XwfError> {
+        let display_ofs = infotype.display_offset().ok_or(XwfError::InvalidInputArgument)?;
+        let minutes = self.get_item_info(display_ofs)?;
+        i32::try_from(minutes).ok()
+            .and_then(|m| m.checked_mul(60))
+            .and_then(FixedOffset::east_opt)
+            .ok_or(XwfError::InvalidEnumValue(("time display offset", minutes)))
+    }
+
+    /// fails with [`XwfError::InvalidItemSize`] if the size is unknown (negative)
+    pub fn get_size(&self) -> Result<usize, XwfError> {
+        require_function("XWF_GetItemSize")?;
+        let size = backend().get_item_size(self.item_id);
+        usize::try_from(size).map_err(|_| XwfError::InvalidItemSize)
+    }
+
+    pub fn get_name(&self) -> Result<String, XwfError> {
+        require_function("XWF_GetItemName")?;
+        Ok(backend().get_item_name(self.item_id as u32))
+    }
 
-        let mut path_components: Vec<String> = self.iter().map(|p| p.get_name()).collect();
+    pub fn get_path(&self) -> Result<String, XwfError> {
+        require_function("XWF_GetItemParent")?;
+        let mut path_components = self.iter().map(|p| p.get_name()).collect::<Result<Vec<String>, XwfError>>()?;
         path_components.pop();
         path_components.reverse();
 
-        "\\".to_string() + &path_components.join("\\")
+        Ok("\\".to_string() + &path_components.join("\\"))
     }
 
-    pub fn add_to_report_table<S: AsRef<str>>(&self, name: S, flags: AddReportTableFlags) {
-        let wchar_c_str = WString::from_str(name);
-        (get_raw_api!().add_to_report_table)(self.item_id, wchar_c_str.as_ptr() as LPWSTR, flags.bits());
+    /// ignores whether X-Ways Forensics assigned the item, see [`Item::try_add_to_report_table`]
+    pub fn add_to_report_table<S: AsRef<str>>(&self, name: S, flags: AddReportTableFlags) -> Result<(), XwfError> {
+        require_function("XWF_AddToReportTable")?;
+        backend().add_to_report_table(self.item_id, name.as_ref(), flags.bits());
+        Ok(())
     }
 
-    pub fn get_parent_dir(&self) -> Option<Item> {
-        self.iter().find(|i|{
+    /// like [`Item::add_to_report_table`], but fails if X-Ways Forensics could not assign the item
+    /// to the report table (return value 0)
+    pub fn try_add_to_report_table<S: AsRef<str>>(&self, name: S, flags: AddReportTableFlags) -> Result<(), XwfError> {
+        require_function("XWF_AddToReportTable")?;
+        if backend().add_to_report_table(self.item_id, name.as_ref(), flags.bits()) == 0 {
+            Err(XwfError::XwfFunctionCallFailed("add_to_report_table"))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_parent_dir(&self) -> Result<Option<Item>, XwfError> {
+        require_function("XWF_GetItemParent")?;
+        require_function("XWF_GetItemInformation")?;
+        Ok(self.iter().find(|i|{
             match i.get_item_info_flags() {
                 Ok(flags) => flags.contains(ItemInfoFlags::IsDirectory),
                 Err(_) => false,
             }
-        })
+        }))
     }
 
 
-    pub fn get_parent_item(&self) -> Option<Item> {
-        let parent_id = (get_raw_api!().get_item_parent)(self.item_id);
+    pub fn get_parent_item(&self) -> Result<Option<Item>, XwfError> {
+        require_function("XWF_GetItemParent")?;
+        let parent_id = backend().get_item_parent(self.item_id);
 
         if parent_id < 0 {
-            None
+            Ok(None)
         } else {
-            Some(Item::new(parent_id))
+            Ok(Some(Item::new(parent_id)))
         }
     }
 
-    pub fn get_hierarchy(&self) -> Vec<Item> {
-        match self.get_parent_item() {
-            Some(parent) => parent.iter().collect(),
-            None => vec![],
+    pub fn get_hierarchy(&self) -> Result<Vec<Item>, XwfError> {
+        match self.get_parent_item()? {
+            Some(parent) => Ok(parent.iter().collect()),
+            None => Ok(vec![]),
         }
     }
 
     pub fn get_item_type(&self, long_desc: bool) -> Result<String, XwfError> {
-        let mut buf = [0u16; 256];
+        require_function("XWF_GetItemType")?;
+        let mut type_descr = String::new();
 
         let mut flags = ItemTypeFlags::empty();
 
@@ -462,30 +553,31 @@ impl Item {
             flags = flags.bitor(ItemTypeFlags::TextualDescriptionType);
         }
 
-        let buf_and_flags = (buf.len() as u32 ) | flags.bits();
-        let _ = (get_raw_api!().get_item_type)(self.item_id, buf.as_mut_ptr(), buf_and_flags);
-        Ok(winsafe::WString::from_wchars_slice(&buf).to_string())
+        let _ = backend().get_item_type(self.item_id, &mut type_descr, flags.bits());
+        Ok(type_descr)
+    }
+
+    pub fn set_item_type(&self, type_descr: &str, type_status: FileTypeStatus) -> Result<(), XwfError> {
+        require_function("XWF_SetItemType")?;
+        backend().set_item_type(self.item_id, type_descr, type_status as i32);
+        Ok(())
     }
     pub fn __get_report_tables(&self) -> Result<Vec<String>, XwfError> {
-        let mut buf = [0u16; BUF_SIZE_REPORT_TABLE_QUERY];
-        let num_assocs = (get_raw_api!().get_report_table_assocs)(self.item_id, buf.as_mut_ptr(), buf.len() as i32);
+        require_function("XWF_GetReportTableAssocs")?;
+        let mut assocs = String::new();
+        let num_assocs = backend().get_report_table_assocs(self.item_id, &mut assocs);
 
         if num_assocs == 0 {
             return Ok(Vec::new());
         }
 
-        let assocs = winsafe::WString::from_wchars_slice(&buf).to_string().to_string();
-
-        if assocs.len() == buf.len() {
-            Err(XwfError::GivenBufferToSmallForContent)
-        } else {
-            util::split_values_by_comma(&assocs, num_assocs as usize)
-        }
+        util::split_values_by_comma(&assocs, num_assocs as usize)
     }
 
     pub fn get_hash_sets(&self) -> Result<Vec<String>, XwfError> {
-        let mut buf = [0u16; BUF_SIZE_REPORT_HASHSET_QUERY];
-        let num_assocs = (get_raw_api!().get_hashset_assocs)(self.item_id, buf.as_mut_ptr(), buf.len() as i32);
+        require_function("XWF_GetHashSetAssocs")?;
+        let mut assocs = String::new();
+        let num_assocs = backend().get_hashset_assocs(self.item_id, &mut assocs);
 
         if num_assocs < 0 {
             return Err(XwfError::XwfFunctionCallFailed("get_hashset_assocs"));
@@ -495,45 +587,37 @@ impl Item {
             return Ok(Vec::new());
         }
 
-        let assocs = winsafe::WString::from_wchars_slice(&buf).to_string();
-
-        if assocs.len() == buf.len() {
+        let vec_assocs: Vec<String> = assocs.split(", ").map(|s| String::from_str(s).unwrap()).collect();
+        if vec_assocs.len() != num_assocs as usize {
             Err(XwfError::GivenBufferToSmallForContent)
-
         } else {
-            let vec_assocs: Vec<String> = assocs.split(", ").map(|s| String::from_str(s).unwrap()).collect();
-            if vec_assocs.len() != num_assocs as usize {
-                Err(XwfError::GivenBufferToSmallForContent)
-            } else {
-                Ok(vec_assocs)
-            }
+            Ok(vec_assocs)
         }
 
     }
 
-    pub fn get_comment(&self) -> Option<String>  {
-        let ptr_wstr = (get_raw_api!().get_comment)(self.item_id);
+    /// `None` if the item has no comment
+    pub fn get_comment(&self) -> Result<Option<String>, XwfError> {
+        require_function("XWF_GetComment")?;
+        Ok(backend().get_comment(self.item_id))
+    }
 
-        if ptr_wstr == null_mut() {
-            None
+    pub fn add_comment(&self, comment: &str, flags: AddTextFlags) -> Result<(), XwfError> {
+        require_function("XWF_AddComment")?;
+        if backend().add_comment(self.item_id, comment, flags.bits()) {
+            Ok(())
         } else {
-            unsafe { Some(WString::from_wchars_nullt(ptr_wstr).to_string()) }
+            Err(XwfError::XwfFunctionCallFailed("add_comment"))
         }
-
     }
 
-    pub fn get_item_offset(&self) -> Option<(i64, i64)>{
-        let mut def_ofs = 0i64;
-        let mut start_sector = 0i64;
-
-        let ptr_def_ofs: *mut i64= &mut def_ofs;
-        let ptr_start_sector: *mut i64 = &mut start_sector;
-
-
-        (get_raw_api!().get_item_ofs)(self.item_id,ptr_def_ofs, ptr_start_sector);
+    /// `None` if the item has no defined offset (e.g. carved or virtual items)
+    pub fn get_item_offset(&self) -> Result<Option<(i64, i64)>, XwfError> {
+        require_function("XWF_GetItemOfs")?;
+        let (mut def_ofs, start_sector) = backend().get_item_ofs(self.item_id);
 
         if ( start_sector < 0 ) || (def_ofs == 0) || ((def_ofs & 0xFFFFFFFF) == 0xFFFFFFFF) {
-            return None;
+            return Ok(None);
         }
 
         if def_ofs < 0 {
@@ -542,47 +626,55 @@ impl Item {
 
         
 
-        Some((def_ofs, start_sector))
+        Ok(Some((def_ofs, start_sector)))
     }
 
-    pub fn get_extracted_metadata(&self) -> Option<Vec<String>>{
-        let ptr = (get_raw_api!().get_extracted_metadata)(self.item_id);
+    /// `None` if no metadata was extracted for the item
+    pub fn get_extracted_metadata(&self) -> Result<Option<Vec<String>>, XwfError> {
+        require_function("XWF_GetExtractedMetadata")?;
+        let Some(s) = backend().get_extracted_metadata(self.item_id) else {
+            return Ok(None);
+        };
+        Ok(Some(s.replace("\r", "")
+            .split("\n").filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+            .collect()))
+    }
 
-        unsafe {
-            if ptr == null_mut() {
-                None
-            } else {
-                let s = WString::from_wchars_nullt(ptr).to_string();
-                Some(s.replace("\r", "")
-                    .split("\n").filter(|v| !v.is_empty())
-                    .map(|v| v.to_string())
-                    .collect())
-            }
+    pub fn add_extracted_metadata(&self, metadata: &str, flags: AddTextFlags) -> Result<(), XwfError> {
+        require_function("XWF_AddExtractedMetadata")?;
+        if backend().add_extracted_metadata(self.item_id, metadata, flags.bits()) {
+            Ok(())
+        } else {
+            Err(XwfError::XwfFunctionCallFailed("add_extracted_metadata"))
         }
-
-        
     }
 
     pub fn get_item_category(&self) -> Result<(FileTypeStatus, FileFormatConsistency, FileTypeCategory), XwfError> {
-        let mut buf = [0u16; 256];
+        require_function("XWF_GetItemType")?;
+        let mut category = String::new();
 
         let flags = ItemTypeFlags::ReceiveTypeStatus.bitor(ItemTypeFlags::TextualDescriptionCategory);
 
-        let buf_and_flags = (buf.len() as u32) | flags.bits();
-        let status = (get_raw_api!().get_item_type)(self.item_id, buf.as_mut_ptr(), buf_and_flags);
+        let status = backend().get_item_type(self.item_id, &mut category, flags.bits());
 
-        if buf[0] == 0 {
+        if category.is_empty() {
             return Err(XwfError::XwfFunctionCallFailed("get_item_type"));
         }
 
         Ok(
             (   FileTypeStatus::try_from(status)?,
                 FileFormatConsistency::try_from(status)?,
-                FileTypeCategory::from(WString::from_wchars_slice(&buf.as_slice()).to_string())
+                FileTypeCategory::from(category)
             ),
         )
     }
 
+    /// collects the information selected by `options` in one serializable struct
+    pub fn to_record(&self, volume: &Volume, evidence: &Evidence, options: RecordOptions) -> Result<ItemRecord, XwfError> {
+        ItemRecord::new(self, volume, evidence, options)
+    }
+
     pub fn unique_id(&self, evidence: &Evidence) -> UniqueItemId {
         UniqueItemId {
             item_id: self.item_id,
@@ -593,24 +685,28 @@ impl Item {
 }
 
 
+/// Item opened for reading, e.g. passed to `XT_ProcessItemEx`.
+///
+/// `Send` and `Sync`: reads pass the offset explicitly, so the handle holds no position that
+/// concurrent reads could race on. It must not be used after it was closed, no matter from which thread.
 #[derive(Debug)]
 pub struct ItemHandle {
-    item_handle: HANDLE,
+    item_handle: XwfHandle,
     item: Item
 }
 
 impl NativeHandle for ItemHandle {
-    fn get_handle(&self) -> HANDLE {
-        return self.item_handle;
+    fn get_handle(&self) -> XwfHandle {
+        self.item_handle
     }
 }
 
 
 impl ItemHandle {
 
-    pub fn new(item_handle: HANDLE, item: Item) -> Result<ItemHandle, XwfError> {
+    pub fn new(item_handle: XwfHandle, item: Item) -> Result<ItemHandle, XwfError> {
 
-        if item_handle == null_mut() {
+        if item_handle.is_null() {
             return Err(XwfError::InputHandleIsNull);
         }
 
@@ -619,128 +715,112 @@ impl ItemHandle {
             item
         })
     }
-    pub fn handle(&self) -> HANDLE {
+    pub fn handle(&self) -> XwfHandle {
         self.item_handle
     }
 
     
 
 
-    pub fn get_metadata(&self, full_output: bool) -> Option<Vec<String>> {
-        let mut flags: DWORD = 1;
-        let flags_ptr: PDWORD = &mut flags;
+    /// `None` if X-Ways Forensics provides no metadata for the item
+    pub fn get_metadata(&self, full_output: bool) -> Result<Option<Vec<String>>, XwfError> {
+        require_function("XWF_GetMetadataEx")?;
+        let mut flags: u32 = 1;
 
         if full_output {
             flags = 0;
         }
-        let ptr = ((get_raw_api!().get_metadata_ex))(self.item_handle, flags_ptr);
-
-        if ptr == null_mut() {
-            None
-        } 
-        else {
-            if ( flags & 0xFF000000) != 0 {
-                (get_raw_api!().release_mem)(ptr);
-                return None;
-            } else {
-
-                let metadata_str;
-                if flags == 0x1 {
-                    metadata_str = char_ptr_to_string(ptr as *mut u8);
-                } else {
-                    unsafe { metadata_str = WString::from_wchars_nullt(ptr as *const u16).to_string(); }
-                }
-                
-                (get_raw_api!().release_mem)(ptr);
+        let Some(metadata_str) = backend().get_metadata_ex(self.item_handle, &mut flags) else {
+            return Ok(None);
+        };
 
-                Some(metadata_str.split('\n').map(|s| s.to_string()).collect())
-            }
-        }
+        Ok(Some(metadata_str.split('\n').map(|s| s.to_string()).collect()))
     }
 
-    pub fn get_prop(&self, prop_type: PropType) -> i64 {
-        (get_raw_api!().get_prop)(self.item_handle, prop_type as DWORD, null_mut())
+    pub fn get_prop(&self, prop_type: PropType) -> Result<i64, XwfError> {
+        require_function("XWF_GetProp")?;
+        Ok(backend().get_prop(self.item_handle, prop_type as u32, None))
     }
 
-    pub fn get_name(&self) -> String {
-        unsafe { WString::from_wchars_nullt(self.get_prop(PropType::PointerName) as LPWSTR).to_string() }
+    pub fn get_name(&self) -> Result<String, XwfError> {
+        require_function("XWF_GetProp")?;
+        let mut name = String::new();
+        backend().get_prop(self.item_handle, PropType::PointerName as u32, Some(&mut name));
+        Ok(name)
     }
 
-    pub fn get_path(&self) -> String {
-        unsafe { WString::from_wchars_nullt(self.get_prop(PropType::PointerFilePath) as LPWSTR).to_string() }
+    pub fn get_path(&self) -> Result<String, XwfError> {
+        require_function("XWF_GetProp")?;
+        let mut path = String::new();
+        backend().get_prop(self.item_handle, PropType::PointerFilePath as u32, Some(&mut path));
+        Ok(path)
     }
 
     pub fn get_logical_size(&self) -> Result<i64, XwfError> {
-        let size = self.get_prop(PropType::LogicalSize);
+        let size = self.get_prop(PropType::LogicalSize)?;
         if size > 0 {
-                return Ok(size);
+                Ok(size)
         } else {
-            return Err(XwfError::InvalidItemSize);
-        };
+            Err(XwfError::InvalidItemSize)
+        }
     }
 
-    pub fn get_physical_size(&self) -> i64 {
+    pub fn get_physical_size(&self) -> Result<i64, XwfError> {
         self.get_prop(PropType::PhysicalSize)
     }
     pub fn close(&self) {
-        (get_raw_api!().close)(self.item_handle);
+        backend().close(self.item_handle);
     }
 
     pub fn item(&self) -> &Item {
         &self.item
     }
 
+    /// returns a buffered reader for the item data, see [`ItemReader`]
+    pub fn reader(&self) -> Result<ItemReader<'_>, XwfError> {
+        ItemReader::new(self)
+    }
+
     pub fn read(&self) -> Result<Vec<u8>, XwfError>{
-        let size = self.get_logical_size()?;
-        if size <= 0 {
+        let mut reader = self.reader()?;
+        if reader.size() == 0 {
             return Err(XwfError::InvalidItemSize);
         }
-        let mut ret: Vec<u8> = Vec::with_capacity(size as usize);
-
-        while let Some(mut data) = self.read_chunk(ret.len(), DEFAULT_DATA_CHUNK_SIZE as usize) {
-            Application::should_stop()?;
-            ret.append(&mut data);
-        }
+        let mut ret: Vec<u8> = Vec::with_capacity(reader.size() as usize);
+        reader.read_to_end(&mut ret).map_err(XwfError::from_io)?;
 
-        if ret.len() == 0 {
+        if ret.is_empty() {
             Err(XwfError::ReadItemDataFailed)
         } else {
             Ok(ret)
         }
     }
 
-    pub fn read_chunk(&self, offset: usize, chunk_size: usize ) -> Option<Vec<u8>> {
+    /// reads up to `chunk_size` bytes at `offset`, `None` if nothing could be read
+    pub fn read_chunk(&self, offset: usize, chunk_size: usize) -> Result<Option<Vec<u8>>, XwfError> {
+        require_function("XWF_Read")?;
         let mut byte_buf: Vec<u8> = vec![0; chunk_size];
-        let r = (get_raw_api!().read)(self.item_handle, offset as __int64, byte_buf.as_mut_ptr(), chunk_size as DWORD);
+        let r = backend().read(self.item_handle, offset as i64, &mut byte_buf);
 
-        if r<= 0 {
-            None
-        } else if r != chunk_size as DWORD {
+        if r == 0 {
+            Ok(None)
+        } else if r != chunk_size as u32 {
             byte_buf.truncate(r as usize);
-            Some(byte_buf)
+            Ok(Some(byte_buf))
         } else {
-            Some(byte_buf)
+            Ok(Some(byte_buf))
         }
-
     }
 
 
     pub fn write_to_file<P: AsRef<Path>>(&self, dest: P) -> Result<(), XwfError>{
 
-        let mut file = File::create(dest).map_err(|e| XwfError::IoError(e) )?;
-
-        let mut current_offset = 0usize;
-
-        while let Some(data) = self.read_chunk(current_offset, DEFAULT_DATA_CHUNK_SIZE as usize) {
-            current_offset+=data.len();
-            file.write_all(&data.as_slice()).map_err(|e| XwfError::IoError(e))?;
-        }
+        let mut file = File::create(dest).map_err(XwfError::IoError )?;
+        let mut reader = self.reader()?;
+        io::copy(&mut reader, &mut file).map_err(XwfError::from_io)?;
 
         Ok(())
     }
 
 
 }
-
-unsafe impl Send for Item {}
-unsafe impl Sync for Item {}
\ No newline at end of file
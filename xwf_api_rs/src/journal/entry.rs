@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Single line of the journal file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    /// evidence object whose volume snapshot was modified, `None` if the X-Tension was not
+    /// prepared for a specific evidence object
+    pub evidence_id: Option<u32>,
+    #[serde(flatten)]
+    pub change: JournalChange,
+}
+
+/// Modification of the volume snapshot with the values before and after the call, hash values
+/// are hex encoded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "call")]
+pub enum JournalChange {
+    #[serde(rename = "XWF_SetItemInformation")]
+    ItemInformation { item_id: i32, info_type: i32, value: i64, before: Option<i64>, after: Option<i64>, success: bool },
+    #[serde(rename = "XWF_SetItemSize")]
+    ItemSize { item_id: i32, before: i64, after: i64 },
+    #[serde(rename = "XWF_SetItemParent")]
+    ItemParent { item_id: i32, before: i32, after: i32 },
+    #[serde(rename = "XWF_SetItemType")]
+    ItemType { item_id: i32, before: String, before_status: i32, after: String, after_status: i32 },
+    #[serde(rename = "XWF_AddToReportTable")]
+    ReportTable { item_id: i32, name: String, flags: u32, result: i32 },
+    #[serde(rename = "XWF_SetHashValue")]
+    HashValue { item_id: i32, param: u32, before: Option<String>, after: String, success: bool },
+    #[serde(rename = "XWF_CreateFile")]
+    CreateFile { name: String, flags: u32, parent_item_id: i32, item_id: i32 },
+    #[serde(rename = "XWF_AddComment")]
+    Comment { item_id: i32, before: Option<String>, after: Option<String>, success: bool },
+    #[serde(rename = "XWF_AddExtractedMetadata")]
+    ExtractedMetadata { item_id: i32, before: Option<String>, after: Option<String>, success: bool },
+}
+
+impl JournalChange {
+    /// name of the journaled API function
+    pub fn call(&self) -> &'static str {
+        match self {
+            JournalChange::ItemInformation { .. } => "XWF_SetItemInformation",
+            JournalChange::ItemSize { .. } => "XWF_SetItemSize",
+            JournalChange::ItemParent { .. } => "XWF_SetItemParent",
+            JournalChange::ItemType { .. } => "XWF_SetItemType",
+            JournalChange::ReportTable { .. } => "XWF_AddToReportTable",
+            JournalChange::HashValue { .. } => "XWF_SetHashValue",
+            JournalChange::CreateFile { .. } => "XWF_CreateFile",
+            JournalChange::Comment { .. } => "XWF_AddComment",
+            JournalChange::ExtractedMetadata { .. } => "XWF_AddExtractedMetadata",
+        }
+    }
+
+    /// the modified item, for created files the new item
+    pub fn item_id(&self) -> i32 {
+        match self {
+            JournalChange::ItemInformation { item_id, .. }
+            | JournalChange::ItemSize { item_id, .. }
+            | JournalChange::ItemParent { item_id, .. }
+            | JournalChange::ItemType { item_id, .. }
+            | JournalChange::ReportTable { item_id, .. }
+            | JournalChange::HashValue { item_id, .. }
+            | JournalChange::CreateFile { item_id, .. }
+            | JournalChange::Comment { item_id, .. }
+            | JournalChange::ExtractedMetadata { item_id, .. } => *item_id,
+        }
+    }
+}
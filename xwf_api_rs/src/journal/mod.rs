@@ -0,0 +1,15 @@
+//! Journal of all modifications of volume snapshots (feature `journal`). In X-Ways Forensics,
+//! every modifying call (item information, report tables, hash values, created files, ...) is
+//! journaled to a file in the case directory by the [`JournalingBackend`], including the values
+//! before the call. A run can be reverted with [`undo_latest`] as far as the X-Tension API permits.
+//! If the journal file cannot be created, the X-Tension runs without journal after a warning.
+
+mod entry;
+mod recorder;
+mod undo;
+
+
+pub use entry::{JournalChange, JournalEntry};
+pub use recorder::JournalingBackend;
+pub(crate) use recorder::{active_journal, set_suspended};
+pub use undo::{undo_latest, Journal, UndoSummary};
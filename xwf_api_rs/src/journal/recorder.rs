@@ -0,0 +1,263 @@
+use std::fs::OpenOptions;
+use std::io;
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use crate::backend::{delegate_to_inner, VolumeInformation, XwfBackend};
+use crate::capabilities::Capabilities;
+use crate::case::case_file;
+use crate::evidence::ReportTableListItem;
+use crate::search::SearchHit;
+use crate::event::Event;
+use crate::volume::HashType;
+use crate::xwf_types::{EvObjPropType, SrcInfo, VsPropType, XwfHandle, XwfItemInfoTypes};
+use crate::xwfwarn;
+use super::entry::{JournalChange, JournalEntry};
+
+// journal file written by the backend installed for the running X-Tension, never undone
+static ACTIVE_JOURNAL: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// set while a journal is undone, so that reverting a journal does not create a new one
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn active_journal() -> Option<PathBuf> {
+    ACTIVE_JOURNAL.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub(crate) fn set_suspended(suspended: bool) {
+    SUSPENDED.store(suspended, Ordering::SeqCst);
+}
+
+struct JournalWriter {
+    out: Box<dyn Write + Send>,
+    seq: u64,
+    // only the first write error is reported
+    failed: bool,
+}
+
+/// [`XwfBackend`] wrapper that forwards all calls to the wrapped backend and journals every call
+/// modifying the volume snapshot with the values before and after the call as JSON lines. The
+/// journal can be undone via [`super::Journal::undo`].
+pub struct JournalingBackend<B: XwfBackend> {
+    inner: B,
+    writer: Mutex<JournalWriter>,
+    evidence_id: Mutex<Option<u32>>,
+}
+
+impl<B: XwfBackend> JournalingBackend<B> {
+    pub fn new<W: Write + Send + 'static>(inner: B, writer: W) -> JournalingBackend<B> {
+        JournalingBackend {
+            inner,
+            writer: Mutex::new(JournalWriter { out: Box::new(writer), seq: 0, failed: false }),
+            evidence_id: Mutex::new(None),
+        }
+    }
+
+    /// appends to the journal file if it already exists
+    pub fn create<P: AsRef<Path>>(inner: B, path: P) -> io::Result<JournalingBackend<B>> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(JournalingBackend::new(inner, LineWriter::new(file)))
+    }
+
+    /// creates the journal file `<extension_name>_journal_<timestamp>.jsonl` in the case directory.
+    /// Gives the backend back on failure.
+    pub fn create_in_case_dir(inner: B, extension_name: &str) -> Result<JournalingBackend<B>, (B, io::Error)> {
+        let path = case_file(&inner, &format!("{}_journal", extension_name), "jsonl");
+
+        let file = match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => file,
+            Err(e) => return Err((inner, e)),
+        };
+        *ACTIVE_JOURNAL.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+        Ok(JournalingBackend::new(inner, LineWriter::new(file)))
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    fn record(&self, change: JournalChange) {
+        if SUSPENDED.load(Ordering::SeqCst) {
+            return;
+        }
+        let evidence_id = *self.evidence_id.lock().unwrap_or_else(|e| e.into_inner());
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writer.seq += 1;
+
+        let entry = JournalEntry { seq: writer.seq, timestamp: chrono::Utc::now(), evidence_id, change };
+
+        // the modification already happened, so write errors cannot be reported back to the caller
+        let res = serde_json::to_string(&entry)
+            .map_err(io::Error::from)
+            .and_then(|line| writeln!(writer.out, "{}", line));
+        if let Err(e) = res {
+            if !writer.failed {
+                writer.failed = true;
+                drop(writer);
+                xwfwarn!("failed to write journal entry {}, the journal is incomplete and cannot be fully undone: {}", entry.seq, e);
+            }
+        }
+    }
+
+    /// true if at least one journal entry could not be written
+    pub fn has_write_errors(&self) -> bool {
+        self.writer.lock().unwrap_or_else(|e| e.into_inner()).failed
+    }
+}
+
+impl<B: XwfBackend> XwfBackend for JournalingBackend<B> {
+    fn set_item_size(&self, item_id: i32, size: i64) {
+        let before = self.inner.get_item_size(item_id);
+        self.inner.set_item_size(item_id, size);
+        self.record(JournalChange::ItemSize { item_id, before, after: size });
+    }
+
+    fn set_item_information(&self, item_id: i32, info_type: i32, value: i64) -> bool {
+        // flags are set and removed via the pseudo types 64 and 65, but read as one value
+        let read_type = if info_type == 64 || info_type == 65 { XwfItemInfoTypes::Flags as i32 } else { info_type };
+        let before = self.inner.get_item_information(item_id, read_type);
+        let ret = self.inner.set_item_information(item_id, info_type, value);
+        let after = self.inner.get_item_information(item_id, read_type);
+        self.record(JournalChange::ItemInformation { item_id, info_type, value, before, after, success: ret });
+        ret
+    }
+
+    fn set_item_type(&self, item_id: i32, type_descr: &str, type_status: i32) {
+        let mut before = String::new();
+        let before_status = self.inner.get_item_type(item_id, &mut before, 0) & 0xFF;
+        self.inner.set_item_type(item_id, type_descr, type_status);
+        self.record(JournalChange::ItemType {
+            item_id,
+            before,
+            before_status,
+            after: type_descr.to_string(),
+            after_status: type_status,
+        });
+    }
+
+    fn set_item_parent(&self, child_item_id: i32, parent_item_id: i32) {
+        let before = self.inner.get_item_parent(child_item_id);
+        self.inner.set_item_parent(child_item_id, parent_item_id);
+        self.record(JournalChange::ItemParent { item_id: child_item_id, before, after: parent_item_id });
+    }
+
+    fn add_to_report_table(&self, item_id: i32, report_table_name: &str, flags: u32) -> i32 {
+        let ret = self.inner.add_to_report_table(item_id, report_table_name, flags);
+        self.record(JournalChange::ReportTable { item_id, name: report_table_name.to_string(), flags, result: ret });
+        ret
+    }
+
+    fn add_comment(&self, item_id: i32, comment: &str, flags: u32) -> bool {
+        let before = self.inner.get_comment(item_id);
+        let ret = self.inner.add_comment(item_id, comment, flags);
+        let after = self.inner.get_comment(item_id);
+        self.record(JournalChange::Comment { item_id, before, after, success: ret });
+        ret
+    }
+
+    fn add_extracted_metadata(&self, item_id: i32, metadata: &str, flags: u32) -> bool {
+        let before = self.inner.get_extracted_metadata(item_id);
+        let ret = self.inner.add_extracted_metadata(item_id, metadata, flags);
+        let after = self.inner.get_extracted_metadata(item_id);
+        self.record(JournalChange::ExtractedMetadata { item_id, before, after, success: ret });
+        ret
+    }
+
+    fn set_hash_value(&self, item_id: i32, hash: &[u8], param: u32) -> bool {
+        // the hash type of the volume determines the length, a hash of another length is not read
+        let prop_type = if param == 2 { VsPropType::HashType2 } else { VsPropType::HashType1 };
+        let hash_size = HashType::try_from(self.inner.get_vs_prop(prop_type as i32, None))
+            .map(|hash_type| hash_type.get_hash_size())
+            .unwrap_or(0);
+        let before = if hash_size > 0 && hash_size == hash.len() {
+            let mut buffer = vec![0u8; hash_size.max(4)];
+            buffer[..4].copy_from_slice(&param.to_le_bytes());
+            self.inner.get_hash_value(item_id, &mut buffer)
+                .then(|| hex::encode(&buffer[..hash_size]))
+        } else {
+            None
+        };
+
+        let ret = self.inner.set_hash_value(item_id, hash, param);
+        self.record(JournalChange::HashValue { item_id, param, before, after: hex::encode(hash), success: ret });
+        ret
+    }
+
+    fn create_file(&self, name: &str, creation_flags: u32, parent_item_id: i32, src_info: &mut SrcInfo) -> i32 {
+        let ret = self.inner.create_file(name, creation_flags, parent_item_id, src_info);
+        self.record(JournalChange::CreateFile {
+            name: name.to_string(),
+            flags: creation_flags,
+            parent_item_id,
+            item_id: ret,
+        });
+        ret
+    }
+
+    fn callback_invoked(&self, name: &str, args: &[i64]) {
+        self.inner.callback_invoked(name, args);
+
+        // all following modifications belong to the evidence object the X-Tension is prepared for
+        if name == "XT_Prepare" {
+            let evidence = XwfHandle::from_raw(args.get(1).copied().unwrap_or(0) as usize);
+            let evidence_id = if evidence.is_null() {
+                None
+            } else {
+                Some(self.inner.get_ev_obj_prop(evidence, EvObjPropType::ObjId as u32, None) as u32)
+            };
+            *self.evidence_id.lock().unwrap_or_else(|e| e.into_inner()) = evidence_id;
+        }
+    }
+
+    // all other calls are not journaled
+    delegate_to_inner! {
+        fn output_message(&self, message: &str, flags: u32);
+        fn get_volume_name(&self, volume: XwfHandle, name_type: u32) -> String;
+        fn get_volume_information(&self, volume: XwfHandle) -> Option<VolumeInformation>;
+        fn select_volume_snapshot(&self, volume: XwfHandle) -> i32;
+        fn get_vs_prop(&self, prop_type: i32, value: Option<u32>) -> i64;
+        fn get_item_count(&self, selected_in_dbc: bool) -> u32;
+        fn get_item_name(&self, item_id: u32) -> String;
+        fn get_item_size(&self, item_id: i32) -> i64;
+        fn get_item_ofs(&self, item_id: i32) -> (i64, i64);
+        fn get_item_information(&self, item_id: i32, info_type: i32) -> Option<i64>;
+        fn get_item_type(&self, item_id: i32, type_descr: &mut String, flags: u32) -> i32;
+        fn get_item_parent(&self, item_id: i32) -> i32;
+        fn get_report_table_assocs(&self, item_id: i32, assocs: &mut String) -> u32;
+        fn get_hashset_assocs(&self, item_id: i32, assocs: &mut String) -> i32;
+        fn get_comment(&self, item_id: i32) -> Option<String>;
+        fn get_extracted_metadata(&self, item_id: i32) -> Option<String>;
+        fn get_metadata_ex(&self, item: XwfHandle, flags: &mut u32) -> Option<String>;
+        fn get_hash_value(&self, item_id: i32, buffer: &mut [u8]) -> bool;
+        fn open_item(&self, volume: XwfHandle, item_id: i32, flags: u32) -> XwfHandle;
+        fn close(&self, volume_or_item: XwfHandle);
+        fn read(&self, volume_or_item: XwfHandle, offset: i64, buffer: &mut [u8]) -> u32;
+        fn get_prop(&self, volume_or_item: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64;
+        fn get_first_ev_obj(&self) -> XwfHandle;
+        fn get_next_ev_obj(&self, prev_evidence: XwfHandle) -> XwfHandle;
+        fn get_ev_obj(&self, ev_obj_id: u32) -> XwfHandle;
+        fn open_ev_obj(&self, evidence: XwfHandle, flags: u32) -> XwfHandle;
+        fn close_ev_obj(&self, evidence: XwfHandle);
+        fn get_ev_obj_prop(&self, evidence: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64;
+        fn get_ev_obj_report_table_assocs(&self, evidence: XwfHandle, flags: i32) -> Option<Vec<ReportTableListItem>>;
+        fn get_case_prop(&self, prop_type: i32, string: Option<&mut String>) -> i64;
+        fn get_report_table_info(&self, report_table_id: i32, optional: &mut i32) -> Option<String>;
+        fn show_progress(&self, caption: &str, flags: u32);
+        fn set_progress_description(&self, description: &str);
+        fn set_progress_percentage(&self, percent: u32);
+        fn hide_progress(&self);
+        fn should_stop(&self) -> bool;
+        fn get_user_input(&self, message: &str, input: Option<&mut String>, flags: u32) -> i64;
+        fn search(&self, volume: XwfHandle, search_terms: &str, flags: u32, search_window: u32, code_pages: Option<[u16; 5]>) -> i32;
+        fn get_search_term(&self, search_term_id: i32) -> Option<String>;
+        fn get_search_term_count(&self) -> i32;
+        fn add_search_term(&self, name: &str, flags: u32) -> i32;
+        fn get_search_hit(&self, hit_no: i32) -> Option<SearchHit>;
+        fn set_search_hit(&self, hit_no: i32, hit: &SearchHit) -> bool;
+        fn add_search_hit(&self, hit: &SearchHit) -> i32;
+        fn get_event(&self, evidence: XwfHandle, event_no: u32) -> Option<Event>;
+        fn add_event(&self, event: &Event) -> i32;
+        fn capabilities(&self) -> Capabilities;
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use crate::backend::backend;
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::xwfwarn;
+use super::entry::{JournalChange, JournalEntry};
+
+/// Result of [`Journal::undo`]
+#[derive(Debug, Default)]
+pub struct UndoSummary {
+    /// number of changes that were reverted
+    pub restored: usize,
+    /// changes that cannot be reverted, e.g. created files or report table associations
+    pub skipped: Vec<(JournalEntry, &'static str)>,
+    pub failed: Vec<(JournalEntry, XwfError)>,
+}
+
+// reason for skipping calls that failed, these did not change anything
+const CALL_FAILED: &str = "call failed, nothing was changed";
+
+impl UndoSummary {
+    /// number of skipped changes per API function and reason, without calls that failed
+    /// and therefore did not change anything
+    pub fn skipped_kinds(&self) -> BTreeMap<(&'static str, &'static str), usize> {
+        let mut kinds = BTreeMap::new();
+        for (entry, reason) in self.skipped.iter().filter(|(_, reason)| *reason != CALL_FAILED) {
+            *kinds.entry((entry.change.call(), *reason)).or_insert(0) += 1;
+        }
+        kinds
+    }
+}
+
+enum Outcome {
+    Restored,
+    Skipped(&'static str),
+}
+
+/// Journal file written by [`super::JournalingBackend`]
+pub struct Journal {
+    path: PathBuf,
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Journal, XwfError> {
+        let reader = BufReader::new(File::open(path.as_ref())?);
+        let mut entries = Vec::new();
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line).map_err(|e| XwfError::InvalidJournal(e.to_string()))?);
+        }
+        Ok(Journal { path: path.as_ref().to_path_buf(), entries })
+    }
+
+    /// loads the most recent journal of the X-Tension in the directory of the currently opened case,
+    /// skipping empty journals, journals that were undone already and the journal of the running X-Tension
+    pub fn latest_in_case_dir(extension_name: &str) -> Result<Option<Journal>, XwfError> {
+        let prefix = format!("{}_journal_", extension_name);
+        let active = super::active_journal();
+
+        let latest = fs::read_dir(crate::case::case_dir(&*backend()))?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".jsonl")))
+            .filter(|p| active.as_ref() != Some(p))
+            .filter(|p| fs::metadata(p).is_ok_and(|m| m.len() > 0))
+            // the timestamp in the file name sorts chronologically
+            .max();
+
+        latest.map(Journal::load).transpose()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// reverts all changes in reverse order. For each evidence object, its volume snapshot is
+    /// selected before its changes are reverted, so this must be called from the main thread.
+    /// The reverting calls are not journaled.
+    pub fn undo(&self) -> UndoSummary {
+        super::set_suspended(true);
+        let summary = self.undo_entries();
+        super::set_suspended(false);
+        summary
+    }
+
+    fn undo_entries(&self) -> UndoSummary {
+        let mut summary = UndoSummary::default();
+        let mut selected: Option<Option<u32>> = None;
+
+        for entry in self.entries.iter().rev() {
+            if selected != Some(entry.evidence_id) {
+                if let Some(evidence_id) = entry.evidence_id {
+                    if let Err(e) = select_evidence(evidence_id) {
+                        summary.failed.push((entry.clone(), e));
+                        continue;
+                    }
+                }
+                selected = Some(entry.evidence_id);
+            }
+
+            match undo_change(&entry.change) {
+                Ok(Outcome::Restored) => summary.restored += 1,
+                Ok(Outcome::Skipped(reason)) => summary.skipped.push((entry.clone(), reason)),
+                Err(e) => summary.failed.push((entry.clone(), e)),
+            }
+        }
+        summary
+    }
+
+    /// renames the journal file to `<name>.jsonl.undone`, so that it is not undone again
+    pub fn mark_undone(self) -> Result<PathBuf, XwfError> {
+        let mut undone = self.path.clone().into_os_string();
+        undone.push(".undone");
+        fs::rename(&self.path, &undone)?;
+        Ok(PathBuf::from(undone))
+    }
+}
+
+/// undoes the most recent journal of the X-Tension in the case directory and marks it as undone,
+/// so that repeated calls undo earlier runs step by step. Intended to be called in `xt_prepare`
+/// for [`crate::xwf_types::XtPrepareOpType::ActionRun`]. `None` if there is nothing to undo.
+/// Changes that cannot be reverted are logged as warnings, see [`UndoSummary::skipped_kinds`].
+pub fn undo_latest(extension_name: &str) -> Result<Option<UndoSummary>, XwfError> {
+    let journal = match Journal::latest_in_case_dir(extension_name)? {
+        Some(journal) => journal,
+        None => return Ok(None),
+    };
+
+    let summary = journal.undo();
+    for ((call, reason), count) in summary.skipped_kinds() {
+        xwfwarn!("{} change(s) by {} were not undone: {}", count, call, reason);
+    }
+    journal.mark_undone()?;
+    Ok(Some(summary))
+}
+
+fn select_evidence(evidence_id: u32) -> Result<(), XwfError> {
+    let evidence = Evidence::get_ev_obj(evidence_id).ok_or(XwfError::NoEvidenceAvaible)?;
+    evidence.open()?.select()?;
+    Ok(())
+}
+
+fn check(success: bool, func: &'static str) -> Result<Outcome, XwfError> {
+    if success {
+        Ok(Outcome::Restored)
+    } else {
+        Err(XwfError::XwfFunctionCallFailed(func))
+    }
+}
+
+fn undo_change(change: &JournalChange) -> Result<Outcome, XwfError> {
+    let backend = backend();
+
+    match change {
+        JournalChange::ItemInformation { success: false, .. }
+        | JournalChange::HashValue { success: false, .. }
+        | JournalChange::Comment { success: false, .. }
+        | JournalChange::ExtractedMetadata { success: false, .. } => Ok(Outcome::Skipped(CALL_FAILED)),
+
+        JournalChange::ItemInformation { before: None, .. } => Ok(Outcome::Skipped("previous value unknown")),
+
+        // only the flags changed by the call are reverted
+        JournalChange::ItemInformation { item_id, info_type: 64 | 65, before: Some(before), after, .. } => {
+            let after = after.unwrap_or(*before);
+            let added = after & !before;
+            let removed = before & !after;
+
+            if added != 0 && !backend.set_item_information(*item_id, 65, added) {
+                return Err(XwfError::XwfFunctionCallFailed("set_item_information"));
+            }
+            check(removed == 0 || backend.set_item_information(*item_id, 64, removed), "set_item_information")
+        },
+
+        JournalChange::ItemInformation { item_id, info_type, before: Some(before), .. } => {
+            check(backend.set_item_information(*item_id, *info_type, *before), "set_item_information")
+        },
+
+        JournalChange::ItemSize { item_id, before, .. } => {
+            backend.set_item_size(*item_id, *before);
+            Ok(Outcome::Restored)
+        },
+
+        JournalChange::ItemParent { before: -1, .. } => Ok(Outcome::Skipped("previous parent unknown")),
+
+        JournalChange::ItemParent { item_id, before, .. } => {
+            backend.set_item_parent(*item_id, *before);
+            crate::tree::invalidate();
+            Ok(Outcome::Restored)
+        },
+
+        JournalChange::ItemType { item_id, before, before_status, .. } => {
+            backend.set_item_type(*item_id, before, *before_status);
+            Ok(Outcome::Restored)
+        },
+
+        JournalChange::ReportTable { .. } => Ok(Outcome::Skipped("report table associations cannot be removed")),
+
+        JournalChange::HashValue { before: None, .. } => Ok(Outcome::Skipped("no previous hash value")),
+
+        JournalChange::HashValue { item_id, param, before: Some(before), .. } => {
+            let hash = hex::decode(before).map_err(|e| XwfError::InvalidJournal(e.to_string()))?;
+            check(backend.set_hash_value(*item_id, &hash, *param), "set_hash_value")
+        },
+
+        JournalChange::CreateFile { .. } => Ok(Outcome::Skipped("created files cannot be deleted")),
+
+        JournalChange::Comment { item_id, before, .. } => {
+            check(backend.add_comment(*item_id, before.as_deref().unwrap_or(""), 0), "add_comment")
+        },
+
+        JournalChange::ExtractedMetadata { item_id, before, .. } => {
+            check(backend.add_extracted_metadata(*item_id, before.as_deref().unwrap_or(""), 0), "add_extracted_metadata")
+        },
+    }
+}
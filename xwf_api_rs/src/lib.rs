@@ -1,5 +1,14 @@
 pub mod item;
+pub mod reader;
+pub mod search;
+pub mod event;
+pub mod annotate;
 pub mod volume;
+pub mod tree;
+pub mod vfs;
+pub mod record;
+pub mod dfxml;
+pub mod timeline;
 pub mod evidence;
 pub mod util;
 pub mod case;
@@ -8,13 +17,34 @@ pub mod error;
 pub mod traits;
 pub mod window;
 pub mod macros;
+pub mod panic_guard;
+pub mod mt;
 pub mod raw_api;
+pub mod ffi_types;
+pub mod wide_string;
+pub mod backend;
+pub mod capabilities;
+#[cfg(any(test, feature = "simulator"))]
+pub mod simulator;
+#[cfg(feature = "trace")]
+pub mod trace;
+#[cfg(feature = "journal")]
+pub mod journal;
+#[cfg(feature = "export")]
+pub mod export;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod dispatch;
 pub mod xwf_types;
 pub mod xwf_function_types;
+#[cfg(test)]
+mod tests;
 
 
 // inherit packages
 pub use chrono;
+pub use xwf_api_rs_macros::xtension;
+#[cfg(windows)]
 pub use winapi;
 
 
@@ -1,242 +1,232 @@
+// The export macros dispatch to the static instance created by `create_static_instance!` or
+// `create_static_instance_mt!`, both instance types provide the same methods.
+
 #[macro_export]
 macro_rules! export_xt_init {
-
     ($variable:ident, $variable_type:ty) => {
         #[no_mangle]
         #[allow(non_snake_case, unused_variables)]
-        pub extern "C"  fn XT_Init(nVersion: DWORD, nFlags: DWORD, hMainWnd: HANDLE, lpReserved: PVOID) -> LONG {
-
-            unsafe {
-                RAW_API = $crate::raw_api::RawApi::load().ok();
-            }
-            
-            unsafe {
-                $variable = Some(<$variable_type>::create());
-            }
+        pub extern "C" fn XT_Init(nVersion: DWORD, nFlags: DWORD, hMainWnd: XwfHandle, lpReserved: PVOID) -> LONG {
+            $crate::panic_guard::catch_panic("XT_Init", || $variable.panic_returns().init.into(), || {
 
-            $crate::xwfdebug!("XT_Init called");
-
-            let result_version_check = XtVersion::try_from(nVersion).and_then(|v| $crate::util::check_supported_xwf_version(v));
-
-            match result_version_check {
-                Ok(_) => { $crate::xwfinfo!("X-Tension API version check successful") },
-                Err(e) => {
-                    $crate::xwferror!("X-Tension API version check failed: {}", e);
+                // keep an already installed backend, e.g. a test double or a wrapper
+                if !$crate::backend::has_backend() && $crate::backend::install_xwf_backend(env!("CARGO_PKG_NAME")).is_err() {
                     return XtInitReturn::PreventFurtherUseOfDll as i32;
                 }
-            }
-
-            let flags = XtInitFlags::from_bits_truncate(nFlags);
-
-
-            $crate::xwfinfo!("X-Tension \"{}\" Version {} started", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") );
-            $crate::xwfinfo!("powered by rust-lang binding xwf-api-rs (https://github.com/ThomasVogl/xwf-api-rs)");
-
-
-            let res = $crate::get_lib_instance!($variable, $variable_type).xt_init(
-                XtVersion::try_from(nVersion).unwrap(),
-                flags,
-                $crate::window::Window::new(hMainWnd), XtLicenseInfo {}
-            );
-
-
-            match res {
-                Ok(ret) => ret as i32,
-                Err(e) => {
-                    $crate::xwferror!("XT_Init: {}", e);
-                    XtInitReturn::PreventFurtherUseOfDll as i32
-                }
-            }
+                $crate::backend::notify_callback("XT_Init", &[nVersion as i64, nFlags as i64]);
+                $crate::xwfdebug!("XT_Init called");
+
+                let version = match XtVersion::try_from(nVersion) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        $crate::xwferror!("X-Tension API version check failed: {}", e);
+                        return XtInitReturn::PreventFurtherUseOfDll as i32;
+                    }
+                };
+
+                $crate::xwfinfo!("X-Tension \"{}\" Version {} started", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") );
+                $crate::xwfinfo!("powered by rust-lang binding xwf-api-rs (https://github.com/ThomasVogl/xwf-api-rs)");
+
+                $variable.xt_init(version, XtInitFlags::from_bits_truncate(nFlags), $crate::window::Window::new(hMainWnd))
+            })
         }
     }
-
 }
-#[macro_export]
-macro_rules! get_lib_instance {
-    ($variable:ident, $variable_type:ty) => {
-        unsafe {
-            $variable.as_mut().unwrap()
-        }
 
-    }
-}
 #[macro_export]
 macro_rules! export_xt_done {
     ($variable:ident, $variable_type:ty) => {
         #[no_mangle]
         #[allow(non_snake_case, unused_variables)]
-        pub extern "C" fn XT_Done(lpReserved: PVOID)
-            -> LONG {
-            $crate::xwfdebug!("XT_Done called");
-            let res = $crate::get_lib_instance!($variable, $variable_type).xt_done();
-
-            if res.is_err() {
-                $crate::xwferror!("XT_Done: {}", res.err().unwrap());
-            }
-
-            $crate::xwfinfo!("X-Tension finished.");
-
-            //uninitalize raw api
-            unsafe {
-                let _ = RAW_API.take();
-                let _ = $variable.take();
-            }
-            0
+        pub extern "C" fn XT_Done(lpReserved: PVOID) -> LONG {
+            $crate::panic_guard::catch_panic("XT_Done", || 0, || {
+                $crate::backend::notify_callback("XT_Done", &[]);
+                $crate::xwfdebug!("XT_Done called");
+                let ret = $variable.xt_done();
+                $crate::xwfinfo!("X-Tension finished.");
+
+                //uninitalize backend
+                let _ = $crate::backend::reset_backend();
+                ret
+            })
         }
     };
 }
+
 #[macro_export]
 macro_rules! export_xt_about {
     ($variable:ident, $variable_type:ty) => {
         #[no_mangle]
         #[allow(non_snake_case, unused_variables)]
-        pub extern "C" fn XT_About(hParentWnd: HANDLE, lpReserved: PVOID)
-            -> LONG {
-            $crate::xwfdebug!("XT_About called");
-            let res = $crate::get_lib_instance!($variable, $variable_type).xt_about($crate::window::Window::new(hParentWnd));
-            match res {
-                Ok(_) => {
-                     0
-                },
-                Err(e) => {
-                    $crate::xwferror!("XT_About: {}", e);
-                    0
-                }
-            }
-
+        pub extern "C" fn XT_About(hParentWnd: XwfHandle, lpReserved: PVOID) -> LONG {
+            $crate::panic_guard::catch_panic("XT_About", || 0, || {
+                $crate::backend::notify_callback("XT_About", &[hParentWnd.as_raw() as i64]);
+                $variable.xt_about($crate::window::Window::new(hParentWnd))
+            })
         }
     };
 }
+
 #[macro_export]
 macro_rules! export_xt_prepare {
     ($variable:ident, $variable_type:ty) => {
         #[no_mangle]
         #[allow(non_snake_case, unused_variables)]
-        pub extern "C" fn XT_Prepare(hVolume: HANDLE, hEvidence: HANDLE,  nOpType: DWORD, lpReserved: PVOID
+        pub extern "C" fn XT_Prepare(hVolume: XwfHandle, hEvidence: XwfHandle,  nOpType: DWORD, lpReserved: PVOID
         ) -> LONG {
-            $crate::xwfdebug!("XT_Prepare called");
-
-            let opt_op_type = XtPrepareOpType::try_from(nOpType);
-            if opt_op_type.is_err() {
-                $crate::xwferror!("error in parsing nOpType argument");
-                return XtPrepareReturn::Negative(XtPrepareNegativeReturn::JustCallXtFinalize).into();
-            }
-
-            let res = $crate::get_lib_instance!($variable, $variable_type).xt_prepare(
-                $crate::volume::Volume::new(hVolume).ok(),
-                $crate::evidence::Evidence::new(hEvidence),
-                opt_op_type.unwrap());
-
-            match res {
-                Ok(ret) => ret.into(),
-                Err(e) => {
-                    $crate::xwferror!("XT_Prepare: {}", e);
-                    XtPrepareNegativeReturn::JustCallXtFinalize.into()
+            $crate::panic_guard::catch_panic("XT_Prepare", || $variable.panic_returns().prepare.into(), || {
+                $crate::backend::notify_callback("XT_Prepare", &[hVolume.as_raw() as i64, hEvidence.as_raw() as i64, nOpType as i64]);
+                match XtPrepareOpType::try_from(nOpType) {
+                    Ok(op_type) => $variable.xt_prepare(hVolume, hEvidence, op_type),
+                    Err(_) => {
+                        $crate::xwferror!("error in parsing nOpType argument");
+                        XtPrepareNegativeReturn::JustCallXtFinalize.into()
+                    }
                 }
-            }
-
+            })
         }
     };
 }
+
 #[macro_export]
 macro_rules! export_xt_finalize {
     ($variable:ident, $variable_type:ty) => {
         #[no_mangle]
         #[allow(non_snake_case, unused_variables)]
-        pub extern "C" fn XT_Finalize(hVolume: HANDLE, hEvidence: HANDLE,  nOpType: DWORD, lpReserved: PVOID
+        pub extern "C" fn XT_Finalize(hVolume: XwfHandle, hEvidence: XwfHandle,  nOpType: DWORD, lpReserved: PVOID
         ) -> LONG {
-            $crate::xwfdebug!("XT_Finalize called");
-            let opt_op_type = XtPrepareOpType::try_from(nOpType);
-            if opt_op_type.is_err() {
-                $crate::xwferror!("error in parsing nOpType argument");
-                return XtFinalizeReturn::Ok.into();
-            }
-
-            let res = $crate::get_lib_instance!($variable, $variable_type).xt_finalize(
-                $crate::volume::Volume::new(hVolume).ok(),
-                $crate::evidence::Evidence::new(hEvidence),
-                opt_op_type.unwrap());
-
-            match res {
-                Ok(ret) => ret.into(),
-                Err(e) => {
-                    $crate::xwferror!("XT_Finalize: {}", e);
-                    XtPrepareNegativeReturn::JustCallXtFinalize.into()
+            $crate::panic_guard::catch_panic("XT_Finalize", || $variable.panic_returns().finalize.into(), || {
+                $crate::backend::notify_callback("XT_Finalize", &[hVolume.as_raw() as i64, hEvidence.as_raw() as i64, nOpType as i64]);
+                match XtPrepareOpType::try_from(nOpType) {
+                    Ok(op_type) => $variable.xt_finalize(hVolume, hEvidence, op_type),
+                    Err(_) => {
+                        $crate::xwferror!("error in parsing nOpType argument");
+                        XtFinalizeReturn::Ok.into()
+                    }
                 }
-            }
+            })
         }
     };
 }
+
 #[macro_export]
 macro_rules! export_xt_process_item {
     ($variable:ident, $variable_type:ty) => {
         #[no_mangle]
         #[allow(non_snake_case, unused_variables)]
         pub extern "C" fn XT_ProcessItem(nItemID: LONG,  lpReserved: PVOID) -> LONG {
-            let item = $crate::item::Item::new(nItemID);
-
-            let res = $crate::get_lib_instance!($variable, $variable_type).xt_process_item(item);
-
-            match res {
-                Ok(ret) => {
-                     ret.into() 
-                },
-                Err(e) => {
-                    $crate::xwferror!("XT_ProcessItem: error occurred in processing item id {}", nItemID);
-                    $crate::xwferror!("XT_ProcessItem: {}", e);
-                    $crate::xwferror!("XT_ProcessItem: stopping operation due to previous error");
-                    XtProcessItemReturn::StopCurrentOperation.into()
-                }
-            }
+            $crate::panic_guard::catch_panic("XT_ProcessItem", || $variable.panic_returns().process_item.into(), || {
+                $crate::backend::notify_callback("XT_ProcessItem", &[nItemID as i64]);
+                $variable.xt_process_item(nItemID)
+            })
         }
     };
 }
+
 #[macro_export]
 macro_rules! export_xt_process_item_ex {
     ($variable:ident, $variable_type:ty) => {
         #[no_mangle]
         #[allow(non_snake_case, unused_variables)]
-        pub extern "C" fn XT_ProcessItemEx(nItemID: LONG, hItem: HANDLE,  lpReserved: PVOID) -> LONG {
-            let res_item = $crate::item::ItemHandle::new(hItem, $crate::item::Item::new(nItemID));
-            if res_item.is_err() {
-                $crate::xwferror!("failed to parse hItem Argument");
-                $crate::xwferror!("XT_ProcessItemEx: stopping operation due to previous error");
-                return XtProcessItemExReturn::StopCurrentOperation.into();
-            }
-
-            let res = $crate::get_lib_instance!($variable, $variable_type).xt_process_item_ex(res_item.unwrap());
-
-            match res {
-                Ok(ret) => ret.into(),
-                Err(e) => {
-                    $crate::xwferror!("XT_ProcessItemEx: error occurred in processing item id {}", nItemID);
-                    $crate::xwferror!("XT_ProcessItemEx: {}", e);
-                    $crate::xwferror!("XT_ProcessItemEx: stopping operation due to previous error");
-                    XtProcessItemExReturn::StopCurrentOperation.into()
+        pub extern "C" fn XT_ProcessItemEx(nItemID: LONG, hItem: XwfHandle,  lpReserved: PVOID) -> LONG {
+            $crate::panic_guard::catch_panic("XT_ProcessItemEx", || $variable.panic_returns().process_item_ex.into(), || {
+                $crate::backend::notify_callback("XT_ProcessItemEx", &[nItemID as i64, hItem.as_raw() as i64]);
+                $variable.xt_process_item_ex(nItemID, hItem)
+            })
+        }
+    };
+}
 
+#[macro_export]
+macro_rules! export_xt_prepare_search {
+    ($variable:ident, $variable_type:ty) => {
+        #[no_mangle]
+        #[allow(non_snake_case, unused_variables)]
+        pub extern "C" fn XT_PrepareSearch(pPSInfo: *mut $crate::xwf_types::PrepareSearchInfo, pCPages: *mut $crate::xwf_types::CodePages) -> LONG {
+            $crate::panic_guard::catch_panic("XT_PrepareSearch", || 0, || {
+                let mut search = match unsafe { $crate::search::PrepareSearch::from_raw(pPSInfo, pCPages) } {
+                    Some(s) => s,
+                    None => return 0,
+                };
+                $crate::backend::notify_callback("XT_PrepareSearch", &[search.flags.bits() as i64]);
+                if $variable.xt_prepare_search(&mut search) == 0 {
+                    return 0;
                 }
-            }
+                match unsafe { search.write_to_raw(pPSInfo, pCPages) } {
+                    Ok(()) => 1,
+                    Err(e) => {
+                        $crate::xwferror!("XT_PrepareSearch: failed to apply search settings: {}", e);
+                        0
+                    }
+                }
+            })
         }
     };
 }
 
+#[macro_export]
+macro_rules! export_xt_process_search_hit {
+    ($variable:ident, $variable_type:ty) => {
+        #[no_mangle]
+        #[allow(non_snake_case, unused_variables)]
+        pub extern "C" fn XT_ProcessSearchHit(info: *mut $crate::xwf_types::SearchHitInfo) -> LONG {
+            $crate::panic_guard::catch_panic("XT_ProcessSearchHit", || $variable.panic_returns().process_search_hit.into(), || {
+                let hit = match unsafe { $crate::search::SearchHit::from_raw(info) } {
+                    Some(h) => h,
+                    None => return $crate::xwf_types::XtProcessSearchHitReturn::Ok.into(),
+                };
+                $crate::backend::notify_callback("XT_ProcessSearchHit", &hit.callback_args());
+                $variable.xt_process_search_hit(hit)
+            })
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! export_xt_view {
+    ($variable:ident, $variable_type:ty) => {
+        #[no_mangle]
+        #[allow(non_snake_case, unused_variables)]
+        pub extern "C" fn XT_View(hItem: XwfHandle, nItemID: LONG, hVolume: XwfHandle, hEvidence: XwfHandle, lpReserved: PVOID, lpResSize: *mut i64) -> PVOID {
+            $crate::panic_guard::catch_panic("XT_View", std::ptr::null_mut, || {
+                $crate::backend::notify_callback("XT_View", &[nItemID as i64, hItem.as_raw() as i64, hVolume.as_raw() as i64, hEvidence.as_raw() as i64]);
+                let data = match $variable.xt_view(nItemID, hItem, hVolume, hEvidence) {
+                    Some(data) => data,
+                    None => return std::ptr::null_mut(),
+                };
+                let (buffer, size) = $crate::dispatch::into_view_buffer(data);
+                if !lpResSize.is_null() {
+                    unsafe { *lpResSize = size; }
+                }
+                buffer
+            })
+        }
 
+        // X-Ways Forensics hands back the buffers returned by XT_View
+        #[no_mangle]
+        #[allow(non_snake_case, unused_variables)]
+        pub extern "C" fn XT_ReleaseMem(lpBuffer: PVOID) -> $crate::ffi_types::BOOL {
+            $crate::panic_guard::catch_panic("XT_ReleaseMem", || 0, || {
+                $crate::dispatch::release_view_buffer(lpBuffer) as $crate::ffi_types::BOOL
+            })
+        }
+    };
+}
 
 #[macro_export]
-macro_rules! create_static_var {
+macro_rules! create_static_instance {
     ($variable_name:ident, $variable_type:ty) => {
-        static mut $variable_name: Option<$variable_type> = None;
+        static $variable_name: $crate::dispatch::StInstance<$variable_type> = $crate::dispatch::StInstance::new();
     }
 }
 
 #[macro_export]
 macro_rules! export_all_functions {
     ($variable_name:ident, $variable_type:ty) => {
-        use $crate::winapi::shared::minwindef::{DWORD, LPVOID};
-        use $crate::winapi::shared::ntdef::{PVOID, LONG, HANDLE};
-        use $crate::raw_api::RAW_API;
+        use $crate::ffi_types::{DWORD, LPVOID, PVOID, LONG};
+        use $crate::xwf_types::XwfHandle;
 
-        $crate::create_static_var!($variable_name, $variable_type);
+        $crate::create_static_instance!($variable_name, $variable_type);
 
         $crate::export_xt_init!($variable_name, $variable_type);
         $crate::export_xt_prepare!($variable_name, $variable_type);
@@ -252,24 +242,65 @@ macro_rules! export_all_functions {
 macro_rules! export_without_process_item {
     ($variable_name:ident, $variable_type:ty) => {
 
-        create_static_var!($variable_name, $variable_type);
+        $crate::create_static_instance!($variable_name, $variable_type);
 
-        export_xt_init!($variable_name, $variable_type);
-        export_xt_prepare!($variable_name, $variable_type);
-        export_xt_finalize!($variable_name, $variable_type);
-        export_xt_done!($variable_name, $variable_type);
-        export_xt_about!($variable_name, $variable_type);
+        $crate::export_xt_init!($variable_name, $variable_type);
+        $crate::export_xt_prepare!($variable_name, $variable_type);
+        $crate::export_xt_finalize!($variable_name, $variable_type);
+        $crate::export_xt_done!($variable_name, $variable_type);
+        $crate::export_xt_about!($variable_name, $variable_type);
     };
 }
 
 #[macro_export]
 macro_rules! export_all_functions_ex {
     ($variable_name:ident, $variable_type:ty) => {
-        use $crate::winapi::shared::minwindef::{DWORD, LPVOID};
-        use $crate::winapi::shared::ntdef::{PVOID, LONG, HANDLE};
-        use $crate::raw_api::RAW_API;
+        use $crate::ffi_types::{DWORD, LPVOID, PVOID, LONG};
+        use $crate::xwf_types::XwfHandle;
+
+        $crate::create_static_instance!($variable_name, $variable_type);
+
+        $crate::export_xt_init!($variable_name, $variable_type);
+        $crate::export_xt_prepare!($variable_name, $variable_type);
+        $crate::export_xt_finalize!($variable_name, $variable_type);
+        $crate::export_xt_done!($variable_name, $variable_type);
+        $crate::export_xt_about!($variable_name, $variable_type);
+        $crate::export_xt_process_item_ex!($variable_name, $variable_type);
+    };
+}
+
+
+#[macro_export]
+macro_rules! create_static_instance_mt {
+    ($variable_name:ident, $variable_type:ty) => {
+        static $variable_name: $crate::mt::MtInstance<$variable_type> = $crate::mt::MtInstance::new();
+    }
+}
+
+#[macro_export]
+macro_rules! export_all_functions_mt {
+    ($variable_name:ident, $variable_type:ty) => {
+        use $crate::ffi_types::{DWORD, LPVOID, PVOID, LONG};
+        use $crate::xwf_types::XwfHandle;
+
+        $crate::create_static_instance_mt!($variable_name, $variable_type);
+
+        $crate::export_xt_init!($variable_name, $variable_type);
+        $crate::export_xt_prepare!($variable_name, $variable_type);
+        $crate::export_xt_finalize!($variable_name, $variable_type);
+        $crate::export_xt_done!($variable_name, $variable_type);
+        $crate::export_xt_about!($variable_name, $variable_type);
+        $crate::export_xt_process_item!($variable_name, $variable_type);
+    };
+}
+
+#[macro_export]
+macro_rules! export_all_functions_mt_ex {
+    ($variable_name:ident, $variable_type:ty) => {
+        use $crate::ffi_types::{DWORD, LPVOID, PVOID, LONG};
+        use $crate::xwf_types::XwfHandle;
 
-        $crate::create_static_var!($variable_name, $variable_type);
+        $crate::create_static_instance_mt!($variable_name, $variable_type);
 
         $crate::export_xt_init!($variable_name, $variable_type);
         $crate::export_xt_prepare!($variable_name, $variable_type);
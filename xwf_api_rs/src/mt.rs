@@ -0,0 +1,239 @@
+//! Support for multi-threaded X-Tensions, see [`XTensionMt`].
+//!
+//! If an X-Tension returns [`XtInitReturn::RunMultiThreaded`] in `XT_Init`, X-Ways Forensics calls
+//! `XT_ProcessItem`/`XT_ProcessItemEx` concurrently from several worker threads. All other callbacks
+//! are still called one after another. While a worker thread executes an item callback,
+//! the following rules apply:
+//!
+//! - reading items, volumes, evidence objects and case properties, reading item data,
+//!   adding items to report tables, setting item properties and writing messages is allowed
+//! - user input ([`Application::get_user_input_integer`], [`Application::get_user_input_str`])
+//!   is not available and returns `None`
+//! - progress bar functions ([`Application::show_progress`] etc.) are ignored,
+//!   as the progress bar belongs to the main thread
+//! - functions changing the global state of X-Ways Forensics fail with
+//!   [`XwfError::NotAllowedInWorkerThread`]: selecting a volume snapshot ([`Volume::select`]),
+//!   closing an evidence object ([`Evidence::close`]), running a search ([`Search::run`]) and
+//!   adding search terms ([`SearchTerm::add`])
+//!
+//! [`Application::get_user_input_integer`]: crate::application::Application::get_user_input_integer
+//! [`Application::get_user_input_str`]: crate::application::Application::get_user_input_str
+//! [`Application::show_progress`]: crate::application::Application::show_progress
+//! [`Volume::select`]: crate::volume::Volume::select
+//! [`Evidence::close`]: crate::evidence::Evidence::close
+//! [`Search::run`]: crate::search::Search::run
+//! [`SearchTerm::add`]: crate::search::SearchTerm::add
+
+use std::cell::Cell;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, ThreadId};
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::dispatch;
+use crate::item::{Item, ItemHandle};
+use crate::panic_guard::PanicReturns;
+use crate::search::{PrepareSearch, SearchHit};
+use crate::traits::XTensionMt;
+use crate::volume::Volume;
+use crate::window::Window;
+use crate::xwf_types::*;
+
+thread_local! {
+    static WORKER_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// true while the current thread executes an item callback of a multi-threaded X-Tension
+pub fn is_worker_thread() -> bool {
+    WORKER_THREAD.with(|w| w.get())
+}
+
+pub(crate) fn require_main_thread(function: &'static str) -> Result<(), XwfError> {
+    if is_worker_thread() {
+        Err(XwfError::NotAllowedInWorkerThread(function))
+    } else {
+        Ok(())
+    }
+}
+
+struct WorkerGuard {
+    previous: bool,
+}
+
+impl WorkerGuard {
+    fn enter() -> WorkerGuard {
+        WorkerGuard { previous: WORKER_THREAD.with(|w| w.replace(true)) }
+    }
+}
+
+impl Drop for WorkerGuard {
+    fn drop(&mut self) {
+        WORKER_THREAD.with(|w| w.set(self.previous));
+    }
+}
+
+/// Holds one state per thread. A state is only accessed by the thread it belongs to,
+/// so no lock is held while it is used.
+pub struct ThreadStates<S> {
+    states: Mutex<Vec<(ThreadId, S)>>,
+}
+
+impl<S> Default for ThreadStates<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> ThreadStates<S> {
+    pub const fn new() -> ThreadStates<S> {
+        ThreadStates { states: Mutex::new(Vec::new()) }
+    }
+
+    /// calls `f` with the state of the current thread, which is created by `create` if necessary
+    pub fn with<R>(&self, create: impl FnOnce() -> S, f: impl FnOnce(&mut S) -> R) -> R {
+        let id = thread::current().id();
+        let state = {
+            let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+            states.iter().position(|(t, _)| *t == id).map(|idx| states.swap_remove(idx).1)
+        };
+
+        // the state is put back even if `f` panics
+        let mut checked_out = CheckedOut { owner: self, id, state: Some(state.unwrap_or_else(create)) };
+        f(checked_out.state.as_mut().unwrap())
+    }
+
+    /// removes and returns the states of all threads
+    pub fn drain(&self) -> Vec<S> {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        states.drain(..).map(|(_, s)| s).collect()
+    }
+}
+
+struct CheckedOut<'a, S> {
+    owner: &'a ThreadStates<S>,
+    id: ThreadId,
+    state: Option<S>,
+}
+
+impl<S> Drop for CheckedOut<'_, S> {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.take() {
+            self.owner.states.lock().unwrap_or_else(|e| e.into_inner()).push((self.id, state));
+        }
+    }
+}
+
+/// Owns the instance of a multi-threaded X-Tension and dispatches the X-Tension callbacks to it.
+/// Used as `static` by the export macros, so no `static mut` is needed.
+pub struct MtInstance<T: XTensionMt> {
+    extension: RwLock<Option<Arc<T>>>,
+    thread_states: ThreadStates<T::ThreadState>,
+}
+
+impl<T: XTensionMt> Default for MtInstance<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: XTensionMt> MtInstance<T> {
+    pub const fn new() -> MtInstance<T> {
+        MtInstance {
+            extension: RwLock::new(None),
+            thread_states: ThreadStates::new(),
+        }
+    }
+
+    /// returns the X-Tension, if `XT_Init` was called successfully
+    pub fn extension(&self) -> Option<Arc<T>> {
+        self.extension.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// return values passed to X-Ways Forensics if one of the callbacks panics
+    pub fn panic_returns(&self) -> PanicReturns {
+        T::panic_returns()
+    }
+
+    fn get(&self) -> Arc<T> {
+        self.extension().expect("X-Tension is not initialized")
+    }
+}
+
+impl<T: XTensionMt> MtInstance<T> where T::XTensionError: Display {
+
+    pub fn xt_init(&self, version: XtVersion, flags: XtInitFlags, window: Option<Window>) -> i32 {
+        if !dispatch::check_version(version, T::min_xwf_version()) {
+            return XtInitReturn::PreventFurtherUseOfDll.into();
+        }
+
+        let ext = Arc::new(T::create());
+        *self.extension.write().unwrap_or_else(|e| e.into_inner()) = Some(ext.clone());
+        dispatch::init_return(ext.xt_init(version, flags, window, XtLicenseInfo {}))
+    }
+
+    pub fn xt_done(&self) -> i32 {
+        let ext = self.extension.write().unwrap_or_else(|e| e.into_inner()).take();
+        self.thread_states.drain();
+        if let Some(ext) = ext {
+            dispatch::log_error("XT_Done", ext.xt_done());
+        }
+        0
+    }
+
+    pub fn xt_about(&self, window: Option<Window>) -> i32 {
+        dispatch::log_error("XT_About", self.get().xt_about(window));
+        0
+    }
+
+    pub fn xt_prepare(&self, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32 {
+        crate::tree::invalidate();
+        dispatch::prepare_return(self.get().xt_prepare(Volume::new(volume).ok(), Evidence::new(evidence), op_type))
+    }
+
+    pub fn xt_finalize(&self, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32 {
+        let ext = self.get();
+        for state in self.thread_states.drain() {
+            ext.thread_state_finished(state);
+        }
+        dispatch::finalize_return(ext.xt_finalize(Volume::new(volume).ok(), Evidence::new(evidence), op_type))
+    }
+
+    pub fn xt_process_item(&self, item_id: i32) -> i32 {
+        let ext = self.get();
+        let _worker = WorkerGuard::enter();
+        let res = self.thread_states.with(|| ext.create_thread_state(), |state| {
+            ext.xt_process_item(state, Item::new(item_id))
+        });
+        dispatch::process_item_return(item_id, res)
+    }
+
+    pub fn xt_process_item_ex(&self, item_id: i32, item: XwfHandle) -> i32 {
+        let item_handle = match dispatch::item_handle(item_id, item) {
+            Some(h) => h,
+            None => return XtProcessItemExReturn::StopCurrentOperation.into(),
+        };
+
+        let ext = self.get();
+        let _worker = WorkerGuard::enter();
+        let res = self.thread_states.with(|| ext.create_thread_state(), |state| {
+            ext.xt_process_item_ex(state, item_handle)
+        });
+        dispatch::process_item_ex_return(item_id, res)
+    }
+
+    pub fn xt_prepare_search(&self, search: &mut PrepareSearch) -> i32 {
+        let before = search.clone();
+        let res = self.get().xt_prepare_search(search);
+        dispatch::prepare_search_return(search, before, res)
+    }
+
+    pub fn xt_process_search_hit(&self, hit: SearchHit) -> i32 {
+        let item_id = hit.item_id;
+        dispatch::process_search_hit_return(item_id, self.get().xt_process_search_hit(hit))
+    }
+
+    pub fn xt_view(&self, item_id: i32, item: XwfHandle, volume: XwfHandle, evidence: XwfHandle) -> Option<Vec<u8>> {
+        let handle = ItemHandle::new(item, Item::new(item_id)).ok()?;
+        dispatch::view_return(self.get().xt_view(handle, Volume::new(volume).ok(), Evidence::new(evidence)))
+    }
+}
@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+use crate::backend::has_backend;
+use crate::xwf_types::*;
+use crate::xwferror;
+
+/// Return values passed to X-Ways Forensics if an X-Tension callback panics.
+/// Can be customized per X-Tension via [`XTension::panic_returns`](crate::traits::XTension::panic_returns).
+pub struct PanicReturns {
+    pub init: XtInitReturn,
+    pub prepare: XtPrepareReturn,
+    pub finalize: XtFinalizeReturn,
+    pub process_item: XtProcessItemReturn,
+    pub process_item_ex: XtProcessItemExReturn,
+    pub process_search_hit: XtProcessSearchHitReturn,
+}
+
+impl Default for PanicReturns {
+    fn default() -> Self {
+        PanicReturns {
+            init: XtInitReturn::PreventFurtherUseOfDll,
+            prepare: XtPrepareReturn::Negative(XtPrepareNegativeReturn::StopWholeOperation),
+            finalize: XtFinalizeReturn::Ok,
+            process_item: XtProcessItemReturn::StopCurrentOperation,
+            process_item_ex: XtProcessItemExReturn::StopCurrentOperation,
+            process_search_hit: XtProcessSearchHitReturn::StopSearch,
+        }
+    }
+}
+
+impl PanicReturns {
+    /// the value returned by the `XT_*` function named `callback` if it panics
+    pub fn for_callback(self, callback: &str) -> i32 {
+        match callback {
+            "XT_Init" => self.init.into(),
+            "XT_Prepare" => self.prepare.into(),
+            "XT_Finalize" => self.finalize.into(),
+            "XT_ProcessItem" => self.process_item.into(),
+            "XT_ProcessItemEx" => self.process_item_ex.into(),
+            "XT_ProcessSearchHit" => self.process_search_hit.into(),
+            _ => 0,
+        }
+    }
+}
+
+thread_local! {
+    // location of the last panic on this thread, set by the panic hook
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+fn install_panic_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+            PANIC_LOCATION.with(|p| *p.borrow_mut() = location);
+            previous(info);
+        }));
+    });
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "unknown panic payload"
+    }
+}
+
+/// Runs `f` and catches any panic, so that it does not unwind into X-Ways Forensics.
+/// The panic is logged to the messages window and `safe_return()` is returned instead.
+///
+/// Every callback is guarded exactly once: by the exported `XT_*` functions, or by the simulator
+/// and trace replay drivers which take their place. [`crate::dispatch`] does not catch panics.
+pub fn catch_panic<V, F, R>(callback: &str, safe_return: R, f: F) -> V
+where
+    F: FnOnce() -> V,
+    R: FnOnce() -> V,
+{
+    install_panic_hook();
+
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(ret) => ret,
+        Err(payload) => {
+            let location = PANIC_LOCATION.with(|p| p.borrow_mut().take())
+                .unwrap_or_else(|| String::from("unknown location"));
+
+            // logging itself must not panic again
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                if has_backend() {
+                    xwferror!("{}: X-Tension panicked at {}: {}", callback, location, panic_message(payload.as_ref()));
+                }
+            }));
+            safe_return()
+        }
+    }
+}
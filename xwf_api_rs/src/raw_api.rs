@@ -1,69 +1,105 @@
+use std::ptr::null_mut;
+use std::ffi::CString;
+#[cfg(windows)]
 use std::ffi::CStr;
+#[cfg(windows)]
 use std::mem::transmute_copy;
-use std::ptr::null_mut;
+#[cfg(windows)]
 use cstr::cstr;
+#[cfg(windows)]
 use winapi::shared::minwindef::{FARPROC, HMODULE};
+#[cfg(windows)]
 use winapi::um::libloaderapi::{GetModuleHandleW, GetProcAddress};
 
+use crate::backend::{VolumeInformation, XwfBackend};
+use crate::capabilities::Capabilities;
+use crate::evidence::{ReportTableListItem, ReportTableListItemPacked};
+use crate::search::SearchHit;
+use crate::event::Event;
+use crate::ffi_types::{BOOL, DWORD, LONG, LPSTR, LPVOID, LPWSTR, PVOID};
+use crate::util::char_ptr_to_string;
+use crate::wide_string::WideString;
 use crate::xwf_function_types::*;
+use crate::xwf_types::{CodePages, EvObjPropType, EventInfo, PropType, SearchHitInfo, SearchInfo, SrcInfo, XwfHandle};
+
+const MAX_PATH: usize = 260;
+const BUF_SIZE_DEFAULT: usize = 256;
+const BUF_SIZE_REPORT_TABLE_QUERY: usize = 8192;
+const BUF_SIZE_REPORT_HASHSET_QUERY: usize = 4096;
+const BUF_SIZE_USER_INPUT: usize = 65535;
+const BUF_SIZE_EVENT_DESCRIPTION: usize = 4096;
 
 pub struct RawApi {
-    pub output_message: FnXwfOutputMessage,
-    pub get_volume_name: FnXwfGetVolumeName,
-    pub get_volume_information: FnXwfGetVolumeInformation,
-    pub set_item_information: FnXwfSetItemInformation,
-    pub get_item_information : FnXwfGetItemInformation,
-    pub get_item_parent : FnXwfGetItemParent,
-    pub get_item_size : FnXwfGetItemSize,
-    pub get_item_type: FnXwfGetItemType,
-    pub set_item_type: FnXwfSetItemType,
-    pub read : FnXwfRead,
-    pub get_first_ev_obj: FnXwfGetFirstEvObj,
-    pub get_next_ev_obj: FnXwfGetNextEvObj,
-    pub get_case_prop: FnXwfGetCaseProp,
-    pub get_ev_obj_prop: FnXwfGetEvObjProp,
-    pub get_ev_obj: FnXwfGetEvObj,
-    pub get_report_table_info: FnXwfGetReportTableInfo,
-    pub get_ev_obj_report_table_assocs: FnXwfGetEvObjReportTableAssocs,
-    pub open_ev_obj: FnXwfOpenEvObj,
-    pub add_to_report_table: FnXwfAddToReportTable,
-    pub get_hash_value: FnXwfGetHashValue,
-    pub set_hash_value: FnXwfSetHashValue,
-    pub open_item: FnXwfOpenItem,
-    pub get_vs_prop: FnXwfGetVsprop,
-    pub select_volume_snapshot: FnXwfSelectVolumeSnapshot,
-    pub get_item_name: FnXwfGetItemName,
-    pub get_item_count: FnXwfGetItemCount,
-    pub get_prop: FnXwfGetProp,
-    pub close_ev_obj: FnXwfCloseEvObj,
-    pub close: FnXwfClose,
-    pub show_progress: FnXwfShowProgress,
-    pub set_progress_description: FnXwfSetProgressDescription,
-    pub set_progress_percentage: FnXwfSetProgressPercentage,
-    pub hide_progress: FnXwfHideProgress,
-    pub should_stop: FnXwfShouldStop,
-    pub get_user_input: FnXwfGetUserInput,
-    pub get_report_table_assocs: FnXwfGetReportTableAssocs,
-    pub get_hashset_assocs: FnXwfGetHashSetAssocs,
-    pub get_extracted_metadata: FnXwfGetExtractedMetadata,
-    pub get_metadata_ex: FnXwfGetMetadataEx,
-    pub release_mem: FnXwfReleaseMem,
-    pub get_item_ofs: FnXwfGetItemOfs,
-    pub get_comment: FnXwfGetComment,
-    pub set_item_parent: FnXwfSetItemParent,
-    pub set_item_size: FnXwfSetItemSize,
-    pub create_file: FnXwfCreateFile,
+    pub output_message: Option<FnXwfOutputMessage>,
+    pub get_volume_name: Option<FnXwfGetVolumeName>,
+    pub get_volume_information: Option<FnXwfGetVolumeInformation>,
+    pub set_item_information: Option<FnXwfSetItemInformation>,
+    pub get_item_information: Option<FnXwfGetItemInformation>,
+    pub get_item_parent: Option<FnXwfGetItemParent>,
+    pub get_item_size: Option<FnXwfGetItemSize>,
+    pub get_item_type: Option<FnXwfGetItemType>,
+    pub set_item_type: Option<FnXwfSetItemType>,
+    pub read: Option<FnXwfRead>,
+    pub get_first_ev_obj: Option<FnXwfGetFirstEvObj>,
+    pub get_next_ev_obj: Option<FnXwfGetNextEvObj>,
+    pub get_case_prop: Option<FnXwfGetCaseProp>,
+    pub get_ev_obj_prop: Option<FnXwfGetEvObjProp>,
+    pub get_ev_obj: Option<FnXwfGetEvObj>,
+    pub get_report_table_info: Option<FnXwfGetReportTableInfo>,
+    pub get_ev_obj_report_table_assocs: Option<FnXwfGetEvObjReportTableAssocs>,
+    pub open_ev_obj: Option<FnXwfOpenEvObj>,
+    pub add_to_report_table: Option<FnXwfAddToReportTable>,
+    pub get_hash_value: Option<FnXwfGetHashValue>,
+    pub set_hash_value: Option<FnXwfSetHashValue>,
+    pub open_item: Option<FnXwfOpenItem>,
+    pub get_vs_prop: Option<FnXwfGetVsprop>,
+    pub select_volume_snapshot: Option<FnXwfSelectVolumeSnapshot>,
+    pub get_item_name: Option<FnXwfGetItemName>,
+    pub get_item_count: Option<FnXwfGetItemCount>,
+    pub get_prop: Option<FnXwfGetProp>,
+    pub close_ev_obj: Option<FnXwfCloseEvObj>,
+    pub close: Option<FnXwfClose>,
+    pub show_progress: Option<FnXwfShowProgress>,
+    pub set_progress_description: Option<FnXwfSetProgressDescription>,
+    pub set_progress_percentage: Option<FnXwfSetProgressPercentage>,
+    pub hide_progress: Option<FnXwfHideProgress>,
+    pub should_stop: Option<FnXwfShouldStop>,
+    pub get_user_input: Option<FnXwfGetUserInput>,
+    pub get_report_table_assocs: Option<FnXwfGetReportTableAssocs>,
+    pub get_hashset_assocs: Option<FnXwfGetHashSetAssocs>,
+    pub get_extracted_metadata: Option<FnXwfGetExtractedMetadata>,
+    pub get_metadata_ex: Option<FnXwfGetMetadataEx>,
+    pub release_mem: Option<FnXwfReleaseMem>,
+    pub get_item_ofs: Option<FnXwfGetItemOfs>,
+    pub get_comment: Option<FnXwfGetComment>,
+    pub add_comment: Option<FnXwfAddComment>,
+    pub add_extracted_metadata: Option<FnXwfAddExtractedMetadata>,
+    pub set_item_parent: Option<FnXwfSetItemParent>,
+    pub set_item_size: Option<FnXwfSetItemSize>,
+    pub create_file: Option<FnXwfCreateFile>,
+    pub search: Option<FnXwfSearch>,
+    pub get_search_term: Option<FnXwfGetSearchTerm>,
+    pub add_search_term: Option<FnXwfAddSearchTerm>,
+    pub get_search_hit: Option<FnXwfGetSearchHit>,
+    pub set_search_hit: Option<FnXwfSetSearchHit>,
+    pub add_search_hit: Option<FnXwfAddSearchHit>,
+    pub get_event: Option<FnXwfGetEvent>,
+    pub add_event: Option<FnXwfAddEvent>,
+    missing: Vec<&'static str>,
 }
 
+// the function pointers are resolved from the executable of X-Ways Forensics, which is only possible on Windows
+#[cfg(windows)]
 impl RawApi {
 
-    fn load_method<T>(h_module: HMODULE, function_name: &CStr) -> Result<T, &'static str> {
+    fn load_method<T>(h_module: HMODULE, function_name: &CStr, name: &'static str, missing: &mut Vec<&'static str>) -> Option<T> {
         unsafe {
             let adr = GetProcAddress(h_module, function_name.as_ptr());
-            if adr == null_mut() {
-                return Err("could not load function")
+            if adr.is_null() {
+                missing.push(name);
+                return None
             }
-            Ok(transmute_copy::<FARPROC, T>(&adr))
+            Some(transmute_copy::<FARPROC, T>(&adr))
         }
 
     }
@@ -71,6 +107,8 @@ impl RawApi {
     pub fn load_no_error() -> RawApi {
         RawApi::load().expect("unable to load RawApi")
     }
+    /// resolves the `XWF_*` functions, functions that are not exported by the running
+    /// X-Ways Forensics are reported by [`RawApi::missing_functions`]
     pub fn load() -> Result<RawApi, &'static str> {
         unsafe {
             let h_module = GetModuleHandleW(std::ptr::null());
@@ -78,69 +116,511 @@ impl RawApi {
                 return Err("could not load module")
             }
 
-            Ok(RawApi {
-               output_message: RawApi::load_method(h_module, cstr!(XWF_OutputMessage))?,
-               get_volume_name: RawApi::load_method(h_module, cstr!(XWF_GetVolumeName))?,
-               get_volume_information: RawApi::load_method(h_module, cstr!(XWF_GetVolumeInformation))?,
-               set_item_information: RawApi::load_method(h_module, cstr!(XWF_SetItemInformation))?,
-               get_item_information: RawApi::load_method(h_module, cstr!(XWF_GetItemInformation))?,
-               get_item_parent: RawApi::load_method(h_module, cstr!(XWF_GetItemParent))?,
-               get_item_size: RawApi::load_method(h_module, cstr!(XWF_GetItemSize))?,
-               get_item_type: RawApi::load_method(h_module, cstr!(XWF_GetItemType))?,
-               set_item_type:  RawApi::load_method(h_module, cstr!(XWF_SetItemType))?,
-               read: RawApi::load_method(h_module, cstr!(XWF_Read))?,
-               get_first_ev_obj: RawApi::load_method(h_module, cstr!(XWF_GetFirstEvObj))?,
-               get_next_ev_obj: RawApi::load_method(h_module, cstr!(XWF_GetNextEvObj))?,
-               get_case_prop: RawApi::load_method(h_module, cstr!(XWF_GetCaseProp))?,
-               get_ev_obj_prop: RawApi::load_method(h_module, cstr!(XWF_GetEvObjProp))?,
-               get_ev_obj: RawApi::load_method(h_module, cstr!(XWF_GetEvObj))?,
-               get_report_table_info: RawApi::load_method(h_module, cstr!(XWF_GetReportTableInfo))?,
-               get_ev_obj_report_table_assocs: RawApi::load_method(h_module, cstr!(XWF_GetEvObjReportTableAssocs))?,
-               open_ev_obj: RawApi::load_method(h_module, cstr!(XWF_OpenEvObj))?,
-               add_to_report_table: RawApi::load_method(h_module, cstr!(XWF_AddToReportTable))?,
-               get_hash_value: RawApi::load_method(h_module, cstr!(XWF_GetHashValue))?,
-               set_hash_value: RawApi::load_method(h_module, cstr!(XWF_SetHashValue))?,
-               open_item: RawApi::load_method(h_module, cstr!(XWF_OpenItem))?,
-               get_vs_prop: RawApi::load_method(h_module, cstr!(XWF_GetVSProp))?,
-               select_volume_snapshot: RawApi::load_method(h_module, cstr!(XWF_SelectVolumeSnapshot))?,
-               get_item_name: RawApi::load_method(h_module, cstr!(XWF_GetItemName))?,
-               get_item_count: RawApi::load_method(h_module, cstr!(XWF_GetItemCount))?,
-               get_prop: RawApi::load_method(h_module, cstr!(XWF_GetProp))?,
-               close_ev_obj: RawApi::load_method(h_module, cstr!(XWF_CloseEvObj))?,
-               close: RawApi::load_method(h_module, cstr!(XWF_Close))?,
-               show_progress: RawApi::load_method(h_module, cstr!(XWF_ShowProgress))?,
-               set_progress_description: RawApi::load_method(h_module, cstr!(XWF_SetProgressDescription))?,
-               set_progress_percentage: RawApi::load_method(h_module, cstr!(XWF_SetProgressPercentage))?,
-               hide_progress: RawApi::load_method(h_module, cstr!(XWF_HideProgress))?,
-               should_stop: RawApi::load_method(h_module, cstr!(XWF_ShouldStop))?,
-               get_user_input: RawApi::load_method(h_module, cstr!(XWF_GetUserInput))?,
-               get_report_table_assocs: RawApi::load_method(h_module, cstr!(XWF_GetReportTableAssocs))?,
-               get_hashset_assocs: RawApi::load_method(h_module, cstr!(XWF_GetHashSetAssocs))?,
-               get_extracted_metadata: RawApi::load_method(h_module, cstr!(XWF_GetExtractedMetadata))?,
-               get_metadata_ex: RawApi::load_method(h_module, cstr!(XWF_GetMetadataEx))?,
-               release_mem: RawApi::load_method(h_module, cstr!(XWF_ReleaseMem))?,
-               get_item_ofs: RawApi::load_method(h_module, cstr!(XWF_GetItemOfs))?,
-               get_comment: RawApi::load_method(h_module, cstr!(XWF_GetComment))?,
-               set_item_parent: RawApi::load_method(h_module, cstr!(XWF_SetItemParent))?,
-               set_item_size: RawApi::load_method(h_module, cstr!(XWF_SetItemSize))?,
-               create_file: RawApi::load_method(h_module, cstr!(XWF_CreateFile))?,
+            let mut missing = Vec::new();
+            macro_rules! load {
+                ($name:ident) => { RawApi::load_method(h_module, cstr!($name), stringify!($name), &mut missing) }
+            }
 
+            Ok(RawApi {
+               output_message: load!(XWF_OutputMessage),
+               get_volume_name: load!(XWF_GetVolumeName),
+               get_volume_information: load!(XWF_GetVolumeInformation),
+               set_item_information: load!(XWF_SetItemInformation),
+               get_item_information: load!(XWF_GetItemInformation),
+               get_item_parent: load!(XWF_GetItemParent),
+               get_item_size: load!(XWF_GetItemSize),
+               get_item_type: load!(XWF_GetItemType),
+               set_item_type:  load!(XWF_SetItemType),
+               read: load!(XWF_Read),
+               get_first_ev_obj: load!(XWF_GetFirstEvObj),
+               get_next_ev_obj: load!(XWF_GetNextEvObj),
+               get_case_prop: load!(XWF_GetCaseProp),
+               get_ev_obj_prop: load!(XWF_GetEvObjProp),
+               get_ev_obj: load!(XWF_GetEvObj),
+               get_report_table_info: load!(XWF_GetReportTableInfo),
+               get_ev_obj_report_table_assocs: load!(XWF_GetEvObjReportTableAssocs),
+               open_ev_obj: load!(XWF_OpenEvObj),
+               add_to_report_table: load!(XWF_AddToReportTable),
+               get_hash_value: load!(XWF_GetHashValue),
+               set_hash_value: load!(XWF_SetHashValue),
+               open_item: load!(XWF_OpenItem),
+               get_vs_prop: load!(XWF_GetVSProp),
+               select_volume_snapshot: load!(XWF_SelectVolumeSnapshot),
+               get_item_name: load!(XWF_GetItemName),
+               get_item_count: load!(XWF_GetItemCount),
+               get_prop: load!(XWF_GetProp),
+               close_ev_obj: load!(XWF_CloseEvObj),
+               close: load!(XWF_Close),
+               show_progress: load!(XWF_ShowProgress),
+               set_progress_description: load!(XWF_SetProgressDescription),
+               set_progress_percentage: load!(XWF_SetProgressPercentage),
+               hide_progress: load!(XWF_HideProgress),
+               should_stop: load!(XWF_ShouldStop),
+               get_user_input: load!(XWF_GetUserInput),
+               get_report_table_assocs: load!(XWF_GetReportTableAssocs),
+               get_hashset_assocs: load!(XWF_GetHashSetAssocs),
+               get_extracted_metadata: load!(XWF_GetExtractedMetadata),
+               get_metadata_ex: load!(XWF_GetMetadataEx),
+               release_mem: load!(XWF_ReleaseMem),
+               get_item_ofs: load!(XWF_GetItemOfs),
+               get_comment: load!(XWF_GetComment),
+               add_comment: load!(XWF_AddComment),
+               add_extracted_metadata: load!(XWF_AddExtractedMetadata),
+               set_item_parent: load!(XWF_SetItemParent),
+               set_item_size: load!(XWF_SetItemSize),
+               create_file: load!(XWF_CreateFile),
+               search: load!(XWF_Search),
+               get_search_term: load!(XWF_GetSearchTerm),
+               add_search_term: load!(XWF_AddSearchTerm),
+               get_search_hit: load!(XWF_GetSearchHit),
+               set_search_hit: load!(XWF_SetSearchHit),
+               add_search_hit: load!(XWF_AddSearchHit),
+               get_event: load!(XWF_GetEvent),
+               add_event: load!(XWF_AddEvent),
+               missing,
             })
         }
     }
 }
 
 
-pub static mut RAW_API: Option<RawApi> = None;
 
+impl RawApi {
+    pub fn missing_functions(&self) -> &[&'static str] {
+        &self.missing
+    }
+}
 
-#[macro_export]
-macro_rules! get_raw_api {
-    () => {
-        unsafe {
-            RAW_API.as_ref().unwrap()
+// returns the function pointer, or returns `$default` from the calling method if the function
+// is not exported by the running X-Ways Forensics. The high-level methods check the export with
+// `require_function` beforehand, so the default is only seen by direct users of the backend.
+macro_rules! xwf_fn {
+    ($self:ident.$func:ident) => {
+        match $self.$func {
+            Some(f) => f,
+            None => return,
+        }
+    };
+    ($self:ident.$func:ident, $default:expr) => {
+        match $self.$func {
+            Some(f) => f,
+            None => return $default,
         }
-        
+    };
+}
+
+unsafe fn wstr_ptr_to_string(ptr: *const u16) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(WideString::from_wchars_nullt(ptr).to_string())
     }
 }
 
+impl XwfBackend for RawApi {
+    fn output_message(&self, message: &str, flags: u32) {
+        xwf_fn!(self.output_message)(WideString::from(message).as_ptr(), flags)
+    }
+
+    fn get_volume_name(&self, volume: XwfHandle, name_type: u32) -> String {
+        let mut buf = [0u16; BUF_SIZE_DEFAULT];
+        xwf_fn!(self.get_volume_name, String::new())(volume, buf.as_mut_ptr(), name_type);
+        WideString::from_wchars_slice(&buf).to_string()
+    }
+
+    fn get_volume_information(&self, volume: XwfHandle) -> Option<VolumeInformation> {
+        let mut info = VolumeInformation::default();
+        let mut bytes_per_sector: DWORD = 0;
+        let mut sectors_per_cluster: DWORD = 0;
+
+        let ret = xwf_fn!(self.get_volume_information, None)(
+            volume,
+            &mut info.file_system,
+            &mut bytes_per_sector,
+            &mut sectors_per_cluster,
+            &mut info.cluster_count,
+            &mut info.first_cluster_sector_no);
+
+        info.bytes_per_sector = bytes_per_sector;
+        info.sectors_per_cluster = sectors_per_cluster;
+
+        if ret != 0 { Some(info) } else { None }
+    }
+
+    fn select_volume_snapshot(&self, volume: XwfHandle) -> i32 {
+        xwf_fn!(self.select_volume_snapshot, -1)(volume)
+    }
+
+    fn get_vs_prop(&self, prop_type: i32, value: Option<u32>) -> i64 {
+        match value {
+            Some(v) => {
+                let buf = v.to_le_bytes();
+                xwf_fn!(self.get_vs_prop, -1)(prop_type, buf.as_ptr() as PVOID)
+            },
+            None => xwf_fn!(self.get_vs_prop, -1)(prop_type, null_mut()),
+        }
+    }
+
+    fn get_item_count(&self, selected_in_dbc: bool) -> u32 {
+        let arg: LPVOID = if selected_in_dbc { 1 as LPVOID } else { null_mut() };
+        xwf_fn!(self.get_item_count, 0)(arg)
+    }
+
+    fn get_item_name(&self, item_id: u32) -> String {
+        unsafe { wstr_ptr_to_string(xwf_fn!(self.get_item_name, String::new())(item_id)).unwrap_or_default() }
+    }
+
+    fn get_item_size(&self, item_id: i32) -> i64 {
+        xwf_fn!(self.get_item_size, -1)(item_id)
+    }
+
+    fn set_item_size(&self, item_id: i32, size: i64) {
+        xwf_fn!(self.set_item_size)(item_id, size)
+    }
+
+    fn get_item_ofs(&self, item_id: i32) -> (i64, i64) {
+        let mut def_ofs = 0i64;
+        let mut start_sector = 0i64;
+        xwf_fn!(self.get_item_ofs, (-1, -1))(item_id, &mut def_ofs, &mut start_sector);
+        (def_ofs, start_sector)
+    }
+
+    fn get_item_information(&self, item_id: i32, info_type: i32) -> Option<i64> {
+        let mut success: BOOL = 1;
+        let result = xwf_fn!(self.get_item_information, None)(item_id, info_type, &mut success);
+        if success != 0 { Some(result) } else { None }
+    }
+
+    fn set_item_information(&self, item_id: i32, info_type: i32, value: i64) -> bool {
+        xwf_fn!(self.set_item_information, false)(item_id, info_type, value) != 0
+    }
+
+    fn get_item_type(&self, item_id: i32, type_descr: &mut String, flags: u32) -> i32 {
+        let mut buf = [0u16; BUF_SIZE_DEFAULT];
+        let ret = xwf_fn!(self.get_item_type, -1)(item_id, buf.as_mut_ptr(), (buf.len() as u32) | flags);
+        *type_descr = WideString::from_wchars_slice(&buf).to_string();
+        ret
+    }
+
+    fn set_item_type(&self, item_id: i32, type_descr: &str, type_status: i32) {
+        let wstr = WideString::from(type_descr);
+        xwf_fn!(self.set_item_type)(item_id, wstr.as_ptr() as LPWSTR, type_status)
+    }
+
+    fn get_item_parent(&self, item_id: i32) -> i32 {
+        xwf_fn!(self.get_item_parent, -1)(item_id)
+    }
+
+    fn set_item_parent(&self, child_item_id: i32, parent_item_id: i32) {
+        xwf_fn!(self.set_item_parent)(child_item_id, parent_item_id)
+    }
+
+    fn get_report_table_assocs(&self, item_id: i32, assocs: &mut String) -> u32 {
+        let mut buf = vec![0u16; BUF_SIZE_REPORT_TABLE_QUERY];
+        let ret = xwf_fn!(self.get_report_table_assocs, 0)(item_id, buf.as_mut_ptr(), buf.len() as LONG);
+        *assocs = WideString::from_wchars_slice(&buf).to_string();
+        ret
+    }
+
+    fn add_to_report_table(&self, item_id: i32, report_table_name: &str, flags: u32) -> i32 {
+        let wstr = WideString::from(report_table_name);
+        xwf_fn!(self.add_to_report_table, 0)(item_id, wstr.as_ptr() as LPWSTR, flags)
+    }
+
+    fn get_hashset_assocs(&self, item_id: i32, assocs: &mut String) -> i32 {
+        let mut buf = vec![0u16; BUF_SIZE_REPORT_HASHSET_QUERY];
+        let ret = xwf_fn!(self.get_hashset_assocs, 0)(item_id, buf.as_mut_ptr(), buf.len() as LONG);
+        *assocs = WideString::from_wchars_slice(&buf).to_string();
+        ret
+    }
+
+    fn get_comment(&self, item_id: i32) -> Option<String> {
+        unsafe { wstr_ptr_to_string(xwf_fn!(self.get_comment, None)(item_id)) }
+    }
+
+    fn add_comment(&self, item_id: i32, comment: &str, flags: u32) -> bool {
+        let wstr = WideString::from(comment);
+        xwf_fn!(self.add_comment, false)(item_id, wstr.as_ptr() as LPWSTR, flags) != 0
+    }
+
+    fn get_extracted_metadata(&self, item_id: i32) -> Option<String> {
+        unsafe { wstr_ptr_to_string(xwf_fn!(self.get_extracted_metadata, None)(item_id)) }
+    }
+
+    fn add_extracted_metadata(&self, item_id: i32, metadata: &str, flags: u32) -> bool {
+        let wstr = WideString::from(metadata);
+        xwf_fn!(self.add_extracted_metadata, false)(item_id, wstr.as_ptr() as LPWSTR, flags) != 0
+    }
+
+    fn get_metadata_ex(&self, item: XwfHandle, flags: &mut u32) -> Option<String> {
+        // without XWF_ReleaseMem, the returned buffer would leak
+        let release_mem = xwf_fn!(self.release_mem, None);
+        let ptr = xwf_fn!(self.get_metadata_ex, None)(item, flags);
+
+        if ptr.is_null() {
+            return None;
+        }
+
+        let ret = if (*flags & 0xFF000000) != 0 {
+            None
+        } else if *flags == 0x1 {
+            Some(unsafe { char_ptr_to_string(ptr as *mut u8) })
+        } else {
+            unsafe { wstr_ptr_to_string(ptr as *const u16) }
+        };
+
+        release_mem(ptr);
+        ret
+    }
+
+    fn get_hash_value(&self, item_id: i32, buffer: &mut [u8]) -> bool {
+        xwf_fn!(self.get_hash_value, false)(item_id, buffer.as_mut_ptr() as LPVOID) != 0
+    }
+
+    fn set_hash_value(&self, item_id: i32, hash: &[u8], param: u32) -> bool {
+        xwf_fn!(self.set_hash_value, false)(item_id, hash.as_ptr() as LPVOID, param) != 0
+    }
+
+    fn create_file(&self, name: &str, creation_flags: u32, parent_item_id: i32, src_info: &mut SrcInfo) -> i32 {
+        let wstr = WideString::from(name);
+        let p_src_info: *mut SrcInfo = src_info;
+        xwf_fn!(self.create_file, -1)(wstr.as_ptr() as LPWSTR, creation_flags, parent_item_id, p_src_info as PVOID)
+    }
+
+    fn open_item(&self, volume: XwfHandle, item_id: i32, flags: u32) -> XwfHandle {
+        xwf_fn!(self.open_item, XwfHandle::NULL)(volume, item_id, flags)
+    }
+
+    fn close(&self, volume_or_item: XwfHandle) {
+        xwf_fn!(self.close)(volume_or_item)
+    }
+
+    fn read(&self, volume_or_item: XwfHandle, offset: i64, buffer: &mut [u8]) -> u32 {
+        xwf_fn!(self.read, 0)(volume_or_item, offset, buffer.as_mut_ptr(), buffer.len() as DWORD)
+    }
+
+    fn get_prop(&self, volume_or_item: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64 {
+        let ret = xwf_fn!(self.get_prop, -1)(volume_or_item, prop_type, null_mut());
+
+        match prop_type {
+            x if x == PropType::PointerFilePath as u32 || x == PropType::PointerName as u32 => {
+                if let Some(s) = string {
+                    *s = unsafe { wstr_ptr_to_string(ret as LPWSTR).unwrap_or_default() };
+                }
+            },
+            _ => {}
+        }
+        ret
+    }
+
+    fn get_first_ev_obj(&self) -> XwfHandle {
+        xwf_fn!(self.get_first_ev_obj, XwfHandle::NULL)(null_mut())
+    }
+
+    fn get_next_ev_obj(&self, prev_evidence: XwfHandle) -> XwfHandle {
+        xwf_fn!(self.get_next_ev_obj, XwfHandle::NULL)(prev_evidence, null_mut())
+    }
+
+    fn get_ev_obj(&self, ev_obj_id: u32) -> XwfHandle {
+        xwf_fn!(self.get_ev_obj, XwfHandle::NULL)(ev_obj_id)
+    }
+
+    fn open_ev_obj(&self, evidence: XwfHandle, flags: u32) -> XwfHandle {
+        xwf_fn!(self.open_ev_obj, XwfHandle::NULL)(evidence, flags)
+    }
+
+    fn close_ev_obj(&self, evidence: XwfHandle) {
+        xwf_fn!(self.close_ev_obj)(evidence)
+    }
+
+    fn get_ev_obj_prop(&self, evidence: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64 {
+        match prop_type {
+            // string properties returned as pointer
+            x if x == EvObjPropType::ObjTitle as u32
+                || x == EvObjPropType::InternalName as u32
+                || x == EvObjPropType::Description as u32
+                || x == EvObjPropType::ExaminerComments as u32 => {
+                let ret = xwf_fn!(self.get_ev_obj_prop, -1)(evidence, prop_type, null_mut());
+                if let Some(s) = string {
+                    if ret != 0 && ret != -1 {
+                        *s = unsafe { wstr_ptr_to_string(ret as LPWSTR).unwrap_or_default() };
+                    }
+                }
+                ret
+            },
+            // string properties written to given buffer
+            x if x == EvObjPropType::ExtObjTitle as u32
+                || x == EvObjPropType::AbbrevObjTitle as u32
+                || x == EvObjPropType::IntUsedDir as u32
+                || x == EvObjPropType::OutputDir as u32 => {
+                let mut buf = [0u16; MAX_PATH];
+                let ret = xwf_fn!(self.get_ev_obj_prop, -1)(evidence, prop_type, buf.as_mut_ptr() as PVOID);
+                if let Some(s) = string {
+                    *s = WideString::from_wchars_slice(&buf).to_string();
+                }
+                ret
+            },
+            _ => xwf_fn!(self.get_ev_obj_prop, -1)(evidence, prop_type, null_mut())
+        }
+    }
+
+    fn get_ev_obj_report_table_assocs(&self, evidence: XwfHandle, flags: i32) -> Option<Vec<ReportTableListItem>> {
+        let mut num_pairs: LONG = 0;
+        let ptr_list = xwf_fn!(self.get_ev_obj_report_table_assocs, None)(evidence, flags, &mut num_pairs) as *const ReportTableListItemPacked;
+
+        if ptr_list.is_null() {
+            return None;
+        }
+
+        let mut ret = Vec::with_capacity(num_pairs.max(0) as usize);
+        for idx in 0..num_pairs.max(0) as usize {
+            let item = unsafe { *(ptr_list.add(idx)) };
+            ret.push(ReportTableListItem {
+                report_table_id: item.report_table_id,
+                item_id: item.item_id,
+            });
+        }
+        Some(ret)
+    }
+
+    fn get_case_prop(&self, prop_type: i32, string: Option<&mut String>) -> i64 {
+        match prop_type {
+            // title, examiner, case file path, case directory
+            1 | 3 | 5 | 6 => {
+                let mut buf = [0u16; BUF_SIZE_DEFAULT];
+                let ret = xwf_fn!(self.get_case_prop, -1)(null_mut(), prop_type, buf.as_mut_ptr() as LPVOID, buf.len() as LONG);
+                if let Some(s) = string {
+                    *s = WideString::from_wchars_slice(&buf).to_string();
+                }
+                ret
+            },
+            _ => xwf_fn!(self.get_case_prop, -1)(null_mut(), prop_type, null_mut(), 0)
+        }
+    }
+
+    fn get_report_table_info(&self, report_table_id: i32, optional: &mut i32) -> Option<String> {
+        let ptr = xwf_fn!(self.get_report_table_info, None)(null_mut(), report_table_id, optional);
+        unsafe { wstr_ptr_to_string(ptr as LPWSTR) }
+    }
+
+    fn show_progress(&self, caption: &str, flags: u32) {
+        xwf_fn!(self.show_progress)(WideString::from(caption).as_ptr() as LPWSTR, flags)
+    }
+
+    fn set_progress_description(&self, description: &str) {
+        xwf_fn!(self.set_progress_description)(WideString::from(description).as_ptr() as LPWSTR)
+    }
+
+    fn set_progress_percentage(&self, percent: u32) {
+        xwf_fn!(self.set_progress_percentage)(percent)
+    }
+
+    fn hide_progress(&self) {
+        xwf_fn!(self.hide_progress)()
+    }
+
+    fn should_stop(&self) -> bool {
+        xwf_fn!(self.should_stop, false)() != 0
+    }
+
+    fn get_user_input(&self, message: &str, input: Option<&mut String>, flags: u32) -> i64 {
+        let msg = WideString::from(message);
+        match input {
+            Some(s) => {
+                let mut buf = vec![0u16; BUF_SIZE_USER_INPUT];
+                let ret = xwf_fn!(self.get_user_input, -1)(msg.as_ptr() as LPWSTR, buf.as_mut_ptr(), buf.len() as DWORD, flags);
+                *s = WideString::from_wchars_slice(&buf).to_string();
+                ret
+            },
+            None => xwf_fn!(self.get_user_input, -1)(msg.as_ptr() as LPWSTR, null_mut(), 0, flags)
+        }
+    }
+
+    fn search(&self, volume: XwfHandle, search_terms: &str, flags: u32, search_window: u32, code_pages: Option<[u16; 5]>) -> i32 {
+        let terms = WideString::from(search_terms);
+        let mut info = SearchInfo {
+            i_size: size_of::<SearchInfo>() as LONG,
+            h_volume: volume,
+            lp_search_terms: terms.as_ptr() as LPWSTR,
+            n_flags: flags,
+            n_search_window: search_window,
+        };
+        let mut pages = code_pages.map(|c| CodePages {
+            i_size: size_of::<CodePages>() as LONG,
+            n_code_page1: c[0],
+            n_code_page2: c[1],
+            n_code_page3: c[2],
+            n_code_page4: c[3],
+            n_code_page5: c[4],
+        });
+        let p_pages = pages.as_mut().map(|p| p as *mut CodePages).unwrap_or(null_mut());
+        xwf_fn!(self.search, -1)(&mut info, p_pages)
+    }
+
+    fn get_search_term(&self, search_term_id: i32) -> Option<String> {
+        unsafe { wstr_ptr_to_string(xwf_fn!(self.get_search_term, None)(search_term_id, null_mut())) }
+    }
+
+    fn get_search_term_count(&self) -> i32 {
+        xwf_fn!(self.get_search_term, -1)(-1, null_mut()) as isize as i32
+    }
+
+    fn add_search_term(&self, name: &str, flags: u32) -> i32 {
+        let wstr = WideString::from(name);
+        xwf_fn!(self.add_search_term, -1)(wstr.as_ptr() as LPWSTR, flags)
+    }
+
+    fn get_search_hit(&self, hit_no: i32) -> Option<SearchHit> {
+        let mut info = SearchHitInfo::from(&SearchHit::default());
+        let ret = xwf_fn!(self.get_search_hit, None)(hit_no, &mut info);
+        if ret > 0 { Some(SearchHit::from(&info)) } else { None }
+    }
+
+    fn set_search_hit(&self, hit_no: i32, hit: &SearchHit) -> bool {
+        let mut info = SearchHitInfo::from(hit);
+        xwf_fn!(self.set_search_hit, false)(hit_no, &mut info) > 0
+    }
+
+    fn add_search_hit(&self, hit: &SearchHit) -> i32 {
+        let mut info = SearchHitInfo::from(hit);
+        xwf_fn!(self.add_search_hit, 0)(&mut info)
+    }
+
+    fn get_event(&self, evidence: XwfHandle, event_no: u32) -> Option<Event> {
+        let mut descr = vec![0u8; BUF_SIZE_EVENT_DESCRIPTION];
+        let mut info = EventInfo {
+            i_size: size_of::<EventInfo>() as LONG,
+            h_evidence: evidence,
+            n_evt_type: 0,
+            n_flags: 0,
+            time_stamp: 0,
+            n_item_id: -1,
+            n_ofs: -1,
+            lp_descr: descr.as_mut_ptr() as LPSTR,
+        };
+        if xwf_fn!(self.get_event, None)(event_no, &mut info) < 0 {
+            return None;
+        }
+        let len = descr.iter().position(|c| *c == 0).unwrap_or(descr.len());
+        Event::from_raw(&info, String::from_utf8_lossy(&descr[..len]).into_owned())
+    }
+
+    fn add_event(&self, event: &Event) -> i32 {
+        let descr = CString::new(event.description.replace('\0', "")).unwrap_or_default();
+        let mut info = EventInfo {
+            i_size: size_of::<EventInfo>() as LONG,
+            h_evidence: event.evidence,
+            n_evt_type: event.event_type.into(),
+            n_flags: event.raw_flags().bits(),
+            time_stamp: event.timestamp.to_filetime(),
+            n_item_id: event.item_id,
+            n_ofs: event.offset,
+            lp_descr: descr.as_ptr() as LPSTR,
+        };
+        xwf_fn!(self.add_event, 0)(&mut info)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::with_missing(self.missing.clone())
+    }
+}
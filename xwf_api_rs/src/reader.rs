@@ -0,0 +1,169 @@
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+use crate::application::Application;
+use crate::backend::backend;
+use crate::capabilities::require_function;
+use crate::error::XwfError;
+use crate::item::ItemHandle;
+use crate::traits::NativeHandle;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+
+pub const DEFAULT_READER_BUFFER_SIZE: usize = 1024*1024;
+
+/// Buffered reader for the data of an opened item
+pub type ItemReader<'a> = HandleReader<'a, ItemHandle>;
+
+/// Buffered reader for the raw bytes of a volume
+pub type VolumeReader<'a> = HandleReader<'a, Volume>;
+
+/// Buffered reader for the data of an item or volume, implementing [`Read`], [`Seek`] and [`BufRead`]
+/// on top of `XWF_Read`. The buffer is allocated once and reused for all reads.
+///
+/// [`Application::should_stop`] is checked before each call of `XWF_Read`. If the user aborted
+/// the operation, reading fails with an [`io::Error`] wrapping [`XwfError::OperationAbortedByUser`].
+/// If `XWF_Read` returns no data before the end of the item or volume, reading fails with
+/// [`io::ErrorKind::UnexpectedEof`] instead of silently truncating the data.
+pub struct HandleReader<'a, H: NativeHandle> {
+    source: &'a H,
+    size: u64,
+    pos: u64,
+    buf: Vec<u8>,
+    // offset of buf[0] within the item or volume
+    buf_start: u64,
+    buf_len: usize,
+    // error returned if XWF_Read fails at the given offset
+    read_error: fn(u64) -> XwfError,
+}
+
+impl<'a> HandleReader<'a, ItemHandle> {
+    pub fn new(item_handle: &'a ItemHandle) -> Result<ItemReader<'a>, XwfError> {
+        Self::with_capacity(DEFAULT_READER_BUFFER_SIZE, item_handle)
+    }
+
+    pub fn with_capacity(capacity: usize, item_handle: &'a ItemHandle) -> Result<ItemReader<'a>, XwfError> {
+        let size = item_handle.get_prop(PropType::LogicalSize)?;
+        if size < 0 {
+            return Err(XwfError::InvalidItemSize);
+        }
+        HandleReader::create(capacity, item_handle, size as u64, |_| XwfError::ReadItemDataFailed)
+    }
+
+    pub fn item_handle(&self) -> &ItemHandle {
+        self.source
+    }
+}
+
+impl<'a> HandleReader<'a, Volume> {
+    pub fn new(volume: &'a Volume) -> Result<VolumeReader<'a>, XwfError> {
+        Self::with_capacity(DEFAULT_READER_BUFFER_SIZE, volume)
+    }
+
+    pub fn with_capacity(capacity: usize, volume: &'a Volume) -> Result<VolumeReader<'a>, XwfError> {
+        HandleReader::create(capacity, volume, volume.get_size()?, XwfError::ReadVolumeDataFailed)
+    }
+
+    pub fn volume(&self) -> &Volume {
+        self.source
+    }
+}
+
+impl<'a, H: NativeHandle> HandleReader<'a, H> {
+    fn create(capacity: usize, source: &'a H, size: u64, read_error: fn(u64) -> XwfError) -> Result<HandleReader<'a, H>, XwfError> {
+        require_function("XWF_Read")?;
+        Ok(HandleReader {
+            source,
+            size,
+            pos: 0,
+            buf: vec![0; capacity.max(1)],
+            buf_start: 0,
+            buf_len: 0,
+            read_error,
+        })
+    }
+
+    /// logical size of the item or volume
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    fn is_buffered(&self, pos: u64) -> bool {
+        pos >= self.buf_start && pos < self.buf_start + self.buf_len as u64
+    }
+}
+
+pub(crate) fn read_at(handle: XwfHandle, size: u64, offset: u64, data: &mut [u8], read_error: fn(u64) -> XwfError) -> io::Result<usize> {
+    if offset >= size || data.is_empty() {
+        return Ok(0);
+    }
+    Application::should_stop().map_err(io::Error::other)?;
+
+    // never read beyond the logical size
+    let len = data.len().min((size - offset) as usize);
+    let num_read = backend().read(handle, offset as i64, &mut data[..len]);
+
+    // nothing read before the end of the data is an error, not the end of the data
+    if num_read == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, read_error(offset)));
+    }
+    Ok(num_read as usize)
+}
+
+impl<H: NativeHandle> Read for HandleReader<'_, H> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        // large reads bypass the buffer
+        if !self.is_buffered(self.pos) && out.len() >= self.buf.len() {
+            let n = read_at(self.source.get_handle(), self.size, self.pos, out, self.read_error)?;
+            self.pos += n as u64;
+            return Ok(n);
+        }
+
+        let available = self.fill_buf()?;
+        let n = available.len().min(out.len());
+        out[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<H: NativeHandle> BufRead for HandleReader<'_, H> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if !self.is_buffered(self.pos) {
+            self.buf_start = self.pos;
+            self.buf_len = 0;
+            self.buf_len = read_at(self.source.get_handle(), self.size, self.pos, &mut self.buf, self.read_error)?;
+        }
+
+        let start = (self.pos - self.buf_start) as usize;
+        Ok(&self.buf[start.min(self.buf_len)..self.buf_len])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt as u64;
+    }
+}
+
+impl<H: NativeHandle> Seek for HandleReader<'_, H> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+
+        match new_pos {
+            Some(p) => {
+                self.pos = p;
+                Ok(p)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")),
+        }
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos)
+    }
+}
@@ -0,0 +1,194 @@
+//! Snapshot of the information about an item in one serializable struct, e.g. for exports or
+//! for comparing the results of different runs.
+
+use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+use crate::backend::backend;
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::{Item, UniqueItemId};
+use crate::tree::VolumeTree;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+
+bitflags! {
+    /// selects the fields of an [`ItemRecord`] that are fetched, all other fields are left empty
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub struct RecordOptions: u32 {
+        const Path              = 0x0001;
+        const Timestamps        = 0x0002;
+        const FileType          = 0x0004; //type, category, type status and format consistency
+        const Hashes            = 0x0008;
+        const ReportTables      = 0x0010;
+        const HashSets          = 0x0020;
+        const Comment           = 0x0040;
+        const ExtractedMetadata = 0x0080;
+        const PhysicalSize      = 0x0100; //requires opening the item
+    }
+}
+
+/// Information about an item as returned by the getters of [`Item`]. Fields that were not
+/// selected via [`RecordOptions`] or are not available are `None` or empty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemRecord {
+    pub evidence_id: u32,
+    pub short_ev_id: u16,
+    pub item_id: i32,
+    pub name: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub physical_size: Option<u64>,
+
+    #[serde(default)]
+    pub creation_time: Option<XwfDateTime>,
+    #[serde(default)]
+    pub modification_time: Option<XwfDateTime>,
+    #[serde(default)]
+    pub last_access_time: Option<XwfDateTime>,
+    #[serde(default)]
+    pub entry_modification_time: Option<XwfDateTime>,
+    #[serde(default)]
+    pub deletion_time: Option<XwfDateTime>,
+    #[serde(default)]
+    pub internal_creation_time: Option<XwfDateTime>,
+
+    pub flags: ItemInfoFlags,
+    pub attributes: ItemInfoAttributes,
+    pub deletion: ItemInfoDeletion,
+    pub classification: ItemInfoClassification,
+
+    #[serde(default)]
+    pub file_type: Option<String>,
+    #[serde(default)]
+    pub file_type_description: Option<String>,
+    #[serde(default)]
+    pub category: Option<FileTypeCategory>,
+    #[serde(default)]
+    pub type_status: Option<FileTypeStatus>,
+    #[serde(default)]
+    pub consistency: Option<FileFormatConsistency>,
+
+    /// hex encoded
+    #[serde(default)]
+    pub hash1: Option<String>,
+    /// hex encoded
+    #[serde(default)]
+    pub hash2: Option<String>,
+
+    #[serde(default)]
+    pub report_tables: Vec<String>,
+    #[serde(default)]
+    pub hash_sets: Vec<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub extracted_metadata: Vec<String>,
+}
+
+impl ItemRecord {
+    pub fn new(item: &Item, volume: &Volume, evidence: &Evidence, options: RecordOptions) -> Result<ItemRecord, XwfError> {
+        let flags = item.get_item_info_flags()?;
+
+        let mut record = ItemRecord {
+            evidence_id: evidence.get_id(),
+            short_ev_id: evidence.get_short_id(),
+            item_id: item.item_id,
+            name: item.get_name()?,
+            path: None,
+            size: u64::try_from(backend().get_item_size(item.item_id)).ok(),
+            physical_size: None,
+            creation_time: None,
+            modification_time: None,
+            last_access_time: None,
+            entry_modification_time: None,
+            deletion_time: None,
+            internal_creation_time: None,
+            flags,
+            attributes: item.get_attributes()?,
+            deletion: item.get_item_info_deletion()?,
+            classification: item.get_item_info_classification()?,
+            file_type: None,
+            file_type_description: None,
+            category: None,
+            type_status: None,
+            consistency: None,
+            hash1: None,
+            hash2: None,
+            report_tables: Vec::new(),
+            hash_sets: Vec::new(),
+            comment: None,
+            extracted_metadata: Vec::new(),
+        };
+
+        if options.contains(RecordOptions::Path) {
+            // the tree is built once per volume snapshot, which is faster for more than a few items
+            record.path = Some(match VolumeTree::get(volume)?.path(item) {
+                Some(path) => path.to_string(),
+                None => item.get_path()?,
+            });
+        }
+
+        if options.contains(RecordOptions::PhysicalSize) {
+            let handle = item.open(volume, OpenItemFlags::SuppressErrorMessages)?;
+            record.physical_size = u64::try_from(handle.get_physical_size()?).ok();
+            handle.close();
+        }
+
+        if options.contains(RecordOptions::Timestamps) {
+            let time = |infotype| item.get_item_info_time(infotype, false, &flags);
+            record.creation_time = time(XwfItemInfoTypes::CreationTime);
+            record.modification_time = time(XwfItemInfoTypes::ModificationTime);
+            record.last_access_time = time(XwfItemInfoTypes::LastAccessTime);
+            record.entry_modification_time = time(XwfItemInfoTypes::EntryModificationTime);
+            record.deletion_time = time(XwfItemInfoTypes::DeletionTime);
+            record.internal_creation_time = time(XwfItemInfoTypes::InternalCreationTime);
+        }
+
+        if options.contains(RecordOptions::FileType) {
+            record.file_type = item.get_item_type(false).ok().filter(|t| !t.is_empty());
+            record.file_type_description = item.get_item_type(true).ok().filter(|t| !t.is_empty());
+            if let Ok((status, consistency, category)) = item.get_item_category() {
+                record.type_status = Some(status);
+                record.consistency = Some(consistency);
+                record.category = Some(category);
+            }
+        }
+
+        if options.contains(RecordOptions::Hashes) {
+            let hash = |secondary| volume.get_hash_type(secondary)
+                .and_then(|hash_type| item.get_hash_value(hash_type, secondary).ok().flatten())
+                .map(hex::encode);
+            record.hash1 = hash(false);
+            record.hash2 = hash(true);
+        }
+
+        if options.contains(RecordOptions::ReportTables) {
+            record.report_tables = item.__get_report_tables().unwrap_or_default();
+        }
+
+        if options.contains(RecordOptions::HashSets) {
+            record.hash_sets = item.get_hash_sets().unwrap_or_default();
+        }
+
+        if options.contains(RecordOptions::Comment) {
+            record.comment = item.get_comment().ok().flatten().filter(|c| !c.is_empty());
+        }
+
+        if options.contains(RecordOptions::ExtractedMetadata) {
+            record.extracted_metadata = item.get_extracted_metadata().ok().flatten().unwrap_or_default();
+        }
+
+        Ok(record)
+    }
+
+    pub fn item(&self) -> Item {
+        Item::new(self.item_id)
+    }
+
+    pub fn unique_id(&self) -> UniqueItemId {
+        UniqueItemId { item_id: self.item_id, evidence_id: self.evidence_id, short_ev_id: self.short_ev_id }
+    }
+}
@@ -0,0 +1,385 @@
+//! Search API of X-Ways Forensics: running simultaneous searches ([`Search`]), search terms
+//! ([`SearchTerm`]) and search hits ([`SearchHit`]). Search hits found by a search can be
+//! processed by implementing [`XTension::xt_process_search_hit`], search settings chosen by the
+//! user can be adjusted by implementing [`XTension::xt_prepare_search`].
+//!
+//! [`XTension::xt_process_search_hit`]: crate::traits::XTension::xt_process_search_hit
+//! [`XTension::xt_prepare_search`]: crate::traits::XTension::xt_prepare_search
+
+use std::ptr::null_mut;
+use serde::{Deserialize, Serialize};
+use crate::backend::backend;
+use crate::capabilities::require_function;
+use crate::error::XwfError;
+use crate::item::Item;
+use crate::mt::require_main_thread;
+use crate::volume::Volume;
+use crate::wide_string::WideString;
+use crate::xwf_types::*;
+
+pub const CODE_PAGE_WINDOWS_1252: u16 = 1252;
+pub const CODE_PAGE_UTF16_LE: u16 = 1200;
+pub const CODE_PAGE_UTF8: u16 = 65001;
+
+/// maximum number of code pages of a search
+pub const MAX_CODE_PAGES: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchTerm {
+    pub id: i32,
+    pub name: String,
+}
+
+impl SearchTerm {
+    pub fn get(id: i32) -> Option<SearchTerm> {
+        if id < 0 || require_function("XWF_GetSearchTerm").is_err() {
+            return None;
+        }
+        backend().get_search_term(id).map(|name| SearchTerm { id, name })
+    }
+
+    /// number of search terms in the case
+    pub fn count() -> Result<i32, XwfError> {
+        require_function("XWF_GetSearchTerm")?;
+        Ok(backend().get_search_term_count().max(0))
+    }
+
+    pub fn all() -> Result<Vec<SearchTerm>, XwfError> {
+        Ok((0..SearchTerm::count()?).filter_map(SearchTerm::get).collect())
+    }
+
+    pub fn find(name: &str) -> Result<Option<SearchTerm>, XwfError> {
+        Ok(SearchTerm::all()?.into_iter().find(|t| t.name == name))
+    }
+
+    /// adds a search term to the case. With [`AddSearchTermFlags::UseExisting`], an existing
+    /// search term with the same name is returned instead.
+    pub fn add(name: &str, flags: AddSearchTermFlags) -> Result<SearchTerm, XwfError> {
+        require_function("XWF_AddSearchTerm")?;
+        require_main_thread("XWF_AddSearchTerm")?;
+        let id = backend().add_search_term(name, flags.bits());
+        if id < 0 {
+            return Err(XwfError::XwfFunctionCallFailed("add_search_term"));
+        }
+        Ok(SearchTerm { id, name: name.to_string() })
+    }
+}
+
+/// Search hit of the search hit list of the currently selected volume snapshot
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub item_id: i32,
+    /// offset relative to the start of the item
+    pub rel_ofs: i64,
+    /// offset relative to the start of the volume, -1 if not available
+    pub abs_ofs: i64,
+    pub search_term_id: u16,
+    pub length: u16,
+    pub code_page: u16,
+    pub flags: SearchHitFlags,
+}
+
+impl SearchHit {
+    pub fn new(item: Item, rel_ofs: i64, search_term: &SearchTerm, length: u16, code_page: u16) -> SearchHit {
+        SearchHit {
+            item_id: item.item_id,
+            rel_ofs,
+            abs_ofs: -1,
+            search_term_id: search_term.id as u16,
+            length,
+            code_page,
+            flags: SearchHitFlags::empty(),
+        }
+    }
+
+    /// returns the search hit with the given number, `None` if there is no such search hit
+    pub fn get(hit_no: i32) -> Option<SearchHit> {
+        if hit_no < 0 || require_function("XWF_GetSearchHit").is_err() {
+            return None;
+        }
+        backend().get_search_hit(hit_no)
+    }
+
+    /// iterates over all search hits of the currently selected volume snapshot
+    pub fn iter() -> Result<SearchHitIterator, XwfError> {
+        require_function("XWF_GetSearchHit")?;
+        Ok(SearchHitIterator { next_hit_no: 0 })
+    }
+
+    /// overwrites the search hit with the given number
+    pub fn set(&self, hit_no: i32) -> Result<(), XwfError> {
+        require_function("XWF_SetSearchHit")?;
+        if backend().set_search_hit(hit_no, self) {
+            Ok(())
+        } else {
+            Err(XwfError::XwfFunctionCallFailed("set_search_hit"))
+        }
+    }
+
+    /// adds the search hit to the search hit list, returns false if it already existed
+    pub fn add(&self) -> Result<bool, XwfError> {
+        require_function("XWF_AddSearchHit")?;
+        match backend().add_search_hit(self) {
+            1 => Ok(true),
+            2 => Ok(false),
+            _ => Err(XwfError::XwfFunctionCallFailed("add_search_hit")),
+        }
+    }
+
+    pub fn item(&self) -> Item {
+        Item::new(self.item_id)
+    }
+
+    pub fn search_term(&self) -> Option<SearchTerm> {
+        SearchTerm::get(self.search_term_id as i32)
+    }
+
+    /// arguments recorded for `XT_ProcessSearchHit`, see [`SearchHit::from_callback_args`]
+    pub fn callback_args(&self) -> [i64; 7] {
+        [self.item_id as i64, self.rel_ofs, self.abs_ofs, self.search_term_id as i64,
+         self.length as i64, self.code_page as i64, self.flags.bits() as i64]
+    }
+
+    /// reconstructs a search hit from the arguments recorded for `XT_ProcessSearchHit`
+    pub fn from_callback_args(args: &[i64]) -> Option<SearchHit> {
+        match *args {
+            [item_id, rel_ofs, abs_ofs, search_term_id, length, code_page, flags] => Some(SearchHit {
+                item_id: item_id as i32,
+                rel_ofs,
+                abs_ofs,
+                search_term_id: search_term_id as u16,
+                length: length as u16,
+                code_page: code_page as u16,
+                flags: SearchHitFlags::from_bits_retain(flags as u16),
+            }),
+            _ => None,
+        }
+    }
+
+    /// # Safety
+    /// `info` must be null or point to a valid `SearchHitInfo`
+    pub unsafe fn from_raw(info: *const SearchHitInfo) -> Option<SearchHit> {
+        info.as_ref().map(SearchHit::from)
+    }
+}
+
+impl From<&SearchHitInfo> for SearchHit {
+    fn from(info: &SearchHitInfo) -> Self {
+        SearchHit {
+            item_id: info.n_item_id,
+            rel_ofs: info.n_rel_ofs,
+            abs_ofs: info.n_abs_ofs,
+            search_term_id: info.n_search_term_id,
+            length: info.n_length,
+            code_page: info.n_code_page,
+            flags: SearchHitFlags::from_bits_retain(info.n_flags),
+        }
+    }
+}
+
+impl From<&SearchHit> for SearchHitInfo {
+    fn from(hit: &SearchHit) -> Self {
+        SearchHitInfo {
+            i_size: size_of::<SearchHitInfo>() as i32,
+            n_item_id: hit.item_id,
+            n_rel_ofs: hit.rel_ofs,
+            n_abs_ofs: hit.abs_ofs,
+            lp_optional_hit_ptr: null_mut(),
+            n_search_term_id: hit.search_term_id,
+            n_length: hit.length,
+            n_code_page: hit.code_page,
+            n_flags: hit.flags.bits(),
+            h_open_item: XwfHandle::NULL,
+        }
+    }
+}
+
+pub struct SearchHitIterator {
+    next_hit_no: i32,
+}
+
+impl Iterator for SearchHitIterator {
+    /// (search hit number, search hit)
+    type Item = (i32, SearchHit);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let hit_no = self.next_hit_no;
+        let hit = backend().get_search_hit(hit_no)?;
+        self.next_hit_no += 1;
+        Some((hit_no, hit))
+    }
+}
+
+/// Simultaneous search via `XWF_Search`. Without code pages, the code pages last used
+/// in X-Ways Forensics are searched.
+///
+/// ```ignore
+/// Search::new(["secret", "password"])
+///     .with_flags(SearchFlags::Logical | SearchFlags::CallProcessSearchHit)
+///     .with_code_pages(&[CODE_PAGE_WINDOWS_1252, CODE_PAGE_UTF16_LE])
+///     .run()?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search {
+    pub search_terms: Vec<String>,
+    pub flags: SearchFlags,
+    pub search_window: u32,
+    pub code_pages: Vec<u16>,
+    volume: XwfHandle,
+}
+
+impl Search {
+    pub fn new<I: IntoIterator<Item=S>, S: Into<String>>(search_terms: I) -> Search {
+        Search {
+            search_terms: search_terms.into_iter().map(|s| s.into()).collect(),
+            flags: SearchFlags::Logical,
+            search_window: 0,
+            code_pages: Vec::new(),
+            volume: XwfHandle::NULL,
+        }
+    }
+
+    pub fn with_flags(mut self, flags: SearchFlags) -> Search {
+        self.flags = flags;
+        self
+    }
+
+    /// size of the search window for GREP searches in bytes
+    pub fn with_search_window(mut self, search_window: u32) -> Search {
+        self.search_window = search_window;
+        self
+    }
+
+    /// up to [`MAX_CODE_PAGES`] code pages, further code pages are ignored
+    pub fn with_code_pages(mut self, code_pages: &[u16]) -> Search {
+        self.code_pages = code_pages.iter().take(MAX_CODE_PAGES).copied().collect();
+        self
+    }
+
+    /// searches the given volume instead of the volume of the active data window
+    pub fn with_volume(mut self, volume: &Volume) -> Search {
+        self.volume = volume.handle();
+        self
+    }
+
+    pub fn volume_handle(&self) -> XwfHandle {
+        self.volume
+    }
+
+    /// code pages padded with 0 to [`MAX_CODE_PAGES`] entries, `None` if no code page was given
+    pub fn code_page_array(&self) -> Option<[u16; MAX_CODE_PAGES]> {
+        code_page_array(&self.code_pages)
+    }
+
+    /// runs the search. With [`SearchFlags::CallProcessSearchHit`], a single-threaded X-Tension that
+    /// starts the search from one of its callbacks gets the hits after that callback returned,
+    /// see [`StInstance`](crate::dispatch::StInstance).
+    pub fn run(&self) -> Result<(), XwfError> {
+        require_function("XWF_Search")?;
+        require_main_thread("Search::run")?;
+        if self.search_terms.is_empty() || self.search_terms.iter().any(|t| t.is_empty() || t.contains(['\r', '\n'])) {
+            return Err(XwfError::InvalidInputArgument);
+        }
+
+        let ret = backend().search(self.volume, &self.search_terms.join("\r\n"), self.flags.bits(), self.search_window, self.code_page_array());
+        if ret < 0 {
+            return Err(XwfError::XwfFunctionCallFailed("search"));
+        }
+        Ok(())
+    }
+}
+
+fn code_page_array(code_pages: &[u16]) -> Option<[u16; MAX_CODE_PAGES]> {
+    if code_pages.is_empty() {
+        return None;
+    }
+    let mut ret = [0u16; MAX_CODE_PAGES];
+    for (dst, src) in ret.iter_mut().zip(code_pages) {
+        *dst = *src;
+    }
+    Some(ret)
+}
+
+/// Search settings chosen by the user, passed to [`XTension::xt_prepare_search`] before the search starts.
+/// Changes to the search terms, flags or code pages are applied to the search.
+///
+/// [`XTension::xt_prepare_search`]: crate::traits::XTension::xt_prepare_search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrepareSearch {
+    pub search_terms: Vec<String>,
+    pub flags: SearchFlags,
+    pub code_pages: Vec<u16>,
+    // maximum number of characters of the search terms including the terminating null
+    buf_len: usize,
+}
+
+impl PrepareSearch {
+    pub fn new(search: &Search) -> PrepareSearch {
+        PrepareSearch {
+            search_terms: search.search_terms.clone(),
+            flags: search.flags,
+            code_pages: search.code_pages.clone(),
+            buf_len: usize::MAX,
+        }
+    }
+
+    /// creates a [`Search`] with the prepared settings
+    pub fn to_search(&self) -> Search {
+        Search::new(self.search_terms.clone())
+            .with_flags(self.flags)
+            .with_code_pages(&self.code_pages)
+    }
+
+    /// # Safety
+    /// `info` must be null or point to a valid `PrepareSearchInfo` with a buffer of `n_buf_len` characters,
+    /// `code_pages` must be null or point to a valid `CodePages`
+    pub unsafe fn from_raw(info: *const PrepareSearchInfo, code_pages: *const CodePages) -> Option<PrepareSearch> {
+        let info = info.as_ref()?;
+        let search_terms = if info.lp_search_terms.is_null() {
+            String::new()
+        } else {
+            WideString::from_wchars_nullt(info.lp_search_terms).to_string()
+        };
+
+        Some(PrepareSearch {
+            search_terms: search_terms.lines().filter(|l| !l.is_empty()).map(|l| l.to_string()).collect(),
+            flags: SearchFlags::from_bits_retain(info.n_flags),
+            code_pages: code_pages.as_ref().map(|c| {
+                [c.n_code_page1, c.n_code_page2, c.n_code_page3, c.n_code_page4, c.n_code_page5]
+                    .into_iter().filter(|c| *c != 0).collect()
+            }).unwrap_or_default(),
+            buf_len: info.n_buf_len as usize,
+        })
+    }
+
+    /// writes the settings back to the structs passed to `XT_PrepareSearch`. Fails with
+    /// [`XwfError::GivenBufferToSmallForContent`] if the search terms do not fit into the buffer.
+    ///
+    /// # Safety
+    /// see [`PrepareSearch::from_raw`]
+    pub unsafe fn write_to_raw(&self, info: *mut PrepareSearchInfo, code_pages: *mut CodePages) -> Result<(), XwfError> {
+        let info = info.as_mut().ok_or(XwfError::InputHandleIsNull)?;
+        let terms: Vec<u16> = self.search_terms.join("\r\n").encode_utf16().collect();
+        if terms.len() >= info.n_buf_len as usize || info.lp_search_terms.is_null() {
+            return Err(XwfError::GivenBufferToSmallForContent);
+        }
+
+        std::ptr::copy_nonoverlapping(terms.as_ptr(), info.lp_search_terms, terms.len());
+        *info.lp_search_terms.add(terms.len()) = 0;
+        info.n_flags = self.flags.bits();
+
+        if let (Some(c), Some(pages)) = (code_pages.as_mut(), code_page_array(&self.code_pages)) {
+            c.n_code_page1 = pages[0];
+            c.n_code_page2 = pages[1];
+            c.n_code_page3 = pages[2];
+            c.n_code_page4 = pages[3];
+            c.n_code_page5 = pages[4];
+        }
+        Ok(())
+    }
+
+    /// maximum length of all search terms in characters, including line breaks
+    pub fn max_len(&self) -> usize {
+        self.buf_len.saturating_sub(1)
+    }
+}
@@ -0,0 +1,678 @@
+use std::fmt::Display;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use crate::backend::{lock_exclusive_backend, notify_callback, reset_backend, set_backend, XwfBackend};
+use crate::dispatch::StInstance;
+use crate::mt::MtInstance;
+use crate::panic_guard::{catch_panic, PanicReturns};
+use crate::search::{PrepareSearch, Search, SearchHit};
+use crate::traits::{XTension, XTensionMt};
+use crate::xwf_types::*;
+use super::host::{SearchHitCallback, SimulatedHost};
+use super::model::SimItem;
+
+/// selects which item callback of the X-Tension is invoked by the [`Driver`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemCallback {
+    ProcessItem,
+    ProcessItemEx,
+}
+
+/// return values of all X-Tension callbacks invoked during [`Driver::run`]
+#[derive(Debug, Default, Clone)]
+pub struct RunReport {
+    /// (evidence id, return value of XT_Prepare)
+    pub prepare: Vec<(Option<u32>, i32)>,
+    /// (evidence id, item id, return value of XT_ProcessItem/XT_ProcessItemEx)
+    pub process_item: Vec<(u32, i32, i32)>,
+    /// (evidence id, return value of XT_Finalize)
+    pub finalize: Vec<(Option<u32>, i32)>,
+    /// return value of XT_PrepareSearch, if a search was run
+    pub prepare_search: Option<i32>,
+    /// (evidence id, item id, return value of XT_ProcessSearchHit)
+    pub process_search_hit: Vec<(u32, i32, i32)>,
+    /// true if the operation was stopped by the X-Tension
+    pub stopped: bool,
+}
+
+impl RunReport {
+    pub fn processed_items(&self) -> Vec<(u32, i32)> {
+        self.process_item.iter().map(|(ev, item, _)| (*ev, *item)).collect()
+    }
+}
+
+// (item id, return value) of the XT_ProcessSearchHit calls made by XWF_Search
+type SearchHitCalls = Arc<Mutex<Vec<(i32, i32)>>>;
+
+/// The X-Tension run by a [`Driver`], locked while it is borrowed
+pub struct ExtensionRef<'a, T>(MutexGuard<'a, Option<T>>);
+
+impl<T> Deref for ExtensionRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0.as_ref().expect("X-Tension is not initialized")
+    }
+}
+
+impl<T> DerefMut for ExtensionRef<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.0.as_mut().expect("X-Tension is not initialized")
+    }
+}
+
+/// Runs an [`XTension`] against a [`SimulatedHost`] the same way X-Ways Forensics would call
+/// the exported `XT_*` functions, so that X-Tensions can be tested without X-Ways.
+/// The callbacks are dispatched by the [`StInstance`] the export macros use.
+pub struct Driver<T: XTension> {
+    host: SimulatedHost,
+    instance: Arc<StInstance<T>>,
+    search_hit_calls: SearchHitCalls,
+    version: XtVersion,
+    item_callback: ItemCallback,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl<T: XTension + Send + 'static> Driver<T> where T::XTensionError: Display {
+    pub fn new(host: SimulatedHost) -> Driver<T> {
+        let guard = lock_exclusive_backend();
+        set_backend(host.clone());
+
+        let instance = Arc::new(StInstance::new());
+        let search_hit_calls = SearchHitCalls::default();
+        let callback_instance = instance.clone();
+        host.set_search_hit_callback(Some(search_hit_callback(
+            move |hit| callback_instance.xt_process_search_hit(hit), T::panic_returns, search_hit_calls.clone())));
+
+        Driver {
+            host,
+            instance,
+            search_hit_calls,
+            version: XtVersion { major: 21, minor: 3, service_release: 0, language: 0 },
+            item_callback: ItemCallback::ProcessItem,
+            _guard: guard,
+        }
+    }
+
+    pub fn with_version(mut self, major: u16, minor: u16) -> Driver<T> {
+        self.version.major = major;
+        self.version.minor = minor;
+        self
+    }
+
+    pub fn with_item_callback(mut self, item_callback: ItemCallback) -> Driver<T> {
+        self.item_callback = item_callback;
+        self
+    }
+
+    /// installs `backend` instead of the host, e.g. a wrapper around a clone of the host
+    pub fn with_backend<B: XwfBackend + 'static>(self, backend: B) -> Driver<T> {
+        set_backend(backend);
+        self
+    }
+
+    pub fn host(&self) -> &SimulatedHost {
+        &self.host
+    }
+
+    /// returns the X-Tension, which is created by `XT_Init`
+    pub fn extension(&self) -> ExtensionRef<'_, T> {
+        ExtensionRef(self.instance.extension())
+    }
+
+    pub fn extension_mut(&mut self) -> ExtensionRef<'_, T> {
+        ExtensionRef(self.instance.extension())
+    }
+
+    /// calls `XT_Init` and returns its return value
+    pub fn init(&mut self, flags: XtInitFlags) -> i32 {
+        notify_callback("XT_Init", &[u32::from(self.version) as i64, flags.bits() as i64]);
+        guarded("XT_Init", T::panic_returns, || self.instance.xt_init(self.version, flags, None))
+    }
+
+    /// calls `XT_Done`
+    pub fn done(&mut self) -> i32 {
+        notify_callback("XT_Done", &[]);
+        guarded("XT_Done", T::panic_returns, || self.instance.xt_done())
+    }
+
+    /// calls `XT_About`
+    pub fn about(&mut self) -> i32 {
+        notify_callback("XT_About", &[0]);
+        guarded("XT_About", T::panic_returns, || self.instance.xt_about(None))
+    }
+
+    /// calls `XT_View` as if the user viewed the given item with the X-Tension as viewer,
+    /// returns the data to display
+    pub fn view(&mut self, evidence_id: u32, item_id: i32) -> Option<Vec<u8>> {
+        let volume = self.host.volume_handle(evidence_id);
+        let evidence = self.host.evidence_handle(evidence_id);
+        let item_handle = self.host.open_item(volume, item_id, 0);
+        notify_callback("XT_View", &[item_id as i64, item_handle.as_raw() as i64, volume.as_raw() as i64, evidence.as_raw() as i64]);
+        let ret = catch_panic("XT_View", || None, || self.instance.xt_view(item_id, item_handle, volume, evidence));
+        self.host.close(item_handle);
+        ret
+    }
+
+    /// simulates an operation of the given type. For volume snapshot refinements and directory
+    /// browser context menu invocations, the given evidence objects are processed one after another
+    /// (all evidence objects of the case if `evidence_ids` is empty). For all other operation types,
+    /// `XT_Prepare` and `XT_Finalize` are called once with the first given evidence object (if any).
+    pub fn run(&mut self, op_type: XtPrepareOpType, evidence_ids: &[u32]) -> RunReport {
+        let mut callbacks = StCallbacks { instance: &self.instance, item_callback: self.item_callback, search_hit_calls: &self.search_hit_calls };
+        run_operation(&mut callbacks, &self.host, op_type, evidence_ids)
+    }
+
+    /// simulates a simultaneous search started by the user: calls `XT_PrepareSearch` once, then searches
+    /// the given evidence objects (all if `evidence_ids` is empty) one after another. `XT_ProcessSearchHit`
+    /// is called for each new search hit if the search has [`SearchFlags::CallProcessSearchHit`] set.
+    pub fn search(&mut self, search: &Search, evidence_ids: &[u32]) -> RunReport {
+        let mut callbacks = StCallbacks { instance: &self.instance, item_callback: self.item_callback, search_hit_calls: &self.search_hit_calls };
+        run_search(&mut callbacks, &self.host, search, evidence_ids)
+    }
+}
+
+impl<T: XTension> Drop for Driver<T> {
+    fn drop(&mut self) {
+        self.host.set_search_hit_callback(None);
+        let _ = reset_backend();
+    }
+}
+
+/// Runs an [`XTensionMt`] against a [`SimulatedHost`] like [`Driver`], but calls the item callbacks
+/// concurrently from several worker threads, as X-Ways Forensics does for multi-threaded X-Tensions.
+pub struct MtDriver<T: XTensionMt> {
+    host: SimulatedHost,
+    instance: Arc<MtInstance<T>>,
+    search_hit_calls: SearchHitCalls,
+    version: XtVersion,
+    item_callback: ItemCallback,
+    threads: usize,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl<T: XTensionMt + 'static> MtDriver<T> where T::XTensionError: Display {
+    pub fn new(host: SimulatedHost) -> MtDriver<T> {
+        let guard = lock_exclusive_backend();
+        set_backend(host.clone());
+
+        let instance = Arc::new(MtInstance::new());
+        let search_hit_calls = SearchHitCalls::default();
+        let callback_instance = instance.clone();
+        host.set_search_hit_callback(Some(search_hit_callback(
+            move |hit| callback_instance.xt_process_search_hit(hit), T::panic_returns, search_hit_calls.clone())));
+
+        MtDriver {
+            host,
+            instance,
+            search_hit_calls,
+            version: XtVersion { major: 21, minor: 3, service_release: 0, language: 0 },
+            item_callback: ItemCallback::ProcessItem,
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(4),
+            _guard: guard,
+        }
+    }
+
+    pub fn with_version(mut self, major: u16, minor: u16) -> MtDriver<T> {
+        self.version.major = major;
+        self.version.minor = minor;
+        self
+    }
+
+    pub fn with_item_callback(mut self, item_callback: ItemCallback) -> MtDriver<T> {
+        self.item_callback = item_callback;
+        self
+    }
+
+    /// number of worker threads calling the item callbacks
+    pub fn with_threads(mut self, threads: usize) -> MtDriver<T> {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// installs `backend` instead of the host, e.g. a wrapper around a clone of the host
+    pub fn with_backend<B: XwfBackend + 'static>(self, backend: B) -> MtDriver<T> {
+        set_backend(backend);
+        self
+    }
+
+    pub fn host(&self) -> &SimulatedHost {
+        &self.host
+    }
+
+    /// returns the X-Tension, if `XT_Init` was called
+    pub fn extension(&self) -> Option<Arc<T>> {
+        self.instance.extension()
+    }
+
+    /// calls `XT_Init` and returns its return value
+    pub fn init(&mut self, flags: XtInitFlags) -> i32 {
+        notify_callback("XT_Init", &[u32::from(self.version) as i64, flags.bits() as i64]);
+        guarded("XT_Init", T::panic_returns, || self.instance.xt_init(self.version, flags, None))
+    }
+
+    /// calls `XT_Done`
+    pub fn done(&mut self) -> i32 {
+        notify_callback("XT_Done", &[]);
+        guarded("XT_Done", T::panic_returns, || self.instance.xt_done())
+    }
+
+    /// calls `XT_About`
+    pub fn about(&mut self) -> i32 {
+        notify_callback("XT_About", &[0]);
+        guarded("XT_About", T::panic_returns, || self.instance.xt_about(None))
+    }
+
+    /// calls `XT_View` as if the user viewed the given item with the X-Tension as viewer,
+    /// returns the data to display
+    pub fn view(&mut self, evidence_id: u32, item_id: i32) -> Option<Vec<u8>> {
+        let volume = self.host.volume_handle(evidence_id);
+        let evidence = self.host.evidence_handle(evidence_id);
+        let item_handle = self.host.open_item(volume, item_id, 0);
+        notify_callback("XT_View", &[item_id as i64, item_handle.as_raw() as i64, volume.as_raw() as i64, evidence.as_raw() as i64]);
+        let ret = catch_panic("XT_View", || None, || self.instance.xt_view(item_id, item_handle, volume, evidence));
+        self.host.close(item_handle);
+        ret
+    }
+
+    /// simulates an operation of the given type, see [`Driver::run`]
+    pub fn run(&mut self, op_type: XtPrepareOpType, evidence_ids: &[u32]) -> RunReport {
+        let mut callbacks = MtCallbacks { instance: &self.instance, item_callback: self.item_callback, threads: self.threads, search_hit_calls: &self.search_hit_calls };
+        run_operation(&mut callbacks, &self.host, op_type, evidence_ids)
+    }
+
+    /// simulates a simultaneous search, see [`Driver::search`]
+    pub fn search(&mut self, search: &Search, evidence_ids: &[u32]) -> RunReport {
+        let mut callbacks = MtCallbacks { instance: &self.instance, item_callback: self.item_callback, threads: self.threads, search_hit_calls: &self.search_hit_calls };
+        run_search(&mut callbacks, &self.host, search, evidence_ids)
+    }
+}
+
+impl<T: XTensionMt> Drop for MtDriver<T> {
+    fn drop(&mut self) {
+        self.host.set_search_hit_callback(None);
+        let _ = reset_backend();
+    }
+}
+
+// the drivers take the place of the exported XT_* functions, so they catch panics like those do
+fn guarded(callback: &str, returns: fn() -> PanicReturns, f: impl FnOnce() -> i32) -> i32 {
+    catch_panic(callback, || returns().for_callback(callback), f)
+}
+
+// XT_ProcessSearchHit as called by the simulated XWF_Search, recording the calls in `calls`
+fn search_hit_callback<F>(process_search_hit: F, returns: fn() -> PanicReturns, calls: SearchHitCalls) -> SearchHitCallback
+where F: Fn(SearchHit) -> i32 + Send + Sync + 'static {
+    Arc::new(move |hit: SearchHit| {
+        let item_id = hit.item_id;
+        notify_callback("XT_ProcessSearchHit", &hit.callback_args());
+        let ret = guarded("XT_ProcessSearchHit", returns, || process_search_hit(hit));
+        calls.lock().unwrap().push((item_id, ret));
+        ret
+    })
+}
+
+// the X-Tension callbacks invoked by run_operation
+trait OperationCallbacks {
+    fn prepare(&mut self, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32;
+
+    fn finalize(&mut self, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32;
+
+    // returns (item id, return value) of all processed items and whether the operation was stopped
+    fn process_items(&mut self, host: &SimulatedHost, volume_handle: XwfHandle, item_ids: Vec<i32>) -> (Vec<(i32, i32)>, bool);
+
+    fn prepare_search(&mut self, search: &mut PrepareSearch) -> i32;
+
+    fn process_search_hit(&mut self, hit: SearchHit) -> i32;
+
+    // removes and returns (item id, return value) of the XT_ProcessSearchHit calls made by XWF_Search
+    fn take_search_hit_calls(&mut self) -> Vec<(i32, i32)>;
+}
+
+struct StCallbacks<'a, T: XTension> {
+    instance: &'a StInstance<T>,
+    item_callback: ItemCallback,
+    search_hit_calls: &'a SearchHitCalls,
+}
+
+impl<T: XTension> OperationCallbacks for StCallbacks<'_, T> where T::XTensionError: Display {
+    fn prepare(&mut self, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32 {
+        guarded("XT_Prepare", T::panic_returns, || self.instance.xt_prepare(volume, evidence, op_type))
+    }
+
+    fn finalize(&mut self, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32 {
+        guarded("XT_Finalize", T::panic_returns, || self.instance.xt_finalize(volume, evidence, op_type))
+    }
+
+    fn prepare_search(&mut self, search: &mut PrepareSearch) -> i32 {
+        guarded("XT_PrepareSearch", T::panic_returns, || self.instance.xt_prepare_search(search))
+    }
+
+    fn process_search_hit(&mut self, hit: SearchHit) -> i32 {
+        guarded("XT_ProcessSearchHit", T::panic_returns, || self.instance.xt_process_search_hit(hit))
+    }
+
+    fn take_search_hit_calls(&mut self) -> Vec<(i32, i32)> {
+        mem::take(&mut *self.search_hit_calls.lock().unwrap())
+    }
+
+    fn process_items(&mut self, host: &SimulatedHost, volume_handle: XwfHandle, item_ids: Vec<i32>) -> (Vec<(i32, i32)>, bool) {
+        let mut processed = Vec::new();
+        for item_id in item_ids {
+            if host.case().stop_requested {
+                return (processed, true);
+            }
+            let ret = match self.item_callback {
+                ItemCallback::ProcessItem => {
+                    notify_callback("XT_ProcessItem", &[item_id as i64]);
+                    guarded("XT_ProcessItem", T::panic_returns, || self.instance.xt_process_item(item_id))
+                },
+                ItemCallback::ProcessItemEx => {
+                    let item_handle = host.open_item(volume_handle, item_id, 0);
+                    notify_callback("XT_ProcessItemEx", &[item_id as i64, item_handle.as_raw() as i64]);
+                    let ret = guarded("XT_ProcessItemEx", T::panic_returns, || self.instance.xt_process_item_ex(item_id, item_handle));
+                    host.close(item_handle);
+                    ret
+                }
+            };
+            processed.push((item_id, ret));
+            if ret == XtProcessItemReturn::StopCurrentOperation as i32 {
+                return (processed, true);
+            }
+        }
+        (processed, false)
+    }
+}
+
+struct MtCallbacks<'a, T: XTensionMt> {
+    instance: &'a MtInstance<T>,
+    item_callback: ItemCallback,
+    threads: usize,
+    search_hit_calls: &'a SearchHitCalls,
+}
+
+impl<T: XTensionMt> OperationCallbacks for MtCallbacks<'_, T> where T::XTensionError: Display {
+    fn prepare(&mut self, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32 {
+        guarded("XT_Prepare", T::panic_returns, || self.instance.xt_prepare(volume, evidence, op_type))
+    }
+
+    fn finalize(&mut self, volume: XwfHandle, evidence: XwfHandle, op_type: XtPrepareOpType) -> i32 {
+        guarded("XT_Finalize", T::panic_returns, || self.instance.xt_finalize(volume, evidence, op_type))
+    }
+
+    fn prepare_search(&mut self, search: &mut PrepareSearch) -> i32 {
+        guarded("XT_PrepareSearch", T::panic_returns, || self.instance.xt_prepare_search(search))
+    }
+
+    fn process_search_hit(&mut self, hit: SearchHit) -> i32 {
+        guarded("XT_ProcessSearchHit", T::panic_returns, || self.instance.xt_process_search_hit(hit))
+    }
+
+    fn take_search_hit_calls(&mut self) -> Vec<(i32, i32)> {
+        mem::take(&mut *self.search_hit_calls.lock().unwrap())
+    }
+
+    fn process_items(&mut self, host: &SimulatedHost, volume_handle: XwfHandle, item_ids: Vec<i32>) -> (Vec<(i32, i32)>, bool) {
+        let next = AtomicUsize::new(0);
+        let stopped = AtomicBool::new(false);
+
+        let mut processed: Vec<(usize, i32, i32)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads).map(|_| scope.spawn(|| {
+                let mut processed = Vec::new();
+                loop {
+                    let idx = next.fetch_add(1, Ordering::SeqCst);
+                    if idx >= item_ids.len() || stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if host.case().stop_requested {
+                        stopped.store(true, Ordering::SeqCst);
+                        break;
+                    }
+
+                    let item_id = item_ids[idx];
+                    let ret = match self.item_callback {
+                        ItemCallback::ProcessItem => {
+                            notify_callback("XT_ProcessItem", &[item_id as i64]);
+                            guarded("XT_ProcessItem", T::panic_returns, || self.instance.xt_process_item(item_id))
+                        },
+                        ItemCallback::ProcessItemEx => {
+                            let item_handle = host.open_item(volume_handle, item_id, 0);
+                            notify_callback("XT_ProcessItemEx", &[item_id as i64, item_handle.as_raw() as i64]);
+                            let ret = guarded("XT_ProcessItemEx", T::panic_returns, || self.instance.xt_process_item_ex(item_id, item_handle));
+                            host.close(item_handle);
+                            ret
+                        }
+                    };
+                    processed.push((idx, item_id, ret));
+                    if ret == XtProcessItemReturn::StopCurrentOperation as i32 {
+                        stopped.store(true, Ordering::SeqCst);
+                    }
+                }
+                processed
+            })).collect();
+
+            workers.into_iter().flat_map(|w| w.join().unwrap()).collect()
+        });
+
+        // report the items in the order they were handed out
+        processed.sort_by_key(|(idx, _, _)| *idx);
+        (processed.into_iter().map(|(_, item_id, ret)| (item_id, ret)).collect(), stopped.into_inner())
+    }
+}
+
+fn run_operation<C: OperationCallbacks>(callbacks: &mut C, host: &SimulatedHost, op_type: XtPrepareOpType, evidence_ids: &[u32]) -> RunReport {
+    let mut report = RunReport::default();
+
+    let evidence_ids: Vec<u32> = if evidence_ids.is_empty() {
+        host.case().evidences.iter().map(|e| e.id).collect()
+    } else {
+        evidence_ids.to_vec()
+    };
+
+    match op_type {
+        XtPrepareOpType::ActionVolumeSnapshotRefinement | XtPrepareOpType::DirectoryBrowserContextMenu
+        | XtPrepareOpType::SearchHitListContextMenu => {
+            for ev_id in evidence_ids {
+                if !run_volume(callbacks, host, op_type, ev_id, &mut report) {
+                    break;
+                }
+            }
+        },
+        _ => {
+            let ev_id = evidence_ids.first().copied();
+            let ret = prepare(callbacks, host, op_type, ev_id);
+            report.prepare.push((ev_id, ret));
+            if ret > XtPrepareNegativeReturn::DontCallOtherFunctions as i32 {
+                let ret = finalize(callbacks, host, op_type, ev_id);
+                report.finalize.push((ev_id, ret));
+            }
+            // the report has no room for search hits without evidence object
+            match ev_id {
+                Some(ev_id) => { record_search_hit_calls(callbacks, ev_id, &mut report); },
+                None => { callbacks.take_search_hit_calls(); },
+            }
+        }
+    }
+
+    report
+}
+
+// returns false if the whole operation has to be stopped
+fn run_volume<C: OperationCallbacks>(callbacks: &mut C, host: &SimulatedHost, op_type: XtPrepareOpType, ev_id: u32, report: &mut RunReport) -> bool {
+    let volume_handle = host.volume_handle(ev_id);
+    host.select_evidence(ev_id);
+
+    let ret = prepare(callbacks, host, op_type, Some(ev_id));
+    report.prepare.push((Some(ev_id), ret));
+    // stopping a search started by the X-Tension does not stop the operation
+    record_search_hit_calls(callbacks, ev_id, report);
+
+    if ret == XtPrepareNegativeReturn::StopWholeOperation as i32 || ret == XtPrepareNegativeReturn::PreventFurtherUse as i32 {
+        report.stopped = true;
+        return false;
+    }
+    if ret == XtPrepareNegativeReturn::ExcludeVolume as i32 || ret == XtPrepareNegativeReturn::DontCallOtherFunctions as i32 {
+        return true;
+    }
+
+    if op_type == XtPrepareOpType::SearchHitListContextMenu {
+        let hits = selected_search_hits(host, ev_id);
+        report.stopped = process_search_hits(callbacks, ev_id, hits, report);
+
+        let ret = finalize(callbacks, host, op_type, Some(ev_id));
+        report.finalize.push((Some(ev_id), ret));
+        return !report.stopped;
+    }
+
+    let flags = XtPreparePositiveReturnFlags::from_bits_truncate(ret);
+    let call_process_item = flags.intersects(
+        XtPreparePositiveReturnFlags::CallProcessItem | XtPreparePositiveReturnFlags::CallProcessItemLate);
+
+    if ret > 0 && call_process_item {
+        let item_ids = items_to_process(host, op_type, ev_id, flags);
+        let (processed, stopped) = callbacks.process_items(host, volume_handle, item_ids);
+        report.process_item.extend(processed.into_iter().map(|(item_id, ret)| (ev_id, item_id, ret)));
+        report.stopped = stopped;
+    }
+
+    let ret = finalize(callbacks, host, op_type, Some(ev_id));
+    report.finalize.push((Some(ev_id), ret));
+    record_search_hit_calls(callbacks, ev_id, report);
+    !report.stopped
+}
+
+fn run_search<C: OperationCallbacks>(callbacks: &mut C, host: &SimulatedHost, search: &Search, evidence_ids: &[u32]) -> RunReport {
+    let mut report = RunReport::default();
+
+    let mut prepared = PrepareSearch::new(search);
+    notify_callback("XT_PrepareSearch", &[prepared.flags.bits() as i64]);
+    let ret = callbacks.prepare_search(&mut prepared);
+    report.prepare_search = Some(ret);
+
+    // the search window is not part of the settings passed to XT_PrepareSearch
+    let search = if ret == 1 {
+        prepared.to_search().with_search_window(search.search_window)
+    } else {
+        search.clone()
+    };
+
+    let op_type = if search.flags.contains(SearchFlags::Logical) {
+        XtPrepareOpType::ActionLogicalSearch
+    } else {
+        XtPrepareOpType::ActionPhysicalSearch
+    };
+
+    let evidence_ids: Vec<u32> = if evidence_ids.is_empty() {
+        host.case().evidences.iter().map(|e| e.id).collect()
+    } else {
+        evidence_ids.to_vec()
+    };
+
+    for ev_id in evidence_ids {
+        let volume_handle = host.volume_handle(ev_id);
+        host.select_evidence(ev_id);
+
+        let ret = prepare(callbacks, host, op_type, Some(ev_id));
+        report.prepare.push((Some(ev_id), ret));
+        if ret == XtPrepareNegativeReturn::StopWholeOperation as i32 || ret == XtPrepareNegativeReturn::PreventFurtherUse as i32 {
+            report.stopped = true;
+            break;
+        }
+        if ret == XtPrepareNegativeReturn::ExcludeVolume as i32 || ret == XtPrepareNegativeReturn::DontCallOtherFunctions as i32 {
+            continue;
+        }
+
+        // XWF_Search calls XT_ProcessSearchHit for each new hit
+        host.search(volume_handle, &search.search_terms.join("\r\n"), search.flags.bits(), search.search_window, search.code_page_array());
+        report.stopped = record_search_hit_calls(callbacks, ev_id, &mut report);
+
+        let ret = finalize(callbacks, host, op_type, Some(ev_id));
+        report.finalize.push((Some(ev_id), ret));
+        if report.stopped {
+            break;
+        }
+    }
+
+    report
+}
+
+// returns true if the X-Tension stopped the search
+fn process_search_hits<C: OperationCallbacks>(callbacks: &mut C, ev_id: u32, hits: Vec<SearchHit>, report: &mut RunReport) -> bool {
+    for hit in hits {
+        let item_id = hit.item_id;
+        notify_callback("XT_ProcessSearchHit", &hit.callback_args());
+        let ret = callbacks.process_search_hit(hit);
+        report.process_search_hit.push((ev_id, item_id, ret));
+        if ret == XtProcessSearchHitReturn::StopSearch as i32 {
+            return true;
+        }
+    }
+    false
+}
+
+// records the XT_ProcessSearchHit calls made by XWF_Search, returns true if the X-Tension stopped the search
+fn record_search_hit_calls<C: OperationCallbacks>(callbacks: &mut C, ev_id: u32, report: &mut RunReport) -> bool {
+    let calls = callbacks.take_search_hit_calls();
+    report.process_search_hit.extend(calls.iter().map(|(item_id, ret)| (ev_id, *item_id, *ret)));
+    calls.iter().any(|(_, ret)| *ret == XtProcessSearchHitReturn::StopSearch as i32)
+}
+
+fn selected_search_hits(host: &SimulatedHost, ev_id: u32) -> Vec<SearchHit> {
+    let case = host.case();
+    match case.evidence_by_id(ev_id) {
+        Some(ev) => ev.search_hit_selection.iter()
+            .filter_map(|hit_no| ev.search_hits.get(*hit_no as usize).cloned())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn items_to_process(host: &SimulatedHost, op_type: XtPrepareOpType, ev_id: u32, flags: XtPreparePositiveReturnFlags) -> Vec<i32> {
+    let case = host.case();
+    let ev = match case.evidence_by_id(ev_id) {
+        Some(e) => e,
+        None => return Vec::new(),
+    };
+
+    if op_type == XtPrepareOpType::DirectoryBrowserContextMenu {
+        return ev.dbc_selection.clone();
+    }
+
+    let is_target = |item: &SimItem| {
+        if item.flags.contains(ItemInfoFlags::IsDirectory) {
+            flags.contains(XtPreparePositiveReturnFlags::TargetDirs)
+        } else if item.size == 0 {
+            flags.contains(XtPreparePositiveReturnFlags::TargetZerorBytesFiles)
+        } else {
+            true
+        }
+    };
+
+    // items created during the refinement are not processed again
+    ev.items.iter().enumerate()
+        .filter(|(_, i)| !i.created && is_target(i))
+        .map(|(idx, _)| idx as i32)
+        .collect()
+}
+
+fn prepare<C: OperationCallbacks>(callbacks: &mut C, host: &SimulatedHost, op_type: XtPrepareOpType, ev_id: Option<u32>) -> i32 {
+    let (volume, evidence) = handles(host, ev_id);
+    notify_callback("XT_Prepare", &[volume.as_raw() as i64, evidence.as_raw() as i64, op_type as i64]);
+    callbacks.prepare(volume, evidence, op_type)
+}
+
+fn finalize<C: OperationCallbacks>(callbacks: &mut C, host: &SimulatedHost, op_type: XtPrepareOpType, ev_id: Option<u32>) -> i32 {
+    let (volume, evidence) = handles(host, ev_id);
+    notify_callback("XT_Finalize", &[volume.as_raw() as i64, evidence.as_raw() as i64, op_type as i64]);
+    callbacks.finalize(volume, evidence, op_type)
+}
+
+fn handles(host: &SimulatedHost, ev_id: Option<u32>) -> (XwfHandle, XwfHandle) {
+    match ev_id {
+        Some(id) => (host.volume_handle(id), host.evidence_handle(id)),
+        None => (XwfHandle::NULL, XwfHandle::NULL),
+    }
+}
@@ -0,0 +1,518 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::error::XwfError;
+use crate::volume::HashType;
+use crate::xwf_types::*;
+use super::host::SimulatedHost;
+use super::model::*;
+
+/// Declarative description of a [`SimCase`], which can be loaded from and saved to JSON or TOML
+/// files. Item data can be given inline as hex string (`data`) or as path to a file (`data_file`),
+/// relative paths are resolved against the directory of the fixture file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaseFixture {
+    pub id: i64,
+    pub title: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub examiner: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub file: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub dir: String,
+    /// FILETIME
+    pub creation_time: i64,
+    /// answers to `XWF_GetUserInput`, in order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub user_inputs: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub report_tables: Vec<ReportTableFixture>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub evidences: Vec<EvidenceFixture>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReportTableFixture {
+    pub name: String,
+    #[serde(default = "ReportTableFlags::empty")]
+    pub flags: ReportTableFlags,
+}
+
+/// evidence object, the fields correspond to the [`EvObjPropType`] properties
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvidenceFixture {
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short_id: Option<u16>,
+    /// id of the parent evidence object (for partitions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<u32>,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extended_title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abbreviated_title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub internal_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub examiner_comments: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub output_dir: String,
+    #[serde(default = "EvObjPropFlags::empty")]
+    pub flags: EvObjPropFlags,
+    /// FILETIME
+    #[serde(default)]
+    pub creation_time: i64,
+    /// FILETIME
+    #[serde(default)]
+    pub modification_time: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<VolumeFixture>,
+    /// item ids that are selected in the directory browser
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dbc_selection: Vec<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<ItemFixture>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VolumeFixture {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// file system identifier as returned by `XWF_GetVolumeInformation`
+    #[serde(default)]
+    pub file_system: i32,
+    #[serde(default = "default_bytes_per_sector")]
+    pub bytes_per_sector: u32,
+    #[serde(default = "default_sectors_per_cluster")]
+    pub sectors_per_cluster: u32,
+    #[serde(default)]
+    pub first_cluster_sector_no: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash_type1: Option<HashType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash_type2: Option<HashType>,
+    /// hex encoded volume data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_file: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ItemFixture {
+    /// item id, must match the position of the item in the evidence if given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    pub name: String,
+    #[serde(default = "default_parent")]
+    pub parent: i32,
+    /// logical size, defaults to the length of the data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<i64>,
+    /// hex encoded item data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_file: Option<PathBuf>,
+
+    #[serde(default = "ItemInfoFlags::empty")]
+    pub flags: ItemInfoFlags,
+    #[serde(default = "ItemInfoAttributes::empty")]
+    pub attributes: ItemInfoAttributes,
+    #[serde(default = "default_classification")]
+    pub classification: ItemInfoClassification,
+    #[serde(default = "default_deletion")]
+    pub deletion: ItemInfoDeletion,
+    #[serde(default)]
+    pub orig_id: i64,
+    #[serde(default = "default_link_count")]
+    pub link_count: i64,
+    #[serde(default)]
+    pub file_count: i64,
+    #[serde(default = "default_color_analysis")]
+    pub color_analysis: i64,
+    #[serde(default)]
+    pub pixel_index: i64,
+    #[serde(default = "default_embedded_offset")]
+    pub embedded_offset: i64,
+    #[serde(default)]
+    pub def_ofs: i64,
+    #[serde(default = "default_start_sector")]
+    pub start_sector: i64,
+
+    /// FILETIME values, 0 if not available
+    #[serde(default)]
+    pub creation_time: i64,
+    #[serde(default)]
+    pub modification_time: i64,
+    #[serde(default)]
+    pub last_access_time: i64,
+    #[serde(default)]
+    pub entry_modification_time: i64,
+    #[serde(default)]
+    pub deletion_time: i64,
+    #[serde(default)]
+    pub internal_creation_time: i64,
+    /// time zone offsets in minutes for displaying the timestamps above
+    #[serde(default)]
+    pub display_offsets: [i64; 6],
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub type_short: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub type_long: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub category: String,
+    #[serde(default = "default_type_status")]
+    pub type_status: FileTypeStatus,
+    #[serde(default = "default_consistency")]
+    pub consistency: FileFormatConsistency,
+
+    /// names of the associated report tables
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub report_tables: Vec<String>,
+    /// names of the hash sets the item is associated with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hash_sets: Vec<String>,
+    /// hex encoded hash values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash2: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extracted_metadata: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<String>,
+    /// true for items created by the X-Tension
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub created: bool,
+}
+
+impl Default for CaseFixture {
+    fn default() -> Self {
+        CaseFixture::from_case(&SimCase::new(""))
+    }
+}
+
+fn default_bytes_per_sector() -> u32 { 512 }
+fn default_sectors_per_cluster() -> u32 { 8 }
+fn default_parent() -> i32 { -1 }
+fn default_link_count() -> i64 { 1 }
+fn default_color_analysis() -> i64 { -1 }
+fn default_start_sector() -> i64 { -1 }
+fn default_embedded_offset() -> i64 { -1 }
+fn default_classification() -> ItemInfoClassification { ItemInfoClassification::NormalFile }
+fn default_deletion() -> ItemInfoDeletion { ItemInfoDeletion::Existing }
+fn default_type_status() -> FileTypeStatus { FileTypeStatus::NotVerified }
+fn default_consistency() -> FileFormatConsistency { FileFormatConsistency::Unknown }
+fn is_false(b: &bool) -> bool { !*b }
+
+fn invalid<E: std::fmt::Display>(e: E) -> XwfError {
+    XwfError::InvalidFixture(e.to_string())
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().map(|e| e.eq_ignore_ascii_case("toml")).unwrap_or(false)
+}
+
+fn load_data(data: &Option<String>, data_file: &Option<PathBuf>, base_dir: &Path) -> Result<Option<Vec<u8>>, XwfError> {
+    match (data, data_file) {
+        (Some(_), Some(_)) => Err(invalid("either data or data_file can be given, not both")),
+        (Some(hex_data), None) => hex::decode(hex_data.replace(char::is_whitespace, "")).map(Some).map_err(invalid),
+        (None, Some(file)) => fs::read(base_dir.join(file)).map(Some).map_err(XwfError::IoError),
+        (None, None) => Ok(None),
+    }
+}
+
+fn decode_hash(hash: &Option<String>) -> Result<Option<Vec<u8>>, XwfError> {
+    hash.as_ref().map(|h| hex::decode(h).map_err(invalid)).transpose()
+}
+
+impl CaseFixture {
+    pub fn from_json(json: &str) -> Result<CaseFixture, XwfError> {
+        serde_json::from_str(json).map_err(invalid)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<CaseFixture, XwfError> {
+        toml::from_str(toml).map_err(invalid)
+    }
+
+    pub fn to_json(&self) -> Result<String, XwfError> {
+        serde_json::to_string_pretty(self).map_err(invalid)
+    }
+
+    pub fn to_toml(&self) -> Result<String, XwfError> {
+        toml::to_string_pretty(self).map_err(invalid)
+    }
+
+    /// reads a fixture file, the format is TOML for files with extension `.toml`, otherwise JSON
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<CaseFixture, XwfError> {
+        let content = fs::read_to_string(path.as_ref()).map_err(XwfError::IoError)?;
+        if is_toml(path.as_ref()) {
+            CaseFixture::from_toml(&content)
+        } else {
+            CaseFixture::from_json(&content)
+        }
+    }
+
+    /// writes the fixture, the format is TOML for files with extension `.toml`, otherwise JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), XwfError> {
+        let content = if is_toml(path.as_ref()) { self.to_toml()? } else { self.to_json()? };
+        fs::write(path, content).map_err(XwfError::IoError)
+    }
+
+    /// builds the simulated case, `data_file` paths are resolved relative to `base_dir`
+    pub fn to_case(&self, base_dir: &Path) -> Result<SimCase, XwfError> {
+        let mut case = SimCase::new(self.title.clone());
+        case.id = self.id;
+        case.examiner = self.examiner.clone();
+        case.file = self.file.clone();
+        case.dir = self.dir.clone();
+        case.creation_time = self.creation_time;
+        case.user_inputs = self.user_inputs.iter().cloned().collect();
+        case.report_tables = self.report_tables.iter()
+            .map(|t| SimReportTable { name: t.name.clone(), flags: t.flags.clone() })
+            .collect();
+
+        for ev in &self.evidences {
+            if case.evidence_by_id(ev.id).is_some() {
+                return Err(invalid(format!("duplicate evidence id {}", ev.id)));
+            }
+            let evidence = ev.to_evidence(&mut case, base_dir)?;
+            case.add_evidence(evidence);
+        }
+
+        for ev in &case.evidences {
+            if let Some(parent_id) = ev.parent_id {
+                if case.evidence_by_id(parent_id).is_none() {
+                    return Err(invalid(format!("parent evidence {} of evidence {} does not exist", parent_id, ev.id)));
+                }
+            }
+        }
+        Ok(case)
+    }
+
+    /// describes the current state of a simulated case, item data is always stored inline
+    pub fn from_case(case: &SimCase) -> CaseFixture {
+        CaseFixture {
+            id: case.id,
+            title: case.title.clone(),
+            examiner: case.examiner.clone(),
+            file: case.file.clone(),
+            dir: case.dir.clone(),
+            creation_time: case.creation_time,
+            user_inputs: case.user_inputs.iter().cloned().collect(),
+            report_tables: case.report_tables.iter()
+                .map(|t| ReportTableFixture { name: t.name.clone(), flags: t.flags.clone() })
+                .collect(),
+            evidences: case.evidences.iter().map(|e| EvidenceFixture::from_evidence(case, e)).collect(),
+        }
+    }
+}
+
+impl EvidenceFixture {
+    fn to_evidence(&self, case: &mut SimCase, base_dir: &Path) -> Result<SimEvidence, XwfError> {
+        let mut ev = SimEvidence::new(self.id, self.title.clone());
+        if let Some(short_id) = self.short_id { ev.short_id = short_id; }
+        if let Some(title) = &self.extended_title { ev.extended_title = title.clone(); }
+        if let Some(title) = &self.abbreviated_title { ev.abbreviated_title = title.clone(); }
+        if let Some(name) = &self.internal_name { ev.internal_name = name.clone(); }
+        ev.parent_id = self.parent_id;
+        ev.description = self.description.clone();
+        ev.examiner_comments = self.examiner_comments.clone();
+        ev.output_dir = self.output_dir.clone();
+        ev.flags = self.flags;
+        ev.creation_time = self.creation_time;
+        ev.modification_time = self.modification_time;
+        ev.dbc_selection = self.dbc_selection.clone();
+
+        if let Some(vol) = &self.volume {
+            if let Some(name) = &vol.name { ev.volume.name = name.clone(); }
+            ev.volume.file_system = vol.file_system;
+            ev.volume.bytes_per_sector = vol.bytes_per_sector;
+            ev.volume.sectors_per_cluster = vol.sectors_per_cluster;
+            ev.volume.first_cluster_sector_no = vol.first_cluster_sector_no;
+            ev.volume.hash_type1 = vol.hash_type1;
+            ev.volume.hash_type2 = vol.hash_type2;
+            ev.volume.data = load_data(&vol.data, &vol.data_file, base_dir)?.unwrap_or_default();
+        }
+
+        for (idx, item) in self.items.iter().enumerate() {
+            if item.id.is_some_and(|id| id != idx as i32) {
+                return Err(invalid(format!("item '{}' of evidence {}: item ids must be consecutive, starting at 0",
+                                           item.name, self.id)));
+            }
+            if item.parent >= self.items.len() as i32 || item.parent == idx as i32 {
+                return Err(invalid(format!("item '{}' of evidence {}: invalid parent id {}", item.name, self.id, item.parent)));
+            }
+            ev.add_item(item.to_item(case, base_dir)?);
+        }
+
+        if let Some(id) = self.dbc_selection.iter().find(|id| ev.item(**id).is_none()) {
+            return Err(invalid(format!("selected item {} of evidence {} does not exist", id, self.id)));
+        }
+        Ok(ev)
+    }
+
+    fn from_evidence(case: &SimCase, ev: &SimEvidence) -> EvidenceFixture {
+        let differs = |s: &String| if *s != ev.title { Some(s.clone()) } else { None };
+
+        EvidenceFixture {
+            id: ev.id,
+            short_id: if ev.short_id != ev.id as u16 { Some(ev.short_id) } else { None },
+            parent_id: ev.parent_id,
+            title: ev.title.clone(),
+            extended_title: differs(&ev.extended_title),
+            abbreviated_title: differs(&ev.abbreviated_title),
+            internal_name: differs(&ev.internal_name),
+            description: ev.description.clone(),
+            examiner_comments: ev.examiner_comments.clone(),
+            output_dir: ev.output_dir.clone(),
+            flags: ev.flags,
+            creation_time: ev.creation_time,
+            modification_time: ev.modification_time,
+            volume: Some(VolumeFixture {
+                name: differs(&ev.volume.name),
+                file_system: ev.volume.file_system,
+                bytes_per_sector: ev.volume.bytes_per_sector,
+                sectors_per_cluster: ev.volume.sectors_per_cluster,
+                first_cluster_sector_no: ev.volume.first_cluster_sector_no,
+                hash_type1: ev.volume.hash_type1,
+                hash_type2: ev.volume.hash_type2,
+                data: if ev.volume.data.is_empty() { None } else { Some(hex::encode(&ev.volume.data)) },
+                data_file: None,
+            }),
+            dbc_selection: ev.dbc_selection.clone(),
+            items: ev.items.iter().enumerate()
+                .map(|(idx, item)| ItemFixture::from_item(case, idx as i32, item))
+                .collect(),
+        }
+    }
+}
+
+impl ItemFixture {
+    fn to_item(&self, case: &mut SimCase, base_dir: &Path) -> Result<SimItem, XwfError> {
+        let mut item = SimItem::new(self.name.clone(), self.parent);
+        item.data = load_data(&self.data, &self.data_file, base_dir)?.unwrap_or_default();
+        item.size = self.size.unwrap_or(item.data.len() as i64);
+        item.flags = self.flags;
+        item.attributes = self.attributes;
+        item.classification = self.classification;
+        item.deletion = self.deletion;
+        item.orig_id = self.orig_id;
+        item.link_count = self.link_count;
+        item.file_count = self.file_count;
+        item.color_analysis = self.color_analysis;
+        item.pixel_index = self.pixel_index;
+        item.embedded_offset = self.embedded_offset;
+        item.def_ofs = self.def_ofs;
+        item.start_sector = self.start_sector;
+        item.creation_time = self.creation_time;
+        item.modification_time = self.modification_time;
+        item.last_access_time = self.last_access_time;
+        item.entry_modification_time = self.entry_modification_time;
+        item.deletion_time = self.deletion_time;
+        item.internal_creation_time = self.internal_creation_time;
+        item.display_offsets = self.display_offsets;
+        item.type_short = self.type_short.clone();
+        item.type_long = self.type_long.clone();
+        item.category = self.category.clone();
+        item.type_status = self.type_status;
+        item.consistency = self.consistency;
+        item.hash_sets = self.hash_sets.clone();
+        item.hash1 = decode_hash(&self.hash1)?;
+        item.hash2 = decode_hash(&self.hash2)?;
+        item.comment = self.comment.clone();
+        item.extracted_metadata = self.extracted_metadata.clone();
+        item.metadata = self.metadata.clone();
+        item.created = self.created;
+
+        // report tables which are not declared in the case are created on demand
+        for name in &self.report_tables {
+            let id = match case.report_table_id(name) {
+                Some(id) => id,
+                None => {
+                    case.report_tables.push(SimReportTable { name: name.clone(), flags: ReportTableFlags::empty() });
+                    (case.report_tables.len() - 1) as u16
+                }
+            };
+            item.report_tables.insert(id);
+        }
+        Ok(item)
+    }
+
+    fn from_item(case: &SimCase, id: i32, item: &SimItem) -> ItemFixture {
+        ItemFixture {
+            id: Some(id),
+            name: item.name.clone(),
+            parent: item.parent_id,
+            size: if item.size != item.data.len() as i64 { Some(item.size) } else { None },
+            data: if item.data.is_empty() { None } else { Some(hex::encode(&item.data)) },
+            data_file: None,
+            flags: item.flags,
+            attributes: item.attributes,
+            classification: item.classification,
+            deletion: item.deletion,
+            orig_id: item.orig_id,
+            link_count: item.link_count,
+            file_count: item.file_count,
+            color_analysis: item.color_analysis,
+            pixel_index: item.pixel_index,
+            embedded_offset: item.embedded_offset,
+            def_ofs: item.def_ofs,
+            start_sector: item.start_sector,
+            creation_time: item.creation_time,
+            modification_time: item.modification_time,
+            last_access_time: item.last_access_time,
+            entry_modification_time: item.entry_modification_time,
+            deletion_time: item.deletion_time,
+            internal_creation_time: item.internal_creation_time,
+            display_offsets: item.display_offsets,
+            type_short: item.type_short.clone(),
+            type_long: item.type_long.clone(),
+            category: item.category.clone(),
+            type_status: item.type_status,
+            consistency: item.consistency,
+            report_tables: item.report_tables.iter()
+                .filter_map(|t| case.report_tables.get(*t as usize))
+                .map(|t| t.name.clone())
+                .collect(),
+            hash_sets: item.hash_sets.clone(),
+            hash1: item.hash1.as_ref().map(hex::encode),
+            hash2: item.hash2.as_ref().map(hex::encode),
+            comment: item.comment.clone(),
+            extracted_metadata: item.extracted_metadata.clone(),
+            metadata: item.metadata.clone(),
+            created: item.created,
+        }
+    }
+}
+
+impl SimCase {
+    /// loads a case from a JSON or TOML fixture file, see [`CaseFixture`]
+    pub fn from_fixture_file<P: AsRef<Path>>(path: P) -> Result<SimCase, XwfError> {
+        let base_dir = path.as_ref().parent().unwrap_or(Path::new("."));
+        CaseFixture::load(path.as_ref())?.to_case(base_dir)
+    }
+
+    pub fn to_fixture(&self) -> CaseFixture {
+        CaseFixture::from_case(self)
+    }
+
+    /// writes the current state of the case to a JSON or TOML fixture file, e.g. for golden file comparisons
+    pub fn dump_fixture<P: AsRef<Path>>(&self, path: P) -> Result<(), XwfError> {
+        self.to_fixture().save(path)
+    }
+}
+
+impl SimulatedHost {
+    pub fn from_fixture_file<P: AsRef<Path>>(path: P) -> Result<SimulatedHost, XwfError> {
+        Ok(SimulatedHost::new(SimCase::from_fixture_file(path)?))
+    }
+}
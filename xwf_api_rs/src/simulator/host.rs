@@ -0,0 +1,959 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use crate::xwf_types::XwfHandle;
+use crate::backend::{VolumeInformation, XwfBackend};
+use crate::capabilities::Capabilities;
+use crate::evidence::ReportTableListItem;
+use crate::event::Event;
+use crate::search::{SearchHit, CODE_PAGE_UTF16_LE, CODE_PAGE_UTF8, CODE_PAGE_WINDOWS_1252};
+use crate::volume::HashType;
+use crate::xwf_types::*;
+use super::model::*;
+
+const INFO_TYPE_SET_FLAGS: i32 = 64;
+const INFO_TYPE_REMOVE_FLAGS: i32 = 65;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum SimHandle {
+    Evidence(u32),
+    Volume(u32),
+    Item(u32, i32),
+}
+
+struct HandleTable {
+    handles: HashMap<usize, SimHandle>,
+    next_handle: usize,
+    selected_evidence: Option<u32>,
+}
+
+impl HandleTable {
+    fn get(&self, handle: XwfHandle) -> Option<SimHandle> {
+        self.handles.get(&handle.as_raw()).copied()
+    }
+
+    // evidence and volume handles are reused, item handles are created with every call
+    fn create(&mut self, sim_handle: SimHandle) -> XwfHandle {
+        if !matches!(sim_handle, SimHandle::Item(_, _)) {
+            if let Some((h, _)) = self.handles.iter().find(|(_, v)| **v == sim_handle) {
+                return XwfHandle::from_raw(*h);
+            }
+        }
+        self.next_handle += 1;
+        self.handles.insert(self.next_handle, sim_handle);
+        XwfHandle::from_raw(self.next_handle)
+    }
+}
+
+/// called by `XWF_Search` for each new search hit, returns the return value of `XT_ProcessSearchHit`
+pub type SearchHitCallback = Arc<dyn Fn(SearchHit) -> i32 + Send + Sync>;
+
+/// [`XwfBackend`] implementation that answers all API calls from a [`SimCase`] held in memory.
+/// Clones share the same case, so a test can keep a clone for inspecting the case after a run.
+#[derive(Clone)]
+pub struct SimulatedHost {
+    case: Arc<Mutex<SimCase>>,
+    handles: Arc<Mutex<HandleTable>>,
+    search_hit_callback: Arc<Mutex<Option<SearchHitCallback>>>,
+}
+
+impl SimulatedHost {
+    pub fn new(case: SimCase) -> SimulatedHost {
+        SimulatedHost {
+            case: Arc::new(Mutex::new(case)),
+            handles: Arc::new(Mutex::new(HandleTable {
+                handles: HashMap::new(),
+                next_handle: 0x1000,
+                selected_evidence: None,
+            })),
+            search_hit_callback: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// sets the callback invoked by `XWF_Search` for searches with [`SearchFlags::CallProcessSearchHit`],
+    /// like X-Ways Forensics calls `XT_ProcessSearchHit` of the X-Tension. Set by the drivers.
+    pub fn set_search_hit_callback(&self, callback: Option<SearchHitCallback>) {
+        *self.search_hit_callback.lock().unwrap() = callback;
+    }
+
+    pub fn case(&self) -> MutexGuard<'_, SimCase> {
+        self.case.lock().unwrap()
+    }
+
+    pub fn evidence_handle(&self, evidence_id: u32) -> XwfHandle {
+        match self.case().evidence_by_id(evidence_id) {
+            Some(_) => self.handles.lock().unwrap().create(SimHandle::Evidence(evidence_id)),
+            None => XwfHandle::NULL,
+        }
+    }
+
+    pub fn volume_handle(&self, evidence_id: u32) -> XwfHandle {
+        match self.case().evidence_by_id(evidence_id) {
+            Some(_) => self.handles.lock().unwrap().create(SimHandle::Volume(evidence_id)),
+            None => XwfHandle::NULL,
+        }
+    }
+
+    pub fn select_evidence(&self, evidence_id: u32) {
+        self.handles.lock().unwrap().selected_evidence = Some(evidence_id);
+    }
+
+    fn resolve(&self, handle: XwfHandle) -> Option<SimHandle> {
+        self.handles.lock().unwrap().get(handle)
+    }
+
+    fn selected_evidence_id(&self, case: &SimCase) -> Option<u32> {
+        self.handles.lock().unwrap().selected_evidence
+            .or_else(|| case.evidences.first().map(|e| e.id))
+    }
+
+    fn with_selected<R, F: FnOnce(&SimCase, &SimEvidence) -> R>(&self, f: F) -> Option<R> {
+        let case = self.case();
+        let ev_id = self.selected_evidence_id(&case)?;
+        let ev = case.evidence_by_id(ev_id)?;
+        Some(f(&case, ev))
+    }
+
+    fn with_selected_mut<R, F: FnOnce(&mut SimEvidence) -> R>(&self, f: F) -> Option<R> {
+        let mut case = self.case();
+        let ev_id = self.selected_evidence_id(&case)?;
+        let ev = case.evidence_by_id_mut(ev_id)?;
+        Some(f(ev))
+    }
+
+    fn with_item<R, F: FnOnce(&SimCase, &SimEvidence, &SimItem) -> R>(&self, item_id: i32, f: F) -> Option<R> {
+        self.with_selected(|case, ev| ev.item(item_id).map(|i| f(case, ev, i))).flatten()
+    }
+
+    fn with_item_mut<R, F: FnOnce(&mut SimItem) -> R>(&self, item_id: i32, f: F) -> Option<R> {
+        self.with_selected_mut(|ev| ev.item_mut(item_id).map(f)).flatten()
+    }
+
+    fn computed_flags(ev: &SimEvidence, item_id: i32, item: &SimItem) -> ItemInfoFlags {
+        let mut flags = item.flags;
+        let mut children = ev.children(item_id).peekable();
+        if children.peek().is_some() {
+            flags |= ItemInfoFlags::HasChildObjects;
+        }
+        if children.any(|(_, c)| c.flags.contains(ItemInfoFlags::IsDirectory)) {
+            flags |= ItemInfoFlags::HasSubDirectories;
+        }
+        if item.comment.is_some() { flags |= ItemInfoFlags::HasExaminerComment; }
+        if item.extracted_metadata.is_some() { flags |= ItemInfoFlags::HasExtractedMetaData; }
+        if item.hash1.is_some() { flags |= ItemInfoFlags::Hash1AlreadyComputed; }
+        if item.hash2.is_some() { flags |= ItemInfoFlags::Hash2AlreadyComputed; }
+        flags
+    }
+
+    fn data_of(&self, handle: XwfHandle) -> Option<Vec<u8>> {
+        let case = self.case();
+        match self.resolve(handle)? {
+            SimHandle::Volume(ev) => case.evidence_by_id(ev).map(|e| e.volume.data.clone()),
+            SimHandle::Item(ev, item) => case.evidence_by_id(ev)?.item(item).map(|i| i.data.clone()),
+            SimHandle::Evidence(_) => None,
+        }
+    }
+}
+
+// the simulator searches these code pages if none are given
+const DEFAULT_CODE_PAGES: [u16; 2] = [CODE_PAGE_WINDOWS_1252, CODE_PAGE_UTF16_LE];
+
+fn encode(term: &str, code_page: u16) -> Option<Vec<u8>> {
+    match code_page {
+        CODE_PAGE_WINDOWS_1252 => term.chars().map(|c| u8::try_from(c as u32).ok()).collect(),
+        CODE_PAGE_UTF8 => Some(term.as_bytes().to_vec()),
+        CODE_PAGE_UTF16_LE => Some(term.encode_utf16().flat_map(|c| c.to_le_bytes()).collect()),
+        _ => None,
+    }
+}
+
+// replaces the text, or appends it as a new line. Replacing with an empty text removes it.
+fn add_text(target: &mut Option<String>, text: &str, append: bool) {
+    match target {
+        Some(existing) if append && !existing.is_empty() => {
+            existing.push('\n');
+            existing.push_str(text);
+        },
+        _ if !append && text.is_empty() => *target = None,
+        _ => *target = Some(text.to_string()),
+    }
+}
+
+// returns the offsets of all occurrences of `pattern` in `data`
+fn find_occurrences(data: &[u8], pattern: &[u8], flags: SearchFlags) -> Vec<usize> {
+    let mut ret = Vec::new();
+    if pattern.is_empty() || pattern.len() > data.len() {
+        return ret;
+    }
+
+    let matches = |ofs: usize| {
+        let candidate = &data[ofs..ofs + pattern.len()];
+        if flags.contains(SearchFlags::MatchCase) {
+            candidate == pattern
+        } else {
+            candidate.eq_ignore_ascii_case(pattern)
+        }
+    };
+    let is_word_char = |ofs: Option<usize>| ofs.and_then(|o| data.get(o)).is_some_and(|b| b.is_ascii_alphanumeric());
+
+    let mut ofs = 0;
+    while ofs + pattern.len() <= data.len() {
+        if matches(ofs) && (!flags.contains(SearchFlags::WholeWords)
+            || (!is_word_char(ofs.checked_sub(1)) && !is_word_char(Some(ofs + pattern.len())))) {
+            ret.push(ofs);
+            if flags.contains(SearchFlags::OneHitPerFile) {
+                break;
+            }
+            if !flags.contains(SearchFlags::AllowOverlapping) {
+                ofs += pattern.len();
+                continue;
+            }
+        }
+        ofs += 1;
+    }
+    ret
+}
+
+fn write_string(target: Option<&mut String>, value: &str) {
+    if let Some(s) = target {
+        *s = value.to_string();
+    }
+}
+
+impl XwfBackend for SimulatedHost {
+    fn output_message(&self, message: &str, flags: u32) {
+        self.case().messages.push(SimMessage { message: message.to_string(), flags });
+    }
+
+    fn get_volume_name(&self, volume: XwfHandle, _name_type: u32) -> String {
+        match self.resolve(volume) {
+            Some(SimHandle::Volume(ev)) => self.case().evidence_by_id(ev).map(|e| e.volume.name.clone()).unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+
+    fn get_volume_information(&self, volume: XwfHandle) -> Option<VolumeInformation> {
+        match self.resolve(volume)? {
+            SimHandle::Volume(ev) => {
+                let case = self.case();
+                let v = &case.evidence_by_id(ev)?.volume;
+                Some(VolumeInformation {
+                    file_system: v.file_system,
+                    bytes_per_sector: v.bytes_per_sector,
+                    sectors_per_cluster: v.sectors_per_cluster,
+                    cluster_count: v.cluster_count(),
+                    first_cluster_sector_no: v.first_cluster_sector_no,
+                })
+            },
+            _ => None
+        }
+    }
+
+    fn select_volume_snapshot(&self, volume: XwfHandle) -> i32 {
+        match self.resolve(volume) {
+            Some(SimHandle::Volume(ev)) => {
+                self.select_evidence(ev);
+                self.case().evidence_by_id(ev).map(|e| e.items.len() as i32).unwrap_or(-1)
+            },
+            _ => -1,
+        }
+    }
+
+    fn get_vs_prop(&self, prop_type: i32, value: Option<u32>) -> i64 {
+        let hash_type = |t: Option<HashType>| t.map(|t| t as i64).unwrap_or(0);
+        match prop_type {
+            x if x == VsPropType::HashType1 as i32 => self.with_selected(|_, e| hash_type(e.volume.hash_type1)).unwrap_or(-1),
+            x if x == VsPropType::HashType2 as i32 => self.with_selected(|_, e| hash_type(e.volume.hash_type2)).unwrap_or(-1),
+            x if x == VsPropType::SetHashType1 as i32 || x == VsPropType::SetHashType2 as i32 => {
+                let new_type = value.and_then(|v| HashType::try_from(v as i64).ok());
+                self.with_selected_mut(|e| {
+                    if x == VsPropType::SetHashType1 as i32 {
+                        e.volume.hash_type1 = new_type;
+                    } else {
+                        e.volume.hash_type2 = new_type;
+                    }
+                    0
+                }).unwrap_or(-1)
+            },
+            _ => -1,
+        }
+    }
+
+    fn get_item_count(&self, selected_in_dbc: bool) -> u32 {
+        self.with_selected(|_, e| {
+            if selected_in_dbc { e.dbc_selection.len() as u32 } else { e.items.len() as u32 }
+        }).unwrap_or(0)
+    }
+
+    fn get_item_name(&self, item_id: u32) -> String {
+        self.with_item(item_id as i32, |_, _, i| i.name.clone()).unwrap_or_default()
+    }
+
+    fn get_item_size(&self, item_id: i32) -> i64 {
+        self.with_item(item_id, |_, _, i| i.size).unwrap_or(-1)
+    }
+
+    fn set_item_size(&self, item_id: i32, size: i64) {
+        self.with_item_mut(item_id, |i| i.size = size);
+    }
+
+    fn get_item_ofs(&self, item_id: i32) -> (i64, i64) {
+        self.with_item(item_id, |_, _, i| (i.def_ofs, i.start_sector)).unwrap_or((0, -1))
+    }
+
+    fn get_item_information(&self, item_id: i32, info_type: i32) -> Option<i64> {
+        self.with_item(item_id, |_, ev, i| {
+            match info_type {
+                x if x == XwfItemInfoTypes::OrigId as i32 => Some(i.orig_id),
+                x if x == XwfItemInfoTypes::Attr as i32 => Some(i.attributes.bits()),
+                x if x == XwfItemInfoTypes::Flags as i32 => Some(Self::computed_flags(ev, item_id, i).bits() as i64),
+                x if x == XwfItemInfoTypes::Deletion as i32 => Some(i.deletion as i64),
+                x if x == XwfItemInfoTypes::Classification as i32 => Some(i.classification as i64),
+                x if x == XwfItemInfoTypes::LinkCount as i32 => Some(i.link_count),
+                x if x == XwfItemInfoTypes::ColorAnalysis as i32 => Some(i.color_analysis),
+                x if x == XwfItemInfoTypes::PixelIndex as i32 => Some(i.pixel_index),
+                x if x == XwfItemInfoTypes::FileCount as i32 => Some(i.file_count),
+                x if x == XwfItemInfoTypes::EmbeddedOffset as i32 => Some(i.embedded_offset),
+                x if x == XwfItemInfoTypes::CreationTime as i32 => Some(i.creation_time),
+                x if x == XwfItemInfoTypes::ModificationTime as i32 => Some(i.modification_time),
+                x if x == XwfItemInfoTypes::LastAccessTime as i32 => Some(i.last_access_time),
+                x if x == XwfItemInfoTypes::EntryModificationTime as i32 => Some(i.entry_modification_time),
+                x if x == XwfItemInfoTypes::DeletionTime as i32 => Some(i.deletion_time),
+                x if x == XwfItemInfoTypes::InternalCreationTime as i32 => Some(i.internal_creation_time),
+                x if (XwfItemInfoTypes::CreationTimeDisplayOfs as i32..=XwfItemInfoTypes::InternalCreationTimeDisplayOfs as i32).contains(&x) =>
+                    Some(i.display_offsets[(x - XwfItemInfoTypes::CreationTimeDisplayOfs as i32) as usize]),
+                _ => None,
+            }
+        }).flatten()
+    }
+
+    fn set_item_information(&self, item_id: i32, info_type: i32, value: i64) -> bool {
+        self.with_item_mut(item_id, |i| {
+            match info_type {
+                INFO_TYPE_SET_FLAGS => i.flags |= ItemInfoFlags::from_bits_truncate(value as u64),
+                INFO_TYPE_REMOVE_FLAGS => i.flags &= !ItemInfoFlags::from_bits_truncate(value as u64),
+                x if x == XwfItemInfoTypes::Flags as i32 => i.flags = ItemInfoFlags::from_bits_truncate(value as u64),
+                x if x == XwfItemInfoTypes::OrigId as i32 => i.orig_id = value,
+                x if x == XwfItemInfoTypes::Attr as i32 => i.attributes = ItemInfoAttributes::from_bits_truncate(value),
+                x if x == XwfItemInfoTypes::Deletion as i32 => match ItemInfoDeletion::try_from(value) {
+                    Ok(d) => i.deletion = d,
+                    Err(_) => return false,
+                },
+                x if x == XwfItemInfoTypes::Classification as i32 => match ItemInfoClassification::try_from(value) {
+                    Ok(c) => i.classification = c,
+                    Err(_) => return false,
+                },
+                x if x == XwfItemInfoTypes::LinkCount as i32 => i.link_count = value,
+                x if x == XwfItemInfoTypes::ColorAnalysis as i32 => i.color_analysis = value,
+                x if x == XwfItemInfoTypes::PixelIndex as i32 => i.pixel_index = value,
+                x if x == XwfItemInfoTypes::FileCount as i32 => i.file_count = value,
+                x if x == XwfItemInfoTypes::EmbeddedOffset as i32 => i.embedded_offset = value,
+                x if x == XwfItemInfoTypes::CreationTime as i32 => i.creation_time = value,
+                x if x == XwfItemInfoTypes::ModificationTime as i32 => i.modification_time = value,
+                x if x == XwfItemInfoTypes::LastAccessTime as i32 => i.last_access_time = value,
+                x if x == XwfItemInfoTypes::EntryModificationTime as i32 => i.entry_modification_time = value,
+                x if x == XwfItemInfoTypes::DeletionTime as i32 => i.deletion_time = value,
+                x if x == XwfItemInfoTypes::InternalCreationTime as i32 => i.internal_creation_time = value,
+                _ => return false,
+            }
+            true
+        }).unwrap_or(false)
+    }
+
+    fn get_item_type(&self, item_id: i32, type_descr: &mut String, flags: u32) -> i32 {
+        let flags = ItemTypeFlags::from_bits_truncate(flags);
+        self.with_item(item_id, |_, _, i| {
+            *type_descr = if flags.contains(ItemTypeFlags::TextualDescriptionCategory) {
+                i.category.clone()
+            } else if flags.contains(ItemTypeFlags::TextualDescriptionType) {
+                i.type_long.clone()
+            } else {
+                i.type_short.clone()
+            };
+
+            if flags.contains(ItemTypeFlags::ReceiveTypeStatus) {
+                (i.type_status as i32) | ((i.consistency as i32) << 8)
+            } else {
+                i.type_status as i32
+            }
+        }).unwrap_or(-1)
+    }
+
+    fn set_item_type(&self, item_id: i32, type_descr: &str, type_status: i32) {
+        self.with_item_mut(item_id, |i| {
+            i.type_short = type_descr.to_string();
+            if let Ok(status) = FileTypeStatus::try_from(type_status) {
+                i.type_status = status;
+            }
+        });
+    }
+
+    fn get_item_parent(&self, item_id: i32) -> i32 {
+        self.with_item(item_id, |_, _, i| i.parent_id).unwrap_or(-1)
+    }
+
+    fn set_item_parent(&self, child_item_id: i32, parent_item_id: i32) {
+        self.with_item_mut(child_item_id, |i| i.parent_id = parent_item_id);
+    }
+
+    fn get_report_table_assocs(&self, item_id: i32, assocs: &mut String) -> u32 {
+        self.with_item(item_id, |case, _, i| {
+            let names: Vec<&str> = i.report_tables.iter()
+                .filter_map(|t| case.report_tables.get(*t as usize))
+                .map(|t| t.name.as_str())
+                .collect();
+            *assocs = names.join(", ");
+            names.len() as u32
+        }).unwrap_or(0)
+    }
+
+    fn add_to_report_table(&self, item_id: i32, report_table_name: &str, flags: u32) -> i32 {
+        let mut case = self.case();
+        let ev_id = match self.selected_evidence_id(&case) {
+            Some(id) => id,
+            None => return 0,
+        };
+
+        if case.evidence_by_id(ev_id).and_then(|e| e.item(item_id)).is_none() {
+            return 0;
+        }
+
+        let table_id = match case.report_table_id(report_table_name) {
+            Some(id) => id,
+            None => {
+                let add_flags = AddReportTableFlags::from_bits_truncate(flags);
+                let mut table_flags = if add_flags.contains(AddReportTableFlags::CreatedByApplication) {
+                    ReportTableFlags::HintByApplication
+                } else {
+                    ReportTableFlags::CreatedByUser
+                };
+                if add_flags.contains(AddReportTableFlags::SelectForInclusionInReport) {
+                    table_flags |= ReportTableFlags::SelectedForInclusion;
+                }
+                if add_flags.contains(AddReportTableFlags::SelectForFiltering) {
+                    table_flags |= ReportTableFlags::SelectedForFilter;
+                }
+                if add_flags.contains(AddReportTableFlags::SelectForManualAssocs) {
+                    table_flags |= ReportTableFlags::SelectedForNewAssociations;
+                }
+                case.report_tables.push(SimReportTable { name: report_table_name.to_string(), flags: table_flags });
+                (case.report_tables.len() - 1) as u16
+            }
+        };
+
+        let item = case.evidence_by_id_mut(ev_id).and_then(|e| e.item_mut(item_id)).unwrap();
+        if item.report_tables.insert(table_id) { 1 } else { 2 }
+    }
+
+    fn get_hashset_assocs(&self, item_id: i32, assocs: &mut String) -> i32 {
+        self.with_item(item_id, |_, _, i| {
+            *assocs = i.hash_sets.join(", ");
+            i.hash_sets.len() as i32
+        }).unwrap_or(-1)
+    }
+
+    fn get_comment(&self, item_id: i32) -> Option<String> {
+        self.with_item(item_id, |_, _, i| i.comment.clone()).flatten()
+    }
+
+    fn add_comment(&self, item_id: i32, comment: &str, flags: u32) -> bool {
+        let append = AddTextFlags::from_bits_retain(flags).contains(AddTextFlags::Append);
+        self.with_item_mut(item_id, |i| add_text(&mut i.comment, comment, append)).is_some()
+    }
+
+    fn get_extracted_metadata(&self, item_id: i32) -> Option<String> {
+        self.with_item(item_id, |_, _, i| i.extracted_metadata.clone()).flatten()
+    }
+
+    fn add_extracted_metadata(&self, item_id: i32, metadata: &str, flags: u32) -> bool {
+        let append = AddTextFlags::from_bits_retain(flags).contains(AddTextFlags::Append);
+        self.with_item_mut(item_id, |i| add_text(&mut i.extracted_metadata, metadata, append)).is_some()
+    }
+
+    fn get_metadata_ex(&self, item: XwfHandle, flags: &mut u32) -> Option<String> {
+        match self.resolve(item)? {
+            SimHandle::Item(ev, item_id) => {
+                *flags &= 0x00FFFFFF;
+                self.case().evidence_by_id(ev)?.item(item_id)?.metadata.clone()
+            },
+            _ => None,
+        }
+    }
+
+    fn get_hash_value(&self, item_id: i32, buffer: &mut [u8]) -> bool {
+        if buffer.len() < 4 {
+            return false;
+        }
+        let flags = u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
+        let hash = self.with_item(item_id, |_, _, i| {
+            if flags & 0x02 != 0 { i.hash2.clone() } else { i.hash1.clone() }
+        }).flatten();
+
+        match hash {
+            Some(h) => {
+                let len = h.len().min(buffer.len());
+                buffer[..len].copy_from_slice(&h[..len]);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn set_hash_value(&self, item_id: i32, hash: &[u8], param: u32) -> bool {
+        self.with_item_mut(item_id, |i| {
+            if param == 2 {
+                i.hash2 = Some(hash.to_vec());
+            } else {
+                i.hash1 = Some(hash.to_vec());
+            }
+        }).is_some()
+    }
+
+    fn create_file(&self, name: &str, creation_flags: u32, parent_item_id: i32, src_info: &mut SrcInfo) -> i32 {
+        let flags = FileCreationFlags::from_bits_truncate(creation_flags);
+        let mut item = SimItem::new(name, parent_item_id);
+
+        if flags.contains(FileCreationFlags::FileContentsFromBuffer) {
+            let buf_size = src_info.n_buf_size;
+            let p_buffer = src_info.p_buffer;
+            if !p_buffer.is_null() && buf_size > 0 {
+                let data = unsafe { std::slice::from_raw_parts(p_buffer as *const u8, buf_size as usize) };
+                item.data = data.to_vec();
+            }
+        }
+        item.size = item.data.len() as i64;
+        item.created = true;
+
+        self.with_selected_mut(|e| {
+            if e.item(parent_item_id).is_none() {
+                return -1;
+            }
+            e.add_item(item)
+        }).unwrap_or(-1)
+    }
+
+    fn open_item(&self, volume: XwfHandle, item_id: i32, _flags: u32) -> XwfHandle {
+        let ev = match self.resolve(volume) {
+            Some(SimHandle::Volume(ev)) => ev,
+            _ => return XwfHandle::NULL,
+        };
+        if self.case().evidence_by_id(ev).and_then(|e| e.item(item_id)).is_none() {
+            return XwfHandle::NULL;
+        }
+        self.handles.lock().unwrap().create(SimHandle::Item(ev, item_id))
+    }
+
+    fn close(&self, volume_or_item: XwfHandle) {
+        let mut handles = self.handles.lock().unwrap();
+        if let Some(SimHandle::Item(_, _)) = handles.get(volume_or_item) {
+            handles.handles.remove(&volume_or_item.as_raw());
+        }
+    }
+
+    fn read(&self, volume_or_item: XwfHandle, offset: i64, buffer: &mut [u8]) -> u32 {
+        let data = match self.data_of(volume_or_item) {
+            Some(d) => d,
+            None => return 0,
+        };
+        if offset < 0 || offset as usize >= data.len() {
+            return 0;
+        }
+        let start = offset as usize;
+        let len = buffer.len().min(data.len() - start);
+        buffer[..len].copy_from_slice(&data[start..start + len]);
+        len as u32
+    }
+
+    fn get_prop(&self, volume_or_item: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64 {
+        let case = self.case();
+        match self.resolve(volume_or_item) {
+            Some(SimHandle::Item(ev_id, item_id)) => {
+                let ev = match case.evidence_by_id(ev_id) { Some(e) => e, None => return -1 };
+                let item = match ev.item(item_id) { Some(i) => i, None => return -1 };
+                match prop_type {
+                    x if x == PropType::PhysicalSize as u32 => item.data.len() as i64,
+                    x if x == PropType::LogicalSize as u32 || x == PropType::ValidDataLength as u32 => item.size,
+                    x if x == PropType::FileAttributes as u32 => item.attributes.bits(),
+                    x if x == PropType::PointerFilePath as u32 => { write_string(string, &ev.path(item_id)); 1 },
+                    x if x == PropType::PointerName as u32 => { write_string(string, &item.name); 1 },
+                    x if x == PropType::ParentVolume as u32 => {
+                        drop(case);
+                        self.volume_handle(ev_id).as_raw() as i64
+                    },
+                    _ => 0,
+                }
+            },
+            Some(SimHandle::Volume(ev_id)) => {
+                let ev = match case.evidence_by_id(ev_id) { Some(e) => e, None => return -1 };
+                match prop_type {
+                    x if x == PropType::PhysicalSize as u32 || x == PropType::LogicalSize as u32 => ev.volume.data.len() as i64,
+                    x if x == PropType::PointerName as u32 || x == PropType::PointerFilePath as u32 => { write_string(string, &ev.volume.name); 1 },
+                    _ => 0,
+                }
+            },
+            _ => -1,
+        }
+    }
+
+    fn get_first_ev_obj(&self) -> XwfHandle {
+        let first = self.case().evidences.first().map(|e| e.id);
+        match first {
+            Some(id) => self.evidence_handle(id),
+            None => XwfHandle::NULL,
+        }
+    }
+
+    fn get_next_ev_obj(&self, prev_evidence: XwfHandle) -> XwfHandle {
+        let prev = match self.resolve(prev_evidence) {
+            Some(SimHandle::Evidence(id)) => id,
+            _ => return XwfHandle::NULL,
+        };
+        let next = {
+            let case = self.case();
+            case.evidences.iter().position(|e| e.id == prev)
+                .and_then(|idx| case.evidences.get(idx + 1))
+                .map(|e| e.id)
+        };
+        match next {
+            Some(id) => self.evidence_handle(id),
+            None => XwfHandle::NULL,
+        }
+    }
+
+    fn get_ev_obj(&self, ev_obj_id: u32) -> XwfHandle {
+        self.evidence_handle(ev_obj_id)
+    }
+
+    fn open_ev_obj(&self, evidence: XwfHandle, _flags: u32) -> XwfHandle {
+        match self.resolve(evidence) {
+            Some(SimHandle::Evidence(id)) => self.volume_handle(id),
+            _ => XwfHandle::NULL,
+        }
+    }
+
+    fn close_ev_obj(&self, _evidence: XwfHandle) {}
+
+    fn get_ev_obj_prop(&self, evidence: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64 {
+        let ev_id = match self.resolve(evidence) {
+            Some(SimHandle::Evidence(id)) => id,
+            _ => return -1,
+        };
+        let case = self.case();
+        let ev_number = case.evidences.iter().position(|e| e.id == ev_id).unwrap_or(0) as i64;
+        let ev = match case.evidence_by_id(ev_id) { Some(e) => e, None => return -1 };
+
+        let string_prop = |target: Option<&mut String>, value: Option<&String>| -> i64 {
+            match value {
+                Some(v) => { write_string(target, v); v.chars().count() as i64 },
+                None => 0,
+            }
+        };
+
+        match prop_type {
+            x if x == EvObjPropType::ObjNumber as u32 => ev_number,
+            x if x == EvObjPropType::ObjId as u32 => ev.id as i64,
+            x if x == EvObjPropType::ParentObjId as u32 => ev.parent_id.unwrap_or(0) as i64,
+            x if x == EvObjPropType::ShortEvObjId as u32 => ev.short_id as i64,
+            x if x == EvObjPropType::VsSnapshotId as u32 => 1,
+            x if x == EvObjPropType::ObjTitle as u32 => string_prop(string, Some(&ev.title)),
+            x if x == EvObjPropType::ExtObjTitle as u32 => string_prop(string, Some(&ev.extended_title)),
+            x if x == EvObjPropType::AbbrevObjTitle as u32 => string_prop(string, Some(&ev.abbreviated_title)),
+            x if x == EvObjPropType::InternalName as u32 => string_prop(string, Some(&ev.internal_name)),
+            x if x == EvObjPropType::Description as u32 => string_prop(string, ev.description.as_ref()),
+            x if x == EvObjPropType::ExaminerComments as u32 => string_prop(string, ev.examiner_comments.as_ref()),
+            x if x == EvObjPropType::IntUsedDir as u32 || x == EvObjPropType::OutputDir as u32 => string_prop(string, Some(&ev.output_dir)),
+            x if x == EvObjPropType::SizeInBytes as u32 => ev.volume.data.len() as i64,
+            x if x == EvObjPropType::VSFileCount as u32 => ev.items.len() as i64,
+            x if x == EvObjPropType::Flags as u32 => ev.flags.bits() as i64,
+            x if x == EvObjPropType::FileSystemID as u32 => ev.volume.file_system as i64,
+            x if x == EvObjPropType::CreationTime as u32 => ev.creation_time,
+            x if x == EvObjPropType::ModificationTime as u32 => ev.modification_time,
+            _ => 0,
+        }
+    }
+
+    fn get_ev_obj_report_table_assocs(&self, evidence: XwfHandle, flags: i32) -> Option<Vec<ReportTableListItem>> {
+        let ev_id = match self.resolve(evidence)? {
+            SimHandle::Evidence(id) => id,
+            _ => return None,
+        };
+        let case = self.case();
+        if case.null_report_table_assocs {
+            return None;
+        }
+        let ev = case.evidence_by_id(ev_id)?;
+
+        let mut ret: Vec<ReportTableListItem> = ev.items.iter().enumerate()
+            .flat_map(|(idx, i)| i.report_tables.iter().map(move |t| ReportTableListItem {
+                report_table_id: *t,
+                item_id: idx as u32,
+            }))
+            .collect();
+
+        // sorted by item id if flag 0x1 is set, otherwise by report table
+        if flags & 0x1 == 0 {
+            ret.sort_by_key(|r| (r.report_table_id, r.item_id));
+        }
+        Some(ret)
+    }
+
+    fn get_case_prop(&self, prop_type: i32, string: Option<&mut String>) -> i64 {
+        let case = self.case();
+        let string_prop = |target: Option<&mut String>, value: &String| -> i64 {
+            write_string(target, value);
+            value.chars().count() as i64
+        };
+        match prop_type {
+            0 => case.id,
+            1 => string_prop(string, &case.title),
+            2 => case.creation_time,
+            3 => string_prop(string, &case.examiner),
+            5 => string_prop(string, &case.file),
+            6 => string_prop(string, &case.dir),
+            _ => -1,
+        }
+    }
+
+    fn get_report_table_info(&self, report_table_id: i32, optional: &mut i32) -> Option<String> {
+        let case = self.case();
+        if report_table_id < 0 {
+            *optional = case.report_tables.len() as i32;
+            return None;
+        }
+        let table = case.report_tables.get(report_table_id as usize)?;
+        *optional = table.flags.bits() as i32;
+        Some(table.name.clone())
+    }
+
+    fn show_progress(&self, caption: &str, _flags: u32) {
+        let progress = &mut self.case().progress;
+        progress.visible = true;
+        progress.caption = caption.to_string();
+        progress.description.clear();
+        progress.percentage = 0;
+    }
+
+    fn set_progress_description(&self, description: &str) {
+        self.case().progress.description = description.to_string();
+    }
+
+    fn set_progress_percentage(&self, percent: u32) {
+        self.case().progress.percentage = percent;
+    }
+
+    fn hide_progress(&self) {
+        self.case().progress.visible = false;
+    }
+
+    fn should_stop(&self) -> bool {
+        let mut case = self.case();
+        match case.stop_after {
+            Some(0) => case.stop_requested = true,
+            Some(calls) => case.stop_after = Some(calls - 1),
+            None => {},
+        }
+        case.stop_requested
+    }
+
+    fn get_user_input(&self, _message: &str, input: Option<&mut String>, flags: u32) -> i64 {
+        let answer = self.case().user_inputs.pop_front();
+        match input {
+            Some(s) => {
+                let answer = answer.unwrap_or_default();
+                // flag 0x2 allows empty input
+                if answer.is_empty() && flags & 0x2 == 0 {
+                    return -1;
+                }
+                *s = answer;
+                s.chars().count() as i64
+            },
+            None => answer.and_then(|a| a.trim().parse::<i64>().ok()).unwrap_or(-1),
+        }
+    }
+
+    /// searches the item data (logical search) or the volume data (physical search) for the given
+    /// search terms in code pages 1252, 1200 and 65001. GREP searches are not simulated and fail.
+    fn search(&self, volume: XwfHandle, search_terms: &str, flags: u32, _search_window: u32, code_pages: Option<[u16; 5]>) -> i32 {
+        let flags = SearchFlags::from_bits_retain(flags);
+        if flags.contains(SearchFlags::Grep) {
+            return -1;
+        }
+
+        let mut case = self.case();
+        let ev_id = match self.resolve(volume) {
+            Some(SimHandle::Volume(ev)) => ev,
+            _ if volume.is_null() => match self.selected_evidence_id(&case) {
+                Some(ev) => ev,
+                None => return -1,
+            },
+            _ => return -1,
+        };
+
+        let terms: Vec<(u16, &str)> = search_terms.lines()
+            .filter(|t| !t.is_empty())
+            .map(|t| (case.add_search_term(t) as u16, t))
+            .collect();
+        let code_pages: Vec<u16> = match code_pages {
+            Some(c) => c.into_iter().filter(|c| *c != 0).collect(),
+            None => DEFAULT_CODE_PAGES.to_vec(),
+        };
+
+        let ev = match case.evidence_by_id_mut(ev_id) {
+            Some(e) => e,
+            None => return -1,
+        };
+        let bytes_per_sector = ev.volume.bytes_per_sector as i64;
+
+        let mut hits = Vec::new();
+        for (term_id, term) in &terms {
+            for code_page in &code_pages {
+                let pattern = match encode(term, *code_page) {
+                    Some(p) => p,
+                    None => continue,
+                };
+                let hit = |item_id: i32, rel_ofs: usize, abs_ofs: i64| SearchHit {
+                    item_id,
+                    rel_ofs: rel_ofs as i64,
+                    abs_ofs,
+                    search_term_id: *term_id,
+                    length: pattern.len() as u16,
+                    code_page: *code_page,
+                    flags: SearchHitFlags::empty(),
+                };
+
+                if flags.contains(SearchFlags::Logical) {
+                    for (item_id, item) in ev.items.iter().enumerate() {
+                        let category = item.flags & ItemInfoFlags::Uncategorized;
+                        if (flags.contains(SearchFlags::TaggedObjectsOnly) && !item.flags.contains(ItemInfoFlags::Tagged))
+                            || (flags.contains(SearchFlags::OmitHidden) && item.flags.contains(ItemInfoFlags::HiddenByExaminer))
+                            || (flags.contains(SearchFlags::OmitIrrelevant) && category == ItemInfoFlags::CategorizedIrrelevant) {
+                            continue;
+                        }
+                        for ofs in find_occurrences(&item.data, &pattern, flags) {
+                            let abs_ofs = if item.start_sector >= 0 { item.start_sector * bytes_per_sector + ofs as i64 } else { -1 };
+                            hits.push(hit(item_id as i32, ofs, abs_ofs));
+                        }
+                    }
+                } else {
+                    for ofs in find_occurrences(&ev.volume.data, &pattern, flags) {
+                        hits.push(hit(-1, ofs, ofs as i64));
+                    }
+                }
+            }
+        }
+
+        let callback = match flags.contains(SearchFlags::CallProcessSearchHit) {
+            true => self.search_hit_callback.lock().unwrap().clone(),
+            false => None,
+        };
+        drop(case);
+
+        // the X-Tension may call the API while processing a hit, so the case is not locked meanwhile
+        for hit in hits {
+            match self.case().evidence_by_id_mut(ev_id) {
+                Some(ev) if !ev.search_hits.contains(&hit) => ev.search_hits.push(hit.clone()),
+                _ => continue,
+            }
+            if let Some(callback) = &callback {
+                if callback(hit) == XtProcessSearchHitReturn::StopSearch as i32 {
+                    break;
+                }
+            }
+        }
+        0
+    }
+
+    fn get_search_term(&self, search_term_id: i32) -> Option<String> {
+        if search_term_id < 0 {
+            return None;
+        }
+        self.case().search_terms.get(search_term_id as usize).cloned()
+    }
+
+    fn get_search_term_count(&self) -> i32 {
+        self.case().search_terms.len() as i32
+    }
+
+    fn add_search_term(&self, name: &str, flags: u32) -> i32 {
+        if name.is_empty() {
+            return -1;
+        }
+        let mut case = self.case();
+        match case.search_term_id(name) {
+            Some(id) if AddSearchTermFlags::from_bits_retain(flags).contains(AddSearchTermFlags::UseExisting) => id,
+            _ => {
+                case.search_terms.push(name.to_string());
+                (case.search_terms.len() - 1) as i32
+            }
+        }
+    }
+
+    fn get_search_hit(&self, hit_no: i32) -> Option<SearchHit> {
+        if hit_no < 0 {
+            return None;
+        }
+        self.with_selected(|_, e| e.search_hits.get(hit_no as usize).cloned()).flatten()
+    }
+
+    fn set_search_hit(&self, hit_no: i32, hit: &SearchHit) -> bool {
+        if hit_no < 0 {
+            return false;
+        }
+        self.with_selected_mut(|e| match e.search_hits.get_mut(hit_no as usize) {
+            Some(h) => {
+                *h = hit.clone();
+                true
+            },
+            None => false,
+        }).unwrap_or(false)
+    }
+
+    fn add_search_hit(&self, hit: &SearchHit) -> i32 {
+        if self.get_search_term(hit.search_term_id as i32).is_none() {
+            return 0;
+        }
+        self.with_selected_mut(|e| {
+            if hit.item_id >= 0 && e.item(hit.item_id).is_none() {
+                0
+            } else if e.search_hits.contains(hit) {
+                2
+            } else {
+                e.search_hits.push(hit.clone());
+                1
+            }
+        }).unwrap_or(0)
+    }
+
+    fn get_event(&self, evidence: XwfHandle, event_no: u32) -> Option<Event> {
+        let ev_id = match self.resolve(evidence) {
+            Some(SimHandle::Evidence(id)) => id,
+            _ => return None,
+        };
+        let mut event = self.case().evidence_by_id(ev_id)?.events.get(event_no as usize)?.clone();
+        event.evidence = evidence;
+        Some(event)
+    }
+
+    fn add_event(&self, event: &Event) -> i32 {
+        let ev_id = match self.resolve(event.evidence) {
+            Some(SimHandle::Evidence(id)) => id,
+            _ => return 0,
+        };
+        let mut case = self.case();
+        let ev = match case.evidence_by_id_mut(ev_id) {
+            Some(e) => e,
+            None => return 0,
+        };
+        if event.item_id >= 0 && ev.item(event.item_id).is_none() {
+            return 0;
+        }
+
+        let mut event = event.clone();
+        event.flags = event.raw_flags();
+        ev.events.push(event);
+        1
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::with_missing(self.case().unavailable_functions.clone())
+    }
+}
@@ -0,0 +1,17 @@
+//! In-memory simulation of X-Ways Forensics, which allows running and testing X-Tensions
+//! without X-Ways: build a [`SimCase`], wrap it into a [`SimulatedHost`] and let a [`Driver`]
+//! invoke the X-Tension callbacks ([`MtDriver`] for multi-threaded X-Tensions). With feature `fixture`,
+//! cases can also be loaded from JSON or TOML fixture files (see [`CaseFixture`]).
+//! Only built with feature `simulator` (implied by `fixture`).
+
+mod model;
+mod host;
+mod driver;
+#[cfg(any(test, feature = "fixture"))]
+mod fixture;
+
+pub use model::*;
+pub use host::{SearchHitCallback, SimulatedHost};
+pub use driver::{Driver, ExtensionRef, ItemCallback, MtDriver, RunReport};
+#[cfg(any(test, feature = "fixture"))]
+pub use fixture::{CaseFixture, EvidenceFixture, ItemFixture, ReportTableFixture, VolumeFixture};
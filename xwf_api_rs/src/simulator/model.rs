@@ -0,0 +1,384 @@
+use std::collections::{BTreeSet, VecDeque};
+use crate::xwf_types::*;
+use crate::search::SearchHit;
+use crate::event::Event;
+use crate::volume::HashType;
+
+
+/// in-memory representation of a X-Ways case used by the [`super::SimulatedHost`]
+#[derive(Clone, Debug)]
+pub struct SimCase {
+    pub id: i64,
+    pub title: String,
+    pub examiner: String,
+    pub file: String,
+    pub dir: String,
+    pub creation_time: i64,
+    pub evidences: Vec<SimEvidence>,
+    pub report_tables: Vec<SimReportTable>,
+    /// search terms, the index is the search term id
+    pub search_terms: Vec<String>,
+
+    pub messages: Vec<SimMessage>,
+    pub progress: SimProgress,
+    pub user_inputs: VecDeque<String>,
+    pub stop_requested: bool,
+    /// number of `XWF_ShouldStop` calls before `stop_requested` is set, to simulate an abort
+    /// in the middle of an operation
+    pub stop_after: Option<u32>,
+    /// `XWF_*` functions reported as not exported, to simulate older versions of X-Ways Forensics
+    pub unavailable_functions: Vec<&'static str>,
+    /// `XWF_GetEvObjReportTableAssocs` returns NULL instead of the list of associations
+    pub null_report_table_assocs: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimReportTable {
+    pub name: String,
+    pub flags: ReportTableFlags,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimMessage {
+    pub message: String,
+    pub flags: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SimProgress {
+    pub visible: bool,
+    pub caption: String,
+    pub description: String,
+    pub percentage: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimEvidence {
+    pub id: u32,
+    pub short_id: u16,
+    pub parent_id: Option<u32>,
+    pub title: String,
+    pub extended_title: String,
+    pub abbreviated_title: String,
+    pub internal_name: String,
+    pub description: Option<String>,
+    pub examiner_comments: Option<String>,
+    pub output_dir: String,
+    pub flags: EvObjPropFlags,
+    pub creation_time: i64,
+    pub modification_time: i64,
+    pub volume: SimVolume,
+    pub items: Vec<SimItem>,
+    /// item ids that are selected in the directory browser (for context menu invocations)
+    pub dbc_selection: Vec<i32>,
+    /// search hit list of the volume snapshot, the index is the search hit number
+    pub search_hits: Vec<SearchHit>,
+    /// search hit numbers that are selected in the search hit list (for context menu invocations)
+    pub search_hit_selection: Vec<i32>,
+    /// event list of the evidence object, the index is the event number
+    pub events: Vec<Event>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimVolume {
+    pub name: String,
+    pub file_system: i32,
+    pub bytes_per_sector: u32,
+    pub sectors_per_cluster: u32,
+    pub first_cluster_sector_no: i64,
+    pub data: Vec<u8>,
+    pub hash_type1: Option<HashType>,
+    pub hash_type2: Option<HashType>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimItem {
+    pub name: String,
+    pub parent_id: i32,
+    pub data: Vec<u8>,
+    pub size: i64,
+
+    pub orig_id: i64,
+    pub attributes: ItemInfoAttributes,
+    pub flags: ItemInfoFlags,
+    pub deletion: ItemInfoDeletion,
+    pub classification: ItemInfoClassification,
+    pub link_count: i64,
+    pub color_analysis: i64,
+    pub pixel_index: i64,
+    pub file_count: i64,
+    /// offset within the parent file, -1 if the item is not embedded
+    pub embedded_offset: i64,
+
+    /// FILETIME values, 0 if not available
+    pub creation_time: i64,
+    pub modification_time: i64,
+    pub last_access_time: i64,
+    pub entry_modification_time: i64,
+    pub deletion_time: i64,
+    pub internal_creation_time: i64,
+    /// time zone offsets in minutes for displaying the timestamps above, in the same order
+    pub display_offsets: [i64; 6],
+
+    pub type_short: String,
+    pub type_long: String,
+    pub category: String,
+    pub type_status: FileTypeStatus,
+    pub consistency: FileFormatConsistency,
+
+    pub def_ofs: i64,
+    pub start_sector: i64,
+
+    pub report_tables: BTreeSet<u16>,
+    pub hash_sets: Vec<String>,
+    pub hash1: Option<Vec<u8>>,
+    pub hash2: Option<Vec<u8>>,
+    pub comment: Option<String>,
+    pub extracted_metadata: Option<String>,
+    pub metadata: Option<String>,
+
+    /// true if the item was created by the X-Tension via `XWF_CreateFile`
+    pub created: bool,
+}
+
+impl SimCase {
+    pub fn new<S: Into<String>>(title: S) -> SimCase {
+        SimCase {
+            id: 1,
+            title: title.into(),
+            examiner: String::new(),
+            file: String::new(),
+            dir: String::new(),
+            creation_time: 0,
+            evidences: Vec::new(),
+            report_tables: Vec::new(),
+            search_terms: Vec::new(),
+            messages: Vec::new(),
+            progress: SimProgress::default(),
+            user_inputs: VecDeque::new(),
+            stop_requested: false,
+            stop_after: None,
+            unavailable_functions: Vec::new(),
+            null_report_table_assocs: false,
+        }
+    }
+
+    /// adds an evidence to the case and returns its index
+    pub fn add_evidence(&mut self, evidence: SimEvidence) -> usize {
+        self.evidences.push(evidence);
+        self.evidences.len() - 1
+    }
+
+    pub fn evidence_by_id(&self, id: u32) -> Option<&SimEvidence> {
+        self.evidences.iter().find(|e| e.id == id)
+    }
+
+    pub fn evidence_by_id_mut(&mut self, id: u32) -> Option<&mut SimEvidence> {
+        self.evidences.iter_mut().find(|e| e.id == id)
+    }
+
+    pub fn report_table_id(&self, name: &str) -> Option<u16> {
+        self.report_tables.iter().position(|t| t.name == name).map(|i| i as u16)
+    }
+
+    /// returns the names of all report tables the given item is associated with
+    pub fn report_tables_of(&self, evidence_id: u32, item_id: i32) -> Vec<String> {
+        self.evidence_by_id(evidence_id)
+            .and_then(|e| e.items.get(item_id as usize))
+            .map(|i| i.report_tables.iter()
+                .filter_map(|t| self.report_tables.get(*t as usize))
+                .map(|t| t.name.clone())
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// returns all items that were assigned to the report table with the given name
+    pub fn report_table_items(&self, name: &str) -> Vec<(u32, i32)> {
+        let table_id = match self.report_table_id(name) {
+            Some(id) => id,
+            None => return Vec::new(),
+        };
+
+        self.evidences.iter().flat_map(|e| {
+            e.items.iter().enumerate()
+                .filter(|(_, i)| i.report_tables.contains(&table_id))
+                .map(|(idx, _)| (e.id, idx as i32))
+                .collect::<Vec<(u32, i32)>>()
+        }).collect()
+    }
+
+    pub fn search_term_id(&self, name: &str) -> Option<i32> {
+        self.search_terms.iter().position(|t| t == name).map(|i| i as i32)
+    }
+
+    /// adds the search term if it does not exist yet and returns its id
+    pub fn add_search_term(&mut self, name: &str) -> i32 {
+        match self.search_term_id(name) {
+            Some(id) => id,
+            None => {
+                self.search_terms.push(name.to_string());
+                (self.search_terms.len() - 1) as i32
+            }
+        }
+    }
+
+    /// returns all items that were created by the X-Tension
+    pub fn created_items(&self) -> Vec<(u32, i32)> {
+        self.evidences.iter().flat_map(|e| {
+            e.items.iter().enumerate()
+                .filter(|(_, i)| i.created)
+                .map(|(idx, _)| (e.id, idx as i32))
+                .collect::<Vec<(u32, i32)>>()
+        }).collect()
+    }
+
+    /// returns all messages that were written via `XWF_OutputMessage`
+    pub fn message_texts(&self) -> Vec<&str> {
+        self.messages.iter().map(|m| m.message.as_str()).collect()
+    }
+}
+
+impl SimEvidence {
+    pub fn new<S: Into<String>>(id: u32, title: S) -> SimEvidence {
+        let title = title.into();
+        SimEvidence {
+            id,
+            short_id: id as u16,
+            parent_id: None,
+            extended_title: title.clone(),
+            abbreviated_title: title.clone(),
+            internal_name: title.clone(),
+            description: None,
+            examiner_comments: None,
+            output_dir: String::new(),
+            flags: EvObjPropFlags::empty(),
+            creation_time: 0,
+            modification_time: 0,
+            volume: SimVolume::new(title.clone()),
+            title,
+            items: Vec::new(),
+            dbc_selection: Vec::new(),
+            search_hits: Vec::new(),
+            search_hit_selection: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// adds an item to the volume snapshot and returns its item id
+    pub fn add_item(&mut self, item: SimItem) -> i32 {
+        self.items.push(item);
+        (self.items.len() - 1) as i32
+    }
+
+    pub fn item(&self, item_id: i32) -> Option<&SimItem> {
+        if item_id < 0 { return None; }
+        self.items.get(item_id as usize)
+    }
+
+    pub fn item_mut(&mut self, item_id: i32) -> Option<&mut SimItem> {
+        if item_id < 0 { return None; }
+        self.items.get_mut(item_id as usize)
+    }
+
+    pub fn children(&self, item_id: i32) -> impl Iterator<Item=(i32, &SimItem)> {
+        self.items.iter().enumerate()
+            .filter(move |(_, i)| i.parent_id == item_id)
+            .map(|(idx, i)| (idx as i32, i))
+    }
+
+    pub fn path(&self, item_id: i32) -> String {
+        let mut components: Vec<&str> = Vec::new();
+        let mut cur = self.item(item_id);
+        while let Some(i) = cur {
+            components.push(i.name.as_str());
+            cur = self.item(i.parent_id);
+        }
+        components.pop();
+        components.reverse();
+        "\\".to_string() + &components.join("\\")
+    }
+}
+
+impl SimVolume {
+    pub fn new<S: Into<String>>(name: S) -> SimVolume {
+        SimVolume {
+            name: name.into(),
+            file_system: 0,
+            bytes_per_sector: 512,
+            sectors_per_cluster: 8,
+            first_cluster_sector_no: 0,
+            data: Vec::new(),
+            hash_type1: None,
+            hash_type2: None,
+        }
+    }
+
+    pub fn cluster_count(&self) -> i64 {
+        let cluster_size = (self.bytes_per_sector * self.sectors_per_cluster) as i64;
+        if cluster_size == 0 { 0 } else { self.data.len() as i64 / cluster_size }
+    }
+}
+
+impl SimItem {
+    pub fn new<S: Into<String>>(name: S, parent_id: i32) -> SimItem {
+        SimItem {
+            name: name.into(),
+            parent_id,
+            data: Vec::new(),
+            size: 0,
+            orig_id: 0,
+            attributes: ItemInfoAttributes::empty(),
+            flags: ItemInfoFlags::empty(),
+            deletion: ItemInfoDeletion::Existing,
+            classification: ItemInfoClassification::NormalFile,
+            link_count: 1,
+            color_analysis: -1,
+            pixel_index: 0,
+            file_count: 0,
+            embedded_offset: -1,
+            creation_time: 0,
+            modification_time: 0,
+            last_access_time: 0,
+            entry_modification_time: 0,
+            deletion_time: 0,
+            internal_creation_time: 0,
+            display_offsets: [0; 6],
+            type_short: String::new(),
+            type_long: String::new(),
+            category: String::new(),
+            type_status: FileTypeStatus::NotVerified,
+            consistency: FileFormatConsistency::Unknown,
+            def_ofs: 0,
+            start_sector: -1,
+            report_tables: BTreeSet::new(),
+            hash_sets: Vec::new(),
+            hash1: None,
+            hash2: None,
+            comment: None,
+            extracted_metadata: None,
+            metadata: None,
+            created: false,
+        }
+    }
+
+    pub fn directory<S: Into<String>>(name: S, parent_id: i32) -> SimItem {
+        let mut item = SimItem::new(name, parent_id);
+        item.flags = ItemInfoFlags::IsDirectory;
+        item
+    }
+
+    pub fn file<S: Into<String>>(name: S, parent_id: i32, data: Vec<u8>) -> SimItem {
+        let mut item = SimItem::new(name, parent_id);
+        item.size = data.len() as i64;
+        item.data = data;
+        item
+    }
+
+    pub fn with_type<S: Into<String>>(mut self, type_short: S, type_long: S, category: S) -> SimItem {
+        self.type_short = type_short.into();
+        self.type_long = type_long.into();
+        self.category = category.into();
+        self.type_status = FileTypeStatus::Confirmed;
+        self
+    }
+}
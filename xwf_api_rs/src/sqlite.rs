@@ -0,0 +1,368 @@
+//! Export of volume snapshots into a SQLite database (feature `sqlite`), so that they can be
+//! queried with SQL outside of X-Ways Forensics. Items are written in batches, each batch in its
+//! own transaction together with the progress of the export, so an interrupted export (aborted
+//! by the user or crashed) continues where it stopped when it is run again on the same database.
+
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use crate::backend::backend;
+use crate::capabilities::require_function;
+use crate::case::{case_dir, Case, ItemVisitor};
+use crate::error::XwfError;
+use crate::evidence::{Evidence, EvidenceIterator};
+use crate::item::Item;
+use crate::record::{ItemRecord, RecordOptions};
+use crate::tree::VolumeTree;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+
+const SCHEMA_VERSION: &str = "1";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS evidences (
+    evidence_id INTEGER PRIMARY KEY,
+    short_id INTEGER NOT NULL,
+    parent_id INTEGER,
+    name TEXT NOT NULL,
+    file_system TEXT,
+    item_count INTEGER NOT NULL,
+    -- items with lower ids are exported already
+    next_item_id INTEGER NOT NULL DEFAULT 0,
+    completed INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS items (
+    evidence_id INTEGER NOT NULL REFERENCES evidences(evidence_id),
+    item_id INTEGER NOT NULL,
+    parent_id INTEGER,
+    name TEXT NOT NULL,
+    path TEXT,
+    size INTEGER,
+    flags INTEGER NOT NULL,
+    attributes INTEGER NOT NULL,
+    deletion TEXT NOT NULL,
+    classification TEXT NOT NULL,
+    creation_time TEXT,
+    modification_time TEXT,
+    last_access_time TEXT,
+    entry_modification_time TEXT,
+    deletion_time TEXT,
+    internal_creation_time TEXT,
+    file_type TEXT,
+    file_type_description TEXT,
+    category TEXT,
+    type_status TEXT,
+    consistency TEXT,
+    hash1 TEXT,
+    hash2 TEXT,
+    PRIMARY KEY (evidence_id, item_id)
+) WITHOUT ROWID;
+CREATE TABLE IF NOT EXISTS report_tables (
+    report_table_id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    flags INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS report_table_items (
+    report_table_id INTEGER NOT NULL REFERENCES report_tables(report_table_id),
+    evidence_id INTEGER NOT NULL,
+    item_id INTEGER NOT NULL,
+    PRIMARY KEY (report_table_id, evidence_id, item_id)
+) WITHOUT ROWID;
+CREATE TABLE IF NOT EXISTS hash_sets (
+    hash_set_id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS hash_set_items (
+    hash_set_id INTEGER NOT NULL REFERENCES hash_sets(hash_set_id),
+    evidence_id INTEGER NOT NULL,
+    item_id INTEGER NOT NULL,
+    PRIMARY KEY (hash_set_id, evidence_id, item_id)
+) WITHOUT ROWID;
+CREATE TABLE IF NOT EXISTS comments (
+    evidence_id INTEGER NOT NULL,
+    item_id INTEGER NOT NULL,
+    comment TEXT NOT NULL,
+    PRIMARY KEY (evidence_id, item_id)
+) WITHOUT ROWID;
+
+CREATE INDEX IF NOT EXISTS items_parent ON items (evidence_id, parent_id);
+CREATE INDEX IF NOT EXISTS items_name ON items (name);
+CREATE INDEX IF NOT EXISTS items_hash1 ON items (hash1);
+CREATE INDEX IF NOT EXISTS items_modification_time ON items (modification_time);
+CREATE INDEX IF NOT EXISTS report_table_items_item ON report_table_items (evidence_id, item_id);
+CREATE INDEX IF NOT EXISTS hash_set_items_item ON hash_set_items (evidence_id, item_id);
+";
+
+const RECORD_OPTIONS: RecordOptions = RecordOptions::Path
+    .union(RecordOptions::Timestamps)
+    .union(RecordOptions::FileType)
+    .union(RecordOptions::Hashes)
+    .union(RecordOptions::HashSets)
+    .union(RecordOptions::Comment);
+
+impl From<rusqlite::Error> for XwfError {
+    fn from(e: rusqlite::Error) -> Self {
+        XwfError::DatabaseError(e.to_string())
+    }
+}
+
+/// Writes evidences, items, report table and hash set associations and comments of volume
+/// snapshots into a SQLite database. Flags and attributes are stored as integers, timestamps
+/// in RFC 3339 format and hash values hex encoded.
+///
+/// ```ignore
+/// let mut exporter = SqliteExporter::open_in_case_dir("my_xtension")?;
+/// exporter.export_all()?;
+/// ```
+pub struct SqliteExporter {
+    conn: Connection,
+    path: PathBuf,
+    batch_size: usize,
+}
+
+impl SqliteExporter {
+    /// opens the database or creates it if it does not exist. Fails if the database was created
+    /// for a different case.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SqliteExporter, XwfError> {
+        let conn = Connection::open(path.as_ref())?;
+        conn.execute_batch(SCHEMA)?;
+
+        let case_id = backend().get_case_prop(0, None).to_string();
+        let stored_id: Option<String> = conn
+            .query_row("SELECT value FROM meta WHERE key = 'case_id'", [], |row| row.get(0))
+            .optional()?;
+        match stored_id {
+            Some(id) if id != case_id => {
+                return Err(XwfError::DatabaseError(format!("{} was created for a different case", path.as_ref().display())));
+            },
+            Some(_) => {},
+            None => {
+                let mut title = String::new();
+                backend().get_case_prop(1, Some(&mut title));
+                conn.execute("INSERT INTO meta (key, value) VALUES ('schema_version', ?1), ('case_id', ?2), ('case_title', ?3)",
+                             params![SCHEMA_VERSION, case_id, title])?;
+            },
+        }
+
+        Ok(SqliteExporter {
+            conn,
+            path: path.as_ref().to_path_buf(),
+            batch_size: 10_000,
+        })
+    }
+
+    /// opens `<name>.sqlite` in the case directory, so that repeated runs continue the same export
+    pub fn open_in_case_dir(name: &str) -> Result<SqliteExporter, XwfError> {
+        SqliteExporter::open(case_dir(&*backend()).join(format!("{}.sqlite", name)))
+    }
+
+    /// number of items written per transaction
+    pub fn with_batch_size(mut self, batch_size: usize) -> SqliteExporter {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// true if all items and associations of the evidence have been exported
+    pub fn is_completed(&self, evidence: &Evidence) -> Result<bool, XwfError> {
+        is_completed(&self.conn, evidence)
+    }
+
+    /// exports the evidences with [`Case::visit_items`], skipping those completed already and
+    /// continuing interrupted ones. If the user aborts, all items written until then are kept.
+    /// Returns the number of items exported by this call.
+    pub fn export_evidences(&mut self, evidences: &[Evidence]) -> Result<usize, XwfError> {
+        self.export(evidences)
+    }
+
+    /// exports all evidences of the case
+    pub fn export_all(&mut self) -> Result<usize, XwfError> {
+        self.export(EvidenceIterator::new())
+    }
+
+    fn export<I>(&mut self, evidences: I) -> Result<usize, XwfError> where I: IntoIterator, I::Item: Borrow<Evidence> {
+        self.export_report_tables()?;
+
+        let mut writer = BatchWriter { conn: &self.conn, batch_size: self.batch_size, evidence: None, in_batch: 0, exported: 0 };
+        let result = Case::visit_items(evidences, "Exporting to SQLite", &mut writer);
+
+        // items written before an abort are kept and the export continues after the last
+        // complete item, the batch is rolled back on all other errors
+        if !self.conn.is_autocommit() {
+            match result {
+                Err(XwfError::OperationAbortedByUser) => writer.commit()?,
+                _ => self.conn.execute_batch("ROLLBACK")?,
+            }
+        }
+        result.map(|()| writer.exported)
+    }
+
+    fn export_report_tables(&mut self) -> Result<(), XwfError> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare("INSERT OR REPLACE INTO report_tables (report_table_id, name, flags) VALUES (?1, ?2, ?3)")?;
+            for table in Case::get_report_tables() {
+                stmt.execute(params![table.id, table.name, table.flags.bits()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn is_completed(conn: &Connection, evidence: &Evidence) -> Result<bool, XwfError> {
+    let completed: Option<bool> = conn
+        .query_row("SELECT completed FROM evidences WHERE evidence_id = ?1", [evidence.get_id()], |row| row.get(0))
+        .optional()?;
+    Ok(completed.unwrap_or(false))
+}
+
+// writes the items of an evidence in batches, each batch in its own transaction together with the
+// id of the next item to export
+struct BatchWriter<'a> {
+    conn: &'a Connection,
+    batch_size: usize,
+    // evidence id, tree and next item id of the evidence exported currently
+    evidence: Option<(u32, Arc<VolumeTree>, i32)>,
+    in_batch: usize,
+    exported: usize,
+}
+
+impl BatchWriter<'_> {
+    fn commit(&mut self) -> Result<(), XwfError> {
+        if let Some((evidence_id, _, next_item_id)) = &self.evidence {
+            self.conn.execute("UPDATE evidences SET next_item_id = ?1 WHERE evidence_id = ?2", params![next_item_id, evidence_id])?;
+        }
+        self.conn.execute_batch("COMMIT")?;
+        self.in_batch = 0;
+        Ok(())
+    }
+}
+
+impl ItemVisitor for BatchWriter<'_> {
+    fn begin_evidence(&mut self, ev: &Evidence, volume: &Volume, num_items: i32) -> Result<Option<i32>, XwfError> {
+        if is_completed(self.conn, ev)? {
+            return Ok(None);
+        }
+        let next_item_id = begin_evidence(self.conn, ev, num_items)?;
+        self.evidence = Some((ev.get_id(), VolumeTree::get(volume)?, next_item_id));
+        self.conn.execute_batch("BEGIN")?;
+        Ok(Some(next_item_id))
+    }
+
+    fn visit_item(&mut self, item: Item, volume: &Volume, ev: &Evidence) -> Result<(), XwfError> {
+        let Some((_, tree, next_item_id)) = &mut self.evidence else {
+            return Ok(());
+        };
+        write_item(self.conn, volume, ev, tree, &item)?;
+        *next_item_id = item.item_id + 1;
+        self.exported += 1;
+
+        self.in_batch += 1;
+        if self.in_batch == self.batch_size {
+            self.commit()?;
+            self.conn.execute_batch("BEGIN")?;
+        }
+        Ok(())
+    }
+
+    fn end_evidence(&mut self, ev: &Evidence, _volume: &Volume) -> Result<(), XwfError> {
+        self.commit()?;
+        self.evidence = None;
+
+        let tx = self.conn.unchecked_transaction()?;
+        write_report_table_assocs(&tx, ev)?;
+        tx.execute("UPDATE evidences SET completed = 1 WHERE evidence_id = ?1", [ev.get_id()])?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+// inserts or updates the evidence and returns the first item that has not been exported yet.
+// If the number of items changed since the interrupted export, the export starts over.
+fn begin_evidence(conn: &Connection, ev: &Evidence, num_items: i32) -> Result<i32, XwfError> {
+    let tx = conn.unchecked_transaction()?;
+    let stored: Option<(i32, i32)> = tx
+        .query_row("SELECT item_count, next_item_id FROM evidences WHERE evidence_id = ?1", [ev.get_id()],
+                   |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()?;
+
+    let next_item_id = match stored {
+        Some((item_count, next_item_id)) if item_count == num_items => next_item_id,
+        Some(_) => {
+            for table in ["items", "report_table_items", "hash_set_items", "comments"] {
+                tx.execute(&format!("DELETE FROM {} WHERE evidence_id = ?1", table), [ev.get_id()])?;
+            }
+            0
+        },
+        None => 0,
+    };
+
+    tx.execute("INSERT OR REPLACE INTO evidences (evidence_id, short_id, parent_id, name, file_system, item_count, next_item_id, completed) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0)",
+               params![ev.get_id(), ev.get_short_id(), ev.get_parent_id(), ev.get_name()?,
+                       ev.get_file_system().map(|fs| fs.to_string()), num_items, next_item_id])?;
+    tx.commit()?;
+    Ok(next_item_id)
+}
+
+fn write_item(conn: &Connection, volume: &Volume, ev: &Evidence, tree: &VolumeTree, item: &Item) -> Result<(), XwfError> {
+    let mut insert_item = conn.prepare_cached("INSERT OR REPLACE INTO items VALUES \
+        (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)")?;
+    let mut insert_hash_set = conn.prepare_cached("INSERT OR IGNORE INTO hash_sets (name) VALUES (?1)")?;
+    let mut insert_hash_set_item = conn.prepare_cached("INSERT OR IGNORE INTO hash_set_items \
+        SELECT hash_set_id, ?2, ?3 FROM hash_sets WHERE name = ?1")?;
+    let mut insert_comment = conn.prepare_cached("INSERT OR REPLACE INTO comments VALUES (?1, ?2, ?3)")?;
+
+    let time = |t: &Option<XwfDateTime>| t.as_ref().map(XwfDateTime::to_rfc3339);
+
+    let r = ItemRecord::new(item, volume, ev, RECORD_OPTIONS)?;
+    insert_item.execute(params![
+        r.evidence_id, r.item_id, tree.parent(item).map(|p| p.item_id), r.name, r.path,
+        r.size.map(|s| s as i64), r.flags.bits() as i64, r.attributes.bits(),
+        format!("{:?}", r.deletion), r.classification.to_string(),
+        time(&r.creation_time), time(&r.modification_time), time(&r.last_access_time),
+        time(&r.entry_modification_time), time(&r.deletion_time), time(&r.internal_creation_time),
+        r.file_type, r.file_type_description,
+        variant_name(&r.category), variant_name(&r.type_status), variant_name(&r.consistency),
+        r.hash1, r.hash2,
+    ])?;
+
+    for hash_set in &r.hash_sets {
+        insert_hash_set.execute([hash_set])?;
+        insert_hash_set_item.execute(params![hash_set, r.evidence_id, r.item_id])?;
+    }
+    if let Some(comment) = &r.comment {
+        insert_comment.execute(params![r.evidence_id, r.item_id, comment])?;
+    }
+    Ok(())
+}
+
+fn variant_name<T: Debug>(value: &Option<T>) -> Option<String> {
+    value.as_ref().map(|v| format!("{:?}", v))
+}
+
+fn write_report_table_assocs(tx: &Transaction, ev: &Evidence) -> Result<(), XwfError> {
+    let mut stmt = tx.prepare_cached("INSERT OR IGNORE INTO report_table_items VALUES (?1, ?2, ?3)")?;
+    require_function("XWF_GetEvObjReportTableAssocs")?;
+    // a missing list is treated as no associations
+    for (table_id, item_ids) in ev.get_report_table_assocs(false).unwrap_or_default() {
+        for item_id in item_ids {
+            stmt.execute(params![table_id, ev.get_id(), item_id])?;
+        }
+    }
+    Ok(())
+}
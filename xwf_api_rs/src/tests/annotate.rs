@@ -0,0 +1,112 @@
+use crate::annotate::*;
+use crate::error::XwfError;
+use crate::item::Item;
+use crate::xwf_types::*;
+use super::*;
+
+#[test]
+fn apply_annotations() -> Result<(), XwfError> {
+    let host = TestHost::new(case("two_files"));
+    let (a, b) = (Item::new(0), Item::new(1));
+    let mut annotator = ItemAnnotator::new();
+    annotator.tag(&a).hide(&a).untag(&a).mark_viewed(&a).tag(&a)
+        .set_comment(&a, "first")
+        .append_comment(&a, "second")
+        .append_extracted_metadata(&b, "key: value")
+        .set_type(&b, "Foo file", FileTypeStatus::Confirmed)
+        .set_classification(&b, ItemInfoClassification::EmailMessage)
+        .add_to_report_table(&b, "table", AddReportTableFlags::empty())
+        .set_hash_value(&b, &[1, 2, 3, 4], true);
+    assert_eq!(annotator.pending_count(), 12);
+
+    // nothing is written before applying
+    assert!(host.case().evidences[0].items[0].comment.is_none());
+    let summary = annotator.apply();
+    assert!(summary.is_success());
+    // the five flag changes of the first item are merged into one
+    assert_eq!(summary.succeeded, 8);
+    assert!(annotator.is_empty());
+
+    assert_eq!(a.get_item_info_flags()?, ItemInfoFlags::Tagged | ItemInfoFlags::HiddenByExaminer
+        | ItemInfoFlags::ViewedByExaminer | ItemInfoFlags::HasExaminerComment);
+    assert_eq!(a.get_comment()?.as_deref(), Some("first\nsecond"));
+    assert_eq!(b.get_extracted_metadata()?, Some(vec!["key: value".to_string()]));
+    assert_eq!(b.get_item_type(false)?, "Foo file");
+    assert_eq!(b.get_item_info_classification()?, ItemInfoClassification::EmailMessage);
+    assert_eq!(host.case().report_table_items("table"), vec![(1, 1)]);
+    assert_eq!(host.case().evidences[0].items[1].hash2, Some(vec![1, 2, 3, 4]));
+    Ok(())
+}
+
+#[test]
+fn failures_do_not_stop_other_annotations() {
+    let host = TestHost::new(case("two_files"));
+    let unknown = Item::new(99);
+    let mut annotator = ItemAnnotator::new();
+    annotator.tag(&unknown)
+        .add_to_report_table(&unknown, "table", AddReportTableFlags::empty())
+        .tag(&Item::new(0));
+    let summary = annotator.apply();
+    assert!(!summary.is_success());
+    assert_eq!(summary.succeeded, 1);
+
+    let failed: Vec<(i32, &Annotation)> = summary.failed.iter().map(|f| (f.item_id, &f.annotation)).collect();
+    assert_eq!(failed, vec![
+        (99, &Annotation::SetFlags(ItemInfoFlags::Tagged)),
+        (99, &Annotation::ReportTable { name: "table".to_string(), flags: AddReportTableFlags::empty() }),
+    ]);
+    assert!(matches!(summary.failed[1].error, XwfError::XwfFunctionCallFailed("add_to_report_table")));
+    assert_eq!(host.case().evidences[0].items[0].flags, ItemInfoFlags::Tagged);
+}
+
+#[test]
+fn discard_annotations() {
+    let host = TestHost::new(case("two_files"));
+    let mut annotator = ItemAnnotator::new();
+    annotator.set_comment(&Item::new(0), "comment");
+    annotator.discard();
+    assert_eq!(annotator.pending_count(), 0);
+    let summary = annotator.apply();
+    assert_eq!((summary.succeeded, summary.failed.len()), (0, 0));
+    assert!(host.case().evidences[0].items[0].comment.is_none());
+}
+
+fn comment(text: &str) -> Annotation {
+    Annotation::Comment { text: text.to_string(), flags: AddTextFlags::Append }
+}
+
+#[test]
+fn flags_are_merged() {
+    let merged = merge_flags(vec![
+        Annotation::SetFlags(ItemInfoFlags::Tagged),
+        comment("first"),
+        Annotation::SetFlags(ItemInfoFlags::HiddenByExaminer),
+        Annotation::RemoveFlags(ItemInfoFlags::ViewedByExaminer),
+        comment("second"),
+    ]);
+    assert_eq!(merged, vec![
+        Annotation::SetFlags(ItemInfoFlags::Tagged | ItemInfoFlags::HiddenByExaminer),
+        Annotation::RemoveFlags(ItemInfoFlags::ViewedByExaminer),
+        comment("first"),
+        comment("second"),
+    ]);
+}
+
+#[test]
+fn later_flag_changes_win() {
+    let merged = merge_flags(vec![
+        Annotation::SetFlags(ItemInfoFlags::Tagged | ItemInfoFlags::HiddenByExaminer),
+        Annotation::RemoveFlags(ItemInfoFlags::Tagged),
+        Annotation::RemoveFlags(ItemInfoFlags::ViewedByExaminer),
+        Annotation::SetFlags(ItemInfoFlags::ViewedByExaminer),
+    ]);
+    assert_eq!(merged, vec![
+        Annotation::SetFlags(ItemInfoFlags::HiddenByExaminer | ItemInfoFlags::ViewedByExaminer),
+        Annotation::RemoveFlags(ItemInfoFlags::Tagged),
+    ]);
+
+    // removing a flag set before still removes it, it may have been set already
+    let merged = merge_flags(vec![Annotation::SetFlags(ItemInfoFlags::Tagged), Annotation::RemoveFlags(ItemInfoFlags::Tagged)]);
+    assert_eq!(merged, vec![Annotation::RemoveFlags(ItemInfoFlags::Tagged)]);
+    assert!(merge_flags(Vec::new()).is_empty());
+}
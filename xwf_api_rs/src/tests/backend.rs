@@ -0,0 +1,25 @@
+use crate::backend::{has_backend, reset_backend, set_backend};
+use crate::item::Item;
+use super::*;
+
+#[test]
+fn api_calls_go_to_installed_backend() {
+    let host = TestHost::new(case("single_file"));
+    assert!(has_backend());
+    assert_eq!(Item::new(0).get_name().unwrap(), "a.txt");
+
+    set_backend(SimulatedHost::new(case("directory_tree")));
+    assert_eq!(Item::new(0).get_name().unwrap(), "root");
+    assert_eq!(host.case().evidences[0].items[0].name, "a.txt");
+
+    assert!(reset_backend().is_some());
+    assert!(!has_backend());
+    assert!(reset_backend().is_none());
+}
+
+#[cfg(not(windows))]
+#[test]
+fn xwf_backend_is_not_available() {
+    let _host = TestHost::new(case("single_file"));
+    assert!(crate::backend::install_xwf_backend("test").is_err());
+}
@@ -0,0 +1,118 @@
+use crate::capabilities::{capabilities, function_min_version, Capabilities};
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::traits::XTension;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+use super::*;
+
+// the case of the fixture on a host which does not export the functions
+fn case_without(unavailable_functions: Vec<&'static str>) -> SimCase {
+    let mut case = case("single_file");
+    case.unavailable_functions = unavailable_functions;
+    case
+}
+
+// collects the errors of the API calls instead of failing
+struct Extension {
+    errors: Vec<XwfError>,
+}
+
+impl XTension for Extension {
+    type XTensionError = XwfError;
+
+    fn create() -> Extension {
+        Extension { errors: Vec::new() }
+    }
+
+    fn xt_prepare(&mut self, volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        if let Err(e) = volume.unwrap().get_information() {
+            self.errors.push(e);
+        }
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::CallProcessItem))
+    }
+
+    fn xt_process_item(&mut self, item: Item) -> Result<XtProcessItemReturn, XwfError> {
+        let volume = Evidence::get_ev_obj(1).unwrap().open()?;
+        match item.open(&volume, OpenItemFlags::empty()) {
+            Ok(handle) => handle.close(),
+            Err(e) => self.errors.push(e),
+        }
+        Ok(XtProcessItemReturn::Ok)
+    }
+}
+
+#[test]
+fn unavailable_functions_return_error() {
+    let host = SimulatedHost::new(case_without(vec!["XWF_GetVolumeInformation", "XWF_OpenItem"]));
+    let mut driver: Driver<Extension> = Driver::new(host.clone());
+    assert_eq!(driver.init(XtInitFlags::empty()), 1);
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1]);
+    assert_eq!(report.processed_items(), vec![(1, 0)]);
+
+    let errors: Vec<&'static str> = driver.extension().errors.iter().map(|e| match e {
+        XwfError::FunctionNotAvailable(name) => *name,
+        e => panic!("unexpected error {}", e),
+    }).collect();
+    assert_eq!(errors, vec!["XWF_GetVolumeInformation", "XWF_OpenItem"]);
+
+    // XT_Init warns once about every missing function
+    let case = host.case();
+    let warnings: Vec<&str> = case.message_texts().into_iter().filter(|m| m.contains("is not exported")).collect();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].contains("XWF_GetVolumeInformation") && warnings[0].contains("21.3"));
+}
+
+#[test]
+fn all_functions_available() {
+    let host = SimulatedHost::new(case("single_file"));
+    let mut driver: Driver<Extension> = Driver::new(host.clone());
+    driver.init(XtInitFlags::empty());
+    driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1]);
+    assert!(driver.extension().errors.is_empty());
+    assert!(host.case().message_texts().iter().all(|m| !m.contains("is not exported")));
+}
+
+#[test]
+fn capabilities_of_backend() {
+    let _host = TestHost::new(case_without(vec!["XWF_GetEvent"]));
+    let caps = capabilities();
+    assert_eq!(caps.version().map(|v| (v.major, v.minor)), Some((21, 3)));
+    assert_eq!(caps.missing_functions(), &["XWF_GetEvent"]);
+    assert!(!caps.is_available("XWF_GetEvent"));
+    assert!(caps.is_available("XWF_AddEvent"));
+    assert!(matches!(caps.require("XWF_GetEvent"), Err(XwfError::FunctionNotAvailable("XWF_GetEvent"))));
+    assert!(caps.require("XWF_AddEvent").is_ok());
+
+    // functions missing from versions before their introduction are expected
+    assert_eq!(function_min_version("XWF_GetEvent"), Some((17, 6)));
+    let old = XtVersion { major: 17, minor: 5, service_release: 0, language: 0 };
+    assert!(caps.unexpectedly_missing(old).is_empty());
+    assert_eq!(caps.unexpectedly_missing(XWF_VERSION), vec!["XWF_GetEvent"]);
+    assert_eq!(Capabilities::with_missing(vec!["XWF_GetMetadataEx", "XWF_GetItemName"]).unexpectedly_missing(old), vec!["XWF_GetItemName"]);
+    assert!(Capabilities::all().missing_functions().is_empty());
+}
+
+#[test]
+fn item_getters_fail_without_export() -> Result<(), XwfError> {
+    let _host = TestHost::new(case_without(vec!["XWF_GetItemName", "XWF_GetItemSize", "XWF_GetComment", "XWF_GetMetadataEx",
+                                                "XWF_AddToReportTable", "XWF_Read"]));
+    let item = Item::new(0);
+    let missing = |e: XwfError| match e {
+        XwfError::FunctionNotAvailable(name) => name,
+        e => panic!("unexpected error {}", e),
+    };
+    assert_eq!(missing(item.get_name().unwrap_err()), "XWF_GetItemName");
+    assert_eq!(missing(item.get_size().unwrap_err()), "XWF_GetItemSize");
+    assert_eq!(missing(item.get_comment().unwrap_err()), "XWF_GetComment");
+    assert_eq!(missing(item.try_add_to_report_table("table", AddReportTableFlags::empty()).unwrap_err()), "XWF_AddToReportTable");
+
+    let volume = Evidence::get_ev_obj(1).unwrap().open()?;
+    let handle = item.open(&volume, OpenItemFlags::empty())?;
+    assert_eq!(missing(handle.get_metadata(false).unwrap_err()), "XWF_GetMetadataEx");
+    assert_eq!(missing(handle.read_chunk(0, 4).unwrap_err()), "XWF_Read");
+    assert_eq!(handle.get_name()?, "a.txt");
+    handle.close();
+    Ok(())
+}
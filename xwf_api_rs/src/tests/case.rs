@@ -0,0 +1,77 @@
+use crate::case::{Case, ItemVisitor};
+use crate::error::XwfError;
+use crate::evidence::{Evidence, EvidenceIterator};
+use crate::item::Item;
+use crate::volume::Volume;
+use super::*;
+
+// records the calls as "begin <ev>", "<ev>:<item>" and "end <ev>", starting each evidence at `first_item`
+struct Recorder {
+    first_item: Option<i32>,
+    calls: Vec<String>,
+}
+
+impl ItemVisitor for Recorder {
+    fn begin_evidence(&mut self, evidence: &Evidence, _volume: &Volume, num_items: i32) -> Result<Option<i32>, XwfError> {
+        self.calls.push(format!("begin {} ({} items)", evidence.get_id(), num_items));
+        Ok(self.first_item)
+    }
+
+    fn visit_item(&mut self, item: Item, _volume: &Volume, evidence: &Evidence) -> Result<(), XwfError> {
+        self.calls.push(format!("{}:{}", evidence.get_id(), item.item_id));
+        Ok(())
+    }
+
+    fn end_evidence(&mut self, evidence: &Evidence, _volume: &Volume) -> Result<(), XwfError> {
+        self.calls.push(format!("end {}", evidence.get_id()));
+        Ok(())
+    }
+}
+
+#[test]
+fn visit_items_visits_all_items() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("two_evidences"));
+    let mut recorder = Recorder { first_item: Some(0), calls: Vec::new() };
+    Case::visit_items(EvidenceIterator::new(), "visiting", &mut recorder)?;
+    assert_eq!(recorder.calls, ["begin 7 (4 items)", "7:0", "7:1", "7:2", "7:3", "end 7", "begin 8 (0 items)", "end 8"]);
+
+    // closures are visitors as well
+    let mut names = Vec::new();
+    Case::visit_items(&[Evidence::get_ev_obj(7).unwrap()], "visiting", &mut |item: Item, _: &Volume, _: &Evidence| {
+        names.push(item.get_name()?);
+        Ok(())
+    })?;
+    assert_eq!(names, ["root", "docs", "a.txt", "b.jpg"]);
+    Ok(())
+}
+
+#[test]
+fn visit_items_starts_at_first_item() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("two_evidences"));
+    let mut recorder = Recorder { first_item: Some(2), calls: Vec::new() };
+    Case::visit_items(EvidenceIterator::new(), "visiting", &mut recorder)?;
+    assert_eq!(recorder.calls, ["begin 7 (4 items)", "7:2", "7:3", "end 7", "begin 8 (0 items)", "end 8"]);
+
+    // skipped evidences are not ended
+    let mut recorder = Recorder { first_item: None, calls: Vec::new() };
+    Case::visit_items(EvidenceIterator::new(), "visiting", &mut recorder)?;
+    assert_eq!(recorder.calls, ["begin 7 (4 items)", "begin 8 (0 items)"]);
+    Ok(())
+}
+
+#[test]
+fn visit_items_stops_on_abort() {
+    let host = TestHost::new(case("two_evidences"));
+    host.case().stop_after = Some(2);
+    let mut recorder = Recorder { first_item: Some(0), calls: Vec::new() };
+    let res = Case::visit_items(EvidenceIterator::new(), "visiting", &mut recorder);
+    assert!(matches!(res, Err(XwfError::OperationAbortedByUser)));
+    assert_eq!(recorder.calls, ["begin 7 (4 items)", "7:0", "7:1"]);
+}
+
+#[test]
+fn iterate_collects_results() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("two_evidences"));
+    assert_eq!(Case::iterate(|item| item.get_name())?, ["root", "docs", "a.txt", "b.jpg"]);
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use crate::dfxml::*;
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::volume::HashType;
+use super::*;
+
+// the output without the start time, which changes with every run
+fn contents(buffer: &SharedBuffer) -> String {
+    String::from_utf8(buffer.contents()).unwrap().lines()
+        .filter(|l| !l.contains("<start_time>"))
+        .map(|l| format!("{}\n", l))
+        .collect()
+}
+
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<dfxml xmlns="http://www.forensicswiki.org/wiki/Category:Digital_Forensics_XML" xmlns:dc="http://purl.org/dc/elements/1.1/" version="1.2.0">
+  <metadata>
+    <dc:type>File system metadata</dc:type>
+    <dc:title>Case &lt;1&gt;</dc:title>
+  </metadata>
+  <creator>
+    <program>xwf-api-rs</program>
+    <version>1.0.0</version>
+    <execution_environment>
+    </execution_environment>
+  </creator>
+"#;
+
+const VOLUME: &str = r#"  <volume>
+    <!-- image- -1.e01 -->
+    <ftype_str>unknown</ftype_str>
+    <block_size>4096</block_size>
+"#;
+
+const ITEMS: &str = r#"    <fileobject>
+      <filename></filename>
+      <id>0</id>
+      <name_type>d</name_type>
+      <filesize>0</filesize>
+      <alloc>1</alloc>
+    </fileobject>
+    <fileobject>
+      <filename>a&amp;b &lt;c&gt;�.txt</filename>
+      <id>1</id>
+      <name_type>r</name_type>
+      <filesize>6</filesize>
+      <alloc>1</alloc>
+      <mtime>2022-06-18T04:26:40Z</mtime>
+      <byte_runs>
+        <byte_run file_offset="0" fs_offset="51200"/>
+      </byte_runs>
+      <hashdigest type="md5">abababababababababababababababab</hashdigest>
+      <hashdigest type="sha1">cdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd</hashdigest>
+    </fileobject>
+    <fileobject>
+      <filename>carved.jpg</filename>
+      <id>2</id>
+      <name_type>r</name_type>
+      <filesize>3</filesize>
+      <alloc>0</alloc>
+      <byte_runs>
+        <byte_run file_offset="0" fs_offset="8192" len="3"/>
+      </byte_runs>
+    </fileobject>
+"#;
+
+#[test]
+fn export_evidences() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("carved_file"));
+    let buffer = SharedBuffer::default();
+    let mut writer = DfxmlWriter::new(buffer.clone())?;
+    assert!(writer.path().is_none());
+    assert_eq!(writer.export_all()?, 3);
+    assert_eq!(writer.finish()?, 3);
+    assert_eq!(contents(&buffer), format!("{}{}{}  </volume>\n</dfxml>\n", HEADER, VOLUME, ITEMS));
+    Ok(())
+}
+
+#[test]
+fn write_single_items() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("carved_file"));
+    let evidence = Evidence::get_ev_obj(1).unwrap();
+    let volume = evidence.open()?;
+    volume.select()?;
+
+    let buffer = SharedBuffer::default();
+    let mut writer = DfxmlWriter::new(buffer.clone())?;
+    // the volume is started with the first item and ended by finish
+    writer.write_item(&Item::new(2), &volume, &evidence)?;
+    assert_eq!(writer.exported(), 1);
+    writer.finish()?;
+    let carved = &ITEMS[ITEMS.find("    <fileobject>\n      <filename>carved").unwrap()..];
+    assert_eq!(contents(&buffer), format!("{}{}{}  </volume>\n</dfxml>\n", HEADER, VOLUME, carved));
+
+    let buffer = SharedBuffer::default();
+    let mut writer = DfxmlWriter::new(buffer.clone())?;
+    writer.begin_volume(&volume, &evidence)?;
+    writer.end_volume()?;
+    writer.end_volume()?;
+    writer.finish()?;
+    assert_eq!(contents(&buffer), format!("{}{}  </volume>\n</dfxml>\n", HEADER, VOLUME));
+    Ok(())
+}
+
+#[test]
+fn aborted_export() -> Result<(), XwfError> {
+    let host = TestHost::new(case("carved_file"));
+    host.case().stop_after = Some(1);
+    let buffer = SharedBuffer::default();
+    let mut writer = DfxmlWriter::new(buffer.clone())?;
+    assert!(matches!(writer.export_all(), Err(XwfError::OperationAbortedByUser)));
+    // the document is still closed properly
+    assert_eq!(writer.finish()?, 1);
+    let first = &ITEMS[..ITEMS.find("    <fileobject>\n      <filename>a&amp;").unwrap()];
+    assert_eq!(contents(&buffer), format!("{}{}{}  </volume>\n</dfxml>\n", HEADER, VOLUME, first));
+    Ok(())
+}
+
+#[test]
+fn hash_algorithm_names() {
+    assert_eq!(hash_algorithm_name(HashType::MD5), "md5");
+    assert_eq!(hash_algorithm_name(HashType::SHA256), "sha256");
+    assert_eq!(hash_algorithm_name(HashType::TigerTreeHash), "tth");
+}
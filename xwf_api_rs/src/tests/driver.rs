@@ -0,0 +1,218 @@
+use std::sync::Mutex;
+use crate::application::Application;
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::dispatch::{into_view_buffer, release_view_buffer};
+use crate::item::{Item, ItemHandle};
+use crate::traits::{XTension, XTensionMt};
+use crate::volume::Volume;
+use crate::xwf_types::*;
+use super::*;
+
+// creates one extracted child item per archive and puts every processed item into a report table
+fn process(item: &Item) -> Result<XtProcessItemReturn, XwfError> {
+    let name = item.get_name()?;
+    if name.ends_with(".zip") {
+        let mut data = format!("extracted from {}", name).into_bytes();
+        item.create_file(&format!("{}.txt", name), FileCreationFlags::FileContentsFromBuffer,
+                         &mut SrcInfo::from_buffer(&mut data))?;
+    }
+    item.try_add_to_report_table("processed", AddReportTableFlags::empty())?;
+    Application::log(format!("processed {}", name));
+    Ok(XtProcessItemReturn::Ok)
+}
+
+struct Extension {
+    processed: Vec<i32>,
+}
+
+impl XTension for Extension {
+    type XTensionError = XwfError;
+
+    fn create() -> Extension {
+        Extension { processed: Vec::new() }
+    }
+
+    fn min_xwf_version() -> (u16, u16) {
+        (20, 5)
+    }
+
+    fn xt_prepare(&mut self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        Application::log("prepare");
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::CallProcessItem))
+    }
+
+    fn xt_process_item(&mut self, item: Item) -> Result<XtProcessItemReturn, XwfError> {
+        self.processed.push(item.item_id);
+        process(&item)
+    }
+
+    fn xt_finalize(&mut self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtFinalizeReturn, XwfError> {
+        Application::log("finalize");
+        Ok(XtFinalizeReturn::Ok)
+    }
+
+    fn xt_done(&mut self) -> Result<(), XwfError> {
+        Application::log("done");
+        Ok(())
+    }
+}
+
+struct ExtensionMt {
+    processed: Mutex<Vec<i32>>,
+}
+
+impl XTensionMt for ExtensionMt {
+    type XTensionError = XwfError;
+    type ThreadState = Vec<i32>;
+
+    fn create() -> ExtensionMt {
+        ExtensionMt { processed: Mutex::new(Vec::new()) }
+    }
+
+    fn create_thread_state(&self) -> Vec<i32> {
+        Vec::new()
+    }
+
+    fn thread_state_finished(&self, state: Vec<i32>) {
+        self.processed.lock().unwrap().extend(state);
+    }
+
+    fn xt_prepare(&self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        Application::log("prepare");
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::CallProcessItem))
+    }
+
+    fn xt_process_item(&self, state: &mut Vec<i32>, item: Item) -> Result<XtProcessItemReturn, XwfError> {
+        state.push(item.item_id);
+        process(&item)
+    }
+
+    fn xt_finalize(&self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtFinalizeReturn, XwfError> {
+        Application::log("finalize");
+        Ok(XtFinalizeReturn::Ok)
+    }
+
+    fn xt_done(&self) -> Result<(), XwfError> {
+        Application::log("done");
+        Ok(())
+    }
+}
+
+fn check_case(host: &SimulatedHost) {
+    let case = host.case();
+    assert_eq!(case.report_table_items("processed"), vec![(1, 1), (1, 2), (1, 3)]);
+
+    // created items are not processed again. Worker threads may create them in any order.
+    assert_eq!(case.created_items(), vec![(1, 4), (1, 5)]);
+    let evidence = case.evidence_by_id(1).unwrap();
+    let mut children: Vec<(i32, &str, &[u8])> = [4, 5].iter()
+        .map(|id| evidence.item(*id).unwrap())
+        .map(|item| (item.parent_id, item.name.as_str(), item.data.as_slice()))
+        .collect();
+    children.sort();
+    assert_eq!(children, vec![(2, "b.zip.txt", &b"extracted from b.zip"[..]), (3, "c.zip.txt", &b"extracted from c.zip"[..])]);
+}
+
+#[test]
+fn driver_runs_volume_snapshot_refinement() {
+    let host = SimulatedHost::new(case("directory_tree"));
+    let mut driver: Driver<Extension> = Driver::new(host.clone());
+
+    assert_eq!(driver.init(XtInitFlags::empty()), XtInitReturn::RunSingleThreaded as i32);
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[]);
+    assert_eq!(driver.extension().processed, vec![1, 2, 3]);
+    assert_eq!(driver.done(), 0);
+
+    let call_process_item = XtPreparePositiveReturnFlags::CallProcessItem.bits();
+    assert_eq!(report.prepare, vec![(Some(1), call_process_item)]);
+    assert_eq!(report.process_item, vec![(1, 1, 0), (1, 2, 0), (1, 3, 0)]);
+    assert_eq!(report.finalize, vec![(Some(1), 0)]);
+    assert!(!report.stopped);
+
+    check_case(&host);
+    assert_eq!(host.case().message_texts(), vec!["prepare", "processed a.txt", "processed b.zip", "processed c.zip", "finalize", "done"]);
+}
+
+#[test]
+fn driver_rejects_older_xwf_version() {
+    let host = SimulatedHost::new(case("directory_tree"));
+    let mut driver: Driver<Extension> = Driver::new(host.clone()).with_version(20, 4);
+
+    assert_eq!(driver.init(XtInitFlags::empty()), XtInitReturn::PreventFurtherUseOfDll as i32);
+    let messages = host.case().message_texts().join("\n");
+    assert!(messages.contains("minimal required version 20.5"), "{}", messages);
+}
+
+#[test]
+fn mt_driver_runs_volume_snapshot_refinement() {
+    let host = SimulatedHost::new(case("directory_tree"));
+    let mut driver: MtDriver<ExtensionMt> = MtDriver::new(host.clone()).with_threads(2);
+
+    assert_eq!(driver.init(XtInitFlags::empty()), XtInitReturn::RunMultiThreaded as i32);
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[]);
+
+    let call_process_item = XtPreparePositiveReturnFlags::CallProcessItem.bits();
+    assert_eq!(report.prepare, vec![(Some(1), call_process_item)]);
+    assert_eq!(report.process_item, vec![(1, 1, 0), (1, 2, 0), (1, 3, 0)]);
+    assert_eq!(report.finalize, vec![(Some(1), 0)]);
+    assert!(!report.stopped);
+
+    let extension = driver.extension().unwrap();
+    let mut processed = extension.processed.lock().unwrap().clone();
+    processed.sort();
+    assert_eq!(processed, vec![1, 2, 3]);
+    drop(extension);
+
+    assert_eq!(driver.done(), 0);
+    assert!(driver.extension().is_none());
+
+    // the items are processed concurrently, so only the order of prepare, finalize and done is fixed
+    check_case(&host);
+    let case = host.case();
+    let messages = case.message_texts();
+    assert_eq!(messages.len(), 6);
+    assert_eq!(messages[0], "prepare");
+    assert_eq!(messages[4..], ["finalize", "done"]);
+    let mut processed = messages[1..4].to_vec();
+    processed.sort();
+    assert_eq!(processed, vec!["processed a.txt", "processed b.zip", "processed c.zip"]);
+}
+
+// displays text files in upper case
+struct Viewer;
+
+impl XTension for Viewer {
+    type XTensionError = XwfError;
+
+    fn create() -> Viewer {
+        Viewer
+    }
+
+    fn xt_view(&mut self, item: ItemHandle, _volume: Option<Volume>, _evidence: Option<Evidence>) -> Result<Option<Vec<u8>>, XwfError> {
+        if !item.get_name()?.ends_with(".txt") {
+            return Ok(None);
+        }
+        let size = item.get_physical_size()? as usize;
+        Ok(item.read_chunk(0, size)?.map(|data| data.to_ascii_uppercase()))
+    }
+}
+
+#[test]
+fn driver_views_items() {
+    let host = SimulatedHost::new(case("directory_tree"));
+    let mut driver: Driver<Viewer> = Driver::new(host);
+    driver.init(XtInitFlags::empty());
+
+    assert_eq!(driver.view(1, 1), Some(b"A".to_vec()));
+    assert_eq!(driver.view(1, 2), None);
+}
+
+#[test]
+fn view_buffers_are_released_once() {
+    let (buffer, size) = into_view_buffer(b"data".to_vec());
+    assert_eq!(size, 4);
+    assert_eq!(unsafe { std::slice::from_raw_parts(buffer as *const u8, 4) }, b"data");
+    assert!(release_view_buffer(buffer));
+    assert!(!release_view_buffer(buffer));
+}
@@ -0,0 +1,82 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use crate::error::XwfError;
+use crate::event::Event;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::xwf_types::*;
+use super::*;
+
+fn utc() -> XwfDateTime {
+    XwfDateTime::Utc(Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 15).unwrap())
+}
+
+fn local() -> XwfDateTime {
+    XwfDateTime::NoTimezone(NaiveDate::from_ymd_opt(2020, 1, 2).unwrap().and_hms_opt(3, 4, 5).unwrap())
+}
+
+#[test]
+fn add_and_read_events() -> Result<(), XwfError> {
+    let host = TestHost::new(case("single_file"));
+    let evidence = Evidence::get_ev_obj(1).unwrap();
+    assert_eq!(evidence.events()?.count(), 0);
+
+    Event::new(&evidence, EventType::InternetVisit, utc(), "visited https://example.org")
+        .with_item(&Item::new(0))
+        .with_offset(42)
+        .with_flags(EventFlags::TimestampImprecise)
+        .add()?;
+    Event::new(&evidence, EventType::Other(777), local(), "log entry").add()?;
+
+    let events: Vec<Event> = evidence.events()?.collect();
+    assert_eq!(events.len(), 2);
+    assert_eq!((events[0].event_type, events[0].timestamp.clone()), (EventType::InternetVisit, utc()));
+    assert_eq!((events[0].item(), events[0].offset), (Some(Item::new(0)), 42));
+    assert_eq!(events[0].flags, EventFlags::TimestampImprecise);
+    assert_eq!(events[0].description, "visited https://example.org");
+    assert_eq!(events[0].evidence().map(|e| e.get_id()), Some(1));
+
+    // timestamps without time zone are marked as not in UTC
+    assert_eq!((events[1].event_type, events[1].timestamp.clone()), (EventType::Other(777), local()));
+    assert_eq!(events[1].flags, EventFlags::TimestampNotInUTC);
+    assert_eq!((events[1].item(), events[1].offset), (None, -1));
+
+    assert_eq!(Event::get(&evidence, 1), Some(events[1].clone()));
+    assert_eq!(Event::get(&evidence, 2), None);
+    assert_eq!(host.case().evidences[0].events.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn invalid_events() {
+    let host = TestHost::new(case("single_file"));
+    let evidence = Evidence::get_ev_obj(1).unwrap();
+    let unknown_item = Event::new(&evidence, EventType::Creation, utc(), "unknown").with_item(&Item::new(99));
+    assert!(matches!(unknown_item.add(), Err(XwfError::XwfFunctionCallFailed("add_event"))));
+    assert!(host.case().evidences[0].events.is_empty());
+
+    host.case().unavailable_functions = vec!["XWF_GetEvent", "XWF_AddEvent"];
+    assert!(matches!(Event::new(&evidence, EventType::Creation, utc(), "x").add(), Err(XwfError::FunctionNotAvailable("XWF_AddEvent"))));
+    assert!(matches!(evidence.events(), Err(XwfError::FunctionNotAvailable("XWF_GetEvent"))));
+    assert_eq!(Event::get(&evidence, 0), None);
+}
+
+#[test]
+fn raw_events() {
+    let _host = TestHost::new(case("single_file"));
+    let evidence = Evidence::get_ev_obj(1).unwrap();
+    let event = Event::new(&evidence, EventType::Deletion, local(), "deleted").with_item(&Item::new(0));
+    assert_eq!(event.raw_flags(), EventFlags::TimestampNotInUTC);
+
+    let info = EventInfo {
+        i_size: std::mem::size_of::<EventInfo>() as i32,
+        h_evidence: evidence.handle(),
+        n_evt_type: 5,
+        n_flags: event.raw_flags().bits(),
+        time_stamp: local().to_filetime(),
+        n_item_id: 0,
+        n_ofs: -1,
+        lp_descr: std::ptr::null_mut(),
+    };
+    // read events keep the flags passed to X-Ways Forensics
+    assert_eq!(Event::from_raw(&info, "deleted".to_string()), Some(event.with_flags(EventFlags::TimestampNotInUTC)));
+}
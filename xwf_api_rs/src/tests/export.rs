@@ -0,0 +1,109 @@
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::export::*;
+use crate::item::Item;
+use crate::xwf_types::*;
+use super::*;
+
+fn contents(buffer: &SharedBuffer) -> String {
+    String::from_utf8(buffer.contents()).unwrap()
+}
+
+const COLUMNS: [Column; 6] = [Column::UniqueId, Column::Name, Column::Path, Column::Size, Column::Hash1, Column::HashSets];
+
+#[test]
+fn csv_export() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("hashed_files"));
+    let buffer = SharedBuffer::default();
+    let mut exporter = ItemExporter::new(buffer.clone(), ExportFormat::Csv).with_columns(&COLUMNS);
+    assert_eq!(exporter.export_all()?, 3);
+    assert_eq!(exporter.finish()?, 3);
+
+    let short_id = Evidence::get_ev_obj(1).unwrap().get_short_id();
+    let expected = [
+        "\u{feff}unique_id,name,path,size,hash1,hash_sets".to_string(),
+        format!("{}-0,(Root directory),\\,0,,", short_id),
+        format!("{}-1,\"Grüße, \"\"ü\"\".txt\",\"\\Grüße, \"\"ü\"\".txt\",6,{},NSRL; known", short_id, "ab".repeat(16)),
+        format!("{}-2,plain.txt,\\plain.txt,1,,", short_id),
+    ];
+    assert_eq!(contents(&buffer), expected.join("\r\n") + "\r\n");
+    Ok(())
+}
+
+#[test]
+fn csv_options() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("hashed_files"));
+    let buffer = SharedBuffer::default();
+    let exporter = ItemExporter::new(buffer.clone(), ExportFormat::Csv)
+        .with_columns(&[Column::Name, Column::Size])
+        .with_delimiter(';')
+        .with_utf8_bom(false);
+    // the header is written even without records
+    assert_eq!(exporter.finish()?, 0);
+    assert_eq!(contents(&buffer), "name;size\r\n");
+
+    let buffer = SharedBuffer::default();
+    let mut exporter = ItemExporter::new(buffer.clone(), ExportFormat::Csv)
+        .with_columns(&[Column::Name, Column::Size])
+        .with_delimiter(';')
+        .with_utf8_bom(false);
+    let evidence = Evidence::get_ev_obj(1).unwrap();
+    exporter.export_item(&Item::new(1), &evidence.open()?, &evidence)?;
+    exporter.finish()?;
+    // the comma needs no quotes with another delimiter
+    assert_eq!(contents(&buffer), "name;size\r\n\"Grüße, \"\"ü\"\".txt\";6\r\n");
+    Ok(())
+}
+
+#[test]
+fn json_lines_export() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("hashed_files"));
+    let time = XwfDateTime::Utc(Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap());
+    let evidence = Evidence::get_ev_obj(1).unwrap();
+    let volume = evidence.open()?;
+    volume.select()?;
+    Item::new(2).set_item_info_time(XwfItemInfoTypes::ModificationTime, Some(&time))?;
+
+    let buffer = SharedBuffer::default();
+    let mut exporter = ItemExporter::new(buffer.clone(), ExportFormat::JsonLines)
+        .with_columns(&[Column::ItemId, Column::Name, Column::ModificationTime, Column::Hash1, Column::HashSets, Column::Deletion]);
+    exporter.export_evidences(&[evidence])?;
+    assert_eq!(exporter.exported(), 3);
+    exporter.finish()?;
+
+    let rows: Vec<Value> = contents(&buffer).lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[1], json!({"item_id": 1, "name": "Grüße, \"ü\".txt", "modification_time": null,
+        "hash1": "ab".repeat(16), "hash_sets": ["NSRL", "known"], "deletion": "Existing"}));
+    assert_eq!(rows[2]["modification_time"], "2024-01-02T03:04:05Z");
+    // the keys are in the order of the columns
+    assert_eq!(contents(&buffer).lines().next(), Some(concat!(r#"{"item_id":0,"name":"(Root directory)","modification_time":null,"#,
+        r#""hash1":null,"hash_sets":[],"deletion":"Existing"}"#)));
+    Ok(())
+}
+
+#[test]
+fn columns_select_record_fields() {
+    assert_eq!(Column::ALL.len(), 29);
+    assert_eq!(Column::Hash2.options(), crate::record::RecordOptions::Hashes);
+    assert_eq!(Column::Name.options(), crate::record::RecordOptions::empty());
+    let exporter = ItemExporter::new(std::io::sink(), ExportFormat::Csv);
+    assert_eq!(exporter.columns(), &Column::DEFAULT);
+    assert!(exporter.path().is_none());
+}
+
+#[test]
+fn aborted_export_keeps_records() -> Result<(), XwfError> {
+    let host = TestHost::new(case("hashed_files"));
+    host.case().stop_requested = true;
+    let buffer = SharedBuffer::default();
+    let mut exporter = ItemExporter::new(buffer.clone(), ExportFormat::JsonLines).with_columns(&[Column::ItemId]);
+    let evidence = Evidence::get_ev_obj(1).unwrap();
+    exporter.export_item(&Item::new(0), &evidence.open()?, &evidence)?;
+    assert!(matches!(exporter.export_all(), Err(XwfError::OperationAbortedByUser)));
+    assert_eq!(exporter.finish()?, 1);
+    assert_eq!(contents(&buffer), "{\"item_id\":0}\n");
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::fs;
+use std::path::PathBuf;
+use crate::error::XwfError;
+use crate::volume::HashType;
+use super::*;
+
+const CASE_TOML: &str = r#"
+title = "fixture"
+examiner = "examiner"
+creation_time = 133485408000000000
+user_inputs = ["42"]
+
+[[report_tables]]
+name = "declared"
+
+[[evidences]]
+id = 3
+title = "image.e01"
+
+[evidences.volume]
+hash_type1 = "MD5"
+data = "00 01 02 03"
+
+[[evidences.items]]
+name = "root"
+flags = "IsDirectory"
+
+[[evidences.items]]
+name = "inline.txt"
+parent = 0
+data = "68656c6c6f"
+hash1 = "000102030405060708090a0b0c0d0e0f"
+report_tables = ["declared", "implicit"]
+comment = "note"
+
+[[evidences.items]]
+name = "from_file.bin"
+parent = 0
+data_file = "data/content.bin"
+"#;
+
+// directory in the temp directory which is removed when the test ends
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!("xwf_fixture_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("data")).unwrap();
+        fs::write(path.join("data").join("content.bin"), b"file content").unwrap();
+        TempDir(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn item_fixture(name: &str) -> ItemFixture {
+    let mut fixture = CaseFixture::from_toml(CASE_TOML).unwrap();
+    let mut item = fixture.evidences[0].items.remove(1);
+    item.name = name.to_string();
+    item
+}
+
+#[test]
+fn round_trip() -> Result<(), XwfError> {
+    let dir = TempDir::new("round_trip");
+    let path = dir.0.join("case.toml");
+    fs::write(&path, CASE_TOML)?;
+
+    let case = CaseFixture::load(&path)?.to_case(&dir.0)?;
+    assert_eq!((case.title.as_str(), case.examiner.as_str()), ("fixture", "examiner"));
+    assert_eq!(case.report_table_items("implicit"), vec![(3, 1)]);
+    let ev = case.evidence_by_id(3).unwrap();
+    assert_eq!(ev.volume.data, vec![0, 1, 2, 3]);
+    assert_eq!(ev.volume.hash_type1, Some(HashType::MD5));
+    let item = ev.item(1).unwrap();
+    assert_eq!((item.data.as_slice(), item.size), (&b"hello"[..], 5));
+    assert_eq!(item.hash1, Some((0..16).collect()));
+
+    // saved fixtures store the data inline, loading them again gives the same case
+    let fixture = CaseFixture::from_case(&case);
+    assert_eq!(fixture.evidences[0].items[2].data.as_deref(), Some(hex::encode(b"file content").as_str()));
+    for name in ["saved.toml", "saved.json"] {
+        let saved = dir.0.join(name);
+        fixture.save(&saved)?;
+        let reloaded = CaseFixture::load(&saved)?;
+        assert_eq!(reloaded, fixture);
+        assert_eq!(CaseFixture::from_case(&reloaded.to_case(&dir.0)?), fixture);
+    }
+    assert!(fs::read_to_string(dir.0.join("saved.json"))?.trim_start().starts_with('{'));
+    Ok(())
+}
+
+#[test]
+fn data_file_is_relative_to_fixture() -> Result<(), XwfError> {
+    let dir = TempDir::new("data_file");
+    let nested = dir.0.join("data");
+    fs::write(nested.join("case.toml"), CASE_TOML.replace("data/content.bin", "content.bin"))?;
+    fs::write(dir.0.join("case.toml"), CASE_TOML)?;
+
+    for path in [nested.join("case.toml"), dir.0.join("case.toml")] {
+        let case = SimCase::from_fixture_file(&path)?;
+        assert_eq!(case.evidence_by_id(3).unwrap().item(2).unwrap().data, b"file content");
+    }
+
+    // resolved against the given directory, not the working directory
+    let fixture = CaseFixture::load(dir.0.join("case.toml"))?;
+    assert!(matches!(fixture.to_case(&nested), Err(XwfError::IoError(_))));
+
+    let mut item = item_fixture("both.bin");
+    item.data_file = Some(PathBuf::from("data/content.bin"));
+    let mut fixture = CaseFixture::from_toml(CASE_TOML)?;
+    fixture.evidences[0].items.push(item);
+    assert!(matches!(fixture.to_case(&dir.0), Err(XwfError::InvalidFixture(_))));
+    Ok(())
+}
+
+#[test]
+fn invalid_hex() {
+    let dir = TempDir::new("invalid_hex");
+    let with_item = |item: ItemFixture| {
+        let mut fixture = CaseFixture::from_toml(CASE_TOML).unwrap();
+        fixture.evidences[0].items.push(item);
+        fixture.to_case(&dir.0)
+    };
+
+    let mut item = item_fixture("odd.bin");
+    item.data = Some("abc".to_string());
+    assert!(matches!(with_item(item), Err(XwfError::InvalidFixture(_))));
+
+    let mut item = item_fixture("nonhex.bin");
+    item.data = Some("zz".to_string());
+    assert!(matches!(with_item(item), Err(XwfError::InvalidFixture(_))));
+
+    let mut item = item_fixture("hash.bin");
+    item.hash2 = Some("not a hash".to_string());
+    assert!(matches!(with_item(item), Err(XwfError::InvalidFixture(_))));
+
+    let mut fixture = CaseFixture::from_toml(CASE_TOML).unwrap();
+    fixture.evidences[0].volume.as_mut().unwrap().data = Some("0g".to_string());
+    assert!(matches!(fixture.to_case(&dir.0), Err(XwfError::InvalidFixture(_))));
+
+    // whitespace between the bytes is allowed
+    let mut item = item_fixture("spaced.bin");
+    item.data = Some("68 65\n6c 6c 6f".to_string());
+    assert_eq!(with_item(item).unwrap().evidence_by_id(3).unwrap().item(3).unwrap().data, b"hello");
+}
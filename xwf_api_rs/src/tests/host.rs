@@ -0,0 +1,119 @@
+use crate::application::Application;
+use crate::case::Case;
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::xwf_types::*;
+use super::*;
+
+#[test]
+fn items_and_hierarchy() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("two_evidences"));
+    let volume = Evidence::get_ev_obj(7).unwrap().open()?;
+    assert_eq!(volume.select()?, 4);
+
+    let a = Item::new(2);
+    assert_eq!(a.get_name()?, "a.txt");
+    assert_eq!(a.get_size()?, 11);
+    assert_eq!(a.get_path()?, "\\docs\\a.txt");
+    assert_eq!(a.get_parent_item()?, Some(Item::new(1)));
+    assert_eq!(a.get_parent_dir()?, Some(Item::new(1)));
+    assert_eq!(a.get_item_type(false)?, "txt");
+    assert_eq!(a.get_item_type(true)?, "Text");
+
+    // flags derived from the children of an item
+    let root_flags = Item::new(0).get_item_info_flags()?;
+    assert!(root_flags.contains(ItemInfoFlags::IsDirectory | ItemInfoFlags::HasChildObjects | ItemInfoFlags::HasSubDirectories));
+    assert!(!Item::new(1).get_item_info_flags()?.contains(ItemInfoFlags::HasSubDirectories));
+
+    assert_eq!(Item::new(3).get_hash_sets()?, vec!["known", "images"]);
+    assert_eq!(Item::new(0).get_hash_sets()?, Vec::<String>::new());
+
+    let handle = a.open(&volume, OpenItemFlags::empty())?;
+    assert_eq!(handle.read()?, b"hello world");
+    assert_eq!(handle.get_path()?, "\\docs\\a.txt");
+    handle.close();
+
+    // unknown items
+    assert_eq!(Item::new(99).get_name()?, "");
+    assert!(Item::new(99).open(&volume, OpenItemFlags::empty()).is_err());
+    Ok(())
+}
+
+#[test]
+fn item_modifications() -> Result<(), XwfError> {
+    let host = TestHost::new(case("two_evidences"));
+    let item = Item::new(2);
+
+    item.add_comment("first", AddTextFlags::empty())?;
+    item.add_comment("second", AddTextFlags::Append)?;
+    assert_eq!(item.get_comment()?.as_deref(), Some("first\nsecond"));
+    item.add_comment("replaced", AddTextFlags::empty())?;
+    assert_eq!(item.get_comment()?.as_deref(), Some("replaced"));
+
+    item.add_extracted_metadata("key: value", AddTextFlags::Append)?;
+    assert_eq!(item.get_extracted_metadata()?, Some(vec!["key: value".to_string()]));
+
+    item.try_add_to_report_table("table", AddReportTableFlags::CreatedByApplication)?;
+    item.try_add_to_report_table("table", AddReportTableFlags::empty())?;
+    Item::new(3).try_add_to_report_table("other", AddReportTableFlags::empty())?;
+    assert!(Item::new(99).try_add_to_report_table("table", AddReportTableFlags::empty()).is_err());
+    assert_eq!(item.__get_report_tables()?, vec!["table"]);
+
+    item.set_item_type("md", FileTypeStatus::MismatchDetected)?;
+    item.set_item_info_flags(ItemInfoFlags::Tagged, false)?;
+
+    let case = host.case();
+    assert_eq!(case.report_table_items("table"), vec![(7, 2)]);
+    assert_eq!(case.report_tables[0].flags, ReportTableFlags::HintByApplication);
+    assert_eq!(case.report_tables[1].flags, ReportTableFlags::CreatedByUser);
+    let sim_item = case.evidence_by_id(7).unwrap().item(2).unwrap();
+    assert_eq!((sim_item.type_short.as_str(), sim_item.type_status), ("md", FileTypeStatus::MismatchDetected));
+    assert!(sim_item.flags.contains(ItemInfoFlags::Tagged));
+    Ok(())
+}
+
+#[test]
+fn evidences_and_case() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("two_evidences"));
+
+    let evidences = Evidence::get_evidences().unwrap();
+    let ids: Vec<u32> = evidences.iter().map(|e| e.get_id()).collect();
+    assert_eq!(ids, vec![7, 8]);
+    assert_eq!(evidences[0].get_name()?, "image.e01");
+    assert!(Evidence::get_ev_obj(9).is_none());
+
+    let info = Case::get_case_infos()?;
+    assert_eq!((info.title.as_str(), info.examiner.as_str(), info.dir.as_str()), ("case", "examiner", "C:\\cases\\case"));
+    assert_eq!(info.creation_date.to_rfc3339(), "2024-01-01T00:00:00+00:00");
+    Ok(())
+}
+
+#[test]
+fn user_interaction() {
+    let host = TestHost::new(case("two_evidences"));
+    host.case().user_inputs.extend(["42".to_string(), String::new(), "text".to_string()]);
+
+    assert_eq!(Application::get_user_input_integer("number"), Some(42));
+    assert_eq!(Application::get_user_input_str("empty", false), None);
+    assert_eq!(Application::get_user_input_str("text", false).as_deref(), Some("text"));
+    assert_eq!(Application::get_user_input_integer("nothing left"), None);
+
+    Application::show_progress("caption", ProgressFlags::empty());
+    Application::set_progress_description("step");
+    Application::set_progress_percentage(1, 4);
+    {
+        let case = host.case();
+        assert!(case.progress.visible);
+        assert_eq!((case.progress.caption.as_str(), case.progress.description.as_str(), case.progress.percentage), ("caption", "step", 25));
+    }
+    Application::hide_progress();
+    assert!(!host.case().progress.visible);
+
+    assert!(Application::should_stop().is_ok());
+    host.case().stop_requested = true;
+    assert!(matches!(Application::should_stop(), Err(XwfError::OperationAbortedByUser)));
+
+    Application::log("message");
+    assert_eq!(host.case().message_texts(), vec!["message"]);
+}
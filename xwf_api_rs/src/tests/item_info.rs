@@ -0,0 +1,79 @@
+use chrono::{FixedOffset, TimeZone, Utc};
+use crate::error::XwfError;
+use crate::item::Item;
+use crate::xwf_types::*;
+use super::*;
+
+#[test]
+fn typed_getters() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("display_offsets"));
+    let item = Item::new(0);
+    assert_eq!(item.get_orig_id()?, 17);
+    assert_eq!(item.get_attributes()?, ItemInfoAttributes::empty());
+    assert_eq!(item.get_link_count()?, 1);
+    assert_eq!(item.get_color_analysis()?, ColorAnalysis::NotAnalyzed);
+    assert_eq!(item.get_pixel_index()?, None);
+    assert_eq!(item.get_file_count()?, 0);
+    assert_eq!(item.get_embedded_offset()?, None);
+
+    assert_eq!(item.get_time_display_offset(XwfItemInfoTypes::CreationTime)?, FixedOffset::east_opt(2 * 3600).unwrap());
+    assert_eq!(item.get_time_display_offset(XwfItemInfoTypes::ModificationTime)?, FixedOffset::west_opt(330 * 60).unwrap());
+    assert!(matches!(item.get_time_display_offset(XwfItemInfoTypes::Attr), Err(XwfError::InvalidInputArgument)));
+    Ok(())
+}
+
+#[test]
+fn typed_setters() -> Result<(), XwfError> {
+    let host = TestHost::new(case("display_offsets"));
+    let item = Item::new(0);
+    item.set_attributes(ItemInfoAttributes::WinAttrHidden | ItemInfoAttributes::WinAttrReadOnly)?;
+    item.set_link_count(3)?;
+    item.set_color_analysis(ColorAnalysis::SkinTone(42))?;
+    item.set_pixel_index(1200)?;
+    item.set_file_count(7)?;
+    item.set_embedded_offset(Some(4096))?;
+    item.set_item_info_deletion(ItemInfoDeletion::CarvedFile)?;
+    let time = XwfDateTime::Utc(Utc.with_ymd_and_hms(2023, 3, 4, 5, 6, 7).unwrap());
+    item.set_item_info_time(XwfItemInfoTypes::ModificationTime, Some(&time))?;
+    // only the six timestamps can be set as time
+    for infotype in [XwfItemInfoTypes::ModificationTimeDisplayOfs, XwfItemInfoTypes::FileCount] {
+        assert!(matches!(item.set_item_info_time(infotype, Some(&time)), Err(XwfError::InvalidInputArgument)));
+    }
+
+    assert_eq!(item.get_attributes()?, ItemInfoAttributes::WinAttrHidden | ItemInfoAttributes::WinAttrReadOnly);
+    assert_eq!(item.get_link_count()?, 3);
+    assert_eq!(item.get_color_analysis()?, ColorAnalysis::SkinTone(42));
+    assert_eq!(item.get_pixel_index()?, Some(1200));
+    assert_eq!(item.get_file_count()?, 7);
+    assert_eq!(item.get_embedded_offset()?, Some(4096));
+    assert_eq!(item.get_item_info_deletion()?, ItemInfoDeletion::CarvedFile);
+    let flags = item.get_item_info_flags()?;
+    assert_eq!(item.get_item_info_time(XwfItemInfoTypes::ModificationTime, false, &flags), Some(time));
+
+    item.set_embedded_offset(None)?;
+    item.set_color_analysis(ColorAnalysis::BlackAndWhite)?;
+    assert_eq!(item.get_embedded_offset()?, None);
+    assert_eq!(host.case().evidences[0].items[0].color_analysis, -2);
+    Ok(())
+}
+
+#[test]
+fn read_only_item_information() {
+    let host = TestHost::new(case("display_offsets"));
+    let item = Item::new(0);
+    for infotype in [XwfItemInfoTypes::OrigId, XwfItemInfoTypes::Flags, XwfItemInfoTypes::CreationTimeDisplayOfs] {
+        assert!(matches!(item.set_item_info(infotype, 5), Err(XwfError::InvalidInputArgument)));
+    }
+    assert_eq!(host.case().evidences[0].items[0].orig_id, 17);
+}
+
+#[test]
+fn color_analysis_values() {
+    for (value, color_analysis) in [(-1, ColorAnalysis::NotAnalyzed), (-2, ColorAnalysis::BlackAndWhite),
+                                    (0, ColorAnalysis::SkinTone(0)), (100, ColorAnalysis::SkinTone(100)),
+                                    (101, ColorAnalysis::Other(101)), (-3, ColorAnalysis::Other(-3))] {
+        assert_eq!(ColorAnalysis::from(value), color_analysis);
+        assert_eq!(i64::from(color_analysis), value);
+    }
+    assert_eq!(i64::from(ColorAnalysis::SkinTone(200)), 100);
+}
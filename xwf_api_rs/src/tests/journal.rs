@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use crate::annotate::ItemAnnotator;
+use crate::backend::{backend, XwfBackend};
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::journal::*;
+use crate::traits::XTension;
+use crate::tree::VolumeTree;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+use super::*;
+
+const EXTENSION_NAME: &str = "ext";
+
+// (restored, skipped kinds, failed) of an undone journal
+type Undone = (usize, BTreeMap<(&'static str, &'static str), usize>, usize);
+
+// modifies the first item of each evidence object, undoes the latest journal when run as action
+struct Extension {
+    undone: Vec<Option<Undone>>,
+}
+
+impl XTension for Extension {
+    type XTensionError = XwfError;
+
+    fn create() -> Extension {
+        Extension { undone: Vec::new() }
+    }
+
+    fn xt_prepare(&mut self, _volume: Option<Volume>, _evidence: Option<Evidence>, op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        if op_type == XtPrepareOpType::ActionRun {
+            let summary = undo_latest(EXTENSION_NAME)?;
+            self.undone.push(summary.map(|s| (s.restored, s.skipped_kinds(), s.failed.len())));
+            return Ok(XtPrepareReturn::Negative(XtPrepareNegativeReturn::JustCallXtFinalize));
+        }
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::CallProcessItem))
+    }
+
+    fn xt_process_item(&mut self, item: Item) -> Result<XtProcessItemReturn, XwfError> {
+        if item.item_id != 0 {
+            return Ok(XtProcessItemReturn::Ok);
+        }
+        let summary = ItemAnnotator::new()
+            .tag(&item)
+            .hide(&item)
+            .set_classification(&item, ItemInfoClassification::NtfsAlternateDataStream)
+            .append_comment(&item, "new")
+            .append_extracted_metadata(&item, "key: value")
+            .set_type(&item, "zzz", FileTypeStatus::MismatchDetected)
+            .add_to_report_table(&item, "table", AddReportTableFlags::empty())
+            .set_hash_value(&item, &[9; 16], false)
+            .apply();
+        assert!(summary.is_success());
+        item.set_link_count(5)?;
+        backend().set_item_size(0, 77);
+        backend().set_item_parent(0, 2);
+        let mut data = b"created".to_vec();
+        item.create_file("created.txt", FileCreationFlags::FileContentsFromBuffer, &mut SrcInfo::from_buffer(&mut data))?;
+        Ok(XtProcessItemReturn::Ok)
+    }
+}
+
+// case directory in the temp directory which is removed when the test ends
+struct CaseDir(PathBuf);
+
+impl CaseDir {
+    fn new(name: &str) -> CaseDir {
+        let path = std::env::temp_dir().join(format!("xwf_journal_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        CaseDir(path)
+    }
+
+    fn files(&self) -> Vec<String> {
+        let mut files: Vec<String> = fs::read_dir(&self.0).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+impl Drop for CaseDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// the case of the fixture, stored in the directory
+fn case_in(dir: &CaseDir) -> SimCase {
+    let mut case = case("annotated_items");
+    case.dir = dir.0.to_string_lossy().to_string();
+    case
+}
+
+// the state of the original items, report table associations and created files cannot be undone
+fn items(host: &SimulatedHost) -> Vec<String> {
+    host.case().evidences.iter()
+        .flat_map(|e| e.items.iter().filter(|i| !i.created))
+        .map(|i| format!("{:?}", SimItem { report_tables: Default::default(), ..i.clone() }))
+        .collect()
+}
+
+#[test]
+fn undo_restores_items() {
+    let dir = CaseDir::new("undo");
+    let host = SimulatedHost::new(case_in(&dir));
+    let original = items(&host);
+
+    let journal = dir.0.join(format!("{}_journal_20260101_000000_000.jsonl", EXTENSION_NAME));
+    {
+        let backend = JournalingBackend::create(host.clone(), &journal).unwrap();
+        let mut driver: Driver<Extension> = Driver::new(host.clone()).with_backend(backend);
+        driver.init(XtInitFlags::empty());
+        driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1, 2]);
+    }
+    assert_ne!(items(&host), original);
+    assert_eq!(host.case().created_items().len(), 2);
+
+    let entries = Journal::load(&journal).unwrap().entries().to_vec();
+    assert_eq!(entries.iter().filter(|e| e.evidence_id == Some(2)).count(), entries.len() / 2);
+    assert!(entries.iter().any(|e| matches!(&e.change,
+        JournalChange::HashValue { before: Some(before), .. } if *before == hex::encode([1; 16]))));
+
+    let mut driver: Driver<Extension> = Driver::new(host.clone());
+    driver.init(XtInitFlags::empty());
+    driver.run(XtPrepareOpType::ActionRun, &[]);
+    // the undone journal is not undone again
+    driver.run(XtPrepareOpType::ActionRun, &[]);
+    assert_eq!(items(&host), original);
+
+    let skipped = BTreeMap::from([
+        (("XWF_AddToReportTable", "report table associations cannot be removed"), 2),
+        (("XWF_CreateFile", "created files cannot be deleted"), 2),
+    ]);
+    let restored = entries.len() - 4;
+    assert_eq!(driver.extension().undone, vec![Some((restored, skipped, 0)), None]);
+    assert_eq!(dir.files(), vec![format!("{}_journal_20260101_000000_000.jsonl.undone", EXTENSION_NAME)]);
+
+    let case = host.case();
+    assert!(case.message_texts().iter().any(|m| m.contains("2 change(s) by XWF_CreateFile were not undone")));
+}
+
+// journal entries written by calling the backend directly
+fn journal_entries(buffer: &SharedBuffer) -> Vec<JournalChange> {
+    String::from_utf8(buffer.contents()).unwrap().lines()
+        .map(|l| serde_json::from_str::<JournalEntry>(l).unwrap().change)
+        .collect()
+}
+
+#[test]
+fn hash_values_of_the_volume_hash_type() {
+    let dir = CaseDir::new("hash");
+    let host = TestHost::new(case_in(&dir));
+    host.select_evidence(1);
+    let buffer = SharedBuffer::default();
+    let backend = JournalingBackend::new(host.clone(), buffer.clone());
+
+    assert!(backend.set_hash_value(0, &[2; 16], 1));
+    // the length does not match the volume hash type, the previous value is not read
+    assert!(backend.set_hash_value(0, &[3; 4], 1));
+    // no secondary hash type
+    assert!(backend.set_hash_value(0, &[4; 16], 2));
+
+    let befores: Vec<Option<String>> = journal_entries(&buffer).into_iter()
+        .map(|c| match c {
+            JournalChange::HashValue { before, .. } => before,
+            other => panic!("unexpected change {:?}", other),
+        })
+        .collect();
+    assert_eq!(befores, vec![Some(hex::encode([1; 16])), None, None]);
+}
+
+#[test]
+fn undo_skips_unknown_previous_values() {
+    let dir = CaseDir::new("skip");
+    let host = TestHost::new(case_in(&dir));
+    host.select_evidence(1);
+    let buffer = SharedBuffer::default();
+    let backend = JournalingBackend::new(host.clone(), buffer.clone());
+    assert!(backend.set_hash_value(0, &[2; 16], 2));
+    assert!(!backend.add_comment(99, "missing", 0));
+    assert!(backend.add_comment(0, "new", 0));
+
+    let path = dir.0.join("journal.jsonl");
+    fs::write(&path, buffer.contents()).unwrap();
+    let summary = Journal::load(&path).unwrap().undo();
+    assert_eq!(summary.restored, 1);
+    assert_eq!(summary.skipped.len(), 2);
+    // failed calls changed nothing and are not reported
+    assert_eq!(summary.skipped_kinds(), BTreeMap::from([(("XWF_SetHashValue", "no previous hash value"), 1)]));
+    assert_eq!(host.case().evidences[0].items[0].comment.as_deref(), Some("old"));
+    assert_eq!(host.case().evidences[0].items[0].hash2, Some(vec![2; 16]));
+}
+
+#[test]
+fn undo_moves_items_back() -> Result<(), XwfError> {
+    let dir = CaseDir::new("parent");
+    let mut case = case("directory_tree");
+    case.dir = dir.0.to_string_lossy().to_string();
+    let host = TestHost::new(case);
+    host.select_evidence(1);
+    let buffer = SharedBuffer::default();
+    let backend = JournalingBackend::new(host.clone(), buffer.clone());
+    backend.set_item_parent(1, 2);
+    // the root directory had no parent before
+    backend.set_item_parent(0, 3);
+
+    let volume = Evidence::get_ev_obj(1).unwrap().open()?;
+    assert_eq!(VolumeTree::get(&volume)?.parent(&Item::new(1)), Some(Item::new(2)));
+
+    let path = dir.0.join("journal.jsonl");
+    fs::write(&path, buffer.contents()).unwrap();
+    let summary = Journal::load(&path).unwrap().undo();
+    assert_eq!(summary.restored, 1);
+    assert_eq!(summary.skipped_kinds(), BTreeMap::from([(("XWF_SetItemParent", "previous parent unknown"), 1)]));
+    assert_eq!(host.case().evidences[0].items[0].parent_id, 3);
+
+    // the cached tree is outdated
+    assert_eq!(VolumeTree::get(&volume)?.parent(&Item::new(1)), Some(Item::new(0)));
+    Ok(())
+}
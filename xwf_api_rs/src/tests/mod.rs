@@ -0,0 +1,103 @@
+// tests of the crate, one module per tested module, run against the simulated host. The cases
+// are described by the fixture files in `fixtures`.
+
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use crate::backend::{lock_exclusive_backend, reset_backend, set_backend};
+use crate::capabilities::init_capabilities;
+use crate::simulator::*;
+use crate::xwf_types::XtVersion;
+
+mod annotate;
+mod backend;
+mod capabilities;
+mod case;
+mod dfxml;
+mod driver;
+mod event;
+#[cfg(feature = "export")]
+mod export;
+mod fixture;
+mod host;
+mod item_info;
+#[cfg(feature = "journal")]
+mod journal;
+mod mt;
+mod panic;
+mod reader;
+mod record;
+mod search;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod timeline;
+#[cfg(feature = "trace")]
+mod trace;
+mod tree;
+mod vfs;
+mod version;
+mod volume;
+mod wide_string;
+
+// version passed to XT_Init by the drivers
+const XWF_VERSION: XtVersion = XtVersion { major: 21, minor: 3, service_release: 0, language: 0 };
+
+// loads the case described by the fixture file `fixtures/<name>.toml`
+pub(super) fn case(name: &str) -> SimCase {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/fixtures").join(format!("{}.toml", name));
+    SimCase::from_fixture_file(&path).unwrap_or_else(|e| panic!("invalid fixture {}: {}", path.display(), e))
+}
+
+// installs a host as backend until it is dropped, for calling the API without running an X-Tension
+struct TestHost {
+    host: SimulatedHost,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl TestHost {
+    fn new(case: SimCase) -> TestHost {
+        let guard = lock_exclusive_backend();
+        let host = SimulatedHost::new(case);
+        set_backend(host.clone());
+        // the version of a previous test must not leak into this one
+        init_capabilities(XWF_VERSION);
+        // neither may the trees cached for its volume handles
+        crate::tree::invalidate();
+        TestHost { host, _guard: guard }
+    }
+}
+
+impl Deref for TestHost {
+    type Target = SimulatedHost;
+
+    fn deref(&self) -> &SimulatedHost {
+        &self.host
+    }
+}
+
+impl Drop for TestHost {
+    fn drop(&mut self) {
+        let _ = reset_backend();
+    }
+}
+
+// writer whose output stays accessible after it was moved into a backend
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn contents(&self) -> Vec<u8> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
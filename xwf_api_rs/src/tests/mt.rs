@@ -0,0 +1,154 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use crate::application::Application;
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::{Item, ItemHandle};
+use crate::mt::is_worker_thread;
+use crate::search::SearchTerm;
+use crate::traits::XTensionMt;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+use super::*;
+
+const ITEMS: i32 = 40;
+
+// item processed by a worker thread and what it observed
+#[derive(Debug, PartialEq)]
+struct Observation {
+    item_id: i32,
+    worker_thread: bool,
+    user_input: Option<String>,
+    // select, close and add_search_term failed with NotAllowedInWorkerThread
+    global_state_denied: bool,
+}
+
+// stops the operation when processing the item with id STOP_AT
+struct Extension<const STOP_AT: i32> {
+    thread_states: AtomicUsize,
+    observations: Mutex<Vec<Observation>>,
+    main_thread_callbacks: Mutex<Vec<bool>>,
+}
+
+impl<const STOP_AT: i32> XTensionMt for Extension<STOP_AT> {
+    type XTensionError = XwfError;
+    type ThreadState = Vec<Observation>;
+
+    fn create() -> Extension<STOP_AT> {
+        Extension {
+            thread_states: AtomicUsize::new(0),
+            observations: Mutex::new(Vec::new()),
+            main_thread_callbacks: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn create_thread_state(&self) -> Vec<Observation> {
+        self.thread_states.fetch_add(1, Ordering::SeqCst);
+        Vec::new()
+    }
+
+    fn thread_state_finished(&self, state: Vec<Observation>) {
+        self.observations.lock().unwrap().extend(state);
+    }
+
+    fn xt_prepare(&self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        self.main_thread_callbacks.lock().unwrap().push(!is_worker_thread());
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::CallProcessItem))
+    }
+
+    fn xt_process_item(&self, state: &mut Vec<Observation>, item: Item) -> Result<XtProcessItemReturn, XwfError> {
+        Application::show_progress("worker", ProgressFlags::empty());
+        let evidence = Evidence::get_ev_obj(1).unwrap();
+        let volume = evidence.open()?;
+        let denied = |res: Result<(), XwfError>| matches!(res, Err(XwfError::NotAllowedInWorkerThread(_)));
+        state.push(Observation {
+            item_id: item.item_id,
+            worker_thread: is_worker_thread(),
+            user_input: Application::get_user_input_str("input", true),
+            global_state_denied: denied(volume.select().map(|_| ()))
+                && denied(evidence.close())
+                && denied(SearchTerm::add("term", AddSearchTermFlags::empty()).map(|_| ())),
+        });
+        // gives the other threads a chance to pick up items
+        std::thread::sleep(Duration::from_millis(1));
+
+        if item.item_id == STOP_AT {
+            return Ok(XtProcessItemReturn::StopCurrentOperation);
+        }
+        Ok(XtProcessItemReturn::Ok)
+    }
+
+    fn xt_finalize(&self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtFinalizeReturn, XwfError> {
+        self.main_thread_callbacks.lock().unwrap().push(!is_worker_thread());
+        Ok(XtFinalizeReturn::Ok)
+    }
+}
+
+fn host() -> SimulatedHost {
+    let mut case = SimCase::new("case");
+    case.user_inputs.push_back("answer".to_string());
+    let mut evidence = SimEvidence::new(1, "image.e01");
+    for i in 0..ITEMS {
+        evidence.add_item(SimItem::file(format!("{}.txt", i), -1, vec![i as u8]));
+    }
+    case.add_evidence(evidence);
+    SimulatedHost::new(case)
+}
+
+#[test]
+fn worker_threads_process_all_items() {
+    let host = host();
+    let mut driver: MtDriver<Extension<-1>> = MtDriver::new(host.clone()).with_threads(4);
+    assert_eq!(driver.init(XtInitFlags::empty()), XtInitReturn::RunMultiThreaded as i32);
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1]);
+    assert_eq!(report.process_item.len(), ITEMS as usize);
+    assert!(!report.stopped);
+
+    // every item is processed exactly once and the states of all threads are handed back
+    let extension = driver.extension().unwrap();
+    let thread_states = extension.thread_states.load(Ordering::SeqCst);
+    assert!((1..=4).contains(&thread_states), "{}", thread_states);
+    let mut observations = std::mem::take(&mut *extension.observations.lock().unwrap());
+    observations.sort_by_key(|o| o.item_id);
+    let expected: Vec<Observation> = (0..ITEMS)
+        .map(|item_id| Observation { item_id, worker_thread: true, user_input: None, global_state_denied: true })
+        .collect();
+    assert_eq!(observations, expected);
+    assert_eq!(*extension.main_thread_callbacks.lock().unwrap(), vec![true, true]);
+
+    // user input and the progress bar are left to the main thread
+    let case = host.case();
+    assert_eq!(case.user_inputs.len(), 1);
+    assert!(!case.progress.visible);
+}
+
+#[test]
+fn item_callback_stops_operation() {
+    let host = host();
+    let mut driver: MtDriver<Extension<5>> = MtDriver::new(host.clone()).with_threads(2);
+    driver.init(XtInitFlags::empty());
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1]);
+    assert!(report.stopped);
+    assert!(report.process_item.len() < ITEMS as usize);
+    assert!(report.process_item.contains(&(1, 5, XtProcessItemReturn::StopCurrentOperation as i32)));
+    assert_eq!(report.finalize.len(), 1);
+    // the states of the threads are handed back even if the operation was stopped
+    assert_eq!(driver.extension().unwrap().observations.lock().unwrap().len(), report.process_item.len());
+
+    // a stop requested by the user ends the operation as well
+    host.case().stop_requested = true;
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1]);
+    assert!(report.stopped);
+    assert!(report.process_item.is_empty());
+}
+
+// the handle wrappers are shared with the worker threads, see their documentation
+#[test]
+fn handle_wrappers_are_send_and_sync() {
+    fn send_sync<T: Send + Sync>() {}
+    send_sync::<Volume>();
+    send_sync::<Evidence>();
+    send_sync::<Item>();
+    send_sync::<ItemHandle>();
+}
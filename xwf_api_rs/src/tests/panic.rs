@@ -0,0 +1,163 @@
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::panic_guard::PanicReturns;
+use crate::traits::{XTension, XTensionMt};
+use crate::volume::Volume;
+use crate::window::Window;
+use crate::xwf_types::*;
+use super::*;
+
+// callback that panics
+const INIT: u8 = 0;
+const PROCESS_ITEM: u8 = 1;
+const FINALIZE: u8 = 2;
+
+fn panic_in(callback: u8, at: u8) {
+    if callback == at {
+        panic!("panic in callback {}", callback);
+    }
+}
+
+struct Panicking<const AT: u8>;
+
+impl<const AT: u8> XTension for Panicking<AT> {
+    type XTensionError = XwfError;
+
+    fn create() -> Panicking<AT> {
+        Panicking
+    }
+
+    fn panic_returns() -> PanicReturns {
+        PanicReturns {
+            process_item: XtProcessItemReturn::SkipAllOtherVSRefinements,
+            finalize: XtFinalizeReturn::RefreshDirectoryListing,
+            ..PanicReturns::default()
+        }
+    }
+
+    fn xt_init(&mut self, _version: XtVersion, _flags: XtInitFlags, _window: Option<Window>, _lic_info: XtLicenseInfo) -> Result<XtInitReturn, XwfError> {
+        panic_in(INIT, AT);
+        Ok(XtInitReturn::RunSingleThreaded)
+    }
+
+    fn xt_prepare(&mut self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::CallProcessItem))
+    }
+
+    fn xt_process_item(&mut self, _item: Item) -> Result<XtProcessItemReturn, XwfError> {
+        panic_in(PROCESS_ITEM, AT);
+        Ok(XtProcessItemReturn::Ok)
+    }
+
+    fn xt_finalize(&mut self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtFinalizeReturn, XwfError> {
+        panic_in(FINALIZE, AT);
+        Ok(XtFinalizeReturn::Ok)
+    }
+}
+
+struct PanickingMt<const AT: u8>;
+
+impl<const AT: u8> XTensionMt for PanickingMt<AT> {
+    type XTensionError = XwfError;
+    type ThreadState = ();
+
+    fn create() -> PanickingMt<AT> {
+        PanickingMt
+    }
+
+    fn create_thread_state(&self) {}
+
+    fn xt_init(&self, _version: XtVersion, _flags: XtInitFlags, _window: Option<Window>, _lic_info: XtLicenseInfo) -> Result<XtInitReturn, XwfError> {
+        panic_in(INIT, AT);
+        Ok(XtInitReturn::RunMultiThreaded)
+    }
+
+    fn xt_prepare(&self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::CallProcessItem))
+    }
+
+    fn xt_process_item(&self, _state: &mut (), _item: Item) -> Result<XtProcessItemReturn, XwfError> {
+        panic_in(PROCESS_ITEM, AT);
+        Ok(XtProcessItemReturn::Ok)
+    }
+
+    fn xt_finalize(&self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtFinalizeReturn, XwfError> {
+        panic_in(FINALIZE, AT);
+        Ok(XtFinalizeReturn::Ok)
+    }
+}
+
+fn host() -> SimulatedHost {
+    let mut case = SimCase::new("case");
+    let mut evidence = SimEvidence::new(1, "image.e01");
+    evidence.add_item(SimItem::file("a.txt", -1, b"a".to_vec()));
+    evidence.add_item(SimItem::file("b.txt", -1, b"b".to_vec()));
+    case.add_evidence(evidence);
+    SimulatedHost::new(case)
+}
+
+// each panic is logged once, by the single guard around each callback
+fn logged_panics(host: &SimulatedHost, callback: &str) -> usize {
+    let prefix = format!("{}: X-Tension panicked at ", callback);
+    host.case().message_texts().iter().filter(|m| m.contains(&prefix) && m.contains("panic in callback")).count()
+}
+
+#[test]
+fn panic_in_init() {
+    let host = host();
+    let mut driver: Driver<Panicking<INIT>> = Driver::new(host.clone());
+    assert_eq!(driver.init(XtInitFlags::empty()), XtInitReturn::PreventFurtherUseOfDll as i32);
+    assert_eq!(logged_panics(&host, "XT_Init"), 1);
+}
+
+#[test]
+fn panic_in_process_item() {
+    let host = host();
+    let mut driver: Driver<Panicking<PROCESS_ITEM>> = Driver::new(host.clone());
+    driver.init(XtInitFlags::empty());
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1]);
+    // the custom return value does not stop the operation
+    assert_eq!(report.processed_items(), vec![(1, 0), (1, 1)]);
+    assert!(report.process_item.iter().all(|r| r.2 == XtProcessItemReturn::SkipAllOtherVSRefinements as i32));
+    assert_eq!(report.finalize, vec![(Some(1), XtFinalizeReturn::Ok as i32)]);
+    assert_eq!(logged_panics(&host, "XT_ProcessItem"), 2);
+}
+
+#[test]
+fn panic_in_finalize() {
+    let host = host();
+    let mut driver: Driver<Panicking<FINALIZE>> = Driver::new(host.clone());
+    driver.init(XtInitFlags::empty());
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1]);
+    assert_eq!(report.processed_items().len(), 2);
+    assert_eq!(report.finalize, vec![(Some(1), XtFinalizeReturn::RefreshDirectoryListing as i32)]);
+    assert_eq!(logged_panics(&host, "XT_Finalize"), 1);
+}
+
+#[test]
+fn panic_in_mt_callbacks() {
+    let host = host();
+    let mut driver: MtDriver<PanickingMt<INIT>> = MtDriver::new(host.clone());
+    assert_eq!(driver.init(XtInitFlags::empty()), XtInitReturn::PreventFurtherUseOfDll as i32);
+    assert_eq!(logged_panics(&host, "XT_Init"), 1);
+    drop(driver);
+
+    // the default return value stops the operation
+    let host = self::host();
+    let mut driver: MtDriver<PanickingMt<PROCESS_ITEM>> = MtDriver::new(host.clone()).with_threads(1);
+    driver.init(XtInitFlags::empty());
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1]);
+    assert_eq!(report.process_item.len(), 1);
+    assert_eq!(report.process_item[0].2, XtProcessItemReturn::StopCurrentOperation as i32);
+    assert!(report.stopped);
+    assert_eq!(logged_panics(&host, "XT_ProcessItem"), 1);
+    drop(driver);
+
+    let host = self::host();
+    let mut driver: MtDriver<PanickingMt<FINALIZE>> = MtDriver::new(host.clone());
+    driver.init(XtInitFlags::empty());
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1]);
+    assert_eq!(report.finalize, vec![(Some(1), XtFinalizeReturn::Ok as i32)]);
+    assert_eq!(logged_panics(&host, "XT_Finalize"), 1);
+}
@@ -0,0 +1,119 @@
+use std::io::{BufRead, ErrorKind, Read, Seek, SeekFrom};
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::reader::{ItemReader, VolumeReader};
+use crate::xwf_types::*;
+use super::*;
+
+// capacity of the readers, smaller than the data
+const CAPACITY: usize = 16;
+
+fn data() -> Vec<u8> {
+    (0..100).collect()
+}
+
+fn set_item_data(host: &SimulatedHost, data: Vec<u8>) {
+    host.case().evidences[0].items[0].data = data;
+}
+
+#[test]
+fn read_and_seek() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("truncated_file"));
+    let volume = Evidence::get_ev_obj(1).unwrap().open()?;
+    let handle = Item::new(0).open(&volume, OpenItemFlags::empty())?;
+    let mut reader = ItemReader::with_capacity(CAPACITY, &handle)?;
+    assert_eq!(reader.size(), 100);
+
+    let mut all = Vec::new();
+    assert_eq!(reader.read_to_end(&mut all)?, 100);
+    assert_eq!(all, data());
+
+    assert_eq!(reader.seek(SeekFrom::End(-10))?, 90);
+    let mut tail = Vec::new();
+    reader.read_to_end(&mut tail)?;
+    assert_eq!(tail, &data()[90..]);
+
+    assert_eq!(reader.seek(SeekFrom::Start(20))?, 20);
+    assert_eq!(reader.seek(SeekFrom::Current(-5))?, 15);
+    let mut buf = [0; 10];
+    reader.read_exact(&mut buf)?;
+    assert_eq!(buf, data()[15..25]);
+    assert_eq!(reader.fill_buf()?, &data()[25..31]);
+
+    // seeking before the start fails, seeking past the end reads nothing
+    assert_eq!(reader.seek(SeekFrom::End(-101)).unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(reader.seek(SeekFrom::Current(-26)).unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(reader.position(), 25);
+    assert_eq!(reader.seek(SeekFrom::End(10))?, 110);
+    assert_eq!(reader.read(&mut buf)?, 0);
+    assert!(reader.fill_buf()?.is_empty());
+    assert_eq!(reader.seek(SeekFrom::Start(1000))?, 1000);
+    assert_eq!(reader.read(&mut [0; 100])?, 0);
+    handle.close();
+    Ok(())
+}
+
+#[test]
+fn large_reads_bypass_buffer() -> Result<(), XwfError> {
+    let host = TestHost::new(case("truncated_file"));
+    let volume = Evidence::get_ev_obj(1).unwrap().open()?;
+    let handle = Item::new(0).open(&volume, OpenItemFlags::empty())?;
+    let mut reader = ItemReader::with_capacity(CAPACITY, &handle)?;
+
+    let mut small = [0; 4];
+    reader.read_exact(&mut small)?;
+    assert_eq!(small, [0, 1, 2, 3]);
+
+    // the buffer holds the data at the start, later reads outside of it see the changed data
+    set_item_data(&host, vec![0xFF; 100]);
+    reader.seek(SeekFrom::Start(50))?;
+    let mut large = [0; CAPACITY];
+    reader.read_exact(&mut large)?;
+    assert_eq!(large, [0xFF; CAPACITY]);
+
+    // reading directly into the output did not replace the buffer
+    reader.seek(SeekFrom::Start(4))?;
+    assert_eq!(reader.fill_buf()?, &data()[4..CAPACITY]);
+    handle.close();
+    Ok(())
+}
+
+#[test]
+fn missing_data_is_unexpected_eof() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("truncated_file"));
+    let volume = Evidence::get_ev_obj(1).unwrap().open()?;
+    let handle = Item::new(1).open(&volume, OpenItemFlags::empty())?;
+    let mut reader = ItemReader::with_capacity(CAPACITY, &handle)?;
+    assert_eq!(reader.size(), 150);
+
+    let mut all = Vec::new();
+    let err = reader.read_to_end(&mut all).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert!(matches!(err.get_ref().and_then(|e| e.downcast_ref::<XwfError>()), Some(XwfError::ReadItemDataFailed)));
+    assert_eq!(all, data());
+
+    // also for reads bypassing the buffer
+    reader.seek(SeekFrom::Start(120))?;
+    assert_eq!(reader.read(&mut [0; 2 * CAPACITY]).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    handle.close();
+    Ok(())
+}
+
+#[test]
+fn volume_reader() -> Result<(), XwfError> {
+    let host = TestHost::new(case("truncated_file"));
+    let volume = Evidence::get_ev_obj(1).unwrap().open()?;
+    let mut reader = VolumeReader::with_capacity(CAPACITY, &volume)?;
+    assert_eq!(reader.size(), 100);
+    let mut all = Vec::new();
+    reader.read_to_end(&mut all)?;
+    assert_eq!(all, data());
+
+    // the user aborted the operation
+    host.case().stop_requested = true;
+    reader.seek(SeekFrom::Start(0))?;
+    let err = reader.read(&mut [0; 2 * CAPACITY]).unwrap_err();
+    assert!(matches!(err.get_ref().and_then(|e| e.downcast_ref::<XwfError>()), Some(XwfError::OperationAbortedByUser)));
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use chrono::{TimeZone, Utc};
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::{Item, UniqueItemId};
+use crate::record::*;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+use super::*;
+
+fn open() -> (Volume, Evidence) {
+    let evidence = Evidence::get_ev_obj(1).unwrap();
+    (evidence.open().unwrap(), evidence)
+}
+
+fn time() -> XwfDateTime {
+    XwfDateTime::Utc(Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap())
+}
+
+#[test]
+fn all_fields() -> Result<(), XwfError> {
+    let host = TestHost::new(case("metadata"));
+    host.select_evidence(1);
+    let (volume, evidence) = open();
+    let item = Item::new(1);
+    item.set_item_info_time(XwfItemInfoTypes::ModificationTime, Some(&time()))?;
+    item.add_to_report_table("table", AddReportTableFlags::empty())?;
+
+    let record = item.to_record(&volume, &evidence, RecordOptions::all())?;
+    assert_eq!((record.evidence_id, record.item_id, record.name.as_str()), (1, 1, "a.jpg"));
+    assert_eq!(record.unique_id(), UniqueItemId { item_id: 1, evidence_id: 1, short_ev_id: record.short_ev_id });
+    assert_eq!(record.item(), item);
+    assert_eq!(record.path.as_deref(), Some("\\a.jpg"));
+    assert_eq!((record.size, record.physical_size), (Some(6), Some(6)));
+    assert_eq!((record.creation_time.clone(), record.modification_time.clone()), (None, Some(time())));
+    assert_eq!(record.file_type.as_deref(), Some("jpg"));
+    assert_eq!(record.file_type_description.as_deref(), Some("JPEG"));
+    assert_eq!(record.category, Some(FileTypeCategory::Picture));
+    assert_eq!(record.hash1, Some("ab".repeat(16)));
+    assert_eq!(record.hash2, None);
+    assert_eq!(record.report_tables, vec!["table"]);
+    assert_eq!(record.hash_sets, vec!["NSRL"]);
+    assert_eq!(record.comment.as_deref(), Some("note"));
+    assert_eq!(record.extracted_metadata, vec!["key: value", "other: value"]);
+    assert!(record.flags.contains(ItemInfoFlags::HasExaminerComment));
+    Ok(())
+}
+
+#[test]
+fn unselected_fields_are_empty() -> Result<(), XwfError> {
+    let host = TestHost::new(case("metadata"));
+    host.select_evidence(1);
+    let (volume, evidence) = open();
+    let record = Item::new(1).to_record(&volume, &evidence, RecordOptions::empty())?;
+    assert_eq!((record.name.as_str(), record.size), ("a.jpg", Some(6)));
+    assert_eq!((record.path, record.physical_size, record.modification_time), (None, None, None));
+    assert_eq!((record.file_type, record.category, record.hash1), (None, None, None));
+    assert!(record.report_tables.is_empty() && record.hash_sets.is_empty() && record.extracted_metadata.is_empty());
+    assert_eq!(record.comment, None);
+
+    let record = Item::new(1).to_record(&volume, &evidence, RecordOptions::Hashes | RecordOptions::Comment)?;
+    assert_eq!((record.hash1.is_some(), record.comment.is_some(), record.path), (true, true, None));
+    Ok(())
+}
+
+#[test]
+fn serialization() -> Result<(), XwfError> {
+    let host = TestHost::new(case("metadata"));
+    host.select_evidence(1);
+    let (volume, evidence) = open();
+    let item = Item::new(1);
+    item.set_item_info_time(XwfItemInfoTypes::ModificationTime, Some(&time()))?;
+    let record = item.to_record(&volume, &evidence, RecordOptions::all())?;
+    let json = serde_json::to_string(&record).unwrap();
+    assert_eq!(serde_json::from_str::<ItemRecord>(&json).unwrap(), record);
+
+    // fields that were not selected may be missing
+    let partial: ItemRecord = serde_json::from_str(r#"{"evidence_id": 1, "short_ev_id": 1, "item_id": 3, "name": "x",
+        "flags": "", "attributes": "", "deletion": "Existing", "classification": "NormalFile", "category": "pictures"}"#).unwrap();
+    assert_eq!(partial.category, Some(FileTypeCategory::Picture));
+    assert_eq!((partial.path, partial.hash1), (None, None));
+    assert!(partial.report_tables.is_empty());
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::search::*;
+use crate::traits::XTension;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+use super::*;
+
+// (item id, offset, search term, code page) of the search hits
+fn hits() -> Result<Vec<(i32, i64, String, u16)>, XwfError> {
+    Ok(SearchHit::iter()?
+        .map(|(_, hit)| (hit.item_id, hit.rel_ofs, hit.search_term().unwrap().name, hit.code_page))
+        .collect())
+}
+
+#[test]
+fn search_terms_and_hits() -> Result<(), XwfError> {
+    let host = TestHost::new(case("search_text"));
+    host.select_evidence(1);
+    let volume = Evidence::get_ev_obj(1).unwrap().open()?;
+
+    Search::new(["alpha"])
+        .with_flags(SearchFlags::Logical | SearchFlags::MatchCase)
+        .with_code_pages(&[CODE_PAGE_WINDOWS_1252])
+        .with_volume(&volume)
+        .run()?;
+    let alpha = |item_id, ofs, code_page| (item_id, ofs, "alpha".to_string(), code_page);
+    assert_eq!(hits()?, vec![alpha(0, 0, 1252), alpha(0, 17, 1252)]);
+    assert_eq!(SearchTerm::count()?, 1);
+    assert_eq!(SearchTerm::all()?, vec![SearchTerm { id: 0, name: "alpha".to_string() }]);
+
+    // ignoring the case and searching the default code pages finds new hits only
+    Search::new(["alpha"]).with_flags(SearchFlags::Logical).with_volume(&volume).run()?;
+    assert_eq!(hits()?, vec![alpha(0, 0, 1252), alpha(0, 17, 1252), alpha(0, 11, 1252), alpha(1, 6, 1200)]);
+    assert_eq!(SearchTerm::count()?, 1);
+
+    let mut hit = SearchHit::get(1).unwrap();
+    hit.flags |= SearchHitFlags::Notable;
+    hit.set(1)?;
+    assert_eq!(SearchHit::get(1).unwrap().flags, SearchHitFlags::Notable);
+    assert!(SearchHit::get(4).is_none());
+
+    let term = SearchTerm::add("manual", AddSearchTermFlags::empty())?;
+    assert_eq!(term.id, 1);
+    assert_eq!(SearchTerm::add("manual", AddSearchTermFlags::UseExisting)?, term);
+    assert_eq!(SearchTerm::find("manual")?, Some(term.clone()));
+    assert_eq!(SearchTerm::find("missing")?, None);
+
+    // adding the same hit twice
+    let hit = SearchHit::new(Item::new(1), 2, &term, 6, CODE_PAGE_UTF16_LE);
+    assert!(hit.add()?);
+    assert!(!hit.add()?);
+    assert_eq!(hits()?.last(), Some(&(1, 2, "manual".to_string(), 1200)));
+    Ok(())
+}
+
+#[test]
+fn invalid_searches() {
+    let host = TestHost::new(case("search_text"));
+    host.select_evidence(1);
+    assert!(matches!(Search::new(["a.*"]).with_flags(SearchFlags::Grep).run(), Err(XwfError::XwfFunctionCallFailed("search"))));
+    assert!(matches!(Search::new(Vec::<String>::new()).run(), Err(XwfError::InvalidInputArgument)));
+    assert!(host.case().evidences[0].search_hits.is_empty());
+}
+
+// searches for an additional term and stops after a number of hits
+struct Extension {
+    prepared: Vec<String>,
+    hits: Vec<(i32, String)>,
+    stop_after: usize,
+}
+
+impl XTension for Extension {
+    type XTensionError = XwfError;
+
+    fn create() -> Extension {
+        Extension { prepared: Vec::new(), hits: Vec::new(), stop_after: usize::MAX }
+    }
+
+    fn xt_prepare(&mut self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::empty()))
+    }
+
+    fn xt_prepare_search(&mut self, search: &mut PrepareSearch) -> Result<(), XwfError> {
+        self.prepared = search.search_terms.clone();
+        search.search_terms.push("beta".to_string());
+        search.flags |= SearchFlags::MatchCase | SearchFlags::CallProcessSearchHit;
+        Ok(())
+    }
+
+    fn xt_process_search_hit(&mut self, hit: SearchHit) -> Result<XtProcessSearchHitReturn, XwfError> {
+        self.hits.push((hit.item_id, hit.search_term().unwrap().name));
+        if self.hits.len() >= self.stop_after {
+            return Ok(XtProcessSearchHitReturn::StopSearch);
+        }
+        Ok(XtProcessSearchHitReturn::Ok)
+    }
+}
+
+#[test]
+fn driver_search() {
+    let host = SimulatedHost::new(case("search_text"));
+    let mut driver: Driver<Extension> = Driver::new(host.clone());
+    driver.init(XtInitFlags::empty());
+    let report = driver.search(&Search::new(["alpha"]).with_flags(SearchFlags::Logical), &[1]);
+    assert_eq!(report.prepare_search, Some(1));
+    assert!(!report.stopped);
+
+    let extension = driver.extension();
+    assert_eq!(extension.prepared, vec!["alpha"]);
+    let term = |item_id, name: &str| (item_id, name.to_string());
+    assert_eq!(extension.hits, vec![term(0, "alpha"), term(0, "alpha"), term(1, "alpha"), term(0, "beta")]);
+    assert_eq!(report.process_search_hit.len(), 4);
+}
+
+#[test]
+fn driver_search_stops() {
+    let host = SimulatedHost::new(case("search_text"));
+    let mut driver: Driver<Extension> = Driver::new(host.clone());
+    driver.init(XtInitFlags::empty());
+    driver.extension_mut().stop_after = 2;
+    let report = driver.search(&Search::new(["alpha"]).with_flags(SearchFlags::Logical), &[1]);
+    assert!(report.stopped);
+    let returns: Vec<i32> = report.process_search_hit.iter().map(|r| r.2).collect();
+    assert_eq!(returns, vec![XtProcessSearchHitReturn::Ok as i32, XtProcessSearchHitReturn::StopSearch as i32]);
+}
+
+#[test]
+fn search_hit_list_context_menu() {
+    let host = SimulatedHost::new(case("search_text"));
+    let mut driver: Driver<Extension> = Driver::new(host.clone());
+    driver.init(XtInitFlags::empty());
+    driver.search(&Search::new(["alpha"]).with_flags(SearchFlags::Logical), &[1]);
+    driver.extension_mut().hits.clear();
+
+    // only the selected search hits are processed
+    host.case().evidences[0].search_hit_selection = vec![3, 2];
+    let report = driver.run(XtPrepareOpType::SearchHitListContextMenu, &[1]);
+    assert_eq!(report.process_search_hit.len(), 2);
+    assert_eq!(driver.extension().hits, vec![(0, "beta".to_string()), (1, "alpha".to_string())]);
+}
+
+// runs a search with XT_ProcessSearchHit calls from xt_prepare, as in the example of `Search`
+struct Searcher {
+    hits: Vec<(i32, String)>,
+    searched: bool,
+}
+
+impl XTension for Searcher {
+    type XTensionError = XwfError;
+
+    fn create() -> Searcher {
+        Searcher { hits: Vec::new(), searched: false }
+    }
+
+    fn xt_prepare(&mut self, volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        Search::new(["alpha"])
+            .with_flags(SearchFlags::Logical | SearchFlags::MatchCase | SearchFlags::CallProcessSearchHit)
+            .with_code_pages(&[CODE_PAGE_WINDOWS_1252])
+            .with_volume(&volume.unwrap())
+            .run()?;
+        // the hits are passed to xt_process_search_hit after xt_prepare returned
+        assert!(self.hits.is_empty());
+        self.searched = true;
+        Ok(XtPrepareReturn::Negative(XtPrepareNegativeReturn::JustCallXtFinalize))
+    }
+
+    fn xt_process_search_hit(&mut self, hit: SearchHit) -> Result<XtProcessSearchHitReturn, XwfError> {
+        assert!(self.searched);
+        self.hits.push((hit.item_id, hit.search_term().unwrap().name));
+        Ok(XtProcessSearchHitReturn::Ok)
+    }
+}
+
+#[test]
+fn search_from_callback() {
+    let host = SimulatedHost::new(case("search_text"));
+    let mut driver: Driver<Searcher> = Driver::new(host.clone());
+    driver.init(XtInitFlags::empty());
+    let report = driver.run(XtPrepareOpType::ActionRun, &[1]);
+    assert_eq!(report.prepare, vec![(Some(1), XtPrepareNegativeReturn::JustCallXtFinalize as i32)]);
+    assert_eq!(driver.extension().hits, vec![(0, "alpha".to_string()), (0, "alpha".to_string())]);
+    assert_eq!(report.process_search_hit, vec![(1, 0, 0), (1, 0, 0)]);
+    assert!(host.case().message_texts().is_empty());
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::PathBuf;
+use rusqlite::params;
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::sqlite::SqliteExporter;
+use super::*;
+
+// database in the temp directory which is removed when the test ends
+struct TempDb(PathBuf);
+
+impl TempDb {
+    fn new(name: &str) -> TempDb {
+        let path = std::env::temp_dir().join(format!("xwf_sqlite_{}_{}.sqlite", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        TempDb(path)
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn query(exporter: &SqliteExporter, sql: &str) -> Vec<String> {
+    let mut stmt = exporter.connection().prepare(sql).unwrap();
+    stmt.query_map([], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+}
+
+// (next item id, completed) of the evidence
+fn progress(exporter: &SqliteExporter, evidence_id: u32) -> (i32, bool) {
+    exporter.connection()
+        .query_row("SELECT next_item_id, completed FROM evidences WHERE evidence_id = ?1", params![evidence_id],
+                   |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+}
+
+#[test]
+fn export_evidences() -> Result<(), XwfError> {
+    let db = TempDb::new("export");
+    let _host = TestHost::new(case("report_table"));
+    let mut exporter = SqliteExporter::open(&db.0)?.with_batch_size(3);
+    assert_eq!(exporter.export_all()?, 8);
+    assert!(exporter.is_completed(&Evidence::get_ev_obj(2).unwrap())?);
+    assert_eq!(progress(&exporter, 1), (4, true));
+
+    assert_eq!(query(&exporter, "SELECT evidence_id || ':' || item_id || ':' || ifnull(parent_id, '-') || ':' || path \
+                                 FROM items ORDER BY evidence_id, item_id"), vec![
+        "1:0:-:\\", "1:1:0:\\dir", "1:2:1:\\dir\\a.txt", "1:3:1:\\dir\\b.txt",
+        "2:0:-:\\", "2:1:0:\\dir", "2:2:1:\\dir\\a.txt", "2:3:1:\\dir\\b.txt",
+    ]);
+    assert_eq!(query(&exporter, "SELECT hash1 FROM items WHERE hash1 IS NOT NULL"), vec!["ab".repeat(16); 2]);
+    assert_eq!(query(&exporter, "SELECT h.name || ':' || evidence_id || ':' || item_id FROM hash_set_items JOIN hash_sets h USING (hash_set_id)"),
+               vec!["NSRL:1:2", "NSRL:2:2"]);
+    assert_eq!(query(&exporter, "SELECT comment FROM comments"), vec!["note", "note"]);
+    assert_eq!(query(&exporter, "SELECT r.name || ':' || evidence_id || ':' || item_id FROM report_table_items JOIN report_tables r USING (report_table_id)"),
+               vec!["relevant:1:2", "relevant:2:2"]);
+
+    // completed evidences are skipped
+    assert_eq!(exporter.export_all()?, 0);
+    Ok(())
+}
+
+#[test]
+fn resume_after_abort() -> Result<(), XwfError> {
+    let db = TempDb::new("resume");
+    let host = TestHost::new(case("report_table"));
+    // the user aborts while item 3 is exported, in the middle of the second batch
+    host.case().stop_after = Some(3);
+    let mut exporter = SqliteExporter::open(&db.0)?.with_batch_size(2);
+    assert!(matches!(exporter.export_all(), Err(XwfError::OperationAbortedByUser)));
+    assert_eq!(progress(&exporter, 1), (3, false));
+    assert_eq!(query(&exporter, "SELECT name FROM items ORDER BY item_id"), vec!["(Root directory)", "dir", "a.txt"]);
+    assert!(query(&exporter, "SELECT name FROM report_table_items JOIN report_tables USING (report_table_id)").is_empty());
+
+    // the export continues with the first item that was not written, also in a new connection
+    host.case().stop_requested = false;
+    host.case().stop_after = None;
+    let mut exporter = SqliteExporter::open(&db.0)?.with_batch_size(2);
+    assert_eq!(exporter.export_all()?, 5);
+    assert_eq!(progress(&exporter, 1), (4, true));
+    assert_eq!(query(&exporter, "SELECT count(*) || '' FROM items"), vec!["8"]);
+    assert_eq!(query(&exporter, "SELECT count(*) || '' FROM report_table_items"), vec!["2"]);
+    Ok(())
+}
+
+#[test]
+fn report_table_errors_are_reported() -> Result<(), XwfError> {
+    let db = TempDb::new("assocs");
+    let mut case = case("report_table");
+    case.unavailable_functions = vec!["XWF_GetEvObjReportTableAssocs"];
+    let _host = TestHost::new(case);
+    let mut exporter = SqliteExporter::open(&db.0)?;
+    assert!(matches!(exporter.export_all(), Err(XwfError::FunctionNotAvailable("XWF_GetEvObjReportTableAssocs"))));
+    // the items are kept, but the evidence is not completed without its associations
+    assert_eq!(progress(&exporter, 1), (4, false));
+    assert_eq!(query(&exporter, "SELECT count(*) || '' FROM items"), vec!["4"]);
+    Ok(())
+}
+
+#[test]
+fn missing_report_table_list() -> Result<(), XwfError> {
+    let db = TempDb::new("null_assocs");
+    let mut case = case("report_table");
+    case.null_report_table_assocs = true;
+    let _host = TestHost::new(case);
+    let mut exporter = SqliteExporter::open(&db.0)?;
+    // no list means no associations, the evidences are completed nevertheless
+    assert_eq!(exporter.export_all()?, 8);
+    assert_eq!(progress(&exporter, 1), (4, true));
+    assert!(query(&exporter, "SELECT name FROM report_table_items JOIN report_tables USING (report_table_id)").is_empty());
+    Ok(())
+}
+
+#[test]
+fn database_of_other_case() -> Result<(), XwfError> {
+    let db = TempDb::new("other");
+    let host = TestHost::new(case("report_table"));
+    SqliteExporter::open(&db.0)?;
+    host.case().id = 2;
+    assert!(matches!(SqliteExporter::open(&db.0), Err(XwfError::DatabaseError(_))));
+    Ok(())
+}
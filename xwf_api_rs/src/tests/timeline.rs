@@ -0,0 +1,123 @@
+use chrono::{FixedOffset, NaiveDate};
+use crate::error::XwfError;
+use crate::event::Event;
+use crate::timeline::*;
+use crate::xwf_types::*;
+use super::*;
+
+// the items of the fixture and an event of the first file
+fn case_with_event() -> SimCase {
+    let mut case = case("timestamps");
+    case.evidences[0].events.push(Event {
+        evidence: XwfHandle::NULL,
+        event_type: EventType::Creation,
+        flags: EventFlags::empty(),
+        timestamp: XwfDateTime::NoTimezone(NaiveDate::from_ymd_opt(2020, 3, 3).unwrap().and_hms_opt(3, 0, 0).unwrap()),
+        item_id: 1,
+        offset: -1,
+        description: "visited x".into(),
+    });
+    case
+}
+
+fn timeline(policy: NaiveTimePolicy) -> Result<Timeline, XwfError> {
+    Ok(TimelineBuilder::new().with_naive_time_policy(policy).with_events(true).add_all()?.build())
+}
+
+fn output(timeline: &Timeline, format: TimelineFormat) -> Result<String, XwfError> {
+    let buffer = SharedBuffer::default();
+    timeline.write(buffer.clone(), format)?;
+    Ok(String::from_utf8(buffer.contents()).unwrap())
+}
+
+#[test]
+fn sorted_entries() -> Result<(), XwfError> {
+    let _host = TestHost::new(case_with_event());
+    let timeline = timeline(NaiveTimePolicy::AssumeUtc)?;
+    let entries: Vec<_> = timeline.entries().iter()
+        .map(|e| (e.time.to_rfc3339(), e.source, e.item_id, e.time_zone_assumed))
+        .collect();
+    assert_eq!(entries, vec![
+        ("2019-01-01T10:00:00+00:00".to_string(), TimestampSource::LastAccess, 1, false),
+        ("2020-01-01T10:00:00+00:00".to_string(), TimestampSource::Creation, 1, false),
+        ("2020-03-03T03:00:00+00:00".to_string(), TimestampSource::Event(EventType::Creation), 1, true),
+        ("2020-06-01T12:00:00+00:00".to_string(), TimestampSource::Modification, 2, true),
+        ("2021-01-01T10:00:00+00:00".to_string(), TimestampSource::Modification, 1, false),
+    ]);
+
+    let event = &timeline.entries()[2];
+    assert_eq!(event.path, "\\a, \"b\".txt");
+    assert_eq!(event.description.as_deref(), Some("visited x"));
+    let fat = &timeline.entries()[3];
+    assert!(fat.is_deleted);
+    assert_eq!(fat.size, Some(1));
+    Ok(())
+}
+
+#[test]
+fn naive_time_policies() -> Result<(), XwfError> {
+    let _host = TestHost::new(case_with_event());
+    let offset = timeline(NaiveTimePolicy::Offset(FixedOffset::east_opt(2 * 3600).unwrap()))?;
+    let assumed: Vec<_> = offset.entries().iter()
+        .filter(|e| e.time_zone_assumed)
+        .map(|e| e.time.to_rfc3339())
+        .collect();
+    assert_eq!(assumed, vec!["2020-03-03T01:00:00+00:00", "2020-06-01T10:00:00+00:00"]);
+
+    let skip = timeline(NaiveTimePolicy::Skip)?;
+    assert_eq!(skip.len(), 3);
+    assert!(skip.entries().iter().all(|e| !e.time_zone_assumed));
+
+    let without_events = TimelineBuilder::new().add_all()?.build();
+    assert_eq!(without_events.len(), 4);
+    assert!(!without_events.entries().iter().any(|e| matches!(e.source, TimestampSource::Event(_))));
+    Ok(())
+}
+
+#[test]
+fn bodyfile() -> Result<(), XwfError> {
+    let _host = TestHost::new(case_with_event());
+    let timeline = timeline(NaiveTimePolicy::AssumeUtc)?;
+    // events have no bodyfile column and are left out
+    assert_eq!(output(&timeline, TimelineFormat::Bodyfile)?,
+               "0|\\a, \"b\".txt|1-1|r/r---------|0|0|6|1546336800|1609495200|0|1577872800\n\
+                0|\\fat.txt (deleted)|1-2|r/r---------|0|0|1|0|1591012800|0|0\n");
+    Ok(())
+}
+
+#[test]
+fn l2t_csv() -> Result<(), XwfError> {
+    let _host = TestHost::new(case_with_event());
+    let timeline = timeline(NaiveTimePolicy::AssumeUtc)?;
+    let csv = output(&timeline, TimelineFormat::L2tCsv)?;
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], "date,time,timezone,MACB,source,sourcetype,type,user,host,short,desc,version,filename,inode,notes,format,extra");
+    assert_eq!(lines[1], "01/01/2019,10:00:00,UTC,.A..,FILE,X-Ways Forensics file system,Last Access Time,-,-,\
+                          \"\\a, \"\"b\"\".txt\",\"\\a, \"\"b\"\".txt\",2,\"\\a, \"\"b\"\".txt\",1-1,-,xwf-api-rs,size: 6");
+    assert_eq!(lines[3], "03/03/2020,03:00:00,UTC,....,EVT,X-Ways Forensics event list,Event: creation,-,-,\
+                          visited x,visited x,2,\"\\a, \"\"b\"\".txt\",1-1,time zone assumed,xwf-api-rs,size: 6");
+    assert_eq!(lines[4], "06/01/2020,12:00:00,UTC,M...,FILE,X-Ways Forensics file system,Content Modification Time,-,-,\
+                          \\fat.txt,\\fat.txt,2,\\fat.txt,1-2,time zone assumed,xwf-api-rs,size: 1");
+    Ok(())
+}
+
+#[cfg(feature = "export")]
+#[test]
+fn json_lines() -> Result<(), XwfError> {
+    let _host = TestHost::new(case_with_event());
+    let timeline = timeline(NaiveTimePolicy::AssumeUtc)?;
+    let json = output(&timeline, TimelineFormat::JsonLines)?;
+    assert_eq!(json.lines().count(), 5);
+    assert_eq!(json.lines().nth(2), Some(r#"{"time":"2020-03-03T03:00:00Z","time_zone_assumed":true,"source":{"Event":"Creation"},"evidence_id":1,"short_ev_id":1,"item_id":1,"path":"\\a, \"b\".txt","size":6,"is_directory":false,"is_deleted":false,"description":"visited x"}"#));
+    let entries: Vec<TimelineEntry> = json.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(entries, timeline.entries());
+    Ok(())
+}
+
+#[test]
+fn aborted_timeline() {
+    let host = TestHost::new(case_with_event());
+    host.case().stop_after = Some(1);
+    assert!(matches!(TimelineBuilder::new().add_all(), Err(XwfError::OperationAbortedByUser)));
+}
@@ -0,0 +1,134 @@
+use std::io::{ErrorKind, Read};
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::ItemHandle;
+use crate::backend::XwfBackend;
+use crate::trace::{ReplayBackend, ReplayDriver, TraceHeader, TracingBackend, DEFAULT_MAX_BUFFER_LEN};
+use crate::traits::XTension;
+use crate::volume::{HashType, Volume};
+use crate::xwf_types::*;
+use super::*;
+
+// reads every item and its hash value completely and comments it
+struct Reader {
+    items: Vec<(String, Result<Vec<u8>, ErrorKind>)>,
+    hashes: Vec<Option<Vec<u8>>>,
+}
+
+impl XTension for Reader {
+    type XTensionError = XwfError;
+
+    fn create() -> Reader {
+        Reader { items: Vec::new(), hashes: Vec::new() }
+    }
+
+    fn xt_prepare(&mut self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::CallProcessItem))
+    }
+
+    fn xt_process_item_ex(&mut self, item: ItemHandle) -> Result<XtProcessItemExReturn, XwfError> {
+        let mut data = Vec::new();
+        let res = item.reader()?.read_to_end(&mut data).map(|_| data).map_err(|e| e.kind());
+        self.items.push((item.get_name()?, res));
+        self.hashes.push(item.item().get_hash_value(HashType::MD5, false)?);
+        item.item().add_comment("read", AddTextFlags::empty())?;
+        Ok(XtProcessItemExReturn::Ok)
+    }
+}
+
+fn large_data() -> Vec<u8> {
+    (0..3 * DEFAULT_MAX_BUFFER_LEN).map(|i| (i % 251) as u8).collect()
+}
+
+// the small item of the fixture and an item larger than the default maximum buffer length
+fn case_with_large_item() -> SimCase {
+    let mut case = case("small_file");
+    case.evidences[0].add_item(SimItem::file("large.bin", -1, large_data()));
+    case
+}
+
+// runs the X-Tension against the simulator and returns the trace and the returned callback values
+fn record(tracer: impl FnOnce(SimulatedHost, SharedBuffer) -> TracingBackend<SimulatedHost>) -> (Vec<u8>, Vec<(String, i32)>) {
+    let trace = SharedBuffer::default();
+    let host = SimulatedHost::new(case_with_large_item());
+    let mut driver: Driver<Reader> = Driver::new(host.clone())
+        .with_item_callback(ItemCallback::ProcessItemEx)
+        .with_backend(tracer(host.clone(), trace.clone()));
+
+    let mut callbacks = vec![("XT_Init".to_string(), driver.init(XtInitFlags::empty()))];
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[]);
+    callbacks.extend(report.prepare.iter().map(|(_, ret)| ("XT_Prepare".to_string(), *ret)));
+    callbacks.extend(report.process_item.iter().map(|(_, _, ret)| ("XT_ProcessItemEx".to_string(), *ret)));
+    callbacks.extend(report.finalize.iter().map(|(_, ret)| ("XT_Finalize".to_string(), *ret)));
+    {
+        // XT_Done drops the X-Tension
+        let extension = driver.extension();
+        let items: Vec<&str> = extension.items.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(items, vec!["small.txt", "large.bin"]);
+        assert!(extension.items.iter().all(|(_, res)| res.is_ok()));
+        assert_eq!(extension.hashes, vec![Some(vec![0xAB; 16]), None]);
+    }
+    callbacks.push(("XT_Done".to_string(), driver.done()));
+    assert_eq!(host.case().evidences[0].items[1].comment.as_deref(), Some("read"));
+
+    (trace.contents(), callbacks)
+}
+
+#[test]
+fn replay_reproduces_recorded_run() {
+    let (trace, recorded) = record(|host, out| TracingBackend::new(host, out).with_max_buffer_len(usize::MAX));
+
+    let mut driver: ReplayDriver<Reader> = ReplayDriver::new(ReplayBackend::from_reader(&trace[..]).unwrap());
+    assert_eq!(driver.run(), recorded);
+    assert_eq!(driver.backend().divergences(), Vec::<String>::new());
+    assert!(driver.backend().is_finished());
+
+    let items = &driver.extension().unwrap().items;
+    assert_eq!(items[0], ("small.txt".to_string(), Ok(b"small".to_vec())));
+    assert_eq!(items[1], ("large.bin".to_string(), Ok(large_data())));
+    assert_eq!(driver.extension().unwrap().hashes, vec![Some(vec![0xAB; 16]), None]);
+}
+
+#[test]
+fn replay_fails_reads_of_truncated_buffers() {
+    let (trace, recorded) = record(TracingBackend::new);
+
+    let mut driver: ReplayDriver<Reader> = ReplayDriver::new(ReplayBackend::from_reader(&trace[..]).unwrap());
+    assert_eq!(driver.run(), recorded);
+
+    // only the recorded part of the large item is returned, the rest was never read from the trace
+    let items = &driver.extension().unwrap().items;
+    assert_eq!(items[0], ("small.txt".to_string(), Ok(b"small".to_vec())));
+    assert_eq!(items[1], ("large.bin".to_string(), Err(ErrorKind::UnexpectedEof)));
+    let divergences = driver.backend().divergences();
+    assert!(divergences.iter().any(|d| d.starts_with("XWF_Read")), "{:?}", divergences);
+}
+
+#[test]
+fn replay_drops_truncated_hash_values() {
+    let (trace, _) = record(|host, out| TracingBackend::new(host, out).with_max_buffer_len(8));
+
+    let mut driver: ReplayDriver<Reader> = ReplayDriver::new(ReplayBackend::from_reader(&trace[..]).unwrap());
+    driver.run();
+    let extension = driver.extension().unwrap();
+    assert_eq!(extension.items[0], ("small.txt".to_string(), Ok(b"small".to_vec())));
+    assert_eq!(extension.hashes, vec![None, None]);
+}
+
+#[test]
+fn replay_reports_functions_missing_during_recording() {
+    let mut case = case("small_file");
+    case.unavailable_functions = vec!["XWF_GetMetadataEx", "XWF_AddEvent"];
+    let trace = SharedBuffer::default();
+    let _tracer = TracingBackend::new(SimulatedHost::new(case), trace.clone());
+
+    let contents = trace.contents();
+    let header: TraceHeader = serde_json::from_slice(contents.split(|b| *b == b'\n').next().unwrap()).unwrap();
+    assert_eq!(header.missing_functions, vec!["XWF_GetMetadataEx", "XWF_AddEvent"]);
+
+    let replay = ReplayBackend::from_reader(&contents[..]).unwrap();
+    assert!(replay.is_finished());
+    assert!(!replay.capabilities().is_available("XWF_GetMetadataEx"));
+    assert!(!replay.capabilities().is_available("XWF_AddEvent"));
+    assert!(replay.capabilities().is_available("XWF_Read"));
+}
@@ -0,0 +1,104 @@
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::tree::VolumeTree;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+use super::*;
+
+fn case_with_items(items: Vec<SimItem>) -> SimCase {
+    let mut case = case("empty_volume");
+    for item in items {
+        case.evidences[0].add_item(item);
+    }
+    case
+}
+
+fn volume() -> Volume {
+    Evidence::get_ev_obj(1).unwrap().open().unwrap()
+}
+
+fn ids(items: impl Iterator<Item = Item>) -> Vec<i32> {
+    items.map(|i| i.item_id).collect()
+}
+
+#[test]
+fn tree_of_volume() -> Result<(), XwfError> {
+    let _host = TestHost::new(case_with_items(vec![
+        SimItem::directory("(Root directory)", -1),
+        SimItem::directory("dir", 0),
+        SimItem::file("a.txt", 1, Vec::new()),
+        SimItem::directory("sub", 1),
+        SimItem::file("b.txt", 3, Vec::new()),
+        SimItem::file("c.txt", 0, Vec::new()),
+    ]));
+    let volume = volume();
+    let tree = VolumeTree::get(&volume)?;
+    assert_eq!(tree.len(), 6);
+    assert_eq!(ids(tree.roots()), vec![0]);
+    assert_eq!(ids(tree.children(&Item::new(1))), vec![2, 3]);
+    assert_eq!(ids(tree.ancestors(&Item::new(4))), vec![3, 1, 0]);
+    assert_eq!(tree.depth(&Item::new(4)), Some(3));
+    assert_eq!(tree.path(&Item::new(4)), Some("\\dir\\sub\\b.txt"));
+    for item_id in 0..6 {
+        let item = Item::new(item_id);
+        assert_eq!(tree.path(&item), Some(item.get_path()?.as_str()));
+    }
+
+    // depth-first, parents before their children and children in ascending order
+    assert_eq!(ids(tree.subtree(&Item::new(0))), vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(ids(tree.subtree(&Item::new(3))), vec![3, 4]);
+    assert_eq!(ids(tree.subtree(&Item::new(6))), Vec::<i32>::new());
+    assert!(tree.is_ancestor(&Item::new(1), &Item::new(4)));
+    assert!(!tree.is_ancestor(&Item::new(4), &Item::new(1)));
+    assert!(!tree.is_ancestor(&Item::new(5), &Item::new(4)));
+
+    // created items make the tree outdated
+    let mut data = b"new".to_vec();
+    let created = Item::new(3).create_file("new.txt", FileCreationFlags::FileContentsFromBuffer, &mut SrcInfo::from_buffer(&mut data))?;
+    assert!(!tree.is_valid());
+    let tree = VolumeTree::get(&volume)?;
+    assert_eq!(ids(tree.children(&Item::new(3))), vec![4, created.item_id]);
+    assert_eq!(tree.path(&created), Some("\\dir\\sub\\new.txt"));
+    Ok(())
+}
+
+#[test]
+fn orphans_are_top_level_items() -> Result<(), XwfError> {
+    let _host = TestHost::new(case_with_items(vec![
+        SimItem::directory("(Root directory)", -1),
+        // parents outside of the volume snapshot and the item itself
+        SimItem::directory("orphan", 99),
+        SimItem::file("a.txt", 1, Vec::new()),
+        SimItem::file("self", 3, Vec::new()),
+    ]));
+    let tree = VolumeTree::build(&volume())?;
+    assert_eq!(ids(tree.roots()), vec![0, 1, 3]);
+    assert_eq!(tree.parent(&Item::new(1)), None);
+    assert_eq!(tree.parent(&Item::new(2)), Some(Item::new(1)));
+    assert_eq!((tree.depth(&Item::new(2)), tree.path(&Item::new(2))), (Some(1), Some("\\a.txt")));
+    assert_eq!(tree.depth(&Item::new(3)), Some(0));
+    Ok(())
+}
+
+#[test]
+fn cycles_are_cut_once() -> Result<(), XwfError> {
+    // 2 -> 4 -> 3 -> 2 is a cycle, item 1 is below it
+    let _host = TestHost::new(case_with_items(vec![
+        SimItem::directory("(Root directory)", -1),
+        SimItem::file("below", 3, Vec::new()),
+        SimItem::directory("x", 4),
+        SimItem::directory("y", 2),
+        SimItem::directory("z", 3),
+    ]));
+    let tree = VolumeTree::build(&volume())?;
+    assert_eq!(ids(tree.roots()), vec![0, 2]);
+    assert_eq!(tree.parent(&Item::new(2)), None);
+    assert_eq!(tree.parent(&Item::new(1)), Some(Item::new(3)));
+    assert_eq!(tree.parent(&Item::new(4)), Some(Item::new(3)));
+    assert_eq!(ids(tree.subtree(&Item::new(2))), vec![2, 3, 1, 4]);
+    assert_eq!(tree.path(&Item::new(1)), Some("\\y\\below"));
+    assert_eq!(tree.depth(&Item::new(4)), Some(2));
+    assert_eq!(ids(tree.ancestors(&Item::new(1))), vec![3, 2]);
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::application::Application;
+use crate::capabilities::xwf_supports;
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::item::Item;
+use crate::traits::XTension;
+use crate::volume::{HashType, Volume};
+use crate::xwf_types::*;
+use super::*;
+
+// results of the version dependent API calls, formatted for comparison
+struct Extension {
+    results: Vec<String>,
+}
+
+impl XTension for Extension {
+    type XTensionError = XwfError;
+
+    fn create() -> Extension {
+        Extension { results: Vec::new() }
+    }
+
+    fn xt_prepare(&mut self, volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, XwfError> {
+        let volume = volume.unwrap();
+        self.results.push(format!("{:?}", volume.select()));
+        self.results.push(format!("{:?}", volume.get_item_count_dbc()));
+        self.results.push(format!("{:?}", volume.set_hash_type(HashType::MD5Folded, false)));
+        Application::output_string("message", OutputMessageFlags::LogToOutputWindow);
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::CallProcessItem | XtPreparePositiveReturnFlags::TargetFilesWithUnknownData))
+    }
+
+    fn xt_process_item(&mut self, item: Item) -> Result<XtProcessItemReturn, XwfError> {
+        self.results.push(format!("{:?}", item.get_item_info(XwfItemInfoTypes::CreationTimeDisplayOfs)));
+        Ok(XtProcessItemReturn::Ok)
+    }
+}
+
+fn run(major: u16, minor: u16) -> (Vec<String>, i32, u32) {
+    let mut case = SimCase::new("case");
+    let mut evidence = SimEvidence::new(1, "image.e01");
+    evidence.add_item(SimItem::file("a.txt", -1, b"data".to_vec()));
+    case.add_evidence(evidence);
+    let host = SimulatedHost::new(case);
+
+    let mut driver: Driver<Extension> = Driver::new(host.clone()).with_version(major, minor);
+    driver.init(XtInitFlags::empty());
+    let report = driver.run(XtPrepareOpType::ActionVolumeSnapshotRefinement, &[1]);
+    let case = host.case();
+    let message = case.messages.iter().find(|m| m.message == "message").unwrap();
+    let results = driver.extension().results.clone();
+    (results, report.prepare[0].1, message.flags)
+}
+
+#[test]
+fn newer_functionality_is_used_by_newer_versions() {
+    let (results, prepare, flags) = run(21, 3);
+    assert_eq!(results, vec!["Ok(1)", "Ok(0)", "Ok(())", "Ok(0)"]);
+    assert_eq!(prepare, (XtPreparePositiveReturnFlags::CallProcessItem | XtPreparePositiveReturnFlags::TargetFilesWithUnknownData).bits());
+    assert_eq!(flags, OutputMessageFlags::LogToOutputWindow.bits());
+}
+
+#[test]
+fn older_versions_degrade_gracefully() {
+    let (results, prepare, flags) = run(20, 0);
+    assert_eq!(results[0], "Ok(1)");
+    for result in &results[1..] {
+        assert!(result.starts_with("Err(RequiresXwfVersion("), "{}", result);
+    }
+    // flags unknown to the running version are not passed
+    assert_eq!(prepare, XtPreparePositiveReturnFlags::CallProcessItem.bits());
+    assert_eq!(flags, 0);
+}
+
+#[test]
+fn supports_version() {
+    let _host = TestHost::new(SimCase::new("case"));
+    assert!(xwf_supports(21, 3));
+    assert!(xwf_supports(20, 9));
+    assert!(!xwf_supports(21, 4));
+    assert!(!xwf_supports(22, 0));
+}
@@ -0,0 +1,54 @@
+use regex::Regex;
+use crate::vfs::{split_path, wildcard_regex};
+
+fn matches(pattern: &str, name: &str) -> bool {
+    Regex::new(&wildcard_regex(pattern)).unwrap().is_match(name)
+}
+
+#[test]
+fn wildcards() {
+    assert!(matches("*.evtx", "System.evtx"));
+    assert!(matches("*", ""));
+    assert!(matches("NTUSER.???", "NTUSER.DAT"));
+    assert!(!matches("NTUSER.???", "NTUSER.DA"));
+    assert!(!matches("*.evtx", "System.evtx.bak"));
+    assert!(matches("a*b*c", "abc"));
+    assert!(matches("a*b*c", "aXXbYYc"));
+}
+
+#[test]
+fn wildcards_do_not_cross_separators() {
+    assert!(!matches("a*", "a/b"));
+    assert!(!matches("a*", "a\\b"));
+    assert!(!matches("a?b", "a/b"));
+    // `**` within a name is the same as `*`
+    assert!(matches("a**", "abc"));
+    assert!(!matches("a**", "a/b"));
+}
+
+#[test]
+fn regex_characters_are_literal() {
+    assert!(matches("file(1).txt", "file(1).txt"));
+    assert!(!matches("file.txt", "fileXtxt"));
+    assert!(matches("[abc]+*", "[abc]+def"));
+    assert!(!matches("[abc]+*", "aaa"));
+    assert!(matches("^$|{1}*", "^$|{1}"));
+    assert!(matches("a\\d*", "a\\d"));
+}
+
+#[test]
+fn path_components() {
+    assert_eq!(split_path("\\Windows\\System32/config\\SYSTEM"), vec!["Windows", "System32", "config", "SYSTEM"]);
+    assert_eq!(split_path("//a\\\\b/"), vec!["a", "b"]);
+    assert_eq!(split_path("\\a\\.\\b\\..\\c"), vec!["a", "c"]);
+    assert!(split_path("").is_empty());
+    assert!(split_path("\\").is_empty());
+    assert_eq!(split_path("\\**\\*.evtx"), vec!["**", "*.evtx"]);
+}
+
+#[test]
+fn parent_of_root_is_root() {
+    assert!(split_path("..").is_empty());
+    assert_eq!(split_path("\\..\\..\\a"), vec!["a"]);
+    assert_eq!(split_path("a\\..\\..\\..\\b\\c"), vec!["b", "c"]);
+}
@@ -0,0 +1,115 @@
+use crate::backend::VolumeInformation;
+use crate::error::XwfError;
+use crate::evidence::Evidence;
+use crate::volume::{Volume, VolumeInfo};
+use crate::xwf_types::FileSystemType;
+use super::*;
+
+// 4 byte sectors, 8 byte clusters starting at sector 1. The volume ends within cluster 4.
+fn volume(ev_id: u32) -> Volume {
+    Evidence::get_ev_obj(ev_id).unwrap().open().unwrap()
+}
+
+fn bytes(start: u8, end: u8) -> Vec<u8> {
+    (start..end).collect()
+}
+
+#[test]
+fn read_sectors() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("volume_geometry"));
+    let volume = volume(1);
+    assert_eq!(volume.read_sectors(0, 2)?, bytes(0, 8));
+    assert_eq!(volume.read_sectors(9, 1)?, bytes(36, 40));
+    assert!(volume.read_sectors(3, 0)?.is_empty());
+
+    // short reads at the end of the volume
+    assert!(matches!(volume.read_sectors(10, 1), Err(XwfError::ReadVolumeDataFailed(42))));
+    assert!(matches!(volume.read_sectors(11, 1), Err(XwfError::ReadVolumeDataFailed(44))));
+    let mut data = [0; 8];
+    assert_eq!(volume.read_at(38, &mut data)?, 4);
+    assert_eq!(data[..4], bytes(38, 42));
+
+    // ranges beyond the address space
+    assert!(matches!(volume.read_sectors(u64::MAX / 2, 1), Err(XwfError::InvalidInputArgument)));
+    assert!(matches!(volume.read_sectors(u64::MAX / 4 - 1, 2), Err(XwfError::InvalidInputArgument)));
+    Ok(())
+}
+
+#[test]
+fn read_clusters() -> Result<(), XwfError> {
+    let _host = TestHost::new(case("volume_geometry"));
+    let volume = volume(1);
+    let info = volume.get_information()?;
+    assert_eq!((info.cluster_size(), info.cluster_count, info.first_cluster_sector), (8, 5, 1));
+
+    assert_eq!(volume.read_clusters(0, 1)?, bytes(4, 12));
+    assert_eq!(volume.read_clusters(1, 3)?, bytes(12, 36));
+
+    // the last cluster ends behind the end of the volume
+    assert!(matches!(volume.read_clusters(4, 1), Err(XwfError::ReadVolumeDataFailed(42))));
+    assert!(matches!(volume.read_clusters(3, 2), Err(XwfError::ReadVolumeDataFailed(42))));
+
+    // clusters beyond the cluster count and overflowing cluster numbers
+    assert!(matches!(volume.read_clusters(5, 1), Err(XwfError::InvalidInputArgument)));
+    assert!(matches!(volume.read_clusters(4, u32::MAX), Err(XwfError::InvalidInputArgument)));
+    assert!(matches!(volume.read_clusters(u64::MAX, 1), Err(XwfError::InvalidInputArgument)));
+    Ok(())
+}
+
+#[test]
+fn unknown_geometry() {
+    let _host = TestHost::new(case("volume_geometry"));
+    let volume = volume(2);
+    assert!(matches!(volume.read_sectors(0, 1), Err(XwfError::InvalidVolumeGeometry)));
+    assert!(matches!(volume.read_clusters(0, 1), Err(XwfError::InvalidVolumeGeometry)));
+}
+
+// 512 byte sectors, 4 KiB clusters starting at sector 63
+fn info() -> VolumeInfo {
+    VolumeInfo::from(VolumeInformation {
+        file_system: 6,
+        bytes_per_sector: 512,
+        sectors_per_cluster: 8,
+        cluster_count: 100,
+        first_cluster_sector_no: 63,
+    })
+}
+
+#[test]
+fn from_volume_information() {
+    let info = info();
+    assert_eq!(info.file_system, FileSystemType::Ntfs);
+    assert_eq!((info.bytes_per_sector, info.sectors_per_cluster), (512, 8));
+    assert_eq!((info.cluster_count, info.first_cluster_sector), (100, 63));
+    assert_eq!(VolumeInfo::from(VolumeInformation { file_system: 1000, ..Default::default() }).file_system, FileSystemType::Other(1000));
+}
+
+#[test]
+fn sizes_and_offsets() {
+    let info = info();
+    assert_eq!(info.cluster_size(), 4096);
+    assert_eq!(info.size(), 63 * 512 + 100 * 4096);
+    assert_eq!(info.sector_offset(2), 1024);
+    assert_eq!(info.cluster_to_sector(0), 63);
+    assert_eq!(info.cluster_to_sector(2), 79);
+    assert_eq!(info.cluster_offset(1), 71 * 512);
+}
+
+#[test]
+fn sector_and_cluster_of_offset() {
+    let info = info();
+    assert_eq!(info.sector_to_cluster(62), None);
+    assert_eq!(info.sector_to_cluster(63), Some(0));
+    assert_eq!(info.sector_to_cluster(70), Some(0));
+    assert_eq!(info.sector_to_cluster(71), Some(1));
+    assert_eq!(info.sector_of_offset(1023), Some(1));
+    assert_eq!(info.cluster_of_offset(info.cluster_offset(5) + 4095), Some(5));
+    assert_eq!(info.cluster_of_offset(0), None);
+
+    // unknown geometry
+    let unknown = VolumeInfo::from(VolumeInformation::default());
+    assert_eq!((unknown.cluster_size(), unknown.size()), (0, 0));
+    assert_eq!(unknown.sector_of_offset(100), None);
+    assert_eq!(unknown.sector_to_cluster(100), None);
+    assert_eq!(unknown.cluster_of_offset(100), None);
+}
@@ -0,0 +1,24 @@
+use crate::wide_string::WideString;
+
+#[test]
+fn round_trip() {
+    let s = WideString::from("Grüße 🦀");
+    assert_eq!(s.len(), 8);
+    assert_eq!(s.to_string(), "Grüße 🦀");
+    assert_eq!(unsafe { WideString::from_wchars_nullt(s.as_ptr()) }, s);
+}
+
+#[test]
+fn stops_at_null() {
+    let wchars: Vec<u16> = "abc\0def".encode_utf16().collect();
+    assert_eq!(WideString::from_wchars_slice(&wchars).to_string(), "abc");
+    assert_eq!(WideString::from_wchars_slice(&wchars[..2]).to_string(), "ab");
+}
+
+#[test]
+fn empty() {
+    assert!(WideString::default().is_empty());
+    assert!(WideString::from("").is_empty());
+    assert_eq!(unsafe { WideString::from_wchars_nullt(std::ptr::null()) }, WideString::default());
+    assert_eq!(unsafe { *WideString::default().as_ptr() }, 0);
+}
@@ -0,0 +1,380 @@
+//! Timeline of all item timestamps and (optionally) events of the event lists, normalised to UTC
+//! and sorted, written as Sleuthkit bodyfile, log2timeline CSV or JSON Lines (with feature `export`).
+
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use crate::backend::backend;
+use crate::case::{case_file, Case, ItemVisitor};
+use crate::error::XwfError;
+use crate::event::Event;
+use crate::evidence::{Evidence, EvidenceIterator};
+use crate::item::Item;
+use crate::record::{ItemRecord, RecordOptions};
+use crate::tree::VolumeTree;
+use crate::util::escape_csv;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+
+/// How timestamps without time zone are converted to UTC. X-Ways Forensics returns them for
+/// file systems that store local times, e.g. FAT ([`ItemInfoFlags::FilesystemTimestampsNotInUTC`]),
+/// and for events flagged with [`EventFlags::TimestampNotInUTC`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NaiveTimePolicy {
+    /// the timestamps are taken as UTC
+    AssumeUtc,
+    /// the timestamps are in the given time zone, e.g. the one of the examined system
+    Offset(FixedOffset),
+    /// the timestamps are in the local time zone of the examiner's system
+    Local,
+    /// the timestamps are left out of the timeline
+    Skip,
+}
+
+impl NaiveTimePolicy {
+    fn convert(&self, time: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            NaiveTimePolicy::AssumeUtc => Some(time.and_utc()),
+            NaiveTimePolicy::Offset(offset) => offset.from_local_datetime(time).single().map(|t| t.to_utc()),
+            // times in the repeated hour of the switch from daylight saving time are ambiguous
+            NaiveTimePolicy::Local => Local.from_local_datetime(time).earliest().map(|t| t.to_utc()),
+            NaiveTimePolicy::Skip => None,
+        }
+    }
+}
+
+/// Origin of a timeline entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimestampSource {
+    Creation,
+    Modification,
+    LastAccess,
+    EntryModification,
+    Deletion,
+    InternalCreation,
+    Event(EventType),
+}
+
+impl TimestampSource {
+    pub fn label(&self) -> String {
+        match self {
+            TimestampSource::Creation => "Creation Time".to_string(),
+            TimestampSource::Modification => "Content Modification Time".to_string(),
+            TimestampSource::LastAccess => "Last Access Time".to_string(),
+            TimestampSource::EntryModification => "Metadata Modification Time".to_string(),
+            TimestampSource::Deletion => "Deletion Time".to_string(),
+            TimestampSource::InternalCreation => "Internal Creation Time".to_string(),
+            TimestampSource::Event(event_type) => format!("Event: {}", event_type),
+        }
+    }
+
+    /// MACB notation of the Sleuthkit and log2timeline
+    pub fn macb(&self) -> &'static str {
+        match self {
+            TimestampSource::Modification => "M...",
+            TimestampSource::LastAccess => ".A..",
+            TimestampSource::EntryModification => "..C.",
+            TimestampSource::Creation => "...B",
+            _ => "....",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub time: DateTime<Utc>,
+    /// the timestamp had no time zone and was converted according to the [`NaiveTimePolicy`]
+    pub time_zone_assumed: bool,
+    pub source: TimestampSource,
+    pub evidence_id: u32,
+    pub short_ev_id: u16,
+    /// -1 for events that do not belong to an item
+    pub item_id: i32,
+    pub path: String,
+    pub size: Option<u64>,
+    pub is_directory: bool,
+    pub is_deleted: bool,
+    /// description of events
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineFormat {
+    /// Sleuthkit bodyfile (3.x) for `mactime`, one line per item with its MACB times
+    Bodyfile,
+    /// log2timeline CSV, one line per timeline entry
+    L2tCsv,
+    /// one JSON object per timeline entry
+    #[cfg(feature = "export")]
+    JsonLines,
+}
+
+impl TimelineFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TimelineFormat::Bodyfile => "body",
+            TimelineFormat::L2tCsv => "csv",
+            #[cfg(feature = "export")]
+            TimelineFormat::JsonLines => "jsonl",
+        }
+    }
+}
+
+/// Collects the timestamps of items and events into a [`Timeline`]
+///
+/// ```ignore
+/// let timeline = TimelineBuilder::new()
+///     .with_naive_time_policy(NaiveTimePolicy::Offset(FixedOffset::east_opt(3600).unwrap()))
+///     .with_events(true)
+///     .add_all()?
+///     .build();
+/// timeline.save_in_case_dir("my_xtension", TimelineFormat::L2tCsv)?;
+/// ```
+pub struct TimelineBuilder {
+    entries: Vec<TimelineEntry>,
+    naive_time_policy: NaiveTimePolicy,
+    events: bool,
+}
+
+impl Default for TimelineBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimelineBuilder {
+    pub fn new() -> TimelineBuilder {
+        TimelineBuilder {
+            entries: Vec::new(),
+            naive_time_policy: NaiveTimePolicy::AssumeUtc,
+            events: false,
+        }
+    }
+
+    pub fn with_naive_time_policy(mut self, policy: NaiveTimePolicy) -> TimelineBuilder {
+        self.naive_time_policy = policy;
+        self
+    }
+
+    /// also adds the events of the event lists of the evidences in [`TimelineBuilder::add_evidences`]
+    pub fn with_events(mut self, events: bool) -> TimelineBuilder {
+        self.events = events;
+        self
+    }
+
+    fn normalize(&self, time: &XwfDateTime) -> Option<(DateTime<Utc>, bool)> {
+        match time {
+            XwfDateTime::Utc(t) => Some((*t, false)),
+            XwfDateTime::Local(t) => Some((t.to_utc(), false)),
+            XwfDateTime::NoTimezone(t) => self.naive_time_policy.convert(t).map(|t| (t, true)),
+        }
+    }
+
+    /// adds the timestamps of the item, returns the number of added entries
+    pub fn add_item(&mut self, item: &Item, volume: &Volume, evidence: &Evidence) -> Result<usize, XwfError> {
+        let record = ItemRecord::new(item, volume, evidence, RecordOptions::Path | RecordOptions::Timestamps)?;
+        let count = self.entries.len();
+
+        for (source, time) in [
+            (TimestampSource::Creation, &record.creation_time),
+            (TimestampSource::Modification, &record.modification_time),
+            (TimestampSource::LastAccess, &record.last_access_time),
+            (TimestampSource::EntryModification, &record.entry_modification_time),
+            (TimestampSource::Deletion, &record.deletion_time),
+            (TimestampSource::InternalCreation, &record.internal_creation_time),
+        ] {
+            let Some((time, time_zone_assumed)) = time.as_ref().and_then(|t| self.normalize(t)) else {
+                continue;
+            };
+            self.entries.push(TimelineEntry {
+                time,
+                time_zone_assumed,
+                source,
+                evidence_id: record.evidence_id,
+                short_ev_id: record.short_ev_id,
+                item_id: record.item_id,
+                path: record.path.clone().unwrap_or_else(|| record.name.clone()),
+                size: record.size,
+                is_directory: record.flags.contains(ItemInfoFlags::IsDirectory),
+                is_deleted: record.deletion != ItemInfoDeletion::Existing,
+                description: None,
+            });
+        }
+        Ok(self.entries.len() - count)
+    }
+
+    /// adds the event, returns false if its timestamp was skipped
+    pub fn add_event(&mut self, event: &Event, volume: &Volume, evidence: &Evidence) -> Result<bool, XwfError> {
+        let Some((time, time_zone_assumed)) = self.normalize(&event.timestamp) else {
+            return Ok(false);
+        };
+
+        let tree = VolumeTree::get(volume)?;
+        let item = event.item().filter(|i| tree.contains(i));
+        self.entries.push(TimelineEntry {
+            time,
+            time_zone_assumed,
+            source: TimestampSource::Event(event.event_type),
+            evidence_id: evidence.get_id(),
+            short_ev_id: evidence.get_short_id(),
+            item_id: event.item_id,
+            path: item.and_then(|i| tree.path(&i)).unwrap_or_default().to_string(),
+            size: item.and_then(|i| u64::try_from(backend().get_item_size(i.item_id)).ok()),
+            is_directory: false,
+            is_deleted: false,
+            description: Some(event.description.clone()),
+        });
+        Ok(true)
+    }
+
+    /// adds all items (and events if enabled) of the evidences with [`Case::visit_items`]
+    pub fn add_evidences(self, evidences: &[Evidence]) -> Result<TimelineBuilder, XwfError> {
+        self.add(evidences)
+    }
+
+    /// adds all evidences of the case
+    pub fn add_all(self) -> Result<TimelineBuilder, XwfError> {
+        self.add(EvidenceIterator::new())
+    }
+
+    fn add<I>(mut self, evidences: I) -> Result<TimelineBuilder, XwfError> where I: IntoIterator, I::Item: Borrow<Evidence> {
+        Case::visit_items(evidences, "Building timeline", &mut self)?;
+        Ok(self)
+    }
+
+    /// sorts the entries by time
+    pub fn build(mut self) -> Timeline {
+        // stable, so entries of the same time keep the order of the items
+        self.entries.sort_by_key(|e| e.time);
+        Timeline { entries: self.entries }
+    }
+}
+
+impl ItemVisitor for TimelineBuilder {
+    fn visit_item(&mut self, item: Item, volume: &Volume, evidence: &Evidence) -> Result<(), XwfError> {
+        self.add_item(&item, volume, evidence).map(|_| ())
+    }
+
+    // the events of an evidence follow its items
+    fn end_evidence(&mut self, evidence: &Evidence, volume: &Volume) -> Result<(), XwfError> {
+        if self.events {
+            for event in Event::iter(evidence)? {
+                self.add_event(&event, volume, evidence)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Timeline entries in chronological order
+pub struct Timeline {
+    entries: Vec<TimelineEntry>,
+}
+
+impl Timeline {
+    pub fn entries(&self) -> &[TimelineEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn write<W: Write>(&self, out: W, format: TimelineFormat) -> Result<(), XwfError> {
+        let mut out = BufWriter::new(out);
+        match format {
+            TimelineFormat::Bodyfile => self.write_bodyfile(&mut out)?,
+            TimelineFormat::L2tCsv => self.write_l2t_csv(&mut out)?,
+            #[cfg(feature = "export")]
+            TimelineFormat::JsonLines => self.write_jsonl(&mut out)?,
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// writes the file `<name>_timeline_<timestamp>.<body|csv|jsonl>` to the case directory
+    /// and returns its path
+    pub fn save_in_case_dir(&self, name: &str, format: TimelineFormat) -> Result<PathBuf, XwfError> {
+        let path = case_file(&*backend(), &format!("{}_timeline", name), format.extension());
+        self.write(File::create(&path)?, format)?;
+        Ok(path)
+    }
+
+    // MD5|name|inode|mode_as_string|UID|GID|size|atime|mtime|ctime|crtime, events are left out
+    fn write_bodyfile<W: Write>(&self, out: &mut W) -> Result<(), XwfError> {
+        let mut items: BTreeMap<(u32, i32), (&TimelineEntry, [i64; 4])> = BTreeMap::new();
+        for entry in self.entries.iter().filter(|e| e.item_id >= 0) {
+            let idx = match entry.source {
+                TimestampSource::LastAccess => 0,
+                TimestampSource::Modification => 1,
+                TimestampSource::EntryModification => 2,
+                TimestampSource::Creation => 3,
+                _ => continue,
+            };
+            items.entry((entry.evidence_id, entry.item_id)).or_insert((entry, [0; 4])).1[idx] = entry.time.timestamp();
+        }
+
+        for (entry, [atime, mtime, ctime, crtime]) in items.values() {
+            let mode = if entry.is_directory { "d/d---------" } else { "r/r---------" };
+            let deleted = if entry.is_deleted { " (deleted)" } else { "" };
+            writeln!(out, "0|{}{}|{}-{}|{}|0|0|{}|{}|{}|{}|{}",
+                     entry.path.replace('|', "\\|"), deleted,
+                     entry.short_ev_id, entry.item_id, mode,
+                     entry.size.unwrap_or(0), atime, mtime, ctime, crtime)?;
+        }
+        Ok(())
+    }
+
+    fn write_l2t_csv<W: Write>(&self, out: &mut W) -> Result<(), XwfError> {
+        writeln!(out, "date,time,timezone,MACB,source,sourcetype,type,user,host,short,desc,version,filename,inode,notes,format,extra")?;
+
+        for entry in &self.entries {
+            let (source, source_type) = match entry.source {
+                TimestampSource::Event(_) => ("EVT", "X-Ways Forensics event list"),
+                _ => ("FILE", "X-Ways Forensics file system"),
+            };
+            let desc = entry.description.clone().unwrap_or_else(|| entry.path.clone());
+            let notes = if entry.time_zone_assumed { "time zone assumed" } else { "-" };
+            let fields = [
+                entry.time.format("%m/%d/%Y").to_string(),
+                entry.time.format("%H:%M:%S").to_string(),
+                "UTC".to_string(),
+                entry.source.macb().to_string(),
+                source.to_string(),
+                source_type.to_string(),
+                entry.source.label(),
+                "-".to_string(),
+                "-".to_string(),
+                desc.clone(),
+                desc,
+                "2".to_string(),
+                entry.path.clone(),
+                format!("{}-{}", entry.short_ev_id, entry.item_id),
+                notes.to_string(),
+                "xwf-api-rs".to_string(),
+                entry.size.map(|s| format!("size: {}", s)).unwrap_or_default(),
+            ];
+            let line: Vec<String> = fields.iter().map(|f| escape_csv(f, ',')).collect();
+            writeln!(out, "{}", line.join(","))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "export")]
+    fn write_jsonl<W: Write>(&self, out: &mut W) -> Result<(), XwfError> {
+        for entry in &self.entries {
+            serde_json::to_writer(&mut *out, entry).map_err(|e| XwfError::IoError(e.into()))?;
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// first line of a trace, describes the X-Ways Forensics the trace was recorded in
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TraceHeader {
+    /// `XWF_*` functions that were not exported by X-Ways Forensics
+    #[serde(default)]
+    pub missing_functions: Vec<String>,
+}
+
+/// a single recorded call, either of an `XWF_*` function or of an `XT_*` callback
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub seq: u64,
+    pub call: String,
+    #[serde(default)]
+    pub args: Vec<Value>,
+    #[serde(default)]
+    pub ret: Value,
+    /// values written to output parameters and buffers, byte buffers are hex encoded
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub out: Map<String, Value>,
+}
+
+impl TraceEntry {
+    pub fn is_callback(&self) -> bool {
+        self.call.starts_with("XT_")
+    }
+
+    pub fn ret_as<T: DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_value(self.ret.clone()).ok()
+    }
+
+    pub fn arg_i64(&self, idx: usize) -> i64 {
+        self.args.get(idx).and_then(|v| v.as_i64()).unwrap_or(0)
+    }
+
+    pub fn out_as<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.out.get(key).and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    pub fn out_string(&self, key: &str) -> Option<String> {
+        self.out.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+    }
+
+    pub fn out_bytes(&self, key: &str) -> Option<Vec<u8>> {
+        self.out.get(key).and_then(|v| v.as_str()).and_then(|s| hex::decode(s).ok())
+    }
+}
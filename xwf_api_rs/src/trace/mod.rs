@@ -0,0 +1,11 @@
+//! Record-and-replay of X-Tension API calls (feature `trace`). In X-Ways Forensics, all calls
+//! are recorded to a trace file in the case directory by the [`TracingBackend`]. The trace can be
+//! replayed offline with the [`ReplayDriver`] to reproduce the X-Tension run.
+
+mod entry;
+mod recorder;
+mod replay;
+
+pub use entry::{TraceEntry, TraceHeader};
+pub use recorder::{TracingBackend, DEFAULT_MAX_BUFFER_LEN};
+pub use replay::{ReplayBackend, ReplayDriver};
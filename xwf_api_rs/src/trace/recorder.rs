@@ -0,0 +1,300 @@
+use std::fs::File;
+use std::io;
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use serde_json::{json, Map, Value};
+use crate::backend::{delegate_to_inner, VolumeInformation, XwfBackend};
+use crate::capabilities::Capabilities;
+use crate::case::case_file;
+use crate::evidence::ReportTableListItem;
+use crate::search::SearchHit;
+use crate::event::Event;
+use crate::xwf_types::{SrcInfo, XwfHandle};
+use super::entry::{TraceEntry, TraceHeader};
+
+/// buffers (e.g. read item data) are recorded up to this number of bytes by default
+pub const DEFAULT_MAX_BUFFER_LEN: usize = 64 * 1024;
+
+struct TraceWriter {
+    out: Box<dyn Write + Send>,
+    seq: u64,
+}
+
+/// [`XwfBackend`] wrapper that forwards all calls to the wrapped backend and records every call
+/// including its arguments, return value and output buffers as JSON lines. The resulting trace
+/// can be fed back via [`super::ReplayBackend`].
+pub struct TracingBackend<B: XwfBackend> {
+    inner: B,
+    writer: Mutex<TraceWriter>,
+    max_buffer_len: usize,
+}
+
+fn out(pairs: Vec<(&str, Value)>) -> Map<String, Value> {
+    pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+}
+
+impl<B: XwfBackend> TracingBackend<B> {
+    /// writes the [`TraceHeader`] with the capabilities of `inner` as first line
+    pub fn new<W: Write + Send + 'static>(inner: B, writer: W) -> TracingBackend<B> {
+        let mut out: Box<dyn Write + Send> = Box::new(writer);
+        let header = TraceHeader {
+            missing_functions: inner.capabilities().missing_functions().iter().map(|f| f.to_string()).collect(),
+        };
+        if let Ok(line) = serde_json::to_string(&header) {
+            let _ = writeln!(out, "{}", line);
+        }
+
+        TracingBackend {
+            inner,
+            writer: Mutex::new(TraceWriter { out, seq: 0 }),
+            max_buffer_len: DEFAULT_MAX_BUFFER_LEN,
+        }
+    }
+
+    pub fn create<P: AsRef<Path>>(inner: B, path: P) -> io::Result<TracingBackend<B>> {
+        let file = File::create(path)?;
+        Ok(TracingBackend::new(inner, LineWriter::new(file)))
+    }
+
+    /// creates the trace file `<extension_name>_trace_<timestamp>.jsonl` in the case directory.
+    /// Gives the backend back on failure.
+    pub fn create_in_case_dir(inner: B, extension_name: &str) -> Result<TracingBackend<B>, (B, io::Error)> {
+        let path = case_file(&inner, &format!("{}_trace", extension_name), "jsonl");
+
+        match File::create(path) {
+            Ok(file) => Ok(TracingBackend::new(inner, LineWriter::new(file))),
+            Err(e) => Err((inner, e)),
+        }
+    }
+
+    pub fn with_max_buffer_len(mut self, max_buffer_len: usize) -> TracingBackend<B> {
+        self.max_buffer_len = max_buffer_len;
+        self
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    fn bytes(&self, data: &[u8]) -> Value {
+        Value::String(hex::encode(&data[..data.len().min(self.max_buffer_len)]))
+    }
+
+    fn record(&self, call: &str, args: Vec<Value>, ret: Value, out: Map<String, Value>) {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writer.seq += 1;
+
+        let entry = TraceEntry { seq: writer.seq, call: call.to_string(), args, ret, out };
+
+        // tracing must never break the X-Tension, so write errors are ignored
+        if let Ok(line) = serde_json::to_string(&entry) {
+            let _ = writeln!(writer.out, "{}", line);
+        }
+    }
+}
+
+// generates methods of [`XwfBackend`] that forward the call to the wrapped backend and record
+// it with all arguments and the return value, for calls without output parameters
+macro_rules! traced {
+    ($($call:literal => fn $name:ident(&self $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)?;)*) => {
+        $(
+            #[allow(clippy::let_unit_value)]
+            fn $name(&self $(, $arg: $ty)*) $(-> $ret)? {
+                let ret = self.inner.$name($($arg),*);
+                self.record($call, vec![$(json!($arg)),*], json!(ret), Map::new());
+                ret
+            }
+        )*
+    };
+}
+
+impl<B: XwfBackend> XwfBackend for TracingBackend<B> {
+    fn get_item_type(&self, item_id: i32, type_descr: &mut String, flags: u32) -> i32 {
+        let ret = self.inner.get_item_type(item_id, type_descr, flags);
+        self.record("XWF_GetItemType", vec![json!(item_id), json!(flags)], json!(ret),
+                    out(vec![("type_descr", json!(type_descr))]));
+        ret
+    }
+
+    fn get_report_table_assocs(&self, item_id: i32, assocs: &mut String) -> u32 {
+        let ret = self.inner.get_report_table_assocs(item_id, assocs);
+        self.record("XWF_GetReportTableAssocs", vec![json!(item_id)], json!(ret),
+                    out(vec![("assocs", json!(assocs))]));
+        ret
+    }
+
+    fn get_hashset_assocs(&self, item_id: i32, assocs: &mut String) -> i32 {
+        let ret = self.inner.get_hashset_assocs(item_id, assocs);
+        self.record("XWF_GetHashSetAssocs", vec![json!(item_id)], json!(ret),
+                    out(vec![("assocs", json!(assocs))]));
+        ret
+    }
+
+    fn get_metadata_ex(&self, item: XwfHandle, flags: &mut u32) -> Option<String> {
+        let in_flags = *flags;
+        let ret = self.inner.get_metadata_ex(item, flags);
+        self.record("XWF_GetMetadataEx", vec![json!(item), json!(in_flags)], json!(ret),
+                    out(vec![("flags", json!(*flags))]));
+        ret
+    }
+
+    fn get_hash_value(&self, item_id: i32, buffer: &mut [u8]) -> bool {
+        let in_flags = buffer.get(..4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).unwrap_or(0);
+        let ret = self.inner.get_hash_value(item_id, buffer);
+        self.record("XWF_GetHashValue", vec![json!(item_id), json!(in_flags), json!(buffer.len())], json!(ret),
+                    out(vec![("buffer", self.bytes(buffer))]));
+        ret
+    }
+
+    fn set_hash_value(&self, item_id: i32, hash: &[u8], param: u32) -> bool {
+        let ret = self.inner.set_hash_value(item_id, hash, param);
+        self.record("XWF_SetHashValue", vec![json!(item_id), self.bytes(hash), json!(param)], json!(ret), Map::new());
+        ret
+    }
+
+    fn create_file(&self, name: &str, creation_flags: u32, parent_item_id: i32, src_info: &mut SrcInfo) -> i32 {
+        let buf_size = src_info.n_buf_size;
+        let ret = self.inner.create_file(name, creation_flags, parent_item_id, src_info);
+        self.record("XWF_CreateFile", vec![json!(name), json!(creation_flags), json!(parent_item_id), json!(buf_size)], json!(ret), Map::new());
+        ret
+    }
+
+    fn read(&self, volume_or_item: XwfHandle, offset: i64, buffer: &mut [u8]) -> u32 {
+        let ret = self.inner.read(volume_or_item, offset, buffer);
+        let len = (ret as usize).min(buffer.len());
+        self.record("XWF_Read", vec![json!(volume_or_item), json!(offset), json!(buffer.len())], json!(ret),
+                    out(vec![("buffer", self.bytes(&buffer[..len]))]));
+        ret
+    }
+
+    fn get_prop(&self, volume_or_item: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64 {
+        match string {
+            Some(s) => {
+                let ret = self.inner.get_prop(volume_or_item, prop_type, Some(s));
+                self.record("XWF_GetProp", vec![json!(volume_or_item), json!(prop_type)], json!(ret),
+                            out(vec![("string", json!(s))]));
+                ret
+            },
+            None => {
+                let ret = self.inner.get_prop(volume_or_item, prop_type, None);
+                self.record("XWF_GetProp", vec![json!(volume_or_item), json!(prop_type)], json!(ret), Map::new());
+                ret
+            }
+        }
+    }
+
+    fn get_ev_obj_prop(&self, evidence: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64 {
+        match string {
+            Some(s) => {
+                let ret = self.inner.get_ev_obj_prop(evidence, prop_type, Some(s));
+                self.record("XWF_GetEvObjProp", vec![json!(evidence), json!(prop_type)], json!(ret),
+                            out(vec![("string", json!(s))]));
+                ret
+            },
+            None => {
+                let ret = self.inner.get_ev_obj_prop(evidence, prop_type, None);
+                self.record("XWF_GetEvObjProp", vec![json!(evidence), json!(prop_type)], json!(ret), Map::new());
+                ret
+            }
+        }
+    }
+
+    fn get_case_prop(&self, prop_type: i32, string: Option<&mut String>) -> i64 {
+        match string {
+            Some(s) => {
+                let ret = self.inner.get_case_prop(prop_type, Some(s));
+                self.record("XWF_GetCaseProp", vec![json!(prop_type)], json!(ret),
+                            out(vec![("string", json!(s))]));
+                ret
+            },
+            None => {
+                let ret = self.inner.get_case_prop(prop_type, None);
+                self.record("XWF_GetCaseProp", vec![json!(prop_type)], json!(ret), Map::new());
+                ret
+            }
+        }
+    }
+
+    fn get_report_table_info(&self, report_table_id: i32, optional: &mut i32) -> Option<String> {
+        let ret = self.inner.get_report_table_info(report_table_id, optional);
+        self.record("XWF_GetReportTableInfo", vec![json!(report_table_id)], json!(ret),
+                    out(vec![("optional", json!(*optional))]));
+        ret
+    }
+
+    fn get_user_input(&self, message: &str, input: Option<&mut String>, flags: u32) -> i64 {
+        match input {
+            Some(s) => {
+                let ret = self.inner.get_user_input(message, Some(s), flags);
+                self.record("XWF_GetUserInput", vec![json!(message), json!(flags)], json!(ret),
+                            out(vec![("input", json!(s))]));
+                ret
+            },
+            None => {
+                let ret = self.inner.get_user_input(message, None, flags);
+                self.record("XWF_GetUserInput", vec![json!(message), json!(flags)], json!(ret), Map::new());
+                ret
+            }
+        }
+    }
+
+    fn get_search_term_count(&self) -> i32 {
+        let ret = self.inner.get_search_term_count();
+        self.record("XWF_GetSearchTerm", vec![json!(-1)], json!(ret), Map::new());
+        ret
+    }
+
+    fn callback_invoked(&self, name: &str, args: &[i64]) {
+        self.inner.callback_invoked(name, args);
+        self.record(name, args.iter().map(|a| json!(a)).collect(), Value::Null, Map::new());
+    }
+
+    traced! {
+        "XWF_OutputMessage" => fn output_message(&self, message: &str, flags: u32);
+        "XWF_GetVolumeName" => fn get_volume_name(&self, volume: XwfHandle, name_type: u32) -> String;
+        "XWF_GetVolumeInformation" => fn get_volume_information(&self, volume: XwfHandle) -> Option<VolumeInformation>;
+        "XWF_SelectVolumeSnapshot" => fn select_volume_snapshot(&self, volume: XwfHandle) -> i32;
+        "XWF_GetVSProp" => fn get_vs_prop(&self, prop_type: i32, value: Option<u32>) -> i64;
+        "XWF_GetItemCount" => fn get_item_count(&self, selected_in_dbc: bool) -> u32;
+        "XWF_GetItemName" => fn get_item_name(&self, item_id: u32) -> String;
+        "XWF_GetItemSize" => fn get_item_size(&self, item_id: i32) -> i64;
+        "XWF_SetItemSize" => fn set_item_size(&self, item_id: i32, size: i64);
+        "XWF_GetItemOfs" => fn get_item_ofs(&self, item_id: i32) -> (i64, i64);
+        "XWF_GetItemInformation" => fn get_item_information(&self, item_id: i32, info_type: i32) -> Option<i64>;
+        "XWF_SetItemInformation" => fn set_item_information(&self, item_id: i32, info_type: i32, value: i64) -> bool;
+        "XWF_SetItemType" => fn set_item_type(&self, item_id: i32, type_descr: &str, type_status: i32);
+        "XWF_GetItemParent" => fn get_item_parent(&self, item_id: i32) -> i32;
+        "XWF_SetItemParent" => fn set_item_parent(&self, child_item_id: i32, parent_item_id: i32);
+        "XWF_AddToReportTable" => fn add_to_report_table(&self, item_id: i32, report_table_name: &str, flags: u32) -> i32;
+        "XWF_GetComment" => fn get_comment(&self, item_id: i32) -> Option<String>;
+        "XWF_AddComment" => fn add_comment(&self, item_id: i32, comment: &str, flags: u32) -> bool;
+        "XWF_GetExtractedMetadata" => fn get_extracted_metadata(&self, item_id: i32) -> Option<String>;
+        "XWF_AddExtractedMetadata" => fn add_extracted_metadata(&self, item_id: i32, metadata: &str, flags: u32) -> bool;
+        "XWF_OpenItem" => fn open_item(&self, volume: XwfHandle, item_id: i32, flags: u32) -> XwfHandle;
+        "XWF_Close" => fn close(&self, volume_or_item: XwfHandle);
+        "XWF_GetFirstEvObj" => fn get_first_ev_obj(&self) -> XwfHandle;
+        "XWF_GetNextEvObj" => fn get_next_ev_obj(&self, prev_evidence: XwfHandle) -> XwfHandle;
+        "XWF_GetEvObj" => fn get_ev_obj(&self, ev_obj_id: u32) -> XwfHandle;
+        "XWF_OpenEvObj" => fn open_ev_obj(&self, evidence: XwfHandle, flags: u32) -> XwfHandle;
+        "XWF_CloseEvObj" => fn close_ev_obj(&self, evidence: XwfHandle);
+        "XWF_GetEvObjReportTableAssocs" => fn get_ev_obj_report_table_assocs(&self, evidence: XwfHandle, flags: i32) -> Option<Vec<ReportTableListItem>>;
+        "XWF_ShowProgress" => fn show_progress(&self, caption: &str, flags: u32);
+        "XWF_SetProgressDescription" => fn set_progress_description(&self, description: &str);
+        "XWF_SetProgressPercentage" => fn set_progress_percentage(&self, percent: u32);
+        "XWF_HideProgress" => fn hide_progress(&self);
+        "XWF_ShouldStop" => fn should_stop(&self) -> bool;
+        "XWF_Search" => fn search(&self, volume: XwfHandle, search_terms: &str, flags: u32, search_window: u32, code_pages: Option<[u16; 5]>) -> i32;
+        "XWF_GetSearchTerm" => fn get_search_term(&self, search_term_id: i32) -> Option<String>;
+        "XWF_AddSearchTerm" => fn add_search_term(&self, name: &str, flags: u32) -> i32;
+        "XWF_GetSearchHit" => fn get_search_hit(&self, hit_no: i32) -> Option<SearchHit>;
+        "XWF_SetSearchHit" => fn set_search_hit(&self, hit_no: i32, hit: &SearchHit) -> bool;
+        "XWF_AddSearchHit" => fn add_search_hit(&self, hit: &SearchHit) -> i32;
+        "XWF_GetEvent" => fn get_event(&self, evidence: XwfHandle, event_no: u32) -> Option<Event>;
+        "XWF_AddEvent" => fn add_event(&self, event: &Event) -> i32;
+    }
+
+    delegate_to_inner! {
+        fn capabilities(&self) -> Capabilities;
+    }
+}
@@ -0,0 +1,536 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use serde_json::{json, Value};
+use crate::backend::{lock_exclusive_backend, reset_backend, set_backend, VolumeInformation, XwfBackend};
+use crate::capabilities::{Capabilities, XWF_FUNCTIONS};
+use crate::dispatch;
+use crate::evidence::ReportTableListItem;
+use crate::panic_guard::catch_panic;
+use crate::search::SearchHit;
+use crate::event::Event;
+use crate::traits::XTension;
+use crate::xwf_types::*;
+use super::entry::{TraceEntry, TraceHeader};
+
+// arguments of these calls are not compared, as they contain e.g. timestamps of log messages
+const UNCOMPARED_CALLS: [&str; 1] = ["XWF_OutputMessage"];
+
+struct ReplayState {
+    entries: Vec<TraceEntry>,
+    cursor: usize,
+    divergences: Vec<String>,
+}
+
+/// [`XwfBackend`] that answers all calls from a trace recorded by [`super::TracingBackend`].
+/// Calls are matched in recorded order; calls that were not recorded or differ in their
+/// arguments are collected as divergences. Buffers that were truncated during recording are
+/// returned up to the recorded length only: `XWF_Read` returns the number of recorded bytes
+/// instead of the original count and truncated hash values are not returned at all.
+#[derive(Clone)]
+pub struct ReplayBackend {
+    state: Arc<Mutex<ReplayState>>,
+    capabilities: Capabilities,
+}
+
+impl ReplayBackend {
+    pub fn new(entries: Vec<TraceEntry>) -> ReplayBackend {
+        ReplayBackend {
+            state: Arc::new(Mutex::new(ReplayState { entries, cursor: 0, divergences: Vec::new() })),
+            capabilities: Capabilities::all(),
+        }
+    }
+
+    /// the functions missing according to the header are reported as missing by [`XwfBackend::capabilities`]
+    pub fn with_header(mut self, header: &TraceHeader) -> ReplayBackend {
+        let missing = XWF_FUNCTIONS.iter()
+            .map(|(f, _)| *f)
+            .filter(|f| header.missing_functions.iter().any(|m| m == f))
+            .collect();
+        self.capabilities = Capabilities::with_missing(missing);
+        self
+    }
+
+    /// reads a trace written by [`super::TracingBackend`], traces without header are accepted as well
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<ReplayBackend> {
+        let mut header = None;
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if header.is_none() && entries.is_empty() {
+                if let Ok(h) = serde_json::from_str::<TraceHeader>(&line) {
+                    header = Some(h);
+                    continue;
+                }
+            }
+            entries.push(serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?);
+        }
+        Ok(ReplayBackend::new(entries).with_header(&header.unwrap_or_default()))
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<ReplayBackend> {
+        ReplayBackend::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn divergences(&self) -> Vec<String> {
+        self.state().divergences.clone()
+    }
+
+    /// true if all recorded entries were consumed
+    pub fn is_finished(&self) -> bool {
+        let state = self.state();
+        state.cursor >= state.entries.len()
+    }
+
+    fn state(&self) -> MutexGuard<'_, ReplayState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // searches the next recorded entry of the given call, but never beyond the next callback
+    fn next_call(&self, call: &str, args: Vec<Value>) -> Option<TraceEntry> {
+        let mut state = self.state();
+        let found = state.entries[state.cursor..].iter()
+            .take_while(|e| !e.is_callback())
+            .position(|e| e.call == call)
+            .map(|idx| idx + state.cursor);
+
+        match found {
+            Some(idx) => {
+                let entry = state.entries[idx].clone();
+                state.cursor = idx + 1;
+                if entry.args != args && !UNCOMPARED_CALLS.contains(&call) {
+                    state.divergences.push(format!("#{} {}: recorded arguments {:?}, replayed arguments {:?}",
+                                                   entry.seq, call, entry.args, args));
+                }
+                Some(entry)
+            },
+            None => {
+                if !UNCOMPARED_CALLS.contains(&call) {
+                    state.divergences.push(format!("{}{:?} was not recorded at this point", call, args));
+                }
+                None
+            }
+        }
+    }
+
+    fn next_callback(&self) -> Option<TraceEntry> {
+        let mut state = self.state();
+        let idx = state.entries[state.cursor..].iter().position(|e| e.is_callback())? + state.cursor;
+
+        let skipped = state.entries[state.cursor..idx].iter()
+            .filter(|e| !UNCOMPARED_CALLS.contains(&e.call.as_str()))
+            .count();
+        if skipped > 0 {
+            let call = state.entries[idx].call.clone();
+            state.divergences.push(format!("{} recorded calls were not replayed before {}", skipped, call));
+        }
+
+        state.cursor = idx + 1;
+        Some(state.entries[idx].clone())
+    }
+
+    fn ret<T: serde::de::DeserializeOwned>(&self, call: &str, args: Vec<Value>, default: T) -> T {
+        self.next_call(call, args).and_then(|e| e.ret_as()).unwrap_or(default)
+    }
+}
+
+// returns the number of bytes copied, which is less than recorded if the buffer was truncated
+fn copy_bytes(entry: &TraceEntry, key: &str, buffer: &mut [u8]) -> usize {
+    match entry.out_bytes(key) {
+        Some(data) => {
+            let len = data.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&data[..len]);
+            len
+        },
+        None => 0,
+    }
+}
+
+impl XwfBackend for ReplayBackend {
+    fn output_message(&self, message: &str, flags: u32) {
+        self.next_call("XWF_OutputMessage", vec![json!(message), json!(flags)]);
+    }
+
+    fn get_volume_name(&self, volume: XwfHandle, name_type: u32) -> String {
+        self.ret("XWF_GetVolumeName", vec![json!(volume), json!(name_type)], String::new())
+    }
+
+    fn get_volume_information(&self, volume: XwfHandle) -> Option<VolumeInformation> {
+        self.ret("XWF_GetVolumeInformation", vec![json!(volume)], None)
+    }
+
+    fn select_volume_snapshot(&self, volume: XwfHandle) -> i32 {
+        self.ret("XWF_SelectVolumeSnapshot", vec![json!(volume)], -1)
+    }
+
+    fn get_vs_prop(&self, prop_type: i32, value: Option<u32>) -> i64 {
+        self.ret("XWF_GetVSProp", vec![json!(prop_type), json!(value)], -1)
+    }
+
+    fn get_item_count(&self, selected_in_dbc: bool) -> u32 {
+        self.ret("XWF_GetItemCount", vec![json!(selected_in_dbc)], 0)
+    }
+
+    fn get_item_name(&self, item_id: u32) -> String {
+        self.ret("XWF_GetItemName", vec![json!(item_id)], String::new())
+    }
+
+    fn get_item_size(&self, item_id: i32) -> i64 {
+        self.ret("XWF_GetItemSize", vec![json!(item_id)], -1)
+    }
+
+    fn set_item_size(&self, item_id: i32, size: i64) {
+        self.next_call("XWF_SetItemSize", vec![json!(item_id), json!(size)]);
+    }
+
+    fn get_item_ofs(&self, item_id: i32) -> (i64, i64) {
+        self.ret("XWF_GetItemOfs", vec![json!(item_id)], (0, -1))
+    }
+
+    fn get_item_information(&self, item_id: i32, info_type: i32) -> Option<i64> {
+        self.ret("XWF_GetItemInformation", vec![json!(item_id), json!(info_type)], None)
+    }
+
+    fn set_item_information(&self, item_id: i32, info_type: i32, value: i64) -> bool {
+        self.ret("XWF_SetItemInformation", vec![json!(item_id), json!(info_type), json!(value)], false)
+    }
+
+    fn get_item_type(&self, item_id: i32, type_descr: &mut String, flags: u32) -> i32 {
+        match self.next_call("XWF_GetItemType", vec![json!(item_id), json!(flags)]) {
+            Some(e) => {
+                *type_descr = e.out_string("type_descr").unwrap_or_default();
+                e.ret_as().unwrap_or(-1)
+            },
+            None => -1,
+        }
+    }
+
+    fn set_item_type(&self, item_id: i32, type_descr: &str, type_status: i32) {
+        self.next_call("XWF_SetItemType", vec![json!(item_id), json!(type_descr), json!(type_status)]);
+    }
+
+    fn get_item_parent(&self, item_id: i32) -> i32 {
+        self.ret("XWF_GetItemParent", vec![json!(item_id)], -1)
+    }
+
+    fn set_item_parent(&self, child_item_id: i32, parent_item_id: i32) {
+        self.next_call("XWF_SetItemParent", vec![json!(child_item_id), json!(parent_item_id)]);
+    }
+
+    fn get_report_table_assocs(&self, item_id: i32, assocs: &mut String) -> u32 {
+        match self.next_call("XWF_GetReportTableAssocs", vec![json!(item_id)]) {
+            Some(e) => {
+                *assocs = e.out_string("assocs").unwrap_or_default();
+                e.ret_as().unwrap_or(0)
+            },
+            None => 0,
+        }
+    }
+
+    fn add_to_report_table(&self, item_id: i32, report_table_name: &str, flags: u32) -> i32 {
+        self.ret("XWF_AddToReportTable", vec![json!(item_id), json!(report_table_name), json!(flags)], 0)
+    }
+
+    fn get_hashset_assocs(&self, item_id: i32, assocs: &mut String) -> i32 {
+        match self.next_call("XWF_GetHashSetAssocs", vec![json!(item_id)]) {
+            Some(e) => {
+                *assocs = e.out_string("assocs").unwrap_or_default();
+                e.ret_as().unwrap_or(-1)
+            },
+            None => -1,
+        }
+    }
+
+    fn get_comment(&self, item_id: i32) -> Option<String> {
+        self.ret("XWF_GetComment", vec![json!(item_id)], None)
+    }
+
+    fn add_comment(&self, item_id: i32, comment: &str, flags: u32) -> bool {
+        self.ret("XWF_AddComment", vec![json!(item_id), json!(comment), json!(flags)], false)
+    }
+
+    fn get_extracted_metadata(&self, item_id: i32) -> Option<String> {
+        self.ret("XWF_GetExtractedMetadata", vec![json!(item_id)], None)
+    }
+
+    fn add_extracted_metadata(&self, item_id: i32, metadata: &str, flags: u32) -> bool {
+        self.ret("XWF_AddExtractedMetadata", vec![json!(item_id), json!(metadata), json!(flags)], false)
+    }
+
+    fn get_metadata_ex(&self, item: XwfHandle, flags: &mut u32) -> Option<String> {
+        let e = self.next_call("XWF_GetMetadataEx", vec![json!(item), json!(*flags)])?;
+        if let Some(f) = e.out_as("flags") {
+            *flags = f;
+        }
+        e.ret_as().flatten()
+    }
+
+    fn get_hash_value(&self, item_id: i32, buffer: &mut [u8]) -> bool {
+        let in_flags = buffer.get(..4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).unwrap_or(0);
+        match self.next_call("XWF_GetHashValue", vec![json!(item_id), json!(in_flags), json!(buffer.len())]) {
+            Some(e) => {
+                let complete = copy_bytes(&e, "buffer", buffer) == buffer.len();
+                complete && e.ret_as().unwrap_or(false)
+            },
+            None => false,
+        }
+    }
+
+    fn set_hash_value(&self, item_id: i32, hash: &[u8], param: u32) -> bool {
+        self.ret("XWF_SetHashValue", vec![json!(item_id), json!(hex::encode(hash)), json!(param)], false)
+    }
+
+    fn create_file(&self, name: &str, creation_flags: u32, parent_item_id: i32, src_info: &mut SrcInfo) -> i32 {
+        let buf_size = src_info.n_buf_size;
+        self.ret("XWF_CreateFile", vec![json!(name), json!(creation_flags), json!(parent_item_id), json!(buf_size)], -1)
+    }
+
+    fn open_item(&self, volume: XwfHandle, item_id: i32, flags: u32) -> XwfHandle {
+        self.ret("XWF_OpenItem", vec![json!(volume), json!(item_id), json!(flags)], XwfHandle::NULL)
+    }
+
+    fn close(&self, volume_or_item: XwfHandle) {
+        self.next_call("XWF_Close", vec![json!(volume_or_item)]);
+    }
+
+    fn read(&self, volume_or_item: XwfHandle, offset: i64, buffer: &mut [u8]) -> u32 {
+        match self.next_call("XWF_Read", vec![json!(volume_or_item), json!(offset), json!(buffer.len())]) {
+            Some(e) => {
+                let len = copy_bytes(&e, "buffer", buffer);
+                e.ret_as::<u32>().unwrap_or(0).min(len as u32)
+            },
+            None => 0,
+        }
+    }
+
+    fn get_prop(&self, volume_or_item: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64 {
+        match self.next_call("XWF_GetProp", vec![json!(volume_or_item), json!(prop_type)]) {
+            Some(e) => {
+                if let (Some(s), Some(v)) = (string, e.out_string("string")) {
+                    *s = v;
+                }
+                e.ret_as().unwrap_or(-1)
+            },
+            None => -1,
+        }
+    }
+
+    fn get_first_ev_obj(&self) -> XwfHandle {
+        self.ret("XWF_GetFirstEvObj", vec![], XwfHandle::NULL)
+    }
+
+    fn get_next_ev_obj(&self, prev_evidence: XwfHandle) -> XwfHandle {
+        self.ret("XWF_GetNextEvObj", vec![json!(prev_evidence)], XwfHandle::NULL)
+    }
+
+    fn get_ev_obj(&self, ev_obj_id: u32) -> XwfHandle {
+        self.ret("XWF_GetEvObj", vec![json!(ev_obj_id)], XwfHandle::NULL)
+    }
+
+    fn open_ev_obj(&self, evidence: XwfHandle, flags: u32) -> XwfHandle {
+        self.ret("XWF_OpenEvObj", vec![json!(evidence), json!(flags)], XwfHandle::NULL)
+    }
+
+    fn close_ev_obj(&self, evidence: XwfHandle) {
+        self.next_call("XWF_CloseEvObj", vec![json!(evidence)]);
+    }
+
+    fn get_ev_obj_prop(&self, evidence: XwfHandle, prop_type: u32, string: Option<&mut String>) -> i64 {
+        match self.next_call("XWF_GetEvObjProp", vec![json!(evidence), json!(prop_type)]) {
+            Some(e) => {
+                if let (Some(s), Some(v)) = (string, e.out_string("string")) {
+                    *s = v;
+                }
+                e.ret_as().unwrap_or(-1)
+            },
+            None => -1,
+        }
+    }
+
+    fn get_ev_obj_report_table_assocs(&self, evidence: XwfHandle, flags: i32) -> Option<Vec<ReportTableListItem>> {
+        self.ret("XWF_GetEvObjReportTableAssocs", vec![json!(evidence), json!(flags)], None)
+    }
+
+    fn get_case_prop(&self, prop_type: i32, string: Option<&mut String>) -> i64 {
+        match self.next_call("XWF_GetCaseProp", vec![json!(prop_type)]) {
+            Some(e) => {
+                if let (Some(s), Some(v)) = (string, e.out_string("string")) {
+                    *s = v;
+                }
+                e.ret_as().unwrap_or(-1)
+            },
+            None => -1,
+        }
+    }
+
+    fn get_report_table_info(&self, report_table_id: i32, optional: &mut i32) -> Option<String> {
+        let e = self.next_call("XWF_GetReportTableInfo", vec![json!(report_table_id)])?;
+        if let Some(v) = e.out_as("optional") {
+            *optional = v;
+        }
+        e.ret_as().flatten()
+    }
+
+    fn show_progress(&self, caption: &str, flags: u32) {
+        self.next_call("XWF_ShowProgress", vec![json!(caption), json!(flags)]);
+    }
+
+    fn set_progress_description(&self, description: &str) {
+        self.next_call("XWF_SetProgressDescription", vec![json!(description)]);
+    }
+
+    fn set_progress_percentage(&self, percent: u32) {
+        self.next_call("XWF_SetProgressPercentage", vec![json!(percent)]);
+    }
+
+    fn hide_progress(&self) {
+        self.next_call("XWF_HideProgress", vec![]);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.ret("XWF_ShouldStop", vec![], false)
+    }
+
+    fn get_user_input(&self, message: &str, input: Option<&mut String>, flags: u32) -> i64 {
+        match self.next_call("XWF_GetUserInput", vec![json!(message), json!(flags)]) {
+            Some(e) => {
+                if let (Some(s), Some(v)) = (input, e.out_string("input")) {
+                    *s = v;
+                }
+                e.ret_as().unwrap_or(-1)
+            },
+            None => -1,
+        }
+    }
+
+    fn search(&self, volume: XwfHandle, search_terms: &str, flags: u32, search_window: u32, code_pages: Option<[u16; 5]>) -> i32 {
+        self.ret("XWF_Search", vec![json!(volume), json!(search_terms), json!(flags), json!(search_window), json!(code_pages)], -1)
+    }
+
+    fn get_search_term(&self, search_term_id: i32) -> Option<String> {
+        self.ret("XWF_GetSearchTerm", vec![json!(search_term_id)], None)
+    }
+
+    fn get_search_term_count(&self) -> i32 {
+        self.ret("XWF_GetSearchTerm", vec![json!(-1)], -1)
+    }
+
+    fn add_search_term(&self, name: &str, flags: u32) -> i32 {
+        self.ret("XWF_AddSearchTerm", vec![json!(name), json!(flags)], -1)
+    }
+
+    fn get_search_hit(&self, hit_no: i32) -> Option<SearchHit> {
+        self.ret("XWF_GetSearchHit", vec![json!(hit_no)], None)
+    }
+
+    fn set_search_hit(&self, hit_no: i32, hit: &SearchHit) -> bool {
+        self.ret("XWF_SetSearchHit", vec![json!(hit_no), json!(hit)], false)
+    }
+
+    fn add_search_hit(&self, hit: &SearchHit) -> i32 {
+        self.ret("XWF_AddSearchHit", vec![json!(hit)], 0)
+    }
+
+    fn get_event(&self, evidence: XwfHandle, event_no: u32) -> Option<Event> {
+        self.ret("XWF_GetEvent", vec![json!(evidence), json!(event_no)], None)
+    }
+
+    fn add_event(&self, event: &Event) -> i32 {
+        self.ret("XWF_AddEvent", vec![json!(event)], 0)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities.clone()
+    }
+}
+
+/// Re-invokes the X-Tension callbacks in the order of a recorded trace, with a [`ReplayBackend`]
+/// answering all API calls, so a run from X-Ways Forensics can be reproduced and debugged offline.
+pub struct ReplayDriver<T: XTension> {
+    backend: ReplayBackend,
+    extension: Option<T>,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl<T: XTension> ReplayDriver<T> where T::XTensionError: Display {
+    pub fn new(backend: ReplayBackend) -> ReplayDriver<T> {
+        let guard = lock_exclusive_backend();
+        set_backend(backend.clone());
+        ReplayDriver { backend, extension: None, _guard: guard }
+    }
+
+    pub fn backend(&self) -> &ReplayBackend {
+        &self.backend
+    }
+
+    pub fn extension(&self) -> Option<&T> {
+        self.extension.as_ref()
+    }
+
+    /// replays all recorded callbacks and returns the callback names with their new return values
+    pub fn run(&mut self) -> Vec<(String, i32)> {
+        let mut ret = Vec::new();
+        while let Some(callback) = self.backend.next_callback() {
+            // takes the place of the exported XT_* functions, which catch panics of the callbacks
+            let returns = || T::panic_returns().for_callback(&callback.call);
+            let res = catch_panic(&callback.call, returns, || self.invoke(&callback));
+            ret.push((callback.call, res));
+        }
+        ret
+    }
+
+    fn invoke(&mut self, callback: &TraceEntry) -> i32 {
+        let handle = |idx: usize| XwfHandle::from_raw(callback.arg_i64(idx) as usize);
+
+        if callback.call == "XT_Init" {
+            let version = match XtVersion::try_from(callback.arg_i64(0) as u32) {
+                Ok(v) => v,
+                Err(_) => return XtInitReturn::PreventFurtherUseOfDll as i32,
+            };
+            let flags = XtInitFlags::from_bits_truncate(callback.arg_i64(1) as u32);
+            return dispatch::xt_init(self.extension.insert(T::create()), version, flags, None);
+        }
+
+        let ext = self.extension.get_or_insert_with(T::create);
+        let op_type = || XtPrepareOpType::try_from(callback.arg_i64(2) as u32);
+
+        match callback.call.as_str() {
+            "XT_Done" => dispatch::xt_done(ext),
+            "XT_About" => dispatch::xt_about(ext, None),
+            "XT_Prepare" => match op_type() {
+                Ok(op) => dispatch::xt_prepare(ext, handle(0), handle(1), op),
+                Err(_) => XtPrepareNegativeReturn::JustCallXtFinalize.into(),
+            },
+            "XT_Finalize" => match op_type() {
+                Ok(op) => dispatch::xt_finalize(ext, handle(0), handle(1), op),
+                Err(_) => XtFinalizeReturn::Ok.into(),
+            },
+            "XT_ProcessItem" => dispatch::xt_process_item(ext, callback.arg_i64(0) as i32),
+            "XT_ProcessItemEx" => dispatch::xt_process_item_ex(ext, callback.arg_i64(0) as i32, handle(1)),
+            "XT_ProcessSearchHit" => {
+                let args: Vec<i64> = (0..7).map(|i| callback.arg_i64(i)).collect();
+                match SearchHit::from_callback_args(&args) {
+                    Some(hit) => dispatch::xt_process_search_hit(ext, hit),
+                    None => XtProcessSearchHitReturn::Ok.into(),
+                }
+            }
+            // the size of the data to display, as the buffer itself is not part of the trace
+            "XT_View" => dispatch::xt_view(ext, callback.arg_i64(0) as i32, handle(1), handle(2), handle(3))
+                .map_or(0, |data| data.len() as i32),
+            // the search terms passed to XT_PrepareSearch are not part of the trace
+            _ => 0,
+        }
+    }
+}
+
+impl<T: XTension> Drop for ReplayDriver<T> {
+    fn drop(&mut self) {
+        let _ = reset_backend();
+    }
+}
@@ -1,13 +1,16 @@
 use crate::xwf_types::*;
-use winapi::shared::ntdef::HANDLE;
+use crate::xwf_types::XwfHandle;
 use crate::evidence::Evidence;
 use crate::item::{Item, ItemHandle};
 use crate::volume::Volume;
 use crate::window::Window;
+use crate::panic_guard::PanicReturns;
+use crate::search::{PrepareSearch, SearchHit};
+use crate::util::MIN_SUPPORTED_XWF_VERSION;
 
 
 pub trait NativeHandle {
-    fn get_handle(&self) -> HANDLE;
+    fn get_handle(&self) -> XwfHandle;
 }
 
 pub trait XTension {
@@ -16,6 +19,16 @@ pub trait XTension {
 
     fn create() -> Self;
 
+    /// return values passed to X-Ways Forensics if one of the callbacks panics
+    fn panic_returns() -> PanicReturns {
+        PanicReturns::default()
+    }
+
+    /// minimum version of X-Ways Forensics required by the X-Tension, checked before `xt_init` is called
+    fn min_xwf_version() -> (u16, u16) {
+        MIN_SUPPORTED_XWF_VERSION
+    }
+
     fn xt_init(&mut self, _version: XtVersion, _flags: XtInitFlags, _window: Option<Window>, _lic_info: XtLicenseInfo) -> Result<XtInitReturn, Self::XTensionError> {
         Ok(XtInitReturn::RunSingleThreaded)
     }
@@ -39,4 +52,96 @@ pub trait XTension {
     fn xt_finalize(&mut self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtFinalizeReturn, Self::XTensionError> {
         Ok(XtFinalizeReturn::Ok)
     }
-}
\ No newline at end of file
+
+    /// called before a search starts, changes to `search` are applied to the search settings
+    fn xt_prepare_search(&mut self, _search: &mut PrepareSearch) -> Result<(), Self::XTensionError> {
+        Ok(())
+    }
+
+    /// called for each search hit if the search was started with [`SearchFlags::CallProcessSearchHit`]
+    fn xt_process_search_hit(&mut self, _hit: SearchHit) -> Result<XtProcessSearchHitReturn, Self::XTensionError> {
+        Ok(XtProcessSearchHitReturn::Ok)
+    }
+
+    /// called if the X-Tension is used as viewer for `item`, returns the data X-Ways Forensics displays
+    /// instead of the file (`None` if the X-Tension cannot display the item). The buffer handed to
+    /// X-Ways Forensics is released by `XT_ReleaseMem`, which is exported together with `XT_View`.
+    fn xt_view(&mut self, _item: ItemHandle, _volume: Option<Volume>, _evidence: Option<Evidence>) -> Result<Option<Vec<u8>>, Self::XTensionError> {
+        Ok(None)
+    }
+}
+
+/// Multi-threaded variant of [`XTension`]. If `xt_init` returns [`XtInitReturn::RunMultiThreaded`],
+/// X-Ways Forensics calls `xt_process_item`/`xt_process_item_ex` concurrently from several worker threads,
+/// so all callbacks take `&self` and shared state has to be synchronized by the implementation.
+///
+/// Each worker thread gets its own [`XTensionMt::ThreadState`], created on its first item callback.
+/// All thread states are handed back via `thread_state_finished` before `xt_finalize` is called.
+///
+/// Functions that interact with the user interface (user input, progress bar) or change the state of
+/// X-Ways Forensics (e.g. [`Volume::select`]) must not be called from worker threads,
+/// see [`crate::mt`] for details.
+pub trait XTensionMt: Send + Sync + Sized {
+
+    type XTensionError;
+
+    type ThreadState: Send;
+
+    fn create() -> Self;
+
+    /// return values passed to X-Ways Forensics if one of the callbacks panics
+    fn panic_returns() -> PanicReturns {
+        PanicReturns::default()
+    }
+
+    /// minimum version of X-Ways Forensics required by the X-Tension, checked before `xt_init` is called
+    fn min_xwf_version() -> (u16, u16) {
+        MIN_SUPPORTED_XWF_VERSION
+    }
+
+    fn create_thread_state(&self) -> Self::ThreadState;
+
+    fn thread_state_finished(&self, _state: Self::ThreadState) {
+    }
+
+    fn xt_init(&self, _version: XtVersion, _flags: XtInitFlags, _window: Option<Window>, _lic_info: XtLicenseInfo) -> Result<XtInitReturn, Self::XTensionError> {
+        Ok(XtInitReturn::RunMultiThreaded)
+    }
+
+    fn xt_done(&self) -> Result<(), Self::XTensionError> {
+        Ok(())
+    }
+    fn xt_about(&self, _: Option<Window>) -> Result<(), Self::XTensionError> {
+        Ok(())
+    }
+    fn xt_prepare(&self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtPrepareReturn, Self::XTensionError> {
+        Ok(XtPrepareReturn::Positive(XtPreparePositiveReturnFlags::CallProcessItemLate))
+    }
+    fn xt_process_item(&self, _state: &mut Self::ThreadState, _item: Item) -> Result<XtProcessItemReturn, Self::XTensionError> {
+        Ok(XtProcessItemReturn::Ok)
+    }
+    fn xt_process_item_ex(&self, _state: &mut Self::ThreadState, _handle: ItemHandle) -> Result<XtProcessItemExReturn, Self::XTensionError> {
+        Ok(XtProcessItemExReturn::Ok)
+    }
+
+    fn xt_finalize(&self, _volume: Option<Volume>, _evidence: Option<Evidence>, _op_type: XtPrepareOpType) -> Result<XtFinalizeReturn, Self::XTensionError> {
+        Ok(XtFinalizeReturn::Ok)
+    }
+
+    /// called before a search starts, changes to `search` are applied to the search settings
+    fn xt_prepare_search(&self, _search: &mut PrepareSearch) -> Result<(), Self::XTensionError> {
+        Ok(())
+    }
+
+    /// called for each search hit if the search was started with [`SearchFlags::CallProcessSearchHit`]
+    fn xt_process_search_hit(&self, _hit: SearchHit) -> Result<XtProcessSearchHitReturn, Self::XTensionError> {
+        Ok(XtProcessSearchHitReturn::Ok)
+    }
+
+    /// called if the X-Tension is used as viewer for `item`, returns the data X-Ways Forensics displays
+    /// instead of the file (`None` if the X-Tension cannot display the item). The buffer handed to
+    /// X-Ways Forensics is released by `XT_ReleaseMem`, which is exported together with `XT_View`.
+    fn xt_view(&self, _item: ItemHandle, _volume: Option<Volume>, _evidence: Option<Evidence>) -> Result<Option<Vec<u8>>, Self::XTensionError> {
+        Ok(None)
+    }
+}
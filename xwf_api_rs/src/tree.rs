@@ -0,0 +1,252 @@
+//! Item tree of a volume snapshot, built with one pass over all items. Lookups of children,
+//! ancestors and paths are answered from memory afterwards instead of walking the snapshot.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use crate::backend::backend;
+use crate::error::XwfError;
+use crate::item::Item;
+use crate::volume::Volume;
+use crate::xwf_types::XwfHandle;
+
+// incremented whenever items may have been added or moved, trees of older generations are outdated
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// trees returned by VolumeTree::get, by volume handle
+static CACHE: Mutex<Option<HashMap<usize, Arc<VolumeTree>>>> = Mutex::new(None);
+
+/// marks all trees as outdated. Done automatically before `XT_Prepare`, when items are
+/// created via [`Item::create_file`] and when undoing a journal moves items back.
+pub fn invalidate() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    CACHE.lock().unwrap_or_else(|e| e.into_inner()).take();
+}
+
+/// Parent/children relations, depths and paths of all items of a volume snapshot
+pub struct VolumeTree {
+    volume: XwfHandle,
+    generation: u64,
+    parents: Vec<i32>,
+    children: Vec<Vec<i32>>,
+    roots: Vec<i32>,
+    depths: Vec<u32>,
+    paths: Vec<String>,
+}
+
+impl VolumeTree {
+    /// selects the volume snapshot and reads the parent and name of every item. Items whose
+    /// parent is unknown are treated as top-level items, as is the lowest item id of a cycle.
+    pub fn build(volume: &Volume) -> Result<VolumeTree, XwfError> {
+        let generation = GENERATION.load(Ordering::SeqCst);
+        let count = volume.select()?.max(0) as usize;
+
+        let mut parents = Vec::with_capacity(count);
+        let mut children = vec![Vec::new(); count];
+        for item_id in 0..count as i32 {
+            let parent_id = backend().get_item_parent(item_id);
+            let parent_id = if parent_id < 0 || parent_id as usize >= count || parent_id == item_id { -1 } else { parent_id };
+            if parent_id >= 0 {
+                children[parent_id as usize].push(item_id);
+            }
+            parents.push(parent_id);
+        }
+
+        let mut tree = VolumeTree {
+            volume: volume.handle(),
+            generation,
+            parents,
+            children,
+            roots: Vec::new(),
+            depths: vec![0; count],
+            paths: vec![String::new(); count],
+        };
+
+        let mut visited = vec![false; count];
+        let mut queue = VecDeque::new();
+        for item_id in 0..count as i32 {
+            if tree.parents[item_id as usize] < 0 {
+                tree.add_root(item_id, &mut visited, &mut queue);
+            }
+        }
+
+        // the remaining items are part of a cycle or below one. Each cycle is cut at its lowest
+        // item id, which becomes a top-level item, all other items keep their parents.
+        for item_id in 0..count as i32 {
+            if !visited[item_id as usize] {
+                let root_id = tree.lowest_in_cycle(item_id);
+                let parent_id = tree.parents[root_id as usize] as usize;
+                tree.children[parent_id].retain(|c| *c != root_id);
+                tree.parents[root_id as usize] = -1;
+                tree.add_root(root_id, &mut visited, &mut queue);
+            }
+        }
+        Ok(tree)
+    }
+
+    // follows the parents of an item that does not lead to a top-level item until they repeat
+    fn lowest_in_cycle(&self, item_id: i32) -> i32 {
+        let mut seen = HashSet::new();
+        let mut current = item_id;
+        while seen.insert(current) {
+            current = self.parents[current as usize];
+        }
+
+        let mut lowest = current;
+        let mut next = self.parents[current as usize];
+        while next != current {
+            lowest = lowest.min(next);
+            next = self.parents[next as usize];
+        }
+        lowest
+    }
+
+    // computes depth and path of the new top-level item and all items below
+    fn add_root(&mut self, root_id: i32, visited: &mut [bool], queue: &mut VecDeque<i32>) {
+        self.roots.push(root_id);
+        visited[root_id as usize] = true;
+        // like Item::get_path, the top-level item itself is not part of the path
+        self.paths[root_id as usize] = "\\".to_string();
+        queue.push_back(root_id);
+
+        while let Some(item_id) = queue.pop_front() {
+            let parent = item_id as usize;
+            for child_idx in 0..self.children[parent].len() {
+                let child = self.children[parent][child_idx] as usize;
+                visited[child] = true;
+                self.depths[child] = self.depths[parent] + 1;
+
+                let name = backend().get_item_name(child as u32);
+                self.paths[child] = if self.depths[parent] == 0 {
+                    format!("\\{}", name)
+                } else {
+                    format!("{}\\{}", self.paths[parent], name)
+                };
+                queue.push_back(child as i32);
+            }
+        }
+    }
+
+    /// returns the tree of the volume snapshot, which is built on first use and after it was
+    /// invalidated
+    pub fn get(volume: &Volume) -> Result<Arc<VolumeTree>, XwfError> {
+        let key = volume.handle().as_raw();
+        {
+            let cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(tree) = cache.as_ref().and_then(|c| c.get(&key)) {
+                if tree.is_valid() {
+                    return Ok(tree.clone());
+                }
+            }
+        }
+
+        let tree = Arc::new(VolumeTree::build(volume)?);
+        let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        cache.get_or_insert_with(HashMap::new).insert(key, tree.clone());
+        Ok(tree)
+    }
+
+    /// false if items may have been created since the tree was built
+    pub fn is_valid(&self) -> bool {
+        self.generation == GENERATION.load(Ordering::SeqCst)
+    }
+
+    pub fn volume(&self) -> XwfHandle {
+        self.volume
+    }
+
+    /// number of items
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn contains(&self, item: &Item) -> bool {
+        item.item_id >= 0 && (item.item_id as usize) < self.len()
+    }
+
+    /// items without parent, e.g. the root directory
+    pub fn roots(&self) -> impl ExactSizeIterator<Item = Item> + '_ {
+        self.roots.iter().map(|id| Item::new(*id))
+    }
+
+    pub fn parent(&self, item: &Item) -> Option<Item> {
+        let parent_id = *self.parents.get(usize::try_from(item.item_id).ok()?)?;
+        if parent_id < 0 { None } else { Some(Item::new(parent_id)) }
+    }
+
+    /// direct children in ascending order of their item ids
+    pub fn children(&self, item: &Item) -> impl ExactSizeIterator<Item = Item> + '_ {
+        self.child_ids(item).iter().map(|id| Item::new(*id))
+    }
+
+    fn child_ids(&self, item: &Item) -> &[i32] {
+        usize::try_from(item.item_id).ok()
+            .and_then(|idx| self.children.get(idx))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// 0 for top-level items
+    pub fn depth(&self, item: &Item) -> Option<u32> {
+        self.depths.get(usize::try_from(item.item_id).ok()?).copied()
+    }
+
+    /// same format as [`Item::get_path`]
+    pub fn path(&self, item: &Item) -> Option<&str> {
+        self.paths.get(usize::try_from(item.item_id).ok()?).map(String::as_str)
+    }
+
+    /// all ancestors, starting with the parent
+    pub fn ancestors(&self, item: &Item) -> Ancestors<'_> {
+        Ancestors { tree: self, next: self.parent(item) }
+    }
+
+    pub fn is_ancestor(&self, ancestor: &Item, item: &Item) -> bool {
+        match (self.depth(ancestor), self.depth(item)) {
+            (Some(ancestor_depth), Some(depth)) if ancestor_depth < depth => {
+                self.ancestors(item).nth((depth - ancestor_depth - 1) as usize) == Some(*ancestor)
+            },
+            _ => false,
+        }
+    }
+
+    /// the item and all items below it, depth-first with parents before their children
+    pub fn subtree(&self, item: &Item) -> Subtree<'_> {
+        let stack = if self.contains(item) { vec![item.item_id] } else { vec![] };
+        Subtree { tree: self, stack }
+    }
+}
+
+pub struct Ancestors<'a> {
+    tree: &'a VolumeTree,
+    next: Option<Item>,
+}
+
+impl Iterator for Ancestors<'_> {
+    type Item = Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.next?;
+        self.next = self.tree.parent(&item);
+        Some(item)
+    }
+}
+
+pub struct Subtree<'a> {
+    tree: &'a VolumeTree,
+    stack: Vec<i32>,
+}
+
+impl Iterator for Subtree<'_> {
+    type Item = Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = Item::new(self.stack.pop()?);
+        self.stack.extend(self.tree.child_ids(&item).iter().rev());
+        Some(item)
+    }
+}
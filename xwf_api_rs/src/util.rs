@@ -1,68 +1,20 @@
 use crate::error::XwfError;
 use crate::xwf_types::XtVersion;
-#[allow(unused_imports)]
-use crate::xwfwarn;
 
-pub fn check_supported_xwf_version(version: XtVersion) -> Result<(), XwfError> {
-    const MIN_VERSION: (u16, u16) = {
-        #[cfg(feature = "api_21_3")]
-        { (21, 3) }
-        #[cfg(not(feature = "api_21_3"))]
-        #[cfg(feature = "api_21_2")]
-        { (21, 2) }
-        #[cfg(not(feature = "api_21_2"))]
-        #[cfg(feature = "api_21_1")]
-        { (21, 1) }
-        #[cfg(not(feature = "api_21_1"))]
-        #[cfg(feature = "api_21_0")]
-        { (21, 0) }
-        #[cfg(not(feature = "api_21_0"))]
-        #[cfg(feature = "api_20_9")]
-        { (20, 9) }
-        #[cfg(not(feature = "api_20_9"))]
-        #[cfg(feature = "api_20_8")]
-        { (20, 8) }
-        #[cfg(not(feature = "api_20_8"))]
-        #[cfg(feature = "api_20_7")]
-        { (20, 7) }
-        #[cfg(not(feature = "api_20_7"))]
-        #[cfg(feature = "api_20_6")]
-        { (20, 6) }
-        #[cfg(not(feature = "api_20_6"))]
-        #[cfg(feature = "api_20_5")]
-        { (20, 5) }
-        #[cfg(not(feature = "api_20_5"))]
-        #[cfg(feature = "api_20_4")]
-        { (20, 4) }
-        #[cfg(not(feature = "api_20_4"))]
-        #[cfg(feature = "api_20_3")]
-        { (20, 3) }
-        #[cfg(not(feature = "api_20_3"))]
-        #[cfg(feature = "api_20_2")]
-        { (20, 2) }
-        #[cfg(not(feature = "api_20_2"))]
-        #[cfg(feature = "api_20_1")]
-        { (20, 1) }
-        #[cfg(not(feature = "api_20_1"))]
-        { (20, 0) }
-    };
+/// oldest version of X-Ways Forensics supported by xwf-api-rs
+pub const MIN_SUPPORTED_XWF_VERSION: (u16, u16) = (20, 0);
 
-
-    #[cfg(not(feature = "api_20_1"))] {
-        xwfwarn!("No API level defined. Assuming minimum supported XWF Version 20.0 of xwf-api-rs crate.");
-        xwfwarn!("You should define your minimal required API Level of xwf-api-rs (via feature api_<major>_<minor>).");
-        xwfwarn!("For differences of XWF API, also consult official X-Tension API Documentation \
-        https://www.x-ways.net/forensics/x-tensions/api.html");
-    }
-
-    if version.major < MIN_VERSION.0 || (version.major == MIN_VERSION.0 && version.minor < MIN_VERSION.1) {
-        Err(XwfError::IncompatibleXwfVersion(version, MIN_VERSION))
+/// checks `version` against the minimum version required by the X-Tension (at least [`MIN_SUPPORTED_XWF_VERSION`])
+pub fn check_supported_xwf_version(version: XtVersion, min_version: (u16, u16)) -> Result<(), XwfError> {
+    let min_version = min_version.max(MIN_SUPPORTED_XWF_VERSION);
+    if (version.major, version.minor) < min_version {
+        Err(XwfError::IncompatibleXwfVersion(version, min_version))
     } else {
         Ok(())
     }
 }
 
-pub fn split_values_by_comma(input: &String, num_expected: usize) -> Result<Vec<String>, XwfError> {
+pub fn split_values_by_comma(input: &str, num_expected: usize) -> Result<Vec<String>, XwfError> {
     let vec_assocs: Vec<String> = input.split(", ").map(|s| s.to_string()).collect();
 
     if vec_assocs.len() != num_expected {
@@ -73,7 +25,9 @@ pub fn split_values_by_comma(input: &String, num_expected: usize) -> Result<Vec<
 }
 
 
-pub fn char_ptr_to_string(mut ptr: *mut u8) -> String {
+/// # Safety
+/// `ptr` must point to a null-terminated string
+pub unsafe fn char_ptr_to_string(mut ptr: *mut u8) -> String {
 
     let mut vec_u8: Vec<u8> = Vec::new();
 
@@ -90,3 +44,11 @@ pub fn char_ptr_to_string(mut ptr: *mut u8) -> String {
     String::from_utf8(vec_u8).unwrap_or_default()
 }
 
+/// quotes the CSV field if required by RFC 4180, quotes within are doubled
+pub fn escape_csv(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\r', '\n']) || field.starts_with(' ') || field.ends_with(' ') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
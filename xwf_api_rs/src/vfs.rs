@@ -0,0 +1,219 @@
+//! File-system-like view of a volume snapshot: items are addressed by paths like
+//! `\Windows\System32\config\SYSTEM` instead of item ids, e.g. for artefact parsers.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use regex::{Regex, RegexBuilder};
+use crate::error::XwfError;
+use crate::item::{Item, ItemHandle};
+use crate::tree::VolumeTree;
+use crate::volume::Volume;
+use crate::xwf_types::*;
+
+/// Entry of [`VolumeFs::read_dir`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    pub item: Item,
+    pub name: String,
+    pub path: String,
+    pub is_directory: bool,
+}
+
+enum Component {
+    Name(String),
+    Wildcard(Regex),
+    // `**`, any number of directories
+    AnyDepth,
+}
+
+/// Path lookup on the [`VolumeTree`] of a volume snapshot. Paths are separated by `\` or `/` and
+/// start at the volume root, which contains the children of all top-level items (the root
+/// directory as well as virtual directories like "Path unknown"), same as [`Item::get_path`].
+pub struct VolumeFs {
+    volume: Volume,
+    tree: Arc<VolumeTree>,
+    case_insensitive: bool,
+}
+
+impl VolumeFs {
+    pub fn new(volume: &Volume) -> Result<VolumeFs, XwfError> {
+        Ok(VolumeFs {
+            volume: Volume::new(volume.handle())?,
+            tree: VolumeTree::get(volume)?,
+            case_insensitive: false,
+        })
+    }
+
+    /// compares names case-insensitively, e.g. for NTFS and FAT volumes
+    pub fn with_case_insensitive(mut self, case_insensitive: bool) -> VolumeFs {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    pub fn tree(&self) -> &VolumeTree {
+        &self.tree
+    }
+
+    /// first item with the given path, in ascending order of item ids if there are several
+    /// (e.g. deleted and existing files of the same name)
+    pub fn resolve(&self, path: &str) -> Option<Item> {
+        self.resolve_all(path).into_iter().next()
+    }
+
+    /// all items with the given path, wildcards are not expanded
+    pub fn resolve_all(&self, path: &str) -> Vec<Item> {
+        let components: Vec<Component> = split_path(path).into_iter()
+            .map(|c| Component::Name(self.normalize(c)))
+            .collect();
+        self.find(&components)
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.resolve(path).is_some()
+    }
+
+    /// expands `*` and `?` within names and `**` as any number of directories,
+    /// e.g. `\Users\*\NTUSER.DAT` or `\**\*.evtx`
+    pub fn glob(&self, pattern: &str) -> Result<Vec<Item>, XwfError> {
+        let components = split_path(pattern).into_iter()
+            .map(|c| self.component(c))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.find(&components))
+    }
+
+    /// lists the children of the directory (or any other item with child objects)
+    pub fn read_dir(&self, path: &str) -> Result<Vec<DirEntry>, XwfError> {
+        let parents = if split_path(path).is_empty() {
+            self.tree.roots().collect()
+        } else {
+            vec![self.resolve(path).ok_or_else(|| XwfError::PathNotFound(path.to_string()))?]
+        };
+
+        let mut entries: Vec<DirEntry> = parents.iter()
+            .flat_map(|p| self.tree.children(p))
+            .map(|item| DirEntry {
+                item,
+                name: self.name(&item).to_string(),
+                path: self.tree.path(&item).unwrap_or_default().to_string(),
+                is_directory: item.get_item_info_flags().is_ok_and(|f| f.contains(ItemInfoFlags::IsDirectory)),
+            })
+            .collect();
+        entries.sort_by_key(|e| e.item.item_id);
+        Ok(entries)
+    }
+
+    /// opens the file for reading via [`ItemHandle::reader`], the handle has to be closed afterwards
+    pub fn open(&self, path: &str) -> Result<ItemHandle, XwfError> {
+        let item = self.resolve(path).ok_or_else(|| XwfError::PathNotFound(path.to_string()))?;
+        item.open(&self.volume, OpenItemFlags::SuppressErrorMessages)
+    }
+
+    /// reads the whole file
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, XwfError> {
+        let handle = self.open(path)?;
+        let data = handle.read();
+        handle.close();
+        data
+    }
+
+    fn normalize(&self, name: &str) -> String {
+        if self.case_insensitive { name.to_lowercase() } else { name.to_string() }
+    }
+
+    // name of the item, the last component of its path
+    fn name<'a>(&'a self, item: &Item) -> &'a str {
+        self.tree.path(item)
+            .and_then(|p| p.rsplit('\\').next())
+            .unwrap_or_default()
+    }
+
+    fn component(&self, pattern: &str) -> Result<Component, XwfError> {
+        if pattern == "**" {
+            return Ok(Component::AnyDepth);
+        }
+        if !pattern.contains(['*', '?']) {
+            return Ok(Component::Name(self.normalize(pattern)));
+        }
+
+        RegexBuilder::new(&wildcard_regex(pattern))
+            .case_insensitive(self.case_insensitive)
+            .build()
+            .map(Component::Wildcard)
+            .map_err(|_| XwfError::InvalidInputArgument)
+    }
+
+    fn matches(&self, component: &Component, item: &Item) -> bool {
+        let name = self.name(item);
+        match component {
+            Component::Name(expected) if self.case_insensitive => name.to_lowercase() == *expected,
+            Component::Name(expected) => name == expected,
+            Component::Wildcard(regex) => regex.is_match(name),
+            Component::AnyDepth => true,
+        }
+    }
+
+    fn find(&self, components: &[Component]) -> Vec<Item> {
+        let mut found = BTreeSet::new();
+        for root in self.tree.roots() {
+            self.find_below(&root, components, &mut found);
+        }
+
+        // the volume root itself is represented by the first top-level item
+        if components.is_empty() {
+            found.extend(self.tree.roots().next().map(|r| r.item_id));
+        }
+        found.into_iter().map(Item::new).collect()
+    }
+
+    // collects all items below `parent` that match the remaining components
+    fn find_below(&self, parent: &Item, components: &[Component], found: &mut BTreeSet<i32>) {
+        let Some((component, rest)) = components.split_first() else {
+            return;
+        };
+
+        if let Component::AnyDepth = component {
+            if rest.is_empty() {
+                found.extend(self.tree.subtree(parent).skip(1).map(|i| i.item_id));
+                return;
+            }
+            self.find_below(parent, rest, found);
+            for child in self.tree.children(parent) {
+                self.find_below(&child, components, found);
+            }
+            return;
+        }
+
+        for child in self.tree.children(parent).filter(|c| self.matches(component, c)) {
+            if rest.is_empty() {
+                found.insert(child.item_id);
+            } else {
+                self.find_below(&child, rest, found);
+            }
+        }
+    }
+}
+
+// translates `*` and `?` within a single name, neither matches a separator
+pub(crate) fn wildcard_regex(pattern: &str) -> String {
+    pattern.chars().fold(String::from("^"), |mut regex, c| {
+        match c {
+            '*' => regex.push_str(r"[^\\/]*"),
+            '?' => regex.push_str(r"[^\\/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        regex
+    }) + "$"
+}
+
+// splits at both separators, `.` is ignored and `..` refers to the parent
+pub(crate) fn split_path(path: &str) -> Vec<&str> {
+    let mut components = Vec::new();
+    for component in path.split(['\\', '/']) {
+        match component {
+            "" | "." => {},
+            ".." => { components.pop(); },
+            c => components.push(c),
+        }
+    }
+    components
+}
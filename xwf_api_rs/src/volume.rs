@@ -1,14 +1,17 @@
 use std::collections::{HashMap, HashSet};
-use std::ptr::null_mut;
-use winapi::shared::minwindef::{DWORD, LPVOID};
-use winapi::shared::ntdef::{HANDLE, LONG, LPWSTR, PVOID};
-use winsafe::WString;
-use crate::get_raw_api;
+use serde::{Deserialize, Serialize};
+use crate::ffi_types::LONG;
+use crate::xwf_types::XwfHandle;
+use crate::backend::{backend, VolumeInformation};
 
 use crate::error::XwfError;
 use crate::item::Item;
 use crate::xwf_types::*;
-use crate::raw_api::RAW_API;
+use crate::capabilities::{require_function, require_xwf_version, xwf_supports};
+use crate::mt::require_main_thread;
+use crate::reader::{read_at, VolumeReader};
+use crate::tree::VolumeTree;
+use crate::traits::NativeHandle;
 
 
 macro_rules! back_to_enum {
@@ -32,9 +35,8 @@ macro_rules! back_to_enum {
         }
     }
 }
-#[cfg(feature="api_20_9")]
 back_to_enum! {
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
     pub enum HashType {
     CS8 = 1,
     CS16 = 2,
@@ -59,31 +61,6 @@ back_to_enum! {
     }
 }
 
-#[cfg(not(feature="api_20_9"))]
-back_to_enum! {
-    #[derive(Copy, Clone)]
-    pub enum HashType {
-    CS8 = 1,
-    CS16 = 2,
-    CS32 = 3,
-    CS64 = 4,
-    CRC16 = 5,
-    CRC32 = 6,
-    MD5 = 7,
-    SHA1 = 8,
-    SHA256 = 9,
-    RIPEMD128 = 10,
-    RIPEMD160 = 11,
-    MD4 = 12,
-    ED2K = 13,
-    ADLER32 = 14,
-    TigerTreeHash = 15,
-    Tiger128 = 16,
-    Tiger160 = 17,
-    Tiger192 = 18,
-    }
-}
-
 
 impl HashType {
     pub fn get_hash_size(&self) -> usize {
@@ -106,10 +83,17 @@ impl HashType {
             HashType::Tiger128 => 16,
             HashType::Tiger160 => 20,
             HashType::Tiger192 => 24,
-            #[cfg(feature="api_20_9")]
             HashType::MD5Folded => 16,
         }
     }
+
+    /// minimum version of X-Ways Forensics that knows the hash type
+    pub fn min_xwf_version(&self) -> (u16, u16) {
+        match self {
+            HashType::MD5Folded => (20, 9),
+            _ => (20, 0),
+        }
+    }
 }
 
 pub struct ItemIterator {
@@ -140,14 +124,103 @@ impl Iterator for ItemIterator {
     }
 }
 
+/// Volume (snapshot) of an opened evidence object.
+///
+/// `Send` and `Sync`: X-Ways Forensics calls the item callbacks of multi-threaded X-Tensions
+/// concurrently for the same volume, so reading through a shared handle is intended. Reads pass
+/// the offset explicitly and keep no position in the handle. [`Volume::select`] changes the global
+/// state and fails in worker threads.
 pub struct Volume {
-    volume_handle: HANDLE,
+    volume_handle: XwfHandle,
 }
 
+impl NativeHandle for Volume {
+    fn get_handle(&self) -> XwfHandle {
+        self.volume_handle
+    }
+}
+
+/// File system and sector/cluster layout of a volume as returned by `XWF_GetVolumeInformation`.
+/// Cluster numbers are relative to the first cluster of the volume, sector numbers and offsets
+/// are relative to the start of the volume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VolumeInfo {
+    pub file_system: FileSystemType,
+    pub bytes_per_sector: u32,
+    pub sectors_per_cluster: u32,
+    pub cluster_count: i64,
+    /// sector number of cluster 0
+    pub first_cluster_sector: i64,
+}
+
+impl VolumeInfo {
+    pub fn cluster_size(&self) -> u64 {
+        self.bytes_per_sector as u64 * self.sectors_per_cluster as u64
+    }
+
+    /// offset of the end of the last cluster
+    pub fn size(&self) -> u64 {
+        self.cluster_offset(self.cluster_count.max(0) as u64)
+    }
+
+    pub fn sector_offset(&self, sector: u64) -> u64 {
+        sector * self.bytes_per_sector as u64
+    }
+
+    pub fn cluster_offset(&self, cluster: u64) -> u64 {
+        self.sector_offset(self.cluster_to_sector(cluster))
+    }
+
+    pub fn cluster_to_sector(&self, cluster: u64) -> u64 {
+        self.first_cluster_sector.max(0) as u64 + cluster * self.sectors_per_cluster as u64
+    }
+
+    /// returns `None` for sectors before cluster 0 or if the cluster size is unknown
+    pub fn sector_to_cluster(&self, sector: u64) -> Option<u64> {
+        if self.sectors_per_cluster == 0 {
+            return None;
+        }
+        sector.checked_sub(self.first_cluster_sector.max(0) as u64)
+            .map(|s| s / self.sectors_per_cluster as u64)
+    }
+
+    /// sector containing the byte at `offset`, `None` if the sector size is unknown
+    pub fn sector_of_offset(&self, offset: u64) -> Option<u64> {
+        offset.checked_div(self.bytes_per_sector as u64)
+    }
+
+    /// cluster containing the byte at `offset`
+    pub fn cluster_of_offset(&self, offset: u64) -> Option<u64> {
+        self.sector_of_offset(offset).and_then(|s| self.sector_to_cluster(s))
+    }
+}
+
+
+// offset and length of `count` sectors or clusters of `unit_size` bytes, an error if the range overflows
+fn byte_range(offset: Option<u64>, count: u32, unit_size: u64) -> Result<(u64, usize), XwfError> {
+    let len = (count as u64).checked_mul(unit_size);
+    match (offset, len) {
+        (Some(offset), Some(len)) if offset.checked_add(len).is_some() => {
+            usize::try_from(len).map(|len| (offset, len)).map_err(|_| XwfError::InvalidInputArgument)
+        },
+        _ => Err(XwfError::InvalidInputArgument),
+    }
+}
+impl From<VolumeInformation> for VolumeInfo {
+    fn from(info: VolumeInformation) -> VolumeInfo {
+        VolumeInfo {
+            file_system: FileSystemType::from(info.file_system),
+            bytes_per_sector: info.bytes_per_sector,
+            sectors_per_cluster: info.sectors_per_cluster,
+            cluster_count: info.cluster_count,
+            first_cluster_sector: info.first_cluster_sector_no,
+        }
+    }
+}
 
 impl Volume {
-    pub fn new(volume_handle: HANDLE) -> Result<Volume, XwfError> {
-        if volume_handle == null_mut() {
+    pub fn new(volume_handle: XwfHandle) -> Result<Volume, XwfError> {
+        if volume_handle.is_null() {
             return Err(XwfError::InputHandleIsNull)
         }
         Ok(Volume {
@@ -156,56 +229,125 @@ impl Volume {
     }
 
 
-    pub fn handle(&self) ->  HANDLE { self.volume_handle }
+    pub fn handle(&self) ->  XwfHandle { self.volume_handle }
 
-    pub fn get_name(&self, name_type: VolumeNameType) -> String {
-        let mut array = [0u16;256];
-        (get_raw_api!().get_volume_name)(self.volume_handle, array.as_mut_ptr(), name_type as DWORD);
-        WString::from_wchars_slice(&array).to_string()
+    pub fn get_information(&self) -> Result<VolumeInfo, XwfError> {
+        require_function("XWF_GetVolumeInformation")?;
+        backend().get_volume_information(self.volume_handle)
+            .map(VolumeInfo::from)
+            .ok_or(XwfError::XwfFunctionCallFailed("get_volume_information"))
     }
 
-    pub fn select(&self) -> Result<i32, XwfError> {
+    pub fn get_file_system(&self) -> Result<FileSystemType, XwfError> {
+        Ok(self.get_information()?.file_system)
+    }
+
+    /// size of the volume in bytes
+    pub fn get_size(&self) -> Result<u64, XwfError> {
+        let size = backend().get_prop(self.volume_handle, PropType::LogicalSize as u32, None);
+        if size >= 0 {
+            return Ok(size as u64);
+        }
+
+        // fall back to the end of the last cluster
+        Ok(self.get_information()?.size())
+    }
 
-        let num_items: LONG;
-        #[cfg(feature = "api_20_9")]
-        {
-            num_items = (get_raw_api!().select_volume_snapshot)(self.volume_handle);
+    /// returns a buffered reader for the raw bytes of the volume, see [`VolumeReader`]
+    pub fn reader(&self) -> Result<VolumeReader<'_>, XwfError> {
+        VolumeReader::new(self)
+    }
+
+    /// reads raw bytes of the volume at `offset` and returns the number of bytes read,
+    /// which is less than `data.len()` at the end of the volume
+    pub fn read_at(&self, offset: u64, data: &mut [u8]) -> Result<usize, XwfError> {
+        require_function("XWF_Read")?;
+        read_at(self.volume_handle, self.get_size()?, offset, data, XwfError::ReadVolumeDataFailed).map_err(XwfError::from_io)
+    }
 
+    /// reads `count` sectors starting at sector number `first_sector`
+    pub fn read_sectors(&self, first_sector: u64, count: u32) -> Result<Vec<u8>, XwfError> {
+        let info = self.get_information()?;
+        if info.bytes_per_sector == 0 {
+            return Err(XwfError::InvalidVolumeGeometry);
         }
-        #[cfg(not(feature = "api_20_9"))]
-        {
-            (get_raw_api!().select_volume_snapshot)(self.volume_handle);
-            num_items = self._get_item_count(false)?;
+        let offset = first_sector.checked_mul(info.bytes_per_sector as u64);
+        let (offset, len) = byte_range(offset, count, info.bytes_per_sector as u64)?;
+        self.read_exact_at(offset, len)
+    }
+
+    /// reads `count` clusters starting at cluster number `first_cluster`
+    pub fn read_clusters(&self, first_cluster: u64, count: u32) -> Result<Vec<u8>, XwfError> {
+        let info = self.get_information()?;
+        if info.cluster_size() == 0 {
+            return Err(XwfError::InvalidVolumeGeometry);
+        }
+        let end = first_cluster.checked_add(count as u64).ok_or(XwfError::InvalidInputArgument)?;
+        if end > info.cluster_count.max(0) as u64 {
+            return Err(XwfError::InvalidInputArgument);
         }
+        let offset = first_cluster.checked_mul(info.sectors_per_cluster as u64)
+            .and_then(|s| s.checked_add(info.first_cluster_sector.max(0) as u64))
+            .and_then(|s| s.checked_mul(info.bytes_per_sector as u64));
+        let (offset, len) = byte_range(offset, count, info.cluster_size())?;
+        self.read_exact_at(offset, len)
+    }
 
-        if num_items < 0 {
-            return Err(XwfError::XwfFunctionCallFailed("select_volume_snapshot"));
+    fn read_exact_at(&self, offset: u64, len: usize) -> Result<Vec<u8>, XwfError> {
+        require_function("XWF_Read")?;
+        // the size is queried once, not for every chunk
+        let size = self.get_size()?;
+
+        let mut data = vec![0u8; len];
+        let mut num_read = 0;
+        while num_read < len {
+            let n = read_at(self.volume_handle, size, offset + num_read as u64, &mut data[num_read..], XwfError::ReadVolumeDataFailed)
+                .map_err(XwfError::from_io)?;
+            if n == 0 {
+                return Err(XwfError::ReadVolumeDataFailed(offset + num_read as u64));
+            }
+            num_read += n;
         }
-        Ok(num_items)
+        Ok(data)
+    }
 
+    pub fn get_name(&self, name_type: VolumeNameType) -> String {
+        backend().get_volume_name(self.volume_handle, name_type as u32)
+    }
 
+    pub fn select(&self) -> Result<i32, XwfError> {
+        require_function("XWF_SelectVolumeSnapshot")?;
+        require_main_thread("XWF_SelectVolumeSnapshot")?;
 
+        // XWF_SelectVolumeSnapshot returns the number of items since v20.9 only
+        let num_items = backend().select_volume_snapshot(self.volume_handle);
+        let num_items = if xwf_supports(20, 9) { num_items } else { self._get_item_count(false)? };
 
+        if num_items < 0 {
+            return Err(XwfError::XwfFunctionCallFailed("select_volume_snapshot"));
+        }
+        Ok(num_items)
     }
 
     pub fn get_hash_type(&self, get_secondary: bool) -> Option<HashType> {
         let mut prop_type = VsPropType::HashType1;
         if get_secondary { prop_type = VsPropType::HashType2; }
-        let ret = (get_raw_api!().get_vs_prop)(prop_type as LONG, null_mut());
+        let ret = backend().get_vs_prop(prop_type as LONG, None);
         if ret <= 0 {
             return None;
         }
-        Some(HashType::try_from(ret).unwrap())
+        HashType::try_from(ret).ok()
     }
 
     pub fn set_hash_type(&self, hash_type: HashType, set_secondary: bool) -> Result<(), XwfError>{
+        require_function("XWF_GetVSProp")?;
+        let (major, minor) = hash_type.min_xwf_version();
+        require_xwf_version("hash type", major, minor)?;
 
         let mut prop_type = VsPropType::SetHashType1;
         if set_secondary { prop_type = VsPropType::SetHashType2; }
 
-        let _buf_hash_type = hash_type as u32;
-
-        let ret = (get_raw_api!().get_vs_prop)(prop_type as LONG, _buf_hash_type.to_le_bytes().as_ptr() as PVOID);
+        let ret = backend().get_vs_prop(prop_type as LONG, Some(hash_type as u32));
 
         if ret < 0 {
             Err(XwfError::XwfFunctionCallFailed("get_vs_prop"))
@@ -214,22 +356,9 @@ impl Volume {
         }
     }
 
-    #[allow(unused_variables)]
     fn _get_item_count(&self, num_via_dbc: bool) -> Result<i32, XwfError> {
-        let arg: LPVOID;
-        #[cfg(feature = "api_20_3")] {
-            arg = match num_via_dbc {
-                false => null_mut(),
-                true => 1 as LPVOID
-            };
-        }
-
-        #[cfg(not(feature = "api_20_3"))] {
-            arg = null_mut();
-        }
-
-
-        let num_items = (get_raw_api!().get_item_count)(arg);
+        require_function("XWF_GetItemCount")?;
+        let num_items = backend().get_item_count(num_via_dbc);
 
         // check if number of items exceeds max value of int32 and if it does, raise an error.
         // nItemID is handled as a LONG value (signed integer) in X-Tension C API,
@@ -250,22 +379,23 @@ impl Volume {
     }
 
     //get number of items selected in directory browser context menu
-    #[cfg(feature = "api_20_3")]
     pub fn get_item_count_dbc(&self) -> Result<i32, XwfError> {
+        require_xwf_version("number of items selected in the directory browser", 20, 3)?;
         self._get_item_count(true)
     }
 
     pub fn get_prop(&self, prop_type: PropType) -> i64 {
-        (get_raw_api!().get_prop)(self.volume_handle, prop_type as DWORD, null_mut())
+        backend().get_prop(self.volume_handle, prop_type as u32, None)
     }
 
     pub fn get_name_2(&self) -> String {
-        let ptr = (get_raw_api!().get_prop)(self.volume_handle, PropType::PointerName as DWORD, null_mut()) as LPWSTR;
-        unsafe { WString::from_wchars_nullt(ptr).to_string() }
+        let mut name = String::new();
+        backend().get_prop(self.volume_handle, PropType::PointerName as u32, Some(&mut name));
+        name
     }
 
     pub fn close(&self) {
-        (get_raw_api!().close)(self.volume_handle);
+        backend().close(self.volume_handle);
     }
 
     pub fn iter_mut(&mut self) -> Result<ItemIterator, XwfError> {
@@ -278,29 +408,28 @@ impl Volume {
         Ok(ItemIterator::create(0, self.get_item_count()?))
     }
 
-    pub fn get_parent_dirs(&self, items: &Vec<u32>) -> HashSet<u32> {
-        items.iter()
-        .map(|i: &u32| Item::new(*i as i32).get_parent_dir())
-        .filter(|i| i.is_some())
-        .map(|i| i.unwrap().item_id as u32)
-        .collect()
+    pub fn get_parent_dirs(&self, items: &[u32]) -> Result<HashSet<u32>, XwfError> {
+        let mut ret = HashSet::new();
+        for i in items {
+            if let Some(dir) = Item::new(*i as i32).get_parent_dir()? {
+                ret.insert(dir.item_id as u32);
+            }
+        }
+        Ok(ret)
     }
 
-    pub fn get_parent_items(&self, items: &HashSet<Item>) -> HashMap<Item, Vec<Item>> 
+    pub fn get_parent_items(&self, items: &HashSet<Item>) -> Result<HashMap<Item, Vec<Item>>, XwfError>
     {
 
         let mut ret: HashMap<Item, Vec<Item>> = HashMap::new();
 
-        items.iter()
-        .filter_map(|i| i.get_parent_item().map(|parent| (parent, i)))
-        .for_each(|i| {
-            match &mut ret.get_mut(&i.0) {
-                Some(v) => {v.push(*i.1);},
-                None => { ret.insert(i.0, vec![*i.1]);},
-            };
-        });
+        for item in items {
+            if let Some(parent) = item.get_parent_item()? {
+                ret.entry(parent).or_default().push(*item);
+            }
+        }
 
-        ret
+        Ok(ret)
     }
 
 
@@ -308,28 +437,11 @@ impl Volume {
         where
             F: FnMut(&Item) -> bool
     {
-        let mut ret: HashMap<Item, Vec<Item>> = HashMap::new();
-        
-        parent_items.iter().for_each(|f| {
-            ret.insert(*f, vec![]);
-        });
-
-        let parent_items: HashSet<&Item> = parent_items.iter().filter(|p| {
-            p.get_item_info_flags().unwrap_or_default().contains(ItemInfoFlags::HasChildObjects)
-        }).collect();
-
-        self.iter()?
-        .filter(|i| pred(i))
-        .filter_map(|i| i.get_parent_item().map(|r: Item| (r, i)))
-        .filter(|i| parent_items.contains(&i.0))
-        .for_each(|i| {
-            match &mut ret.get_mut(&i.0) {
-                Some(v) => {v.push(i.1);},
-                None => { ret.insert(i.0, vec![i.1]);},
-            };
-        });
+        let tree = VolumeTree::get(self)?;
 
-        Ok(ret)
+        Ok(parent_items.iter()
+            .map(|p| (*p, tree.children(p).filter(|i| pred(i)).collect()))
+            .collect())
     }
 
 
@@ -337,22 +449,7 @@ impl Volume {
         where
             F: FnMut(&Item) -> bool
     {
-        let mut ret: Vec<Item> = Vec::new();
-
-        if parent_item.get_item_info_flags().is_ok_and(|f| !f.contains(ItemInfoFlags::HasChildObjects)) {
-            return Ok(ret);
-        }
-
-        let it = ItemIterator::create(parent_item.item_id, self.get_item_count()?);
-        
-        it
-        .filter(|i| pred(i))
-        .filter(|i| i.get_parent_item().is_some_and(|i| i == *parent_item) )
-        .for_each(|i| {
-            ret.push(i)
-        });
-
-        Ok(ret)
+        Ok(VolumeTree::get(self)?.children(parent_item).filter(|i| pred(i)).collect())
     }
 
 
@@ -369,14 +466,13 @@ impl Volume {
             ret.insert(*f, vec![]);
         });
 
-        let mut it = self.iter()?;
+        let tree = VolumeTree::get(self)?;
 
-        while let Some(i) = it.next() {
+        for i in (0..tree.len() as i32).map(Item::new) {
 
             if !pred(&i).unwrap_or(false) { continue; }
 
-            let hierarchy = i.get_hierarchy();
-            for p in hierarchy {
+            for p in tree.ancestors(&i) {
                 if ret.contains_key(&p) {
                     ret.get_mut(&p).unwrap().push(i);
                     break;
@@ -399,14 +495,10 @@ impl Volume {
         for i in 0..num_items {
             let item = Item::new(i);
 
-            if pred(item)? == true {
+            if pred(item)? {
                 ret.push(i);
             }
         }
         Ok(ret)
     }
 }
-
-
-unsafe impl Send for Volume {}
-unsafe impl Sync for Volume {}
\ No newline at end of file
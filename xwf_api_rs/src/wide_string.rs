@@ -0,0 +1,63 @@
+
+/// Null-terminated UTF-16 string, used for passing strings to and receiving strings from
+/// the X-Tension API (replaces `winsafe::WString`, which is only available on Windows).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WideString {
+    buf: Vec<u16>,
+}
+
+impl WideString {
+    /// uses the characters of the given buffer up to the first null character
+    pub fn from_wchars_slice(wchars: &[u16]) -> WideString {
+        let len = wchars.iter().position(|c| *c == 0).unwrap_or(wchars.len());
+        let mut buf = wchars[..len].to_vec();
+        buf.push(0);
+        WideString { buf }
+    }
+
+    /// # Safety
+    /// `ptr` must be null or point to a null-terminated UTF-16 string
+    pub unsafe fn from_wchars_nullt(ptr: *const u16) -> WideString {
+        if ptr.is_null() {
+            return WideString::default();
+        }
+        let mut len = 0;
+        while *ptr.add(len) != 0 {
+            len += 1;
+        }
+        WideString::from_wchars_slice(std::slice::from_raw_parts(ptr, len))
+    }
+
+    pub fn as_ptr(&self) -> *const u16 {
+        self.buf.as_ptr()
+    }
+
+    /// length in UTF-16 code units, without the terminating null character
+    pub fn len(&self) -> usize {
+        self.buf.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<&str> for WideString {
+    fn from(s: &str) -> Self {
+        let mut buf: Vec<u16> = s.encode_utf16().collect();
+        buf.push(0);
+        WideString { buf }
+    }
+}
+
+impl Default for WideString {
+    fn default() -> Self {
+        WideString { buf: vec![0] }
+    }
+}
+
+impl std::fmt::Display for WideString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", String::from_utf16_lossy(&self.buf[..self.len()]))
+    }
+}
@@ -1,15 +1,14 @@
-use std::ptr::null_mut;
-use winapi::shared::ntdef::HANDLE;
+use crate::xwf_types::XwfHandle;
 use crate::traits::NativeHandle;
 
 pub struct Window {
-    window_handle: HANDLE,
+    window_handle: XwfHandle,
 }
 
 impl Window {
-    pub fn new(handle: HANDLE) -> Option<Window> {
+    pub fn new(handle: XwfHandle) -> Option<Window> {
 
-        if handle == null_mut() {
+        if handle.is_null() {
             None
         } else {
             Some(Window {
@@ -20,7 +19,7 @@ impl Window {
 }
 
 impl NativeHandle for Window {
-    fn get_handle(&self) -> HANDLE {
+    fn get_handle(&self) -> XwfHandle {
         self.window_handle
     }
 }
\ No newline at end of file
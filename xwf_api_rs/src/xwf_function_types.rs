@@ -1,22 +1,21 @@
-use winapi::shared::minwindef::{BOOL, BYTE, DWORD, LPBOOL, LPLONG, LPVOID, PDWORD};
-use winapi::shared::ntdef::{LONG, HANDLE, WCHAR, LPWCH, PVOID, PLONG, LPWSTR};
-use winapi::ctypes::{__int64};
+use crate::ffi_types::{BOOL, BYTE, DWORD, LPBOOL, LPLONG, LPVOID, PDWORD, LONG, WCHAR, LPWCH, PVOID, PLONG, LPWSTR, __int64};
+use crate::xwf_types::{CodePages, EventInfo, SearchHitInfo, SearchInfo, XwfHandle};
 
 type LPINT64 = *mut i64;
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfOutputMessage = extern "stdcall" fn(lpMessage: *const WCHAR, nFlags: DWORD);
+pub type FnXwfOutputMessage = extern "system" fn(lpMessage: *const WCHAR, nFlags: DWORD);
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetVolumeName = extern "stdcall" fn(
-    hVolume: HANDLE,
+pub type FnXwfGetVolumeName = extern "system" fn(
+    hVolume: XwfHandle,
     lpString: LPWSTR,
     nType: DWORD);
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetVolumeInformation = extern "stdcall" fn(
-    hVolume: HANDLE,
+pub type FnXwfGetVolumeInformation = extern "system" fn(
+    hVolume: XwfHandle,
     lpFileSystem: LPLONG,
     nBytesPerSector: *mut DWORD,
     nSectorsPerCluster: *mut DWORD,
@@ -25,61 +24,62 @@ pub type FnXwfGetVolumeInformation = extern "stdcall" fn(
 ) -> BOOL;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfRead = extern "stdcall" fn(
-    hVolumeOrItem: HANDLE,
+pub type FnXwfRead = extern "system" fn(
+    hVolumeOrItem: XwfHandle,
     nOffset: __int64,
     lpBuffer: *mut BYTE,
     nNumberOfBytesToRead: DWORD,
 ) -> DWORD;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetItemSize = extern "stdcall" fn(
+pub type FnXwfGetItemSize = extern "system" fn(
     nItemID: LONG,
 ) -> __int64;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetItemInformation = extern "stdcall" fn(
+pub type FnXwfGetItemInformation = extern "system" fn(
     nItemID: LONG,
     nInfoType: LONG,
     lpSuccess: LPBOOL
 ) -> __int64;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfSetItemInformation = extern "stdcall" fn(
+pub type FnXwfSetItemInformation = extern "system" fn(
     nItemID: LONG,
     nInfoType: LONG,
     nInfoValue: __int64
 ) -> BOOL;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetItemType = extern "stdcall" fn(
+pub type FnXwfGetItemType = extern "system" fn(
     nItemId: LONG,
     lpTypeDescr: LPWSTR,
     nBufferLenAndFlags: DWORD
 ) -> LONG;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfSetItemType = extern "stdcall" fn(
+pub type FnXwfSetItemType = extern "system" fn(
+    nItemID: LONG,
     lpTypeDescr: LPWCH,
     nTypeStatus: LONG
 );
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetItemParent = extern "stdcall" fn(
+pub type FnXwfGetItemParent = extern "system" fn(
     nItemID: LONG
 ) -> LONG;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetNextEvObj = extern "stdcall" fn(
-    hPrevEvidence: HANDLE,
-    pReserved: LPVOID) -> HANDLE;
+pub type FnXwfGetNextEvObj = extern "system" fn(
+    hPrevEvidence: XwfHandle,
+    pReserved: LPVOID) -> XwfHandle;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetFirstEvObj = extern "stdcall" fn(
-    pReserved: LPVOID) -> HANDLE;
+pub type FnXwfGetFirstEvObj = extern "system" fn(
+    pReserved: LPVOID) -> XwfHandle;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetCaseProp = extern "stdcall" fn(
+pub type FnXwfGetCaseProp = extern "system" fn(
     pReserved: LPVOID ,
     nPropType: LONG,
     pBuffer: LPVOID,
@@ -88,20 +88,20 @@ pub type FnXwfGetCaseProp = extern "stdcall" fn(
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetEvObjProp = extern "stdcall" fn(
-    hEvidence: HANDLE,
+pub type FnXwfGetEvObjProp = extern "system" fn(
+    hEvidence: XwfHandle,
     nPropType: DWORD,
     pBuffer: PVOID,
 ) -> __int64;
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetEvObj = extern "stdcall" fn(
+pub type FnXwfGetEvObj = extern "system" fn(
     nEvObjID: DWORD
-) -> HANDLE;
+) -> XwfHandle;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetReportTableInfo = extern "stdcall" fn(
+pub type FnXwfGetReportTableInfo = extern "system" fn(
     pReserved: LPVOID,
     nReportTableID: LONG,
     lpOptional: PLONG
@@ -109,14 +109,14 @@ pub type FnXwfGetReportTableInfo = extern "stdcall" fn(
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetReportTableAssocs = extern "stdcall" fn(
+pub type FnXwfGetReportTableAssocs = extern "system" fn(
     nItemID: LONG,
     lpBuffer: LPWSTR,
     nBufferLen: LONG
 ) -> DWORD;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetHashSetAssocs = extern "stdcall" fn(
+pub type FnXwfGetHashSetAssocs = extern "system" fn(
     nItemID: LONG,
     lpBuffer: LPWSTR,
     nBufferLen: LONG,
@@ -125,120 +125,114 @@ pub type FnXwfGetHashSetAssocs = extern "stdcall" fn(
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetEvObjReportTableAssocs = extern "stdcall" fn(
-    hEvidence: HANDLE,
+pub type FnXwfGetEvObjReportTableAssocs = extern "system" fn(
+    hEvidence: XwfHandle,
     nFlags: LONG,
     lpValue: PLONG
 ) -> LPVOID;
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfOpenEvObj = extern "stdcall" fn(
-    hEvidence: HANDLE,
+pub type FnXwfOpenEvObj = extern "system" fn(
+    hEvidence: XwfHandle,
     nFlags: DWORD
-) -> HANDLE;
+) -> XwfHandle;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfAddToReportTable = extern "stdcall" fn(
+pub type FnXwfAddToReportTable = extern "system" fn(
     nItemID: LONG,
     lpReportTableName: LPWSTR,
     nFlags: DWORD
 ) -> LONG;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetHashValue = extern "stdcall" fn(
+pub type FnXwfGetHashValue = extern "system" fn(
     nItemID: LONG,
     lpBuffer: LPVOID
 ) -> BOOL;
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfSetHashValue = extern "stdcall" fn(
+pub type FnXwfSetHashValue = extern "system" fn(
     nItemID: LONG,
     lpHash: LPVOID,
     nParam: DWORD
 ) -> BOOL;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfShowProgress = extern "stdcall" fn(
+pub type FnXwfShowProgress = extern "system" fn(
     lpCaption: LPWSTR,
     nFlags: DWORD,
 );
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfOpenItem = extern "stdcall" fn(
-    hVolume: HANDLE,
+pub type FnXwfOpenItem = extern "system" fn(
+    hVolume: XwfHandle,
     nItemID: LONG,
     nFlags: DWORD,
-) -> HANDLE;
+) -> XwfHandle;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetVsprop = extern "stdcall" fn(
+pub type FnXwfGetVsprop = extern "system" fn(
     nPropType: LONG,
     pBuffer: PVOID,
 ) -> __int64;
 
 
-#[cfg(feature="api_20_9")]
+// returns void before v20.9, the return value must be ignored for older versions
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfSelectVolumeSnapshot = extern "stdcall" fn(
-    hVolume: HANDLE,
+pub type FnXwfSelectVolumeSnapshot = extern "system" fn(
+    hVolume: XwfHandle,
 ) -> LONG;
 
-#[cfg(not(feature="api_20_9"))]
-#[allow(non_snake_case, unused_variables)]
-pub type FnXwfSelectVolumeSnapshot = extern "stdcall" fn(
-    hVolume: HANDLE,
-);
-
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetItemName = extern "stdcall" fn(
+pub type FnXwfGetItemName = extern "system" fn(
     nItemID: DWORD
 ) -> LPWSTR;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetItemCount = extern "stdcall" fn(
+pub type FnXwfGetItemCount = extern "system" fn(
     pTarget: LPVOID
 ) -> DWORD;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetProp = extern "stdcall" fn(
-    hVolumeOrItem: HANDLE,
+pub type FnXwfGetProp = extern "system" fn(
+    hVolumeOrItem: XwfHandle,
     nPropType: DWORD ,
     lpBuffer: PVOID,
 ) -> __int64;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfCloseEvObj = extern "stdcall" fn(
-    hEvidence: HANDLE,
+pub type FnXwfCloseEvObj = extern "system" fn(
+    hEvidence: XwfHandle,
 );
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfClose = extern "stdcall" fn(
-    hVolumeOrItem: HANDLE,
+pub type FnXwfClose = extern "system" fn(
+    hVolumeOrItem: XwfHandle,
 );
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfSetProgressDescription = extern "stdcall" fn(
+pub type FnXwfSetProgressDescription = extern "system" fn(
     lpStr: LPWSTR,
 );
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfSetProgressPercentage = extern "stdcall" fn(
+pub type FnXwfSetProgressPercentage = extern "system" fn(
     nPercent: DWORD,
 );
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfHideProgress = extern "stdcall" fn(
+pub type FnXwfHideProgress = extern "system" fn(
 );
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfShouldStop = extern "stdcall" fn(
+pub type FnXwfShouldStop = extern "system" fn(
 ) -> BOOL;
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetUserInput = extern "stdcall" fn(
+pub type FnXwfGetUserInput = extern "system" fn(
     lpMessage: LPWSTR,
     lpBuffer: LPWSTR,
     nBufferLen: DWORD,
@@ -248,40 +242,54 @@ pub type FnXwfGetUserInput = extern "stdcall" fn(
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetExtractedMetadata = extern "stdcall" fn(
+pub type FnXwfGetExtractedMetadata = extern "system" fn(
     nItemID: LONG,
 ) -> LPWSTR;
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetMetadataEx = extern "stdcall" fn(
-    hItem: HANDLE,
+pub type FnXwfGetMetadataEx = extern "system" fn(
+    hItem: XwfHandle,
     lpnFlags: PDWORD
 ) -> LPVOID;
 
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfReleaseMem = extern "stdcall" fn(
+pub type FnXwfReleaseMem = extern "system" fn(
     lpBuffer: PVOID,
 ) -> BOOL;
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetItemOfs = extern "stdcall" fn(
+pub type FnXwfGetItemOfs = extern "system" fn(
     nItemID: LONG ,
     nDefOfs: LPINT64,
     nStartSector: LPINT64,
 );
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfGetComment = extern "stdcall" fn(
+pub type FnXwfGetComment = extern "system" fn(
     nItemID: LONG ,
 ) -> LPWSTR;
 
+#[allow(non_snake_case, unused_variables)]
+pub type FnXwfAddComment = extern "system" fn(
+    nItemID: LONG,
+    lpComment: LPWSTR,
+    nFlagsHowToAdd: DWORD,
+) -> BOOL;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfCreateFile = extern "stdcall" fn(
+pub type FnXwfAddExtractedMetadata = extern "system" fn(
+    nItemID: LONG,
+    lpComment: LPWSTR,
+    nFlagsHowToAdd: DWORD,
+) -> BOOL;
+
+
+#[allow(non_snake_case, unused_variables)]
+pub type FnXwfCreateFile = extern "system" fn(
     pName: LPWSTR,
     nCreationFlags: DWORD,
     nParentItemID: LONG,
@@ -289,18 +297,60 @@ pub type FnXwfCreateFile = extern "stdcall" fn(
 ) -> LONG;
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfSetItemSize = extern "stdcall" fn(
+pub type FnXwfSetItemSize = extern "system" fn(
     nItemID: LONG,
     nSize: __int64,
 );
 
 
 #[allow(non_snake_case, unused_variables)]
-pub type FnXwfSetItemParent = extern "stdcall" fn(
+pub type FnXwfSetItemParent = extern "system" fn(
     nChildItemID: LONG,
     nParentItemID: LONG
 );
 
+#[allow(non_snake_case, unused_variables)]
+pub type FnXwfSearch = extern "system" fn(
+    SInfo: *mut SearchInfo,
+    CPages: *mut CodePages,
+) -> LONG;
+
+#[allow(non_snake_case, unused_variables)]
+pub type FnXwfGetSearchTerm = extern "system" fn(
+    nSearchTermID: LONG,
+    pReserved: LPVOID,
+) -> LPWSTR;
+
+#[allow(non_snake_case, unused_variables)]
+pub type FnXwfAddSearchTerm = extern "system" fn(
+    lpSearchTermName: LPWSTR,
+    nFlags: DWORD,
+) -> LONG;
 
+#[allow(non_snake_case, unused_variables)]
+pub type FnXwfGetSearchHit = extern "system" fn(
+    nSearchHitNo: LONG,
+    pInfo: *mut SearchHitInfo,
+) -> LONG;
 
+#[allow(non_snake_case, unused_variables)]
+pub type FnXwfSetSearchHit = extern "system" fn(
+    nSearchHitNo: LONG,
+    pInfo: *mut SearchHitInfo,
+) -> LONG;
 
+#[allow(non_snake_case, unused_variables)]
+pub type FnXwfAddSearchHit = extern "system" fn(
+    pInfo: *mut SearchHitInfo,
+) -> LONG;
+
+#[allow(non_snake_case, unused_variables)]
+pub type FnXwfGetEvent = extern "system" fn(
+    nEventNo: DWORD,
+    pEvt: *mut EventInfo,
+) -> LONG;
+
+#[allow(non_snake_case, unused_variables)]
+pub type FnXwfAddEvent = extern "system" fn(
+    pEvt: *mut EventInfo,
+) -> LONG;
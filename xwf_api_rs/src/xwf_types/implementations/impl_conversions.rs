@@ -1,8 +1,8 @@
 use crate::xwf_types::*;
 
-impl Into<i32> for XtPrepareNegativeReturn {
-    fn into(self) -> i32 {
-        match self {
+impl From<XtPrepareNegativeReturn> for i32 {
+    fn from(val: XtPrepareNegativeReturn) -> Self {
+        match val {
             XtPrepareNegativeReturn::StopWholeOperation => { XtPrepareNegativeReturn::StopWholeOperation as i32 }
             XtPrepareNegativeReturn::PreventFurtherUse => { XtPrepareNegativeReturn::PreventFurtherUse as i32 }
             XtPrepareNegativeReturn::ExcludeVolume => { XtPrepareNegativeReturn::ExcludeVolume as i32 }
@@ -13,18 +13,18 @@ impl Into<i32> for XtPrepareNegativeReturn {
 }
 
 
-impl Into<i32> for XtPrepareReturn {
-    fn into(self) -> i32 {
-        match self {
+impl From<XtPrepareReturn> for i32 {
+    fn from(val: XtPrepareReturn) -> Self {
+        match val {
             XtPrepareReturn::Negative(x) => {x.into() },
-            XtPrepareReturn::Positive(x) => { x.bits() }
+            XtPrepareReturn::Positive(x) => { x.supported().bits() }
         }
     }
 }
 
-impl Into<i32> for XtProcessItemReturn {
-    fn into(self) -> i32 {
-        match self {
+impl From<XtProcessItemReturn> for i32 {
+    fn from(val: XtProcessItemReturn) -> Self {
+        match val {
             XtProcessItemReturn::StopCurrentOperation => { XtProcessItemReturn::StopCurrentOperation as i32}
             XtProcessItemReturn::SkipAllOtherVSRefinements => { XtProcessItemReturn::SkipAllOtherVSRefinements as i32 }
             XtProcessItemReturn::Ok => { XtProcessItemReturn::Ok as i32}
@@ -33,15 +33,23 @@ impl Into<i32> for XtProcessItemReturn {
 }
 
 
-impl Into<i32> for XtProcessItemExReturn {
-    fn into(self) -> i32 {
-        match self {
+impl From<XtProcessItemExReturn> for i32 {
+    fn from(val: XtProcessItemExReturn) -> Self {
+        match val {
             XtProcessItemExReturn::StopCurrentOperation => { XtProcessItemExReturn::StopCurrentOperation as i32}
             XtProcessItemExReturn::Ok => { XtProcessItemExReturn::Ok as i32 }
         }
     }
 }
 
+impl From<XtProcessSearchHitReturn> for i32 {
+    fn from(val: XtProcessSearchHitReturn) -> Self {
+        match val {
+            XtProcessSearchHitReturn::StopSearch => { XtProcessSearchHitReturn::StopSearch as i32 }
+            XtProcessSearchHitReturn::Ok => { XtProcessSearchHitReturn::Ok as i32 }
+        }
+    }
+}
 
 impl TryFrom<i64> for ItemInfoClassification {
     type Error = XwfError;
@@ -72,9 +80,19 @@ impl TryFrom<i64> for ItemInfoClassification {
     }
 }
 
-impl Into<i32> for XtFinalizeReturn {
-    fn into(self) -> i32 {
-        match self {
+impl From<XtInitReturn> for i32 {
+    fn from(val: XtInitReturn) -> Self {
+        match val {
+            XtInitReturn::PreventFurtherUseOfDll => {XtInitReturn::PreventFurtherUseOfDll as i32}
+            XtInitReturn::RunSingleThreaded => {XtInitReturn::RunSingleThreaded as i32}
+            XtInitReturn::RunMultiThreaded => {XtInitReturn::RunMultiThreaded as i32}
+        }
+    }
+}
+
+impl From<XtFinalizeReturn> for i32 {
+    fn from(val: XtFinalizeReturn) -> Self {
+        match val {
             XtFinalizeReturn::RefreshDirectoryListing => {XtFinalizeReturn::RefreshDirectoryListing as i32}
             XtFinalizeReturn::Ok => {XtFinalizeReturn::Ok as i32}
         }
@@ -96,11 +114,18 @@ impl TryFrom<u32> for XtVersion {
             major: (version / 100),
             minor: (version % 100) / 10,
             service_release: ((value & 0x0000FF00) >> 8) as u8,
-            language: ((value & 0x000000FF) >> 0) as u8,
+            language: (value & 0x000000FF) as u8,
         })
     }
 }
 
+impl From<XtVersion> for u32 {
+    fn from(value: XtVersion) -> u32 {
+        let version = (value.major as u32) * 100 + (value.minor as u32) * 10;
+        (version << 16) | ((value.service_release as u32) << 8) | value.language as u32
+    }
+}
+
 impl TryFrom<u32> for XtPrepareOpType {
     type Error = ();
 
@@ -216,4 +241,140 @@ impl From<String> for FileTypeCategory {
             _ => FileTypeCategory::Other
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<i32> for FileSystemType {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => FileSystemType::Unknown,
+            1 => FileSystemType::Fat12,
+            2 => FileSystemType::Fat16,
+            3 => FileSystemType::Fat32,
+            4 => FileSystemType::ExFat,
+            5 => FileSystemType::Tfat,
+            6 => FileSystemType::Ntfs,
+            7 => FileSystemType::Next3,
+            8 => FileSystemType::Ext2,
+            9 => FileSystemType::Ext3,
+            10 => FileSystemType::Ext4,
+            11 => FileSystemType::ReiserFs,
+            12 => FileSystemType::Reiser4,
+            13 => FileSystemType::Ufs,
+            14 => FileSystemType::Ufs2,
+            15 => FileSystemType::Hfs,
+            16 => FileSystemType::HfsPlus,
+            17 => FileSystemType::Xfs,
+            18 => FileSystemType::Btrfs,
+            19 => FileSystemType::Apfs,
+            20 => FileSystemType::ReFs,
+            21 => FileSystemType::Xwfs,
+            22 => FileSystemType::Xwfs2,
+            -1 => FileSystemType::Iso9660,
+            -2 => FileSystemType::Udf,
+            x => FileSystemType::Other(x),
+        }
+    }
+}
+
+impl From<FileSystemType> for i32 {
+    fn from(val: FileSystemType) -> Self {
+        match val {
+            FileSystemType::Unknown => 0,
+            FileSystemType::Fat12 => 1,
+            FileSystemType::Fat16 => 2,
+            FileSystemType::Fat32 => 3,
+            FileSystemType::ExFat => 4,
+            FileSystemType::Tfat => 5,
+            FileSystemType::Ntfs => 6,
+            FileSystemType::Next3 => 7,
+            FileSystemType::Ext2 => 8,
+            FileSystemType::Ext3 => 9,
+            FileSystemType::Ext4 => 10,
+            FileSystemType::ReiserFs => 11,
+            FileSystemType::Reiser4 => 12,
+            FileSystemType::Ufs => 13,
+            FileSystemType::Ufs2 => 14,
+            FileSystemType::Hfs => 15,
+            FileSystemType::HfsPlus => 16,
+            FileSystemType::Xfs => 17,
+            FileSystemType::Btrfs => 18,
+            FileSystemType::Apfs => 19,
+            FileSystemType::ReFs => 20,
+            FileSystemType::Xwfs => 21,
+            FileSystemType::Xwfs2 => 22,
+            FileSystemType::Iso9660 => -1,
+            FileSystemType::Udf => -2,
+            FileSystemType::Other(x) => x,
+        }
+    }
+}
+
+impl From<u32> for EventType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => EventType::Unspecified,
+            1 => EventType::Creation,
+            2 => EventType::Modification,
+            3 => EventType::LastAccess,
+            4 => EventType::EntryModification,
+            5 => EventType::Deletion,
+            6 => EventType::InternalCreation,
+            7 => EventType::LastPrinted,
+            100 => EventType::InternetVisit,
+            101 => EventType::InternetDownload,
+            102 => EventType::InternetCookie,
+            200 => EventType::EmailSent,
+            201 => EventType::EmailReceived,
+            300 => EventType::EventLogEntry,
+            301 => EventType::RegistryKeyLastWritten,
+            400 => EventType::PhotoTaken,
+            x => EventType::Other(x),
+        }
+    }
+}
+
+impl From<EventType> for u32 {
+    fn from(val: EventType) -> Self {
+        match val {
+            EventType::Unspecified => 0,
+            EventType::Creation => 1,
+            EventType::Modification => 2,
+            EventType::LastAccess => 3,
+            EventType::EntryModification => 4,
+            EventType::Deletion => 5,
+            EventType::InternalCreation => 6,
+            EventType::LastPrinted => 7,
+            EventType::InternetVisit => 100,
+            EventType::InternetDownload => 101,
+            EventType::InternetCookie => 102,
+            EventType::EmailSent => 200,
+            EventType::EmailReceived => 201,
+            EventType::EventLogEntry => 300,
+            EventType::RegistryKeyLastWritten => 301,
+            EventType::PhotoTaken => 400,
+            EventType::Other(x) => x,
+        }
+    }
+}
+
+impl From<i64> for ColorAnalysis {
+    fn from(value: i64) -> Self {
+        match value {
+            -1 => ColorAnalysis::NotAnalyzed,
+            -2 => ColorAnalysis::BlackAndWhite,
+            0..=100 => ColorAnalysis::SkinTone(value as u8),
+            x => ColorAnalysis::Other(x),
+        }
+    }
+}
+
+impl From<ColorAnalysis> for i64 {
+    fn from(val: ColorAnalysis) -> Self {
+        match val {
+            ColorAnalysis::NotAnalyzed => -1,
+            ColorAnalysis::BlackAndWhite => -2,
+            ColorAnalysis::SkinTone(x) => x.min(100) as i64,
+            ColorAnalysis::Other(x) => x,
+        }
+    }
+}
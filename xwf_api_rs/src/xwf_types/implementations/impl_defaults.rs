@@ -4,4 +4,21 @@ impl Default for ItemInfoFlags {
     fn default() -> Self {
         ItemInfoFlags::empty()
     }
-}
\ No newline at end of file
+}
+impl Default for SearchFlags {
+    fn default() -> Self {
+        SearchFlags::empty()
+    }
+}
+
+impl Default for SearchHitFlags {
+    fn default() -> Self {
+        SearchHitFlags::empty()
+    }
+}
+
+impl Default for EventFlags {
+    fn default() -> Self {
+        EventFlags::empty()
+    }
+}
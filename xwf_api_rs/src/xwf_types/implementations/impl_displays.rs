@@ -1,8 +1,65 @@
 use std::fmt;
-use crate::xwf_types::ItemInfoClassification;
+use crate::xwf_types::{EventType, FileSystemType, ItemInfoClassification};
 
 impl fmt::Display for ItemInfoClassification {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}", self)
     }
-}
\ No newline at end of file
+}
+
+impl fmt::Display for FileSystemType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileSystemType::Unknown => write!(f, "unknown"),
+            FileSystemType::Fat12 => write!(f, "FAT12"),
+            FileSystemType::Fat16 => write!(f, "FAT16"),
+            FileSystemType::Fat32 => write!(f, "FAT32"),
+            FileSystemType::ExFat => write!(f, "exFAT"),
+            FileSystemType::Tfat => write!(f, "TFAT"),
+            FileSystemType::Ntfs => write!(f, "NTFS"),
+            FileSystemType::Next3 => write!(f, "Next3"),
+            FileSystemType::Ext2 => write!(f, "Ext2"),
+            FileSystemType::Ext3 => write!(f, "Ext3"),
+            FileSystemType::Ext4 => write!(f, "Ext4"),
+            FileSystemType::ReiserFs => write!(f, "ReiserFS"),
+            FileSystemType::Reiser4 => write!(f, "Reiser4"),
+            FileSystemType::Ufs => write!(f, "UFS"),
+            FileSystemType::Ufs2 => write!(f, "UFS2"),
+            FileSystemType::Hfs => write!(f, "HFS"),
+            FileSystemType::HfsPlus => write!(f, "HFS+"),
+            FileSystemType::Xfs => write!(f, "XFS"),
+            FileSystemType::Btrfs => write!(f, "Btrfs"),
+            FileSystemType::Apfs => write!(f, "APFS"),
+            FileSystemType::ReFs => write!(f, "ReFS"),
+            FileSystemType::Xwfs => write!(f, "XWFS"),
+            FileSystemType::Xwfs2 => write!(f, "XWFS2"),
+            FileSystemType::Iso9660 => write!(f, "ISO 9660"),
+            FileSystemType::Udf => write!(f, "UDF"),
+            FileSystemType::Other(x) => write!(f, "file system {}", x),
+        }
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventType::Unspecified => write!(f, "unspecified"),
+            EventType::Creation => write!(f, "creation"),
+            EventType::Modification => write!(f, "modification"),
+            EventType::LastAccess => write!(f, "last access"),
+            EventType::EntryModification => write!(f, "entry modification"),
+            EventType::Deletion => write!(f, "deletion"),
+            EventType::InternalCreation => write!(f, "internal creation"),
+            EventType::LastPrinted => write!(f, "last printed"),
+            EventType::InternetVisit => write!(f, "internet visit"),
+            EventType::InternetDownload => write!(f, "internet download"),
+            EventType::InternetCookie => write!(f, "internet cookie"),
+            EventType::EmailSent => write!(f, "email sent"),
+            EventType::EmailReceived => write!(f, "email received"),
+            EventType::EventLogEntry => write!(f, "event log entry"),
+            EventType::RegistryKeyLastWritten => write!(f, "registry key last written"),
+            EventType::PhotoTaken => write!(f, "photo taken"),
+            EventType::Other(x) => write!(f, "event type {}", x),
+        }
+    }
+}
@@ -1,6 +1,48 @@
 use serde::{Deserialize, Deserializer};
+use serde::de::IntoDeserializer;
 use crate::xwf_types::FileTypeCategory;
 
+// variant names of FileTypeCategory as written by its Serialize implementation
+#[derive(Deserialize)]
+#[serde(remote = "FileTypeCategory")]
+enum FileTypeCategoryDef {
+    Picture,
+    Word,
+    Email,
+    Internet,
+    PageLayout,
+    Spreadsheet,
+    Misc,
+    Text,
+    Archive,
+    Audio,
+    Video,
+    WindowsInternal,
+    Thumbnail,
+    Database,
+    Program,
+    MobilePhone,
+    Chat,
+    AddressBook,
+    MacOsXIos,
+    Cad,
+    VariousData,
+    Gps,
+    DiskImage,
+    SourceCode,
+    Cryptography,
+    WindowsRegistry,
+    P2P,
+    Ebook,
+    Graphics3D,
+    Projects,
+    UnixLinux,
+    Font,
+    StillImage,
+    Unknown,
+    Other,
+}
+
 impl<'de> Deserialize<'de> for FileTypeCategory {
 
     fn deserialize<D>(deserializer: D) -> Result<FileTypeCategory, D::Error>
@@ -9,7 +51,12 @@ impl<'de> Deserialize<'de> for FileTypeCategory {
     {
         let s: String = Deserialize::deserialize(deserializer)?;
 
-        FileTypeCategory::try_from(s).map_err(serde::de::Error::custom)
+        // otherwise the category name as returned by X-Ways Forensics
+        let variant: serde::de::value::StrDeserializer<'_, D::Error> = s.as_str().into_deserializer();
+        match FileTypeCategoryDef::deserialize(variant) {
+            Ok(category) => Ok(category),
+            Err(_) => Ok(FileTypeCategory::from(s)),
+        }
 
     }
-}
\ No newline at end of file
+}
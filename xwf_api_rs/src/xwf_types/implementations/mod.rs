@@ -1,6 +1,6 @@
-use chrono::NaiveDateTime;
-use winapi::ctypes::__int64;
-use winapi::shared::minwindef::LPVOID;
+use chrono::{DateTime, NaiveDateTime, SecondsFormat};
+use crate::capabilities::xwf_supports;
+use crate::ffi_types::{__int64, LPVOID};
 use crate::xwf_types::*;
 
 mod impl_conversions;
@@ -11,9 +11,59 @@ mod impl_serde;
 
 impl ItemInfoDeletion {
     pub fn is_existing(&self) -> bool {
-        match &self {
-            ItemInfoDeletion::Existing => true,
-            _ => false
+        matches!(self, ItemInfoDeletion::Existing)
+    }
+}
+
+impl XwfItemInfoTypes {
+    /// minimum X-Ways Forensics version that provides this item information
+    pub fn min_xwf_version(&self) -> (u16, u16) {
+        match self {
+            XwfItemInfoTypes::CreationTimeDisplayOfs |
+            XwfItemInfoTypes::ModificationTimeDisplayOfs |
+            XwfItemInfoTypes::LastAccessTimeDisplayOfs |
+            XwfItemInfoTypes::EntryModificationTimeDisplayOfs |
+            XwfItemInfoTypes::DeletionTimeDisplayOfs |
+            XwfItemInfoTypes::InternalCreationTimeDisplayOfs => (21, 2),
+            _ => (20, 0),
+        }
+    }
+
+    /// true if the item information can be changed via `XWF_SetItemInformation`.
+    /// Flags are changed with [`Item::set_item_info_flags`](crate::item::Item::set_item_info_flags) instead.
+    pub fn is_writable(&self) -> bool {
+        !matches!(self,
+            XwfItemInfoTypes::OrigId |
+            XwfItemInfoTypes::Flags |
+            XwfItemInfoTypes::CreationTimeDisplayOfs |
+            XwfItemInfoTypes::ModificationTimeDisplayOfs |
+            XwfItemInfoTypes::LastAccessTimeDisplayOfs |
+            XwfItemInfoTypes::EntryModificationTimeDisplayOfs |
+            XwfItemInfoTypes::DeletionTimeDisplayOfs |
+            XwfItemInfoTypes::InternalCreationTimeDisplayOfs)
+    }
+
+    /// the `*DisplayOfs` item information belonging to a timestamp
+    pub fn display_offset(&self) -> Option<XwfItemInfoTypes> {
+        match self {
+            XwfItemInfoTypes::CreationTime => Some(XwfItemInfoTypes::CreationTimeDisplayOfs),
+            XwfItemInfoTypes::ModificationTime => Some(XwfItemInfoTypes::ModificationTimeDisplayOfs),
+            XwfItemInfoTypes::LastAccessTime => Some(XwfItemInfoTypes::LastAccessTimeDisplayOfs),
+            XwfItemInfoTypes::EntryModificationTime => Some(XwfItemInfoTypes::EntryModificationTimeDisplayOfs),
+            XwfItemInfoTypes::DeletionTime => Some(XwfItemInfoTypes::DeletionTimeDisplayOfs),
+            XwfItemInfoTypes::InternalCreationTime => Some(XwfItemInfoTypes::InternalCreationTimeDisplayOfs),
+            _ => None,
+        }
+    }
+}
+
+impl XtPreparePositiveReturnFlags {
+    /// removes flags unknown to the running X-Ways Forensics
+    pub fn supported(self) -> XtPreparePositiveReturnFlags {
+        if xwf_supports(21, 2) {
+            self
+        } else {
+            self - XtPreparePositiveReturnFlags::TargetFilesWithUnknownData
         }
     }
 }
@@ -26,8 +76,43 @@ impl XwfDateTime {
             XwfDateTime::NoTimezone(v) => *v,
         }
     }
+
+    /// RFC 3339 with `Z` or the offset of the local time zone, timestamps without time zone have no offset
+    pub fn to_rfc3339(&self) -> String {
+        match &self {
+            XwfDateTime::Utc(v) => v.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            XwfDateTime::Local(v) => v.to_rfc3339_opts(SecondsFormat::AutoSi, false),
+            XwfDateTime::NoTimezone(v) => v.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+        }
+    }
+
+    /// converts a FILETIME value, timestamps not in UTC are returned as `NoTimezone`
+    pub fn from_filetime(filetime: i64, no_timezone: bool) -> Option<XwfDateTime> {
+        let unix_epoch_sec = filetime.div_euclid(10_000_000) - FILETIME_UNIX_EPOCH_SEC;
+        let nsec_fraction = (filetime.rem_euclid(10_000_000) * 100) as u32;
+        let time = DateTime::from_timestamp(unix_epoch_sec, nsec_fraction)?;
+
+        if no_timezone {
+            Some(XwfDateTime::NoTimezone(time.naive_utc()))
+        } else {
+            Some(XwfDateTime::Utc(time))
+        }
+    }
+
+    /// converts to a FILETIME value, timestamps without time zone are converted as if they were UTC
+    pub fn to_filetime(&self) -> i64 {
+        let time = match &self {
+            XwfDateTime::Utc(v) => *v,
+            XwfDateTime::Local(v) => v.to_utc(),
+            XwfDateTime::NoTimezone(v) => v.and_utc(),
+        };
+        (time.timestamp() + FILETIME_UNIX_EPOCH_SEC) * 10_000_000 + (time.timestamp_subsec_nanos() / 100) as i64
+    }
 }
 
+// seconds between 1601-01-01 and 1970-01-01
+const FILETIME_UNIX_EPOCH_SEC: i64 = 11644473600;
+
 
 
 impl SrcInfo {
@@ -2,7 +2,7 @@ use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
 bitflags! {
-    #[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
     pub struct ItemInfoFlags: u64 {
         const IsDirectory                           = 0x00000001;
         const HasChildObjects                       = 0x00000002;
@@ -49,6 +49,7 @@ bitflags! {
 
 
 
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
     pub struct ItemInfoAttributes: i64 {
         const WinAttrReadOnly           = 0x00000001; //Windows attribute read only
         const WinAttrHidden             = 0x00000002; //Windows attribute hidden
@@ -119,7 +120,6 @@ bitflags! {
         const AppendWithoutLineBreak    = 0x00000001; //append without line break (will be delimited from the previous message with a space instead)
         const DontLogToMsgLog           = 0x00000002; //don't log this error message in msglog.txt even if logging is active by default
         const IsAnsiString              = 0x00000004; //lpMessage points to an ANSI string, not a Unicode string (v16.5 and later)
-        #[cfg(feature = "api_20_6")]
         const LogToOutputWindow         = 0x00000008; //output the message in the Output window instead of the Messages window (v20.6 and later), where no [XT] prefix is inserted
         const OutputAsCaseLogEntry      = 0x00000010; //output the message as an entry in the case log,
                                                       // not in the Messages window (v19.4 and later), flag is ignored if no case is active, may be combined with the 0x4 flag
@@ -127,6 +127,7 @@ bitflags! {
         const _ = !0;
     }
 
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
     pub struct AddReportTableFlags: u32 {
         const CreatedByApplication          = 0x01; // show as created by application, not by examiner
         const SelectForInclusionInReport    = 0x02; // select for inclusion in report
@@ -146,6 +147,7 @@ bitflags! {
         const _ = !0;
     }
 
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
     pub struct EvObjPropFlags: u32 {
         const DataWindowActive = 0x01; //Data window active yes/no
         const DataWindowOpen = 0x02; // Data window open yes/no
@@ -200,7 +202,6 @@ bitflags! {
 
         const TargetZerorBytesFiles = 0x20;  //in case of XT_ACTION_RVS, to signal XWF that you wish to receive calls for XT_ProcessItem[Ex]()
                                              //even for files that have a size of 0 bytes, which are otherwise skipped for performance reasons (v18.9 SR-7 and later only)
-        #[cfg(feature = "api_21_2")]
         const TargetFilesWithUnknownData = 0x40; //in case of XT_ACTION_RVS, to signal XWF that you wish to receive calls for XT_ProcessItem[Ex]() even for files that of which only metadata are known, which are otherwise skipped for performance reasons (v21.2 SR-5 and later only)
         const _ = !0;
     }
@@ -221,5 +222,55 @@ bitflags! {
         const _ = !0;
     }
 
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+    pub struct SearchFlags: u32 {
+        const Logical                   = 0x00000001; //logical search instead of physical search
+        const TaggedObjectsOnly         = 0x00000004; //tagged objects in volume snapshot only
+        const MatchCase                 = 0x00000010; //match case
+        const WholeWords                = 0x00000020; //whole words only
+        const Grep                      = 0x00000040; //GREP syntax
+        const AllowOverlapping          = 0x00000080; //allow overlapping hits
+        const CoverSlack                = 0x00000100; //cover slack space
+        const CoverSlackTransition      = 0x00000200; //cover slack/free space transition
+        const DecodeText                = 0x00000400; //decode text in standard file types
+        const DecodeTextEx              = 0x00000800; //decode text in specified file types
+        const OneHitPerFile             = 0x00001000; //1 hit per file needed only
+        const OmitIrrelevant            = 0x00010000; //omit files classified as irrelevant
+        const OmitHidden                = 0x00020000; //omit hidden files
+        const OmitFiltered              = 0x00040000; //omit files that are filtered out
+        const DataReduction             = 0x00080000; //recommendable data reduction
+        const OmitDirectories           = 0x00100000; //omit directories
+        const CallProcessSearchHit      = 0x01000000; //call XT_ProcessSearchHit for each search hit
+        const DisplayHits               = 0x04000000; //display the search hit list when the search completes
+        const _ = !0;
+    }
 
-}
\ No newline at end of file
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+    pub struct SearchHitFlags: u16 {
+        const InSlack                   = 0x0002; //search hit in slack space
+        const Notable                   = 0x0008; //search hit marked as notable
+        const InDecodedText             = 0x0010; //search hit in decoded text instead of raw data
+        const Deleted                   = 0x0040; //search hit deleted by the user
+        const _ = !0;
+    }
+
+    pub struct AddSearchTermFlags: u32 {
+        const UseExisting               = 0x01; //return the id of an existing search term with the same name instead of adding it again
+        const ForUserSearchHits         = 0x02; //search term for search hits added by the user
+        const _ = !0;
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+    pub struct EventFlags: u32 {
+        const TimestampNotInUTC         = 0x01; //timestamp is given in local time of unknown time zone
+        const TimestampImprecise        = 0x02; //timestamp is only accurate to the day
+        const _ = !0;
+    }
+
+    /// how `XWF_AddComment` and `XWF_AddExtractedMetadata` add the text, replaces existing text by default
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub struct AddTextFlags: u32 {
+        const Append                    = 0x01; //append to the existing text
+        const _ = !0;
+    }
+}
@@ -1,5 +1,5 @@
 use chrono::{DateTime, Local, NaiveDateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use crate::xwf_types::XtPreparePositiveReturnFlags;
 
 pub enum XtPrepareNegativeReturn {
@@ -31,6 +31,11 @@ pub enum XtProcessItemExReturn {
     Ok                          =  0, //otherwise 0.
 }
 
+pub enum XtProcessSearchHitReturn {
+    StopSearch                  = -1, //Return -1 if you want X-Ways Forensics to abort the search,
+    Ok                          =  0, //otherwise 0.
+}
+
 pub enum PropType {
     PhysicalSize            = 0,
     LogicalSize             = 1,
@@ -43,7 +48,7 @@ pub enum PropType {
 }
 
 
-#[derive(Debug, PartialEq, Eq, Serialize, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
 pub enum ItemInfoClassification {
     NormalFile                            = 0x00, //normal file
     HfsResourceFork                       = 0x04, //HFS resource fork
@@ -83,7 +88,7 @@ pub enum EvObjPropType {
     SizeInBytes         = 16,	//INT64	(unused)	size in bytes
     VSFileCount         = 17,	//DWORD	(unused)	volume snapshot file count
     Flags               = 18,	//INT64	(unused)	flags*
-    FileSystemID        = 19,	//INT64	(unused)	file system identifier (see XWF_GetVolumeInformation for possible values)
+    FileSystemID        = 19,	//INT64	(unused)	file system identifier, see FileSystemType
     HashType            = 20,	//DWORD	(unused)	hash type
     HashValue           = 21,	//DWORD	LPVOID	hash value (buffer size according to hash type), returns the hash size in bytes
     CreationTime        = 32,	//FILETIME	(unused)	creation time (when the ev. obj. was added to the case)
@@ -94,7 +99,7 @@ pub enum EvObjPropType {
 }
 
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum XtPrepareOpType {
     ActionRun                       = 0, // simply run directly from the main menu or command line3
     ActionVolumeSnapshotRefinement  = 1, // volume snapshot refinement starting2
@@ -105,6 +110,7 @@ pub enum XtPrepareOpType {
     EventListContextMenu            = 6 // event list context menu command invoked (since v20.3 SR-3)
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum XwfItemInfoTypes {
     OrigId                  = 1,
     Attr                    = 2,
@@ -122,17 +128,11 @@ pub enum XwfItemInfoTypes {
     EntryModificationTime   = 35,
     DeletionTime            = 36,
     InternalCreationTime    = 37,
-    #[cfg(feature = "api_21_2")]
     CreationTimeDisplayOfs          = 48,
-    #[cfg(feature = "api_21_2")]
     ModificationTimeDisplayOfs      = 49,
-    #[cfg(feature = "api_21_2")]
     LastAccessTimeDisplayOfs        = 50,
-    #[cfg(feature = "api_21_2")]
     EntryModificationTimeDisplayOfs = 51,
-    #[cfg(feature = "api_21_2")]
     DeletionTimeDisplayOfs          = 52,
-    #[cfg(feature = "api_21_2")]
     InternalCreationTimeDisplayOfs  = 53,
 
 }
@@ -151,7 +151,7 @@ pub enum XtFinalizeReturn {
     Ok = 0
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct XtVersion {
     pub major: u16,
     pub minor: u16,
@@ -159,7 +159,7 @@ pub struct XtVersion {
     pub language: u8,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
 pub enum FileFormatConsistency {
     Unknown = 0,
     Ok = 1,
@@ -167,7 +167,7 @@ pub enum FileFormatConsistency {
     NotDocumented = 3,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
 pub enum ItemInfoDeletion {
     Existing                    = 0,   //existing
     PossiblyReverable           = 1,   //previously existing, possibly recoverable
@@ -177,7 +177,7 @@ pub enum ItemInfoDeletion {
     CarvedFile                  = 5    //carved file (since v19.3 SR-3, used to be 1)
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
 pub enum FileTypeStatus {
     NotVerified = 0,
     TooSmall = 1,
@@ -227,7 +227,7 @@ pub enum FileTypeCategory {
     Other,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum XwfDateTime {
     Utc(DateTime<Utc>),            //timestamp is given in UTC
     Local(DateTime<Local>),        //timestamp is given in local time zone
@@ -242,7 +242,6 @@ pub enum VsPropType {
     HashType2 =     21,
     SetHashType1 =  25,
     SetHashType2 =  26,
-    #[cfg(feature = "api_20_9")]
     SetHasChanged =  30,
 }
 
@@ -250,4 +249,70 @@ pub enum VolumeNameType {
     SHORT =  3,
     NORMAL = 2,
     LONG =   1
-}
\ No newline at end of file
+}
+
+/// File system of a volume, as returned by `XWF_GetVolumeInformation` and `EvObjPropType::FileSystemID`.
+/// Identifiers not known to this crate are kept as `Other`.
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Copy)]
+pub enum FileSystemType {
+    Unknown,
+    Fat12,
+    Fat16,
+    Fat32,
+    ExFat,
+    Tfat,
+    Ntfs,
+    Next3,
+    Ext2,
+    Ext3,
+    Ext4,
+    ReiserFs,
+    Reiser4,
+    Ufs,
+    Ufs2,
+    Hfs,
+    HfsPlus,
+    Xfs,
+    Btrfs,
+    Apfs,
+    ReFs,
+    Xwfs,
+    Xwfs2,
+    Iso9660,
+    Udf,
+    Other(i32),
+}
+
+/// Type of an event in the event list of an evidence object, see `XWF_GetEvent`/`XWF_AddEvent`.
+/// Types not known to this crate are kept as `Other`.
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Copy)]
+pub enum EventType {
+    Unspecified,
+    Creation,
+    Modification,
+    LastAccess,
+    EntryModification,
+    Deletion,
+    InternalCreation,
+    LastPrinted,
+    InternetVisit,
+    InternetDownload,
+    InternetCookie,
+    EmailSent,
+    EmailReceived,
+    EventLogEntry,
+    RegistryKeyLastWritten,
+    PhotoTaken,
+    Other(u32),
+}
+
+/// Result of the color analysis of a picture (`XwfItemInfoTypes::ColorAnalysis`).
+/// Values not known to this crate are kept as `Other`.
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Copy)]
+pub enum ColorAnalysis {
+    NotAnalyzed,
+    BlackAndWhite,
+    /// percentage of skin colors
+    SkinTone(u8),
+    Other(i64),
+}
@@ -1,9 +1,93 @@
-use winapi::ctypes::__int64;
-use winapi::shared::minwindef::{DWORD, LPVOID};
+use serde::{Deserialize, Serialize};
+use crate::ffi_types::{__int64, DWORD, LONG, LPSTR, LPVOID, LPWSTR, WORD};
 
-#[repr(packed(2))]
+#[repr(C, packed(2))]
 pub struct SrcInfo {
     pub n_struct_size: DWORD,
     pub n_buf_size: __int64 ,
     pub p_buffer: LPVOID
 }
+
+/// Parameters of `XWF_Search`
+#[repr(C, packed(2))]
+pub struct SearchInfo {
+    pub i_size: LONG,
+    pub h_volume: XwfHandle,
+    pub lp_search_terms: LPWSTR,    // search terms delimited by line breaks
+    pub n_flags: DWORD,
+    pub n_search_window: DWORD,
+}
+
+/// Code pages of a search, unused entries are 0
+#[repr(C, packed(2))]
+pub struct CodePages {
+    pub i_size: LONG,
+    pub n_code_page1: WORD,
+    pub n_code_page2: WORD,
+    pub n_code_page3: WORD,
+    pub n_code_page4: WORD,
+    pub n_code_page5: WORD,
+}
+
+/// Search hit as passed to `XT_ProcessSearchHit` and used by `XWF_GetSearchHit`, `XWF_SetSearchHit` and `XWF_AddSearchHit`
+#[repr(C, packed(2))]
+pub struct SearchHitInfo {
+    pub i_size: LONG,
+    pub n_item_id: LONG,
+    pub n_rel_ofs: __int64,             // offset relative to the start of the item
+    pub n_abs_ofs: __int64,             // offset relative to the start of the volume, -1 if not available
+    pub lp_optional_hit_ptr: LPVOID,
+    pub n_search_term_id: WORD,
+    pub n_length: WORD,
+    pub n_code_page: WORD,
+    pub n_flags: WORD,
+    pub h_open_item: XwfHandle,
+}
+
+/// Search settings passed to `XT_PrepareSearch`, `lp_search_terms` points to a buffer of `n_buf_len` characters
+#[repr(C, packed(2))]
+pub struct PrepareSearchInfo {
+    pub i_size: LONG,
+    pub lp_search_terms: LPWSTR,
+    pub n_buf_len: DWORD,
+    pub n_flags: DWORD,
+}
+
+/// Event of the event list of an evidence object, used by `XWF_GetEvent` and `XWF_AddEvent`
+#[repr(C, packed(2))]
+pub struct EventInfo {
+    pub i_size: LONG,
+    pub h_evidence: XwfHandle,
+    pub n_evt_type: DWORD,
+    pub n_flags: DWORD,
+    pub time_stamp: __int64,            // FILETIME
+    pub n_item_id: LONG,                // -1 if the event does not belong to an item
+    pub n_ofs: __int64,                 // offset of the event data within the item, -1 if not available
+    pub lp_descr: LPSTR,                // null terminated UTF-8 string
+}
+
+/// Opaque handle of a volume, item, evidence object or window as passed by X-Ways Forensics.
+/// Pointer sized, so it can be used directly in the X-Tension function signatures.
+///
+/// Stored as integer and thus `Send` and `Sync`. Whether a wrapper holding a handle may be
+/// shared between threads is documented on the wrapper ([`Volume`](crate::volume::Volume),
+/// [`Evidence`](crate::evidence::Evidence), [`ItemHandle`](crate::item::ItemHandle)).
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct XwfHandle(usize);
+
+impl XwfHandle {
+    pub const NULL: XwfHandle = XwfHandle(0);
+
+    pub const fn from_raw(value: usize) -> XwfHandle {
+        XwfHandle(value)
+    }
+
+    pub const fn as_raw(&self) -> usize {
+        self.0
+    }
+
+    pub const fn is_null(&self) -> bool {
+        self.0 == 0
+    }
+}
@@ -0,0 +1,129 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Error, ImplItem, ItemImpl, Type};
+
+// optional callbacks of the XTension trait and the macro exporting the corresponding XT_* function
+// (export_xt_view also exports XT_ReleaseMem, which releases the buffers returned by XT_View)
+const OPTIONAL_CALLBACKS: [(&str, &str); 8] = [
+    ("xt_about", "export_xt_about"),
+    ("xt_prepare", "export_xt_prepare"),
+    ("xt_finalize", "export_xt_finalize"),
+    ("xt_process_item", "export_xt_process_item"),
+    ("xt_process_item_ex", "export_xt_process_item_ex"),
+    ("xt_prepare_search", "export_xt_prepare_search"),
+    ("xt_process_search_hit", "export_xt_process_search_hit"),
+    ("xt_view", "export_xt_view"),
+];
+
+// callbacks of which only one may be implemented, as X-Ways Forensics would call both of them
+const EXCLUSIVE_CALLBACKS: [(&str, &str); 1] = [
+    ("xt_process_item", "xt_process_item_ex"),
+];
+
+// callbacks that rely on XT_Prepare to be exported, as its default implementation decides whether they are called
+const REQUIRE_PREPARE: [&str; 2] = ["xt_process_item", "xt_process_item_ex"];
+
+// callbacks of XTensionMt that rely on XT_Finalize to be exported, which hands back the thread states
+const REQUIRE_FINALIZE_MT: [&str; 2] = ["xt_process_item", "xt_process_item_ex"];
+
+/// Registers the annotated `impl XTension for T` (or `impl XTensionMt for T`) block as the X-Tension of the DLL.
+///
+/// `XT_Init` and `XT_Done` are always exported, all other `XT_*` functions only if the
+/// corresponding callback is implemented (`XT_View` together with `XT_ReleaseMem`).
+/// Replaces the `export_all_functions!` macro family.
+///
+/// ```ignore
+/// #[xtension]
+/// impl XTension for MyXTension {
+///     type XTensionError = XwfError;
+///     fn create() -> Self { MyXTension {} }
+///     fn xt_process_item_ex(&mut self, item: ItemHandle) -> Result<XtProcessItemExReturn, XwfError> { ... }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn xtension(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return Error::new(proc_macro2::TokenStream::from(attr).span(), "#[xtension] does not take any arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let item_impl = parse_macro_input!(item as ItemImpl);
+    match expand(&item_impl) {
+        Ok(exports) => quote!(#item_impl #exports).into(),
+        Err(e) => {
+            let err = e.to_compile_error();
+            quote!(#item_impl #err).into()
+        }
+    }
+}
+
+fn expand(item_impl: &ItemImpl) -> Result<proc_macro2::TokenStream, Error> {
+    let trait_path = match &item_impl.trait_ {
+        Some((None, path, _)) => path,
+        _ => return Err(Error::new(item_impl.self_ty.span(), "#[xtension] must be placed on an `impl XTension for ...` or `impl XTensionMt for ...` block")),
+    };
+    let multi_threaded = match trait_path.segments.last() {
+        Some(s) if s.ident == "XTension" => false,
+        Some(s) if s.ident == "XTensionMt" => true,
+        _ => return Err(Error::new(trait_path.span(), "#[xtension] must be placed on an `impl XTension for ...` or `impl XTensionMt for ...` block")),
+    };
+    if !item_impl.generics.params.is_empty() {
+        return Err(Error::new(item_impl.generics.span(), "#[xtension] does not support generic X-Tension types"));
+    }
+
+    let self_ty: &Type = &item_impl.self_ty;
+
+    let implemented: Vec<&Ident> = item_impl.items.iter()
+        .filter_map(|i| match i {
+            ImplItem::Fn(f) => Some(&f.sig.ident),
+            _ => None,
+        })
+        .collect();
+    let is_implemented = |name: &str| implemented.iter().any(|i| *i == name);
+
+    for (a, b) in EXCLUSIVE_CALLBACKS {
+        if is_implemented(a) && is_implemented(b) {
+            let ident = implemented.iter().find(|i| **i == b).unwrap();
+            return Err(Error::new(ident.span(), format!("an X-Tension must not implement both `{}` and `{}`", a, b)));
+        }
+    }
+
+    let export_prepare = REQUIRE_PREPARE.iter().any(|c| is_implemented(c));
+    let export_finalize = multi_threaded && REQUIRE_FINALIZE_MT.iter().any(|c| is_implemented(c));
+
+    let instance = Ident::new("__XTENSION_INSTANCE", Span::call_site());
+    let exports = OPTIONAL_CALLBACKS.iter()
+        .filter(|(callback, _)| is_implemented(callback)
+            || (*callback == "xt_prepare" && export_prepare)
+            || (*callback == "xt_finalize" && export_finalize))
+        .map(|(_, export_macro)| {
+            let export_macro = format_ident!("{}", export_macro);
+            quote!(::xwf_api_rs::#export_macro!(#instance, #self_ty);)
+        });
+
+    let create_instance = if multi_threaded {
+        quote!(::xwf_api_rs::create_static_instance_mt!(#instance, #self_ty);)
+    } else {
+        quote!(::xwf_api_rs::create_static_instance!(#instance, #self_ty);)
+    };
+    let trait_ident = if multi_threaded { format_ident!("XTensionMt") } else { format_ident!("XTension") };
+
+    Ok(quote! {
+        const _: () = {
+            #[allow(unused_imports)]
+            use ::xwf_api_rs::ffi_types::{DWORD, LPVOID, PVOID, LONG};
+            #[allow(unused_imports)]
+            use ::xwf_api_rs::xwf_types::*;
+            #[allow(unused_imports)]
+            use ::xwf_api_rs::traits::#trait_ident;
+
+            #create_instance
+            ::xwf_api_rs::export_xt_init!(#instance, #self_ty);
+            ::xwf_api_rs::export_xt_done!(#instance, #self_ty);
+            #(#exports)*
+        };
+    })
+}
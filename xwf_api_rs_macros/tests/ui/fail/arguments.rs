@@ -0,0 +1,14 @@
+use xwf_api_rs::xtension;
+
+pub struct Extension;
+
+#[xtension(multi_threaded)]
+impl xwf_api_rs::traits::XTension for Extension {
+    type XTensionError = xwf_api_rs::error::XwfError;
+
+    fn create() -> Extension {
+        Extension
+    }
+}
+
+fn main() {}
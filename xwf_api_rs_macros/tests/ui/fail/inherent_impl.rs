@@ -0,0 +1,12 @@
+use xwf_api_rs::xtension;
+
+pub struct Extension;
+
+#[xtension]
+impl Extension {
+    pub fn create() -> Extension {
+        Extension
+    }
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use xwf_api_rs::xtension;
+
+#[xtension]
+pub struct Extension;
+
+fn main() {}
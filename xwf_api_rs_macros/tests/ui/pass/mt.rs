@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use xwf_api_rs::error::XwfError;
+use xwf_api_rs::item::Item;
+use xwf_api_rs::traits::XTensionMt;
+use xwf_api_rs::xtension;
+use xwf_api_rs::xwf_types::*;
+
+pub struct Extension {
+    processed: AtomicUsize,
+}
+
+#[xtension]
+impl XTensionMt for Extension {
+    type XTensionError = XwfError;
+    type ThreadState = usize;
+
+    fn create() -> Extension {
+        Extension { processed: AtomicUsize::new(0) }
+    }
+
+    fn create_thread_state(&self) -> usize {
+        0
+    }
+
+    fn thread_state_finished(&self, state: usize) {
+        self.processed.fetch_add(state, Ordering::Relaxed);
+    }
+
+    fn xt_process_item(&self, state: &mut usize, _item: Item) -> Result<XtProcessItemReturn, XwfError> {
+        *state += 1;
+        Ok(XtProcessItemReturn::Ok)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use xwf_api_rs::error::XwfError;
+use xwf_api_rs::item::Item;
+use xwf_api_rs::traits::XTension;
+use xwf_api_rs::xtension;
+use xwf_api_rs::xwf_types::*;
+
+pub struct Extension;
+
+#[xtension]
+impl XTension for Extension {
+    type XTensionError = XwfError;
+
+    fn create() -> Extension {
+        Extension
+    }
+
+    fn xt_process_item(&mut self, _item: Item) -> Result<XtProcessItemReturn, XwfError> {
+        Ok(XtProcessItemReturn::Ok)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use xwf_api_rs::error::XwfError;
+use xwf_api_rs::item::ItemHandle;
+use xwf_api_rs::traits::XTension;
+use xwf_api_rs::xtension;
+use xwf_api_rs::xwf_types::*;
+
+pub struct Extension;
+
+#[xtension]
+impl XTension for Extension {
+    type XTensionError = XwfError;
+
+    fn create() -> Extension {
+        Extension
+    }
+
+    fn xt_process_item_ex(&mut self, _item: ItemHandle) -> Result<XtProcessItemExReturn, XwfError> {
+        Ok(XtProcessItemExReturn::Ok)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use xwf_api_rs::error::XwfError;
+use xwf_api_rs::evidence::Evidence;
+use xwf_api_rs::item::ItemHandle;
+use xwf_api_rs::traits::XTension;
+use xwf_api_rs::volume::Volume;
+use xwf_api_rs::xtension;
+
+pub struct Extension;
+
+#[xtension]
+impl XTension for Extension {
+    type XTensionError = XwfError;
+
+    fn create() -> Extension {
+        Extension
+    }
+
+    fn xt_view(&mut self, item: ItemHandle, _volume: Option<Volume>, _evidence: Option<Evidence>) -> Result<Option<Vec<u8>>, XwfError> {
+        Ok(item.get_name().ok().map(String::into_bytes))
+    }
+}
+
+fn main() {}